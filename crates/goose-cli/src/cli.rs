@@ -27,6 +27,7 @@ use goose_bench::runners::eval_runner::EvalRunner;
 use goose_bench::runners::metric_aggregator::MetricAggregator;
 use goose_bench::runners::model_runner::ModelRunner;
 use std::io::Read;
+use std::net::SocketAddr;
 use std::path::PathBuf;
 
 #[derive(Parser)]
@@ -285,7 +286,18 @@ enum Command {
 
     /// Manage system prompts and behaviors
     #[command(about = "Run one of the mcp servers bundled with goose")]
-    Mcp { name: String },
+    Mcp {
+        name: String,
+
+        /// Serve over streamable HTTP instead of stdio
+        #[arg(
+            long,
+            value_name = "ADDRESS",
+            help = "Serve over streamable HTTP on this address (e.g., '127.0.0.1:8765')",
+            long_help = "Serve over streamable HTTP on this address instead of stdio, so a goose on another machine can attach to the extension. Clients must send the token from GOOSE_MCP_TOKEN, or the generated one that is printed, as a bearer token. Only the developer server supports this."
+        )]
+        http: Option<SocketAddr>,
+    },
 
     /// Start or resume interactive chat sessions
     #[command(
@@ -710,8 +722,8 @@ pub async fn cli() -> Result<()> {
             handle_info(verbose)?;
             return Ok(());
         }
-        Some(Command::Mcp { name }) => {
-            let _ = run_server(&name).await;
+        Some(Command::Mcp { name, http }) => {
+            run_server(&name, http).await?;
         }
         Some(Command::Session {
             command,
@@ -1,11 +1,14 @@
-use anyhow::Result;
+use anyhow::{bail, Result};
 use goose_mcp::{
     ComputerControllerRouter, DeveloperRouter, GoogleDriveRouter, MemoryRouter, TutorialRouter,
 };
 use mcp_server::router::RouterService;
-use mcp_server::{BoundedService, ByteTransport, Server};
+use mcp_server::{BoundedService, ByteTransport, HttpServer, Server};
+use rand::Rng;
 use tokio::io::{stdin, stdout};
+use tokio::net::TcpListener;
 
+use std::net::SocketAddr;
 use std::sync::Arc;
 use tokio::sync::Notify;
 
@@ -16,14 +19,20 @@ use nix::unistd::getpgrp;
 #[cfg(unix)]
 use nix::unistd::Pid;
 
-pub async fn run_server(name: &str) -> Result<()> {
+pub async fn run_server(name: &str, http: Option<SocketAddr>) -> Result<()> {
     // Initialize logging
     crate::logging::setup_logging(Some(&format!("mcp-{name}")), None)?;
 
     tracing::info!("Starting MCP server");
 
+    if let Some(address) = http {
+        return serve_http(name, address).await;
+    }
+
     let router: Option<Box<dyn BoundedService>> = match name {
-        "developer" => Some(Box::new(RouterService(DeveloperRouter::new()))),
+        "developer" => Some(Box::new(RouterService(
+            DeveloperRouter::new().with_plugins(),
+        ))),
         "computercontroller" => Some(Box::new(RouterService(ComputerControllerRouter::new()))),
         "google_drive" | "googledrive" => {
             let router = GoogleDriveRouter::new().await;
@@ -52,9 +61,11 @@ pub async fn run_server(name: &str) -> Result<()> {
 
     tokio::select! {
         result = server.run(transport) => {
+            goose::tracing::shutdown_otlp();
             Ok(result?)
         }
         _ = shutdown.notified() => {
+            goose::tracing::shutdown_otlp();
             // On Unix systems, kill the entire process group
             #[cfg(unix)]
             {
@@ -69,3 +80,55 @@ pub async fn run_server(name: &str) -> Result<()> {
         }
     }
 }
+
+/// Serve the developer extension over streamable HTTP, so a goose on another machine can
+/// attach to it. Clients must send the token from `GOOSE_MCP_TOKEN`, or the one generated
+/// and printed here, as a bearer token.
+async fn serve_http(name: &str, address: SocketAddr) -> Result<()> {
+    if name != "developer" {
+        bail!("Only the developer server can be served over HTTP");
+    }
+
+    let token = match std::env::var("GOOSE_MCP_TOKEN") {
+        Ok(token) if !token.is_empty() => token,
+        _ => {
+            let token = generate_token();
+            eprintln!("Generated a token, set GOOSE_MCP_TOKEN to choose your own:\n  {token}");
+            token
+        }
+    };
+
+    // Each client gets its own session, with its own file history, hints and caches
+    let cwd = std::env::current_dir()?;
+    let router = DeveloperRouter::new().with_plugins();
+    let server = HttpServer::new(move || -> Box<dyn BoundedService> {
+        Box::new(RouterService(router.session(cwd.clone())))
+    })
+    .with_token(token);
+
+    let listener = TcpListener::bind(address).await?;
+    eprintln!(
+        "Serving the developer extension at http://{}/mcp",
+        listener.local_addr()?
+    );
+    eprintln!(
+        "Attach it as a streamable HTTP extension with the header `Authorization: Bearer <token>`"
+    );
+
+    tokio::select! {
+        result = server.run(listener) => {
+            goose::tracing::shutdown_otlp();
+            Ok(result?)
+        }
+        _ = crate::signal::shutdown_signal() => {
+            goose::tracing::shutdown_otlp();
+            Ok(())
+        }
+    }
+}
+
+/// A random token with 256 bits of entropy, as hex
+fn generate_token() -> String {
+    let bytes: [u8; 32] = rand::thread_rng().gen();
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
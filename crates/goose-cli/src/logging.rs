@@ -11,7 +11,7 @@ use tracing_subscriber::{
     Registry,
 };
 
-use goose::tracing::langfuse_layer;
+use goose::tracing::{create_otlp_layer, langfuse_layer};
 use goose_bench::bench_session::BenchAgentError;
 use goose_bench::error_capture::ErrorCaptureLayer;
 
@@ -55,6 +55,7 @@ fn get_log_directory_with_date(test_date: Option<String>) -> Result<PathBuf> {
 /// - File-based logging with JSON formatting (DEBUG level)
 /// - Console output for development (INFO level)
 /// - Optional Langfuse integration (DEBUG level)
+/// - Optional OTLP export of spans and metrics (INFO level)
 /// - Optional error capture layer for benchmarking
 pub fn setup_logging(
     name: Option<&str>,
@@ -145,6 +146,17 @@ fn setup_logging_internal(
                 layers.push(langfuse.with_filter(LevelFilter::DEBUG).boxed());
             }
 
+            // Add OTLP export if an OpenTelemetry collector is configured. A broken setup
+            // shouldn't stop logging, so it is only reported once the subscriber is running.
+            let otlp_error = match create_otlp_layer(name.unwrap_or("goose")) {
+                Ok(Some(otlp)) => {
+                    layers.push(otlp.with_filter(LevelFilter::INFO).boxed());
+                    None
+                }
+                Ok(None) => None,
+                Err(e) => Some(e),
+            };
+
             // Build the subscriber
             let subscriber = Registry::default().with(layers);
 
@@ -162,6 +174,9 @@ fn setup_logging_internal(
                 subscriber
                     .try_init()
                     .context("Failed to set global subscriber")?;
+                if let Some(e) = otlp_error {
+                    tracing::warn!("Not exporting over OTLP: {:#}", e);
+                }
                 Ok(())
             }
         })();
@@ -105,6 +105,7 @@ impl McpClientTrait for MockClient {
                 Ok(content) => Ok(CallToolResult {
                     content,
                     is_error: None,
+                    structured_content: None,
                 }),
                 Err(e) => Err(Error::UnexpectedResponse(e.to_string())),
             }
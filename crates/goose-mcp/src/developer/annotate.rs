@@ -0,0 +1,454 @@
+use std::path::PathBuf;
+
+use ab_glyph::{FontVec, PxScale};
+use base64::Engine;
+use imageproc::{
+    drawing::{
+        draw_filled_rect_mut, draw_hollow_rect_mut, draw_line_segment_mut, draw_polygon_mut,
+        draw_text_mut, text_size,
+    },
+    point::Point,
+    rect::Rect,
+};
+use indoc::indoc;
+use mcp_core::handler::ToolError;
+use rmcp::model::{Content, JsonRpcMessage, Role, Tool, ToolAnnotations};
+use rmcp::object;
+use serde::Deserialize;
+use serde_json::Value;
+use tokio::sync::mpsc;
+use xcap::image::{DynamicImage, Rgba, RgbaImage};
+
+use super::images::ImageOptions;
+use super::rasterize;
+use super::DeveloperRouter;
+
+/// Fonts tried in order for text labels when `GOOSE_ANNOTATION_FONT` is not set
+const FONT_CANDIDATES: &[&str] = &[
+    "/System/Library/Fonts/Helvetica.ttc",
+    "/System/Library/Fonts/Supplemental/Arial.ttf",
+    "C:\\Windows\\Fonts\\arial.ttf",
+    "C:\\Windows\\Fonts\\segoeui.ttf",
+    "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf",
+    "/usr/share/fonts/TTF/DejaVuSans.ttf",
+    "/usr/share/fonts/dejavu/DejaVuSans.ttf",
+    "/usr/share/fonts/truetype/liberation/LiberationSans-Regular.ttf",
+    "/usr/share/fonts/noto/NotoSans-Regular.ttf",
+];
+
+const LINE_THICKNESS: i32 = 3;
+const DEFAULT_TEXT_SIZE: f32 = 20.0;
+const ARROW_HEAD_LENGTH: f32 = 18.0;
+
+/// A shape to draw on an image, in the image's own pixel coordinates
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Annotation {
+    Rectangle {
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+        #[serde(default)]
+        color: Option<String>,
+        #[serde(default)]
+        label: Option<String>,
+    },
+    Arrow {
+        from: [i32; 2],
+        to: [i32; 2],
+        #[serde(default)]
+        color: Option<String>,
+        #[serde(default)]
+        label: Option<String>,
+    },
+    Text {
+        x: i32,
+        y: i32,
+        text: String,
+        #[serde(default)]
+        color: Option<String>,
+        #[serde(default)]
+        size: Option<f32>,
+    },
+}
+
+/// Draw the annotations onto the image.
+///
+/// Returns the number of text labels that were skipped because no font could be loaded.
+pub fn annotate(image: &mut RgbaImage, annotations: &[Annotation]) -> Result<usize, ToolError> {
+    let font = load_font();
+    let mut skipped_labels = 0;
+
+    let mut label = |image: &mut RgbaImage, x, y, text: &str, color, size| {
+        if let Some(font) = &font {
+            draw_label(image, font, x, y, text, color, size);
+        } else {
+            skipped_labels += 1;
+        }
+    };
+
+    for annotation in annotations {
+        match annotation {
+            Annotation::Rectangle {
+                x,
+                y,
+                width,
+                height,
+                color,
+                label: text,
+            } => {
+                let color = parse_color(color.as_deref())?;
+                for offset in 0..LINE_THICKNESS {
+                    let (w, h) = (
+                        width.saturating_sub(2 * offset as u32),
+                        height.saturating_sub(2 * offset as u32),
+                    );
+                    if w == 0 || h == 0 {
+                        break;
+                    }
+                    draw_hollow_rect_mut(
+                        image,
+                        Rect::at(x + offset, y + offset).of_size(w, h),
+                        color,
+                    );
+                }
+                if let Some(text) = text {
+                    // Put the label above the box, or inside it at the top edge of the image
+                    let size = DEFAULT_TEXT_SIZE;
+                    let label_y = if *y >= size as i32 + 4 {
+                        y - size as i32 - 4
+                    } else {
+                        *y
+                    };
+                    label(image, *x, label_y, text, color, size);
+                }
+            }
+            Annotation::Arrow {
+                from,
+                to,
+                color,
+                label: text,
+            } => {
+                let color = parse_color(color.as_deref())?;
+                draw_arrow(image, *from, *to, color);
+                if let Some(text) = text {
+                    label(image, from[0], from[1], text, color, DEFAULT_TEXT_SIZE);
+                }
+            }
+            Annotation::Text {
+                x,
+                y,
+                text,
+                color,
+                size,
+            } => {
+                let color = parse_color(color.as_deref())?;
+                label(
+                    image,
+                    *x,
+                    *y,
+                    text,
+                    color,
+                    size.unwrap_or(DEFAULT_TEXT_SIZE),
+                );
+            }
+        }
+    }
+
+    Ok(skipped_labels)
+}
+
+fn load_font() -> Option<FontVec> {
+    let configured = std::env::var("GOOSE_ANNOTATION_FONT").ok();
+    configured
+        .iter()
+        .map(String::as_str)
+        .chain(FONT_CANDIDATES.iter().copied())
+        .find_map(|path| {
+            let data = std::fs::read(path).ok()?;
+            FontVec::try_from_vec_and_index(data, 0).ok()
+        })
+}
+
+/// Text on a filled background so it stays readable on any screenshot
+fn draw_label(
+    image: &mut RgbaImage,
+    font: &FontVec,
+    x: i32,
+    y: i32,
+    text: &str,
+    color: Rgba<u8>,
+    size: f32,
+) {
+    let scale = PxScale::from(size);
+    let padding = 3;
+    let (width, height) = text_size(scale, font, text);
+    draw_filled_rect_mut(
+        image,
+        Rect::at(x, y).of_size(width + 2 * padding as u32, height + 2 * padding as u32),
+        color,
+    );
+    draw_text_mut(
+        image,
+        contrasting(color),
+        x + padding,
+        y + padding,
+        scale,
+        font,
+        text,
+    );
+}
+
+fn draw_arrow(image: &mut RgbaImage, from: [i32; 2], to: [i32; 2], color: Rgba<u8>) {
+    let (x0, y0) = (from[0] as f32, from[1] as f32);
+    let (x1, y1) = (to[0] as f32, to[1] as f32);
+    let angle = (y1 - y0).atan2(x1 - x0);
+
+    let thick_line = |image: &mut RgbaImage, start: (f32, f32), end: (f32, f32)| {
+        for dx in 0..LINE_THICKNESS {
+            for dy in 0..LINE_THICKNESS {
+                let (ox, oy) = (
+                    (dx - LINE_THICKNESS / 2) as f32,
+                    (dy - LINE_THICKNESS / 2) as f32,
+                );
+                draw_line_segment_mut(
+                    image,
+                    (start.0 + ox, start.1 + oy),
+                    (end.0 + ox, end.1 + oy),
+                    color,
+                );
+            }
+        }
+    };
+
+    thick_line(image, (x0, y0), (x1, y1));
+    for side in [-1.0f32, 1.0] {
+        let head_angle = angle + std::f32::consts::PI - side * std::f32::consts::FRAC_PI_6;
+        let head = (
+            x1 + ARROW_HEAD_LENGTH * head_angle.cos(),
+            y1 + ARROW_HEAD_LENGTH * head_angle.sin(),
+        );
+        thick_line(image, (x1, y1), head);
+    }
+}
+
+/// Draw a mouse pointer with its tip at (x, y), scaled for HiDPI captures
+pub fn draw_cursor(image: &mut RgbaImage, x: i32, y: i32, scale: f32) {
+    const ARROW: [(f32, f32); 7] = [
+        (0.0, 0.0),
+        (0.0, 17.0),
+        (4.0, 13.0),
+        (7.0, 20.0),
+        (10.0, 19.0),
+        (7.0, 12.0),
+        (12.0, 12.0),
+    ];
+    let points = |grow: f32| {
+        ARROW
+            .iter()
+            .map(|(px, py)| {
+                Point::new(
+                    x + ((px + grow * (px - 4.0).signum()) * scale).round() as i32,
+                    y + ((py + grow * (py - 10.0).signum()) * scale).round() as i32,
+                )
+            })
+            .collect::<Vec<_>>()
+    };
+    // A white outline keeps the black pointer visible on dark backgrounds
+    draw_polygon_mut(image, &points(1.0), Rgba([255, 255, 255, 255]));
+    draw_polygon_mut(image, &points(0.0), Rgba([0, 0, 0, 255]));
+}
+
+/// Parse `#rrggbb` or a few common color names, defaulting to red
+fn parse_color(color: Option<&str>) -> Result<Rgba<u8>, ToolError> {
+    let Some(color) = color else {
+        return Ok(Rgba([255, 0, 0, 255]));
+    };
+
+    let rgb = match color.to_lowercase().as_str() {
+        "red" => [255, 0, 0],
+        "green" => [0, 200, 0],
+        "blue" => [0, 90, 255],
+        "yellow" => [255, 220, 0],
+        "orange" => [255, 140, 0],
+        "purple" => [160, 32, 240],
+        "black" => [0, 0, 0],
+        "white" => [255, 255, 255],
+        hex => {
+            let digits = hex.strip_prefix('#').unwrap_or(hex);
+            let channel = |i: usize| {
+                digits
+                    .get(i..i + 2)
+                    .and_then(|c| u8::from_str_radix(c, 16).ok())
+            };
+            match (digits.len(), channel(0), channel(2), channel(4)) {
+                (6, Some(r), Some(g), Some(b)) => [r, g, b],
+                _ => {
+                    return Err(ToolError::InvalidParameters(format!(
+                        "Invalid color '{}'. Use #rrggbb or a name like red, green or blue",
+                        color
+                    )))
+                }
+            }
+        }
+    };
+
+    Ok(Rgba([rgb[0], rgb[1], rgb[2], 255]))
+}
+
+/// Black or white, whichever reads better on the given background
+fn contrasting(background: Rgba<u8>) -> Rgba<u8> {
+    let [r, g, b, _] = background.0;
+    let luminance = 0.299 * r as f32 + 0.587 * g as f32 + 0.114 * b as f32;
+    if luminance > 150.0 {
+        Rgba([0, 0, 0, 255])
+    } else {
+        Rgba([255, 255, 255, 255])
+    }
+}
+
+/// The definition of the `annotate_image` tool
+pub fn tool() -> Tool {
+    Tool::new(
+        "annotate_image",
+        indoc! {r#"
+            Draw rectangles, arrows and text labels on an image file and return the result,
+            e.g. to point at specific UI elements in a screenshot when reporting findings.
+
+            Coordinates are pixels in the original image, with the origin at the top left.
+            Annotations are objects with a "type" of:
+            - "rectangle": x, y, width, height and an optional label drawn above it
+            - "arrow": from [x, y] and to [x, y], with the head at "to" and an optional label
+              at "from"
+            - "text": x, y, text and an optional size in pixels
+            Each can have a color as #rrggbb or a name such as red, green, blue or yellow
+            (default red).
+
+            The result is returned like image_processor, or saved as a full-resolution PNG to
+            output_path.
+        "#},
+        object!({
+            "type": "object",
+            "required": ["path", "annotations"],
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "Absolute path to the image file to annotate"
+                },
+                "annotations": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "required": ["type"],
+                        "properties": {
+                            "type": {"type": "string", "enum": ["rectangle", "arrow", "text"]},
+                            "x": {"type": "integer"},
+                            "y": {"type": "integer"},
+                            "width": {"type": "integer"},
+                            "height": {"type": "integer"},
+                            "from": {"type": "array", "items": {"type": "integer"}, "minItems": 2, "maxItems": 2},
+                            "to": {"type": "array", "items": {"type": "integer"}, "minItems": 2, "maxItems": 2},
+                            "text": {"type": "string"},
+                            "label": {"type": "string"},
+                            "size": {"type": "number"},
+                            "color": {"type": "string"}
+                        }
+                    }
+                },
+                "output_path": {
+                    "type": "string",
+                    "description": "Optional: absolute path of a .png file to save the annotated image to instead of returning it"
+                },
+                "max_width": {
+                    "type": "integer",
+                    "description": "Optional: width in pixels to scale the result down to (default 768, 0 keeps the original size)"
+                },
+                "format": {
+                    "type": "string",
+                    "enum": ["png", "jpeg", "webp"],
+                    "description": "Optional: encoding of the returned image (default png)"
+                },
+                "quality": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": 100,
+                    "description": "Optional: JPEG quality (default 85)"
+                }
+            }
+        }),
+    )
+    .annotate(ToolAnnotations {
+        title: Some("Annotate Image".to_string()),
+        read_only_hint: Some(false),
+        destructive_hint: Some(false),
+        idempotent_hint: Some(true),
+        open_world_hint: Some(false),
+    })
+}
+
+impl DeveloperRouter {
+    pub(super) async fn annotate_image(
+        &self,
+        params: Value,
+        notifier: &mpsc::Sender<JsonRpcMessage>,
+    ) -> Result<Vec<Content>, ToolError> {
+        let path_str = params
+            .get("path")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| ToolError::InvalidParameters("Missing 'path' parameter".into()))?;
+        let annotations: Vec<Annotation> = params
+            .get("annotations")
+            .cloned()
+            .map(serde_json::from_value)
+            .transpose()
+            .map_err(|e| ToolError::InvalidParameters(format!("Invalid annotations: {}", e)))?
+            .ok_or_else(|| {
+                ToolError::InvalidParameters("Missing 'annotations' parameter".into())
+            })?;
+        let options = ImageOptions::from_params(&params)?;
+
+        let (path, image) = self.open_image(path_str).await?;
+        let mut image = image.to_rgba8();
+        let skipped_labels = annotate(&mut image, &annotations)?;
+
+        let mut message = format!(
+            "Drew {} annotations on {}",
+            annotations.len(),
+            path.display()
+        );
+        if skipped_labels > 0 {
+            message.push_str(&format!(
+                ". {} text labels were skipped because no font was found, set GOOSE_ANNOTATION_FONT to a .ttf file to enable them",
+                skipped_labels
+            ));
+        }
+
+        if let Some(output_path) = params.get("output_path").and_then(|v| v.as_str()) {
+            let saved = self
+                .save_png(&image, output_path, "annotate_image", notifier)
+                .await?;
+            message.push_str(&format!(", saved to {}", saved.display()));
+            return Ok(vec![
+                Content::text(message.clone()).with_audience(vec![Role::Assistant]),
+                Content::text(message)
+                    .with_audience(vec![Role::User])
+                    .with_priority(0.0),
+            ]);
+        }
+
+        let image = options.resize(DynamicImage::ImageRgba8(image));
+        let data = base64::prelude::BASE64_STANDARD.encode(options.encode(&image)?);
+
+        Ok(vec![
+            Content::text(message).with_audience(vec![Role::Assistant]),
+            Content::image(data, options.format.mime_type()).with_priority(0.0),
+        ])
+    }
+
+    async fn open_image(&self, path_str: &str) -> Result<(PathBuf, DynamicImage), ToolError> {
+        let path = self.checked_image_path(path_str)?;
+        let image = rasterize::load_image(&path, &self.workspace).await?;
+        Ok((path, image))
+    }
+}
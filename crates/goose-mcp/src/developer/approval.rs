@@ -0,0 +1,452 @@
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+
+use mcp_core::handler::ToolError;
+use mcp_server::confirm::{confirm, PendingAction};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use rmcp::model::JsonRpcMessage;
+use serde_json::Value;
+use tokio::{process::Command, sync::mpsc};
+
+use super::locale::Catalog;
+use super::shell::expand_path;
+
+static RM_COMMAND: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?:^\s*|[;&|(]\s*|\bsudo\s+|\bxargs\s+)rm\s").unwrap());
+static FORCE_PUSH_COMMAND: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"\bgit\s+(?:-\S+\s+)*push\b[^;&|]*(?:\s--force(?:-with-lease)?\b|\s-[a-zA-Z]*f\b|\s\+\S)",
+    )
+    .unwrap()
+});
+
+/// Destructive operations that can be configured to require user approval
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ApprovalRule {
+    WriteTrackedFiles,
+    Overwrite,
+    Remove,
+    ForcePush,
+}
+
+impl ApprovalRule {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "write_tracked_files" => Some(Self::WriteTrackedFiles),
+            "overwrite" => Some(Self::Overwrite),
+            "rm" => Some(Self::Remove),
+            "force_push" => Some(Self::ForcePush),
+            _ => None,
+        }
+    }
+}
+
+/// Which operations need approval, read from `GOOSE_REQUIRE_APPROVAL` as a JSON list of
+/// `write_tracked_files`, `overwrite` (replacing an existing file with `write`), `rm` and
+/// `force_push`. Nothing requires approval by default.
+#[derive(Debug, Clone, Default)]
+pub struct ApprovalPolicy {
+    rules: HashSet<ApprovalRule>,
+}
+
+impl ApprovalPolicy {
+    pub fn from_env() -> Self {
+        let names: Vec<String> = std::env::var("GOOSE_REQUIRE_APPROVAL")
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default();
+
+        let rules = names
+            .iter()
+            .filter_map(|name| {
+                let rule = ApprovalRule::from_name(name);
+                if rule.is_none() {
+                    tracing::warn!("Unknown approval rule '{}'", name);
+                }
+                rule
+            })
+            .collect();
+
+        Self { rules }
+    }
+
+    /// Describe the pending action, in the catalog's language, if this tool call needs
+    /// approval before it runs
+    pub async fn pending_action(
+        &self,
+        tool_name: &str,
+        arguments: &Value,
+        catalog: &Catalog,
+    ) -> Option<PendingAction> {
+        if self.rules.is_empty() {
+            return None;
+        }
+
+        match tool_name {
+            "shell" => {
+                let command = arguments.get("command").and_then(|v| v.as_str())?;
+                if self.rules.contains(&ApprovalRule::ForcePush)
+                    && FORCE_PUSH_COMMAND.is_match(command)
+                {
+                    Some(
+                        PendingAction::new(
+                            "force_push",
+                            catalog.text("approval.force_push", "Force-push to the remote", &[]),
+                        )
+                        .with_target(command)
+                        .with_risk(catalog.text(
+                            "approval.force_push_risk",
+                            "Commits on the remote can be discarded.",
+                            &[],
+                        )),
+                    )
+                } else if self.rules.contains(&ApprovalRule::Remove) && RM_COMMAND.is_match(command)
+                {
+                    Some(
+                        PendingAction::new(
+                            "delete",
+                            catalog.text(
+                                "approval.delete",
+                                "Run a command that deletes files",
+                                &[],
+                            ),
+                        )
+                        .with_target(command)
+                        .with_risk(catalog.text(
+                            "approval.delete_risk",
+                            "Deleted files can't be restored with undo_edit.",
+                            &[],
+                        )),
+                    )
+                } else {
+                    None
+                }
+            }
+            "text_editor" => {
+                let command = arguments.get("command").and_then(|v| v.as_str())?;
+                if command == "view" {
+                    return None;
+                }
+                let path = arguments.get("path").and_then(|v| v.as_str())?;
+                let path = expand_path(path);
+                if command == "write"
+                    && self.rules.contains(&ApprovalRule::Overwrite)
+                    && Path::new(&path).is_file()
+                {
+                    let summary = catalog.text(
+                        "approval.overwrite",
+                        "Overwrite the file {path}",
+                        &[("path", &path)],
+                    );
+                    Some(
+                        PendingAction::new("overwrite", summary)
+                            .with_target(path)
+                            .with_risk(catalog.text(
+                                "approval.overwrite_risk",
+                                "The current contents are replaced entirely.",
+                                &[],
+                            )),
+                    )
+                } else if self.rules.contains(&ApprovalRule::WriteTrackedFiles)
+                    && is_tracked_by_git(Path::new(&path)).await
+                {
+                    Some(
+                        PendingAction::new(
+                            "write_tracked_file",
+                            catalog.text(
+                                "approval.write_tracked_file",
+                                "Modify the git-tracked file {path} ({command})",
+                                &[("path", &path), ("command", command)],
+                            ),
+                        )
+                        .with_target(path),
+                    )
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// The approval a rename needs before changing `paths`, when tracked files may only be
+    /// changed with approval and any of them is tracked
+    pub async fn rename_action(
+        &self,
+        catalog: &Catalog,
+        symbol: &str,
+        paths: &[&Path],
+    ) -> Option<PendingAction> {
+        if !self.rules.contains(&ApprovalRule::WriteTrackedFiles) {
+            return None;
+        }
+        let tracked = tracked_paths(paths.iter().copied()).await;
+        if tracked.is_empty() {
+            return None;
+        }
+        let count = tracked.len().to_string();
+        let action = PendingAction::new(
+            "write_tracked_file",
+            catalog.text(
+                "approval.rename_tracked_files",
+                "Rename {symbol} in {count} git-tracked files",
+                &[("symbol", symbol), ("count", &count)],
+            ),
+        );
+        Some(tracked.into_iter().fold(action, PendingAction::with_target))
+    }
+
+    /// The approval a dependency change needs before the package manager rewrites `paths`,
+    /// the manifest and lockfile, when tracked files may only be changed with approval
+    pub async fn dependency_action(
+        &self,
+        catalog: &Catalog,
+        command: &str,
+        package: &str,
+        paths: &[PathBuf],
+    ) -> Option<PendingAction> {
+        if !self.rules.contains(&ApprovalRule::WriteTrackedFiles) {
+            return None;
+        }
+        let tracked = tracked_paths(paths.iter().map(PathBuf::as_path)).await;
+        if tracked.is_empty() {
+            return None;
+        }
+        let action = PendingAction::new(
+            "write_tracked_file",
+            catalog.text(
+                "approval.change_dependency",
+                "Change the dependency {package} ({command}) in git-tracked files",
+                &[("package", package), ("command", command)],
+            ),
+        );
+        Some(tracked.into_iter().fold(action, PendingAction::with_target))
+    }
+
+    /// The approval formatting needs before rewriting `paths`, when tracked files may only be
+    /// changed with approval and any of them is tracked
+    pub async fn format_action(&self, catalog: &Catalog, paths: &[&Path]) -> Option<PendingAction> {
+        if !self.rules.contains(&ApprovalRule::WriteTrackedFiles) {
+            return None;
+        }
+        let tracked = tracked_paths(paths.iter().copied()).await;
+        if tracked.is_empty() {
+            return None;
+        }
+        let count = tracked.len().to_string();
+        let action = PendingAction::new(
+            "write_tracked_file",
+            catalog.text(
+                "approval.format_tracked_files",
+                "Reformat {count} git-tracked files",
+                &[("count", &count)],
+            ),
+        );
+        Some(tracked.into_iter().fold(action, PendingAction::with_target))
+    }
+
+    /// The approval a tool other than text_editor needs before writing `path` for `command`,
+    /// like a download or a saved screenshot, the same a text_editor write to it would need
+    pub async fn write_action(
+        &self,
+        catalog: &Catalog,
+        path: &Path,
+        command: &str,
+    ) -> Option<PendingAction> {
+        let target = path.display().to_string();
+        if self.rules.contains(&ApprovalRule::Overwrite) && path.is_file() {
+            Some(
+                PendingAction::new(
+                    "overwrite",
+                    catalog.text(
+                        "approval.overwrite",
+                        "Overwrite the file {path}",
+                        &[("path", &target)],
+                    ),
+                )
+                .with_target(target)
+                .with_risk(catalog.text(
+                    "approval.overwrite_risk",
+                    "The current contents are replaced entirely.",
+                    &[],
+                )),
+            )
+        } else if self.rules.contains(&ApprovalRule::WriteTrackedFiles)
+            && is_tracked_by_git(path).await
+        {
+            Some(
+                PendingAction::new(
+                    "write_tracked_file",
+                    catalog.text(
+                        "approval.write_tracked_file",
+                        "Modify the git-tracked file {path} ({command})",
+                        &[("path", &target), ("command", command)],
+                    ),
+                )
+                .with_target(target),
+            )
+        } else {
+            None
+        }
+    }
+}
+
+/// The paths, as shown to the user, of those tracked by git
+async fn tracked_paths(paths: impl Iterator<Item = &Path>) -> Vec<String> {
+    let mut tracked = Vec::new();
+    for path in paths {
+        if is_tracked_by_git(path).await {
+            tracked.push(path.display().to_string());
+        }
+    }
+    tracked
+}
+
+/// Amending a commit that hasn't been pushed, which always needs approval since it rewrites
+/// history
+pub fn amend_action(catalog: &Catalog, commit: &str) -> PendingAction {
+    PendingAction::new(
+        "rewrite_history",
+        catalog.text(
+            "approval.amend",
+            "Amend the last commit ({commit})",
+            &[("commit", commit)],
+        ),
+    )
+    .with_target(commit)
+    .with_risk(catalog.text(
+        "approval.amend_risk",
+        "The commit is replaced by a new one, and its old contents are only left in the reflog.",
+        &[],
+    ))
+}
+
+/// Dropping stashed changes, which always needs approval since they can't be restored
+pub fn stash_drop_action(catalog: &Catalog, entry: &str) -> PendingAction {
+    PendingAction::new(
+        "stash_drop",
+        catalog.text(
+            "approval.stash_drop",
+            "Drop the stashed changes {entry}",
+            &[("entry", entry)],
+        ),
+    )
+    .with_target(entry)
+    .with_risk(catalog.text(
+        "approval.stash_drop_risk",
+        "The stashed changes are discarded.",
+        &[],
+    ))
+}
+
+/// Pushing a branch and opening a draft pull request from it, which always needs approval
+/// since both are visible to others
+pub fn draft_request_action(catalog: &Catalog, branch: &str, title: &str) -> PendingAction {
+    PendingAction::new(
+        "open_draft_request",
+        catalog.text(
+            "approval.draft_request",
+            "Push {branch} and open the draft \"{title}\" from it",
+            &[("branch", branch), ("title", title)],
+        ),
+    )
+    .with_target(branch)
+    .with_risk(catalog.text(
+        "approval.draft_request_risk",
+        "The branch and the draft are published to everyone with access to the repository.",
+        &[],
+    ))
+}
+
+/// Restarting the pods of a deployment, which always needs approval since it affects
+/// whoever uses the cluster
+pub fn restart_deployment_action(
+    catalog: &Catalog,
+    deployment: &str,
+    context: &str,
+) -> PendingAction {
+    PendingAction::new(
+        "restart_deployment",
+        catalog.text(
+            "approval.restart_deployment",
+            "Restart the pods of deployment {deployment} in {context}",
+            &[("deployment", deployment), ("context", context)],
+        ),
+    )
+    .with_target(deployment)
+    .with_risk(catalog.text(
+        "approval.restart_deployment_risk",
+        "Requests may fail while the pods are replaced, and the running pods' state is lost.",
+        &[],
+    ))
+}
+
+/// Letting shell commands read a secret, which always needs approval since the model
+/// chooses the commands
+pub fn expose_secret_action(catalog: &Catalog, secret: &str, variable: &str) -> PendingAction {
+    PendingAction::new(
+        "expose_secret",
+        catalog.text(
+            "approval.expose_secret",
+            "Let shell commands in this session read the secret {secret} as ${variable}",
+            &[("secret", secret), ("variable", variable)],
+        ),
+    )
+    .with_target(secret)
+    .with_risk(catalog.text(
+        "approval.expose_secret_risk",
+        "The value is hidden from the commands' output, but a command can still send it elsewhere.",
+        &[],
+    ))
+}
+
+/// Deleting a stored secret, which always needs approval since it can't be restored
+pub fn delete_secret_action(catalog: &Catalog, secret: &str) -> PendingAction {
+    PendingAction::new(
+        "delete_secret",
+        catalog.text(
+            "approval.delete_secret",
+            "Delete the secret {secret} from the keyring",
+            &[("secret", secret)],
+        ),
+    )
+    .with_target(secret)
+    .with_risk(catalog.text(
+        "approval.delete_secret_risk",
+        "The value can't be restored.",
+        &[],
+    ))
+}
+
+async fn is_tracked_by_git(path: &Path) -> bool {
+    let (Some(dir), Some(name)) = (path.parent(), path.file_name()) else {
+        return false;
+    };
+    if !dir.is_dir() {
+        return false;
+    }
+
+    Command::new("git")
+        .arg("ls-files")
+        .arg("--error-unmatch")
+        .arg(name)
+        .current_dir(dir)
+        .output()
+        .await
+        .map(|output| output.status.success())
+        .unwrap_or(false)
+}
+
+/// Ask the user to approve an action before running it, failing the tool call unless they
+/// explicitly accept
+pub async fn request_approval(
+    notifier: &mpsc::Sender<JsonRpcMessage>,
+    action: &PendingAction,
+) -> Result<(), ToolError> {
+    confirm(notifier, action)
+        .await
+        .map_err(|e| ToolError::ExecutionError(e.to_string()))
+}
@@ -0,0 +1,401 @@
+use std::{
+    fs::OpenOptions,
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+
+use chrono::{DateTime, Utc};
+use etcetera::{choose_app_strategy, AppStrategy};
+use indoc::indoc;
+use mcp_core::handler::ToolError;
+use rmcp::model::{Content, Role, Tool, ToolAnnotations};
+use rmcp::object;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+use super::DeveloperRouter;
+
+/// Arguments that carry file contents, logged as a hash and length instead of verbatim
+const CONTENT_ARGUMENTS: [&str; 3] = ["file_text", "old_str", "new_str"];
+
+/// Largest file hashed before and after a call. Bigger ones are logged without a hash rather
+/// than holding up the call to read them.
+const MAX_HASHED_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Append-only JSONL record of every tool call made through the developer extension.
+///
+/// Enabled with `GOOSE_AUDIT_LOG=true`, written to `audit.jsonl` in the goose data
+/// directory unless `GOOSE_AUDIT_LOG_PATH` points somewhere else.
+#[derive(Debug, Clone)]
+pub struct AuditLog {
+    path: PathBuf,
+    write_lock: Arc<Mutex<()>>,
+}
+
+/// A tool call that has started but whose outcome is not known yet
+pub struct PendingEntry {
+    timestamp: DateTime<Utc>,
+    started: Instant,
+    tool: String,
+    arguments: Value,
+    paths: Vec<(PathBuf, Option<String>)>,
+}
+
+impl AuditLog {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            write_lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    pub fn from_env() -> Option<Self> {
+        let enabled = std::env::var("GOOSE_AUDIT_LOG")
+            .map(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "yes"))
+            .unwrap_or(false);
+        if !enabled {
+            return None;
+        }
+
+        // choose_app_strategy().data_dir()
+        // - macOS/Linux: ~/.local/share/goose/
+        // - Windows:     ~\AppData\Roaming\Block\goose\data\
+        let path = std::env::var("GOOSE_AUDIT_LOG_PATH")
+            .ok()
+            .filter(|p| !p.is_empty())
+            .map(|p| PathBuf::from(shellexpand::tilde(&p).to_string()))
+            .unwrap_or_else(|| {
+                choose_app_strategy(crate::APP_STRATEGY.clone())
+                    .map(|strategy| strategy.in_data_dir("audit.jsonl"))
+                    .unwrap_or_else(|_| {
+                        PathBuf::from(
+                            shellexpand::tilde("~/.local/share/goose/audit.jsonl").to_string(),
+                        )
+                    })
+            });
+
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+
+        Some(Self::new(path))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Capture the arguments and the state of any target files before the call runs
+    pub async fn begin(&self, tool: &str, arguments: &Value) -> PendingEntry {
+        let paths = hash_files(target_paths(arguments)).await;
+
+        PendingEntry {
+            timestamp: Utc::now(),
+            started: Instant::now(),
+            tool: tool.to_string(),
+            arguments: redact_arguments(arguments),
+            paths,
+        }
+    }
+
+    /// Record the outcome of a call started with [`AuditLog::begin`]
+    pub async fn finish(&self, entry: PendingEntry, result: &Result<Vec<Content>, ToolError>) {
+        let (targets, befores): (Vec<_>, Vec<_>) = entry.paths.into_iter().unzip();
+        let paths: Vec<Value> = hash_files(targets)
+            .await
+            .into_iter()
+            .zip(befores)
+            .map(|((path, after), before)| {
+                json!({
+                    "path": path.to_string_lossy(),
+                    "before_sha256": before,
+                    "after_sha256": after,
+                })
+            })
+            .collect();
+
+        let (outcome, error) = match result {
+            Ok(_) => ("success", None),
+            Err(e) => ("error", Some(e.to_string())),
+        };
+
+        let record = json!({
+            "timestamp": entry.timestamp.to_rfc3339(),
+            "tool": entry.tool,
+            "arguments": entry.arguments,
+            "paths": paths,
+            "outcome": outcome,
+            "error": error,
+            "duration_ms": entry.started.elapsed().as_millis() as u64,
+        });
+
+        if let Err(e) = self.append(&record) {
+            tracing::warn!("Failed to write audit log entry: {}", e);
+        }
+    }
+
+    fn append(&self, record: &Value) -> std::io::Result<()> {
+        let _guard = self.write_lock.lock().unwrap();
+        let mut options = OpenOptions::new();
+        options.create(true).append(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options.open(&self.path)?;
+        writeln!(file, "{}", record)
+    }
+
+    /// Read back entries matching the `tool`, `path`, `since` and `errors_only` filters,
+    /// newest first, capped at `limit`
+    pub fn query(&self, params: &Value) -> Result<Vec<Value>, ToolError> {
+        let tool = params.get("tool").and_then(|v| v.as_str());
+        let path = params.get("path").and_then(|v| v.as_str());
+        let errors_only = params
+            .get("errors_only")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        let limit = params.get("limit").and_then(|v| v.as_u64()).unwrap_or(50) as usize;
+        let since = params
+            .get("since")
+            .and_then(|v| v.as_str())
+            .map(|s| {
+                DateTime::parse_from_rfc3339(s)
+                    .map(|dt| dt.with_timezone(&Utc))
+                    .map_err(|e| {
+                        ToolError::InvalidParameters(format!(
+                            "'since' must be an RFC 3339 timestamp: {}",
+                            e
+                        ))
+                    })
+            })
+            .transpose()?;
+
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let file = std::fs::File::open(&self.path)
+            .map_err(|e| ToolError::ExecutionError(format!("Failed to open audit log: {}", e)))?;
+
+        let mut entries: Vec<Value> = BufReader::new(file)
+            .lines()
+            .map_while(Result::ok)
+            .filter_map(|line| serde_json::from_str::<Value>(&line).ok())
+            .filter(|entry| tool.is_none_or(|t| entry["tool"] == t))
+            .filter(|entry| !errors_only || entry["outcome"] == "error")
+            .filter(|entry| {
+                path.is_none_or(|p| {
+                    entry["paths"].as_array().is_some_and(|paths| {
+                        paths
+                            .iter()
+                            .any(|e| e["path"].as_str().is_some_and(|s| s.contains(p)))
+                    })
+                })
+            })
+            .filter(|entry| {
+                since.is_none_or(|since| {
+                    entry["timestamp"]
+                        .as_str()
+                        .and_then(|ts| DateTime::parse_from_rfc3339(ts).ok())
+                        .is_some_and(|ts| ts.with_timezone(&Utc) >= since)
+                })
+            })
+            .collect();
+
+        entries.reverse();
+        entries.truncate(limit);
+        Ok(entries)
+    }
+}
+
+/// Paths a call is going to touch, taken from its `path` argument
+fn target_paths(arguments: &Value) -> Vec<PathBuf> {
+    arguments
+        .get("path")
+        .and_then(|v| v.as_str())
+        .map(|p| vec![PathBuf::from(shellexpand::tilde(p).to_string())])
+        .unwrap_or_default()
+}
+
+fn redact_arguments(arguments: &Value) -> Value {
+    let mut redacted = arguments.clone();
+    if let Some(object) = redacted.as_object_mut() {
+        for key in CONTENT_ARGUMENTS {
+            if let Some(text) = object.get(key).and_then(|v| v.as_str()) {
+                let summary = json!({
+                    "sha256": hash_bytes(text.as_bytes()),
+                    "length": text.len(),
+                });
+                object.insert(key.to_string(), summary);
+            }
+        }
+    }
+    redacted
+}
+
+/// Each path with its hash, read on a blocking thread since the files can be big
+async fn hash_files(paths: Vec<PathBuf>) -> Vec<(PathBuf, Option<String>)> {
+    if paths.is_empty() {
+        return Vec::new();
+    }
+    tokio::task::spawn_blocking(|| {
+        paths
+            .into_iter()
+            .map(|path| {
+                let hash = hash_file(&path);
+                (path, hash)
+            })
+            .collect()
+    })
+    .await
+    .unwrap_or_default()
+}
+
+fn hash_file(path: &Path) -> Option<String> {
+    let metadata = std::fs::metadata(path).ok()?;
+    if !metadata.is_file() || metadata.len() > MAX_HASHED_BYTES {
+        return None;
+    }
+    std::fs::read(path).ok().map(|bytes| hash_bytes(&bytes))
+}
+
+fn hash_bytes(bytes: &[u8]) -> String {
+    let digest = Sha256::digest(bytes);
+    digest.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// The definition of the `audit` tool
+pub fn tool() -> Tool {
+    Tool::new(
+        "audit",
+        indoc! {r#"
+            Query the audit log of tool calls made by the developer extension.
+
+            Entries are returned newest first as JSON lines, each with the tool name,
+            arguments (file contents are reduced to a sha256 and length), target paths with
+            their sha256 before and after the call, the outcome and the duration.
+        "#},
+        object!({
+            "type": "object",
+            "required": [],
+            "properties": {
+                "tool": {"type": "string", "description": "Only return calls to this tool"},
+                "path": {"type": "string", "description": "Only return calls whose target path contains this string"},
+                "since": {"type": "string", "description": "Only return calls at or after this RFC 3339 timestamp"},
+                "errors_only": {"type": "boolean", "description": "Only return failed calls"},
+                "limit": {"type": "integer", "default": 50, "description": "Maximum number of entries to return"}
+            }
+        }),
+    )
+    .annotate(ToolAnnotations {
+        title: Some("Query the audit log".to_string()),
+        read_only_hint: Some(true),
+        destructive_hint: Some(false),
+        idempotent_hint: Some(false),
+        open_world_hint: Some(false),
+    })
+}
+
+impl DeveloperRouter {
+    pub(super) async fn audit(&self, params: Value) -> Result<Vec<Content>, ToolError> {
+        let audit_log = self
+            .audit_log
+            .as_ref()
+            .ok_or_else(|| ToolError::ExecutionError("The audit log is not enabled".into()))?;
+
+        let entries = audit_log.query(&params)?;
+        let lines = entries
+            .iter()
+            .map(|entry| entry.to_string())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        Ok(vec![
+            Content::text(format!(
+                "{} audit entries from {}:\n{}",
+                entries.len(),
+                audit_log.path().display(),
+                lines
+            ))
+            .with_audience(vec![Role::Assistant]),
+            Content::text(lines)
+                .with_audience(vec![Role::User])
+                .with_priority(0.0),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries(log: &AuditLog) -> Vec<Value> {
+        std::fs::read_to_string(log.path())
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_entries_record_the_call_and_its_outcome() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = AuditLog::new(dir.path().join("audit.jsonl"));
+        let arguments = json!({"command": "write", "file_text": "hello", "path": "/nowhere"});
+
+        let entry = log.begin("text_editor", &arguments).await;
+        log.finish(entry, &Ok(vec![])).await;
+        let entry = log.begin("shell", &json!({"command": "false"})).await;
+        log.finish(entry, &Err(ToolError::ExecutionError("exit 1".into())))
+            .await;
+
+        let entries = entries(&log);
+        assert_eq!(entries.len(), 2);
+        let written = &entries[0];
+        assert_eq!(written["tool"], "text_editor");
+        assert_eq!(written["outcome"], "success");
+        assert!(written["error"].is_null());
+        assert_eq!(written["arguments"]["command"], "write");
+        assert_eq!(
+            written["arguments"]["file_text"],
+            json!({"sha256": hash_bytes(b"hello"), "length": 5})
+        );
+        assert_eq!(
+            written["paths"],
+            json!([{"path": "/nowhere", "before_sha256": null, "after_sha256": null}])
+        );
+        assert!(DateTime::parse_from_rfc3339(written["timestamp"].as_str().unwrap()).is_ok());
+        assert!(written["duration_ms"].is_u64());
+
+        let failed = &entries[1];
+        assert_eq!(failed["outcome"], "error");
+        assert!(failed["error"].as_str().unwrap().contains("exit 1"));
+        assert_eq!(failed["paths"], json!([]));
+    }
+
+    #[tokio::test]
+    async fn test_hashes_show_whether_the_file_changed() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = AuditLog::new(dir.path().join("audit.jsonl"));
+        let file = dir.path().join("notes.txt");
+        let arguments = json!({"path": file});
+        std::fs::write(&file, "before").unwrap();
+
+        let entry = log.begin("text_editor", &arguments).await;
+        std::fs::write(&file, "after").unwrap();
+        log.finish(entry, &Ok(vec![])).await;
+        let entry = log.begin("text_editor", &arguments).await;
+        log.finish(entry, &Ok(vec![])).await;
+
+        let entries = entries(&log);
+        let changed = &entries[0]["paths"][0];
+        assert_eq!(changed["before_sha256"], hash_bytes(b"before"));
+        assert_eq!(changed["after_sha256"], hash_bytes(b"after"));
+        let unchanged = &entries[1]["paths"][0];
+        assert_eq!(unchanged["before_sha256"], unchanged["after_sha256"]);
+    }
+}
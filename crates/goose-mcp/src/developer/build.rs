@@ -0,0 +1,522 @@
+use std::collections::HashSet;
+use std::path::Path;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use indoc::indoc;
+use mcp_core::handler::ToolError;
+use mcp_server::structured::structured_content;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use rmcp::model::{Content, JsonRpcMessage, Tool, ToolAnnotations};
+use rmcp::object;
+use serde::Serialize;
+use serde_json::Value;
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::process::Command;
+use tokio::sync::mpsc;
+
+use super::progress::ProgressReporter;
+use super::shell::Spawner;
+use super::DeveloperRouter;
+
+const TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+/// Most diagnostics returned, errors first
+pub const MAX_DIAGNOSTICS: usize = 100;
+
+/// Lines of output returned when a build fails without diagnostics that explain it
+const OUTPUT_TAIL_LINES: usize = 40;
+
+/// `src/app.ts(3,5): error TS2322: Type 'string' is not assignable to type 'number'.`
+pub static TSC_DIAGNOSTIC: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(?P<file>[^\s(][^(]*)\((?P<line>\d+),(?P<column>\d+)\): (?P<severity>error|warning) (?P<code>TS\d+): (?P<message>.*)$").unwrap()
+});
+
+/// javac through gradle: `/src/App.java:12: error: cannot find symbol`
+static JAVAC_DIAGNOSTIC: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"^(?P<file>\S.*\.java):(?P<line>\d+): (?P<severity>error|warning): (?P<message>.*)$",
+    )
+    .unwrap()
+});
+
+/// kotlinc through gradle: `e: file:///src/App.kt:12:5 Unresolved reference: foo`
+static KOTLIN_DIAGNOSTIC: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(?P<severity>[ew]): (?:file://)?(?P<file>\S+\.kts?):(?P<line>\d+):(?P<column>\d+) (?P<message>.*)$")
+        .unwrap()
+});
+
+/// The compiler through maven: `[ERROR] /src/App.java:[12,5] cannot find symbol`
+pub static MAVEN_DIAGNOSTIC: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\[(?P<severity>ERROR|WARNING)\] (?P<file>\S.*\.(?:java|kt|scala)):\[(?P<line>\d+),(?P<column>\d+)\] (?P<message>.*)$")
+        .unwrap()
+});
+
+/// A build system, and how to get machine-readable output from it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BuildSystem {
+    Cargo,
+    Tsc,
+    Gradle,
+    Maven,
+}
+
+impl BuildSystem {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Cargo => "cargo",
+            Self::Tsc => "tsc",
+            Self::Gradle => "gradle",
+            Self::Maven => "maven",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "cargo" => Some(Self::Cargo),
+            "tsc" => Some(Self::Tsc),
+            "gradle" => Some(Self::Gradle),
+            "maven" => Some(Self::Maven),
+            _ => None,
+        }
+    }
+
+    /// The build system of the project in `dir`
+    pub fn detect(dir: &Path) -> Option<Self> {
+        let has = |name: &str| dir.join(name).is_file();
+        if has("Cargo.toml") {
+            Some(Self::Cargo)
+        } else if has("tsconfig.json") {
+            Some(Self::Tsc)
+        } else if has("build.gradle") || has("build.gradle.kts") || has("settings.gradle.kts") {
+            Some(Self::Gradle)
+        } else if has("pom.xml") {
+            Some(Self::Maven)
+        } else {
+            None
+        }
+    }
+
+    /// The command line that builds the project in `dir`, followed by `args`
+    pub fn command(self, dir: &Path, args: &[String]) -> Vec<String> {
+        let wrapper = |name: &str, fallback: &str| {
+            let wrapper = dir.join(if cfg!(windows) {
+                format!("{}.bat", name)
+            } else {
+                name.to_string()
+            });
+            if wrapper.is_file() {
+                wrapper.to_string_lossy().into_owned()
+            } else {
+                fallback.to_string()
+            }
+        };
+        let mut command: Vec<String> = match self {
+            Self::Cargo => vec![
+                "cargo".into(),
+                "build".into(),
+                "--message-format=json".into(),
+            ],
+            Self::Tsc => {
+                let local = dir.join("node_modules/.bin/tsc");
+                let tsc = if local.is_file() {
+                    local.to_string_lossy().into_owned()
+                } else {
+                    "tsc".to_string()
+                };
+                vec![tsc, "--noEmit".into(), "--pretty".into(), "false".into()]
+            }
+            Self::Gradle => vec![
+                wrapper("gradlew", "gradle"),
+                "build".into(),
+                "--console=plain".into(),
+            ],
+            Self::Maven => vec![
+                wrapper("mvnw", "mvn"),
+                "--batch-mode".into(),
+                "compile".into(),
+            ],
+        };
+        // For cargo, a different subcommand like `check` or `test --no-run` replaces build
+        if self == Self::Cargo && args.first().is_some_and(|arg| !arg.starts_with('-')) {
+            command[1] = args[0].clone();
+            command.extend(args[1..].iter().cloned());
+        } else {
+            command.extend(args.iter().cloned());
+        }
+        command
+    }
+}
+
+/// A fix the compiler proposes
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, Hash)]
+pub struct Suggestion {
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replacement: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<u64>,
+}
+
+/// An error or warning, at a place in a file when the compiler gave one
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, Hash)]
+pub struct Diagnostic {
+    pub severity: String,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<u64>,
+    /// Notes that explain the error, like where a moved value was used
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub notes: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub suggestions: Vec<Suggestion>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BuildReport {
+    pub system: BuildSystem,
+    pub command: String,
+    pub success: bool,
+    pub errors: usize,
+    pub warnings: usize,
+    pub diagnostics: Vec<Diagnostic>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
+    /// The end of the output, when the build failed without an error explaining why
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+    pub elapsed_ms: u64,
+}
+
+/// Run the build in `dir` and parse the diagnostics from its output
+pub async fn run(
+    spawner: &Spawner,
+    system: BuildSystem,
+    dir: &Path,
+    args: &[String],
+    progress: Option<&ProgressReporter>,
+) -> Result<BuildReport, ToolError> {
+    let command = system.command(dir, args);
+    let started = Instant::now();
+    let mut child = spawner
+        .spawn(
+            Command::new(&command[0])
+                .args(&command[1..])
+                .current_dir(dir)
+                .env("CARGO_TERM_COLOR", "never")
+                .env("NO_COLOR", "1")
+                .stdin(Stdio::null())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped()),
+        )
+        .await
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => ToolError::ExecutionError(format!(
+                "{} is not installed, and is needed to build this project",
+                command[0]
+            )),
+            _ => ToolError::ExecutionError(format!("Failed to run {}: {}", command[0], e)),
+        })?;
+
+    // Builds can print a lot, so the lines read so far are reported as progress
+    let lines_read = &AtomicUsize::new(0);
+    let read = move |pipe: Option<Box<dyn AsyncRead + Unpin + Send>>| async move {
+        let mut lines = Vec::new();
+        let Some(pipe) = pipe else {
+            return lines;
+        };
+        let mut reader = BufReader::new(pipe).lines();
+        while let Ok(Some(line)) = reader.next_line().await {
+            let count = lines_read.fetch_add(1, Ordering::Relaxed) + 1;
+            if let Some(progress) = progress {
+                progress.report(count as f64, None);
+            }
+            lines.push(line);
+        }
+        lines
+    };
+    let stdout = child
+        .stdout
+        .take()
+        .map(|pipe| Box::new(pipe) as Box<dyn AsyncRead + Unpin + Send>);
+    let stderr = child
+        .stderr
+        .take()
+        .map(|pipe| Box::new(pipe) as Box<dyn AsyncRead + Unpin + Send>);
+    let finished = async {
+        let (stdout, stderr) = tokio::join!(read(stdout), read(stderr));
+        let status = child.wait().await;
+        (stdout, stderr, status)
+    };
+    let (stdout, stderr, status) = tokio::time::timeout(TIMEOUT, finished).await.map_err(|_| {
+        ToolError::ExecutionError(format!(
+            "The build took longer than {} minutes",
+            TIMEOUT.as_secs() / 60
+        ))
+    })?;
+    let status = status
+        .map_err(|e| ToolError::ExecutionError(format!("Failed to run {}: {}", command[0], e)))?;
+
+    let mut diagnostics = match system {
+        BuildSystem::Cargo => parse_cargo(&stdout),
+        BuildSystem::Tsc => parse_lines(&stdout, &TSC_DIAGNOSTIC),
+        BuildSystem::Gradle => {
+            let mut diagnostics = parse_lines(&stderr, &JAVAC_DIAGNOSTIC);
+            diagnostics.extend(parse_lines(&stderr, &KOTLIN_DIAGNOSTIC));
+            diagnostics.extend(parse_lines(&stdout, &JAVAC_DIAGNOSTIC));
+            diagnostics.extend(parse_lines(&stdout, &KOTLIN_DIAGNOSTIC));
+            diagnostics
+        }
+        BuildSystem::Maven => parse_lines(&stdout, &MAVEN_DIAGNOSTIC),
+    };
+    // Paths are given relative to the project, as the compilers mostly do already
+    for diagnostic in &mut diagnostics {
+        if let Some(file) = &diagnostic.file {
+            if let Ok(relative) = Path::new(file).strip_prefix(dir) {
+                diagnostic.file = Some(relative.to_string_lossy().into_owned());
+            }
+        }
+    }
+    // Workspaces report the same warning once for each target that includes the file
+    let mut seen = HashSet::new();
+    diagnostics.retain(|diagnostic| seen.insert(diagnostic.clone()));
+    diagnostics.sort_by_key(|diagnostic| diagnostic.severity != "error");
+
+    let errors = diagnostics.iter().filter(|d| d.severity == "error").count();
+    let warnings = diagnostics
+        .iter()
+        .filter(|d| d.severity == "warning")
+        .count();
+    let truncated = diagnostics.len() > MAX_DIAGNOSTICS;
+    diagnostics.truncate(MAX_DIAGNOSTICS);
+
+    let success = status.success();
+    let output = (!success && errors == 0).then(|| {
+        // cargo's JSON lines say nothing a person can read, its stderr does
+        let lines: Vec<&String> = match system {
+            BuildSystem::Cargo => stderr.iter().collect(),
+            _ => stdout.iter().chain(stderr.iter()).collect(),
+        };
+        let tail = &lines[lines.len().saturating_sub(OUTPUT_TAIL_LINES)..];
+        tail.iter()
+            .map(|line| line.as_str())
+            .collect::<Vec<_>>()
+            .join("\n")
+    });
+
+    Ok(BuildReport {
+        system,
+        command: command.join(" "),
+        success,
+        errors,
+        warnings,
+        diagnostics,
+        truncated,
+        output,
+        elapsed_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+/// The diagnostics in the lines cargo prints with `--message-format=json`
+pub fn parse_cargo(lines: &[String]) -> Vec<Diagnostic> {
+    lines
+        .iter()
+        .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+        .filter(|message| message["reason"] == "compiler-message")
+        .filter_map(|message| rustc_diagnostic(&message["message"]))
+        .collect()
+}
+
+fn rustc_diagnostic(message: &Value) -> Option<Diagnostic> {
+    let severity = match message["level"].as_str()? {
+        "error" | "error: internal compiler error" => "error",
+        "warning" => "warning",
+        _ => return None,
+    };
+    let text = message["message"].as_str()?;
+    // The summaries at the end repeat what the other diagnostics said
+    if text.starts_with("aborting due to") || text.ends_with("emitted") {
+        return None;
+    }
+    let primary = message["spans"]
+        .as_array()
+        .and_then(|spans| spans.iter().find(|span| span["is_primary"] == true));
+
+    // The label says what is wrong at the span, e.g. "expected `u32`, found `&str`"
+    let mut notes: Vec<String> = primary
+        .and_then(|span| span["label"].as_str())
+        .map(str::to_string)
+        .into_iter()
+        .collect();
+    let mut suggestions = Vec::new();
+    for child in message["children"].as_array().into_iter().flatten() {
+        let Some(child_message) = child["message"].as_str() else {
+            continue;
+        };
+        let replacements: Vec<&Value> = child["spans"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter(|span| span["suggested_replacement"].is_string())
+            .collect();
+        if child["level"] == "help" || !replacements.is_empty() {
+            if replacements.is_empty() {
+                suggestions.push(Suggestion {
+                    message: child_message.to_string(),
+                    replacement: None,
+                    line: None,
+                    column: None,
+                });
+            }
+            for span in replacements {
+                suggestions.push(Suggestion {
+                    message: child_message.to_string(),
+                    replacement: span["suggested_replacement"].as_str().map(str::to_string),
+                    line: span["line_start"].as_u64(),
+                    column: span["column_start"].as_u64(),
+                });
+            }
+        } else {
+            notes.push(child_message.to_string());
+        }
+    }
+
+    Some(Diagnostic {
+        severity: severity.to_string(),
+        message: text.to_string(),
+        code: message["code"]["code"].as_str().map(str::to_string),
+        file: primary.and_then(|span| span["file_name"].as_str().map(str::to_string)),
+        line: primary.and_then(|span| span["line_start"].as_u64()),
+        column: primary.and_then(|span| span["column_start"].as_u64()),
+        notes,
+        suggestions,
+    })
+}
+
+/// The diagnostics in lines matching `pattern`, which names the groups file, line, severity
+/// and message, and optionally column and code
+pub fn parse_lines(lines: &[String], pattern: &Regex) -> Vec<Diagnostic> {
+    lines
+        .iter()
+        .filter_map(|line| pattern.captures(line.trim_end()))
+        .map(|captures| {
+            let group = |name: &str| captures.name(name).map(|m| m.as_str());
+            let number = |name: &str| group(name).and_then(|value| value.parse().ok());
+            let severity = match group("severity").unwrap_or_default() {
+                "error" | "ERROR" | "e" => "error",
+                _ => "warning",
+            };
+            Diagnostic {
+                severity: severity.to_string(),
+                message: group("message").unwrap_or_default().trim().to_string(),
+                code: group("code").map(str::to_string),
+                file: group("file").map(str::to_string),
+                line: number("line"),
+                column: number("column"),
+                notes: Vec::new(),
+                suggestions: Vec::new(),
+            }
+        })
+        .collect()
+}
+
+/// The definition of the `build` tool
+pub fn tool() -> Tool {
+    Tool::new(
+        "build",
+        indoc! {r#"
+            Build the project with cargo, tsc, gradle or maven and return the compiler's errors
+            and warnings with their file, line, code and suggested fixes, instead of the raw
+            build log. Errors come first, and at most 100 are returned.
+
+            The build system is found from the files in `path` (default the working
+            directory), or given as `system`. `args` are added to the command; for cargo, one
+            that isn't a flag replaces `build`, e.g. ["check", "--all-targets"]. When the build
+            fails without a diagnostic, the end of its output is returned instead.
+        "#},
+        object!({
+            "type": "object",
+            "required": [],
+            "properties": {
+                "path": {"type": "string", "description": "Absolute path of the project directory"},
+                "system": {
+                    "type": "string",
+                    "enum": ["cargo", "tsc", "gradle", "maven"]
+                },
+                "args": {
+                    "type": "array",
+                    "items": {"type": "string"},
+                    "description": "Extra arguments for the build command"
+                }
+            }
+        }),
+    )
+    .annotate(ToolAnnotations {
+        title: Some("Build the project".to_string()),
+        read_only_hint: Some(false),
+        destructive_hint: Some(false),
+        idempotent_hint: Some(true),
+        open_world_hint: Some(false),
+    })
+}
+
+impl DeveloperRouter {
+    pub(super) async fn build(
+        &self,
+        params: Value,
+        notifier: &mpsc::Sender<JsonRpcMessage>,
+    ) -> Result<Vec<Content>, ToolError> {
+        let dir = match params.get("path").and_then(Value::as_str) {
+            Some(path) => self.resolve_path(path)?,
+            None => self.cwd(),
+        };
+        if !dir.is_dir() {
+            return Err(ToolError::InvalidParameters(format!(
+                "'{}' is not a directory",
+                dir.display()
+            )));
+        }
+        let system = match params.get("system").and_then(Value::as_str) {
+            Some(name) => BuildSystem::from_name(name).ok_or_else(|| {
+                ToolError::InvalidParameters(format!("Unknown build system '{}'", name))
+            })?,
+            None => BuildSystem::detect(&dir).ok_or_else(|| {
+                ToolError::ExecutionError(format!(
+                    "No Cargo.toml, tsconfig.json, gradle build or pom.xml in {}. Pass `system` or `path`.",
+                    dir.display()
+                ))
+            })?,
+        };
+        let args: Vec<String> = match params.get("args") {
+            None | Some(Value::Null) => Vec::new(),
+            Some(args) => serde_json::from_value(args.clone()).map_err(|_| {
+                ToolError::InvalidParameters("'args' must be a list of strings".into())
+            })?,
+        };
+
+        let progress = ProgressReporter::requested(&params, notifier, || {
+            format!("Building with {}", system.name())
+        });
+        let report = run(&self.spawner(), system, &dir, &args, progress.as_ref()).await?;
+
+        let text = serde_json::to_string_pretty(&report)
+            .map_err(|e| ToolError::ExecutionError(format!("Failed to encode result: {}", e)))?;
+        let text = self.redactor.redact(&text).into_owned();
+        let mut content = vec![Content::text(text.clone())];
+        if let Ok(redacted) = serde_json::from_str::<Value>(&text) {
+            content.push(structured_content(&redacted));
+        }
+        Ok(content)
+    }
+}
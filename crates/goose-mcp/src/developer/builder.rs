@@ -0,0 +1,193 @@
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context, Result};
+use ignore::gitignore::GitignoreBuilder;
+use mcp_core::handler::ToolError;
+use rmcp::model::{Content, JsonRpcMessage, Tool};
+use serde_json::Value;
+use tokio::sync::mpsc;
+
+use super::custom_tools::CustomTool;
+
+use super::instructions::{InstructionLayer, Instructions};
+use super::reload::FileStamps;
+use super::secrets::SecretStore;
+use super::system::{Clock, Faults, FileSystem, RealFileSystem, System, SystemClock};
+use super::DeveloperRouter;
+
+/// Builds a [`DeveloperRouter`] from explicit settings instead of the process's current
+/// directory and the hints and ignore files in it.
+///
+/// Anything left unset is configured the way [`DeveloperRouter::new`] does it.
+#[derive(Debug, Clone, Default)]
+pub struct DeveloperRouterBuilder {
+    workspace_root: Option<PathBuf>,
+    ignore_patterns: Option<Vec<String>>,
+    hints: Option<String>,
+    tools: Option<Vec<String>>,
+    custom_tools: Vec<CustomTool>,
+    plugin_dir: Option<PathBuf>,
+    file_system: Option<Arc<dyn FileSystem>>,
+    clock: Option<Arc<dyn Clock>>,
+    faults: Option<Faults>,
+    secret_store: Option<Arc<dyn SecretStore>>,
+}
+
+impl DeveloperRouterBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The directory paths and commands are resolved in, used instead of the process's
+    /// current directory
+    pub fn with_workspace_root(mut self, root: impl Into<PathBuf>) -> Self {
+        self.workspace_root = Some(root.into());
+        self
+    }
+
+    /// Gitignore-style patterns for paths the tools may not touch, used instead of the
+    /// `.gooseignore` and `.gitignore` files
+    pub fn with_ignore_patterns<I, S>(mut self, patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.ignore_patterns = Some(patterns.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Project hints for the instructions, used instead of the `.goosehints` files
+    pub fn with_hints(mut self, hints: impl Into<String>) -> Self {
+        self.hints = Some(hints.into());
+        self
+    }
+
+    /// Offer only these built-in tools. Tools added with
+    /// [`with_custom_tool`](Self::with_custom_tool) are always offered.
+    pub fn with_tools<I, S>(mut self, tools: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.tools = Some(tools.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Offer an extra tool, run by `handler`; see [`DeveloperRouter::register_tool`]
+    pub fn with_custom_tool<F, Fut>(mut self, tool: Tool, handler: F) -> Self
+    where
+        F: Fn(Value, mpsc::Sender<JsonRpcMessage>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Vec<Content>, ToolError>> + Send + 'static,
+    {
+        self.custom_tools.push(CustomTool::new(tool, handler));
+        self
+    }
+
+    /// Offer the tools of the WebAssembly plugins in `dir`. None are loaded otherwise.
+    pub fn with_plugin_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.plugin_dir = Some(dir.into());
+        self
+    }
+
+    /// Read and write files through `file_system` instead of the machine's
+    pub fn with_file_system(mut self, file_system: Arc<dyn FileSystem>) -> Self {
+        self.file_system = Some(file_system);
+        self
+    }
+
+    /// Time tool calls with `clock`, e.g. a [`ManualClock`](super::system::ManualClock) for
+    /// predictable durations
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    /// Inject these faults into file operations and command spawns
+    pub fn with_faults(mut self, faults: Faults) -> Self {
+        self.faults = Some(faults);
+        self
+    }
+
+    /// Keep the secrets for shell commands in `store` instead of the OS keyring
+    pub fn with_secret_store(mut self, store: Arc<dyn SecretStore>) -> Self {
+        self.secret_store = Some(store);
+        self
+    }
+
+    /// Build the router, failing on an unknown or duplicate tool name or an invalid ignore
+    /// pattern
+    pub fn build(self) -> Result<DeveloperRouter> {
+        let fixed_cwd = self.workspace_root.is_some();
+        let cwd = match self.workspace_root {
+            Some(root) => root,
+            None => std::env::current_dir().context("no current directory")?,
+        };
+        let mut router = DeveloperRouter::in_directory(cwd.clone(), fixed_cwd);
+        if let Some(dir) = &self.plugin_dir {
+            router = router.with_plugins_from(dir);
+        }
+
+        if let Some(names) = self.tools {
+            if let Some(unknown) = names.iter().find(|name| {
+                !router
+                    .all_tools
+                    .iter()
+                    .any(|tool| tool.name == name.as_str())
+            }) {
+                return Err(anyhow!("Unknown developer tool '{}'", unknown));
+            }
+            let keep = |tools: &[rmcp::model::Tool]| {
+                tools
+                    .iter()
+                    .filter(|tool| names.iter().any(|name| tool.name == name.as_str()))
+                    .cloned()
+                    .collect::<Vec<_>>()
+            };
+            router.all_tools = Arc::new(keep(&router.all_tools));
+            let offered = keep(&router.tools.read().unwrap());
+            *router.tools.write().unwrap() = offered;
+        }
+
+        // Explicit hints and ignore patterns are not replaced when files change on disk
+        if let Some(hints) = self.hints {
+            let mut instructions = router.instructions.write().unwrap();
+            let base = instructions
+                .layer(InstructionLayer::Base)
+                .unwrap_or_default()
+                .to_string();
+            *instructions = Instructions::with_hints(base, &hints);
+            router.watched_files.lock().unwrap().hints = FileStamps::new(Vec::new());
+        }
+
+        if let Some(patterns) = self.ignore_patterns {
+            let mut builder = GitignoreBuilder::new(&cwd);
+            for pattern in &patterns {
+                builder
+                    .add_line(None, pattern)
+                    .with_context(|| format!("Invalid ignore pattern '{}'", pattern))?;
+            }
+            *router.ignore_patterns.write().unwrap() = builder.build()?;
+            router.watched_files.lock().unwrap().ignore = FileStamps::new(Vec::new());
+        }
+
+        if self.file_system.is_some() || self.clock.is_some() || self.faults.is_some() {
+            router.system = System::new(
+                self.file_system.unwrap_or_else(|| Arc::new(RealFileSystem)),
+                self.clock.unwrap_or_else(|| Arc::new(SystemClock)),
+                self.faults.unwrap_or_default(),
+            );
+        }
+
+        if let Some(store) = self.secret_store {
+            router.secret_store = store;
+        }
+
+        for custom in self.custom_tools {
+            router.add_custom_tool(custom)?;
+        }
+
+        Ok(router)
+    }
+}
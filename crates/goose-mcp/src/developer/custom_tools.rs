@@ -0,0 +1,43 @@
+use std::{fmt, future::Future, pin::Pin, sync::Arc};
+
+use mcp_core::handler::ToolError;
+use rmcp::model::{Content, JsonRpcMessage, Tool};
+use serde_json::Value;
+use tokio::sync::mpsc;
+
+pub type ToolFuture = Pin<Box<dyn Future<Output = Result<Vec<Content>, ToolError>> + Send>>;
+
+type Handler = dyn Fn(Value, mpsc::Sender<JsonRpcMessage>) -> ToolFuture + Send + Sync;
+
+/// A tool added to the developer extension by the embedding application, with the closure
+/// that runs it
+#[derive(Clone)]
+pub struct CustomTool {
+    pub tool: Tool,
+    handler: Arc<Handler>,
+}
+
+impl CustomTool {
+    pub fn new<F, Fut>(tool: Tool, handler: F) -> Self
+    where
+        F: Fn(Value, mpsc::Sender<JsonRpcMessage>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Vec<Content>, ToolError>> + Send + 'static,
+    {
+        Self {
+            tool,
+            handler: Arc::new(move |arguments, notifier| Box::pin(handler(arguments, notifier))),
+        }
+    }
+
+    pub fn call(&self, arguments: Value, notifier: mpsc::Sender<JsonRpcMessage>) -> ToolFuture {
+        (self.handler)(arguments, notifier)
+    }
+}
+
+impl fmt::Debug for CustomTool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CustomTool")
+            .field("name", &self.tool.name)
+            .finish_non_exhaustive()
+    }
+}
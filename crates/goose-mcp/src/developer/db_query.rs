@@ -0,0 +1,851 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use indoc::indoc;
+use mcp_core::handler::ToolError;
+use mcp_server::structured::structured_content;
+use rmcp::model::{Content, Role, Tool, ToolAnnotations};
+use rmcp::object;
+use serde::de::{MapAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+use tokio::process::Command;
+use url::Url;
+
+use super::editor_models::load_user_list;
+use super::shell::Spawner;
+use super::DeveloperRouter;
+
+/// The environment variables `database` may name, a list in the environment or the global
+/// config. None by default, since any other variable could hold a secret.
+pub const VARIABLES_KEY: &str = "GOOSE_DB_QUERY_VARIABLES";
+
+pub const DEFAULT_MAX_ROWS: usize = 100;
+pub const MAX_ROWS: usize = 1000;
+
+/// Longer text values are cut, so one large column doesn't take up the whole result
+const MAX_CELL_CHARS: usize = 1000;
+const TIMEOUT: Duration = Duration::from_secs(60);
+const CONNECT_TIMEOUT_SECS: &str = "10";
+
+/// Statements that are run inside `SELECT * FROM (...)`, which limits the rows and keeps
+/// anything else from being appended
+const QUERY_KEYWORDS: &[&str] = &["select", "with", "values", "table"];
+
+/// The parameters a postgres:// URL may have. Others, like `options` or `service`, could
+/// override the read-only setting the connection is made with.
+const POSTGRES_PARAMETERS: &[&str] = &[
+    "sslmode",
+    "sslrootcert",
+    "sslcert",
+    "sslkey",
+    "sslcrl",
+    "connect_timeout",
+    "application_name",
+    "target_session_attrs",
+];
+
+/// SQLite statements that can't be wrapped, and are safe because the file is opened read-only
+/// in safe mode
+const SQLITE_KEYWORDS: &[&str] = &["pragma", "explain"];
+
+/// A database the tool can query, through the engine's own command line client
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Database {
+    Sqlite(PathBuf),
+    Postgres(Url),
+    MySql(Url),
+}
+
+impl Database {
+    /// The database for a connection string, or None when `database` is not one and should
+    /// be treated as the path to a SQLite file
+    pub fn from_url(database: &str) -> Result<Option<Self>, ToolError> {
+        if !database.contains("://") {
+            return Ok(None);
+        }
+        let url = Url::parse(database).map_err(|_| {
+            ToolError::InvalidParameters("The connection string is not a valid URL".into())
+        })?;
+        match url.scheme() {
+            "postgres" | "postgresql" => {
+                if let Some((name, _)) = url
+                    .query_pairs()
+                    .find(|(name, _)| !POSTGRES_PARAMETERS.contains(&name.as_ref()))
+                {
+                    return Err(ToolError::InvalidParameters(format!(
+                        "The connection string's '{}' parameter isn't supported, only {} are",
+                        name,
+                        POSTGRES_PARAMETERS.join(", ")
+                    )));
+                }
+                Ok(Some(Self::Postgres(url)))
+            }
+            "mysql" | "mariadb" => Ok(Some(Self::MySql(url))),
+            scheme => Err(ToolError::InvalidParameters(format!(
+                "Unsupported database '{}://', expected postgres:// or mysql://, or the path of a SQLite file",
+                scheme
+            ))),
+        }
+    }
+
+    /// The database whose connection string is in the environment variable `name`, if the
+    /// user allowed it. Errors never include the value, which can hold a password.
+    pub fn from_variable(name: &str) -> Result<Self, ToolError> {
+        if !load_user_list(VARIABLES_KEY)
+            .iter()
+            .any(|allowed| allowed == name)
+        {
+            return Err(ToolError::InvalidParameters(format!(
+                "The variable {} can't be read. If it holds a connection string, ask the user to add it to {}.",
+                name, VARIABLES_KEY
+            )));
+        }
+        let value = std::env::var(name).map_err(|_| {
+            ToolError::InvalidParameters(format!("The variable {} is not set", name))
+        })?;
+        Self::from_url(&value)?.ok_or_else(|| {
+            ToolError::InvalidParameters(format!(
+                "The variable {} doesn't hold a postgres:// or mysql:// connection string",
+                name
+            ))
+        })
+    }
+
+    /// The query listing the tables and their columns
+    fn schema_query(&self) -> &'static str {
+        match self {
+            Self::Sqlite(_) => {
+                "SELECT m.name AS \"table\", p.name AS \"column\", p.type AS type, \
+                 p.\"notnull\" = 0 AS nullable, p.pk > 0 AS primary_key \
+                 FROM sqlite_master m JOIN pragma_table_info(m.name) p \
+                 WHERE m.type IN ('table', 'view') AND m.name NOT LIKE 'sqlite_%' \
+                 ORDER BY m.name, p.cid"
+            }
+            Self::Postgres(_) => {
+                "SELECT table_schema AS schema, table_name AS \"table\", \
+                 column_name AS \"column\", data_type AS type, is_nullable = 'YES' AS nullable \
+                 FROM information_schema.columns \
+                 WHERE table_schema NOT IN ('pg_catalog', 'information_schema') \
+                 ORDER BY table_schema, table_name, ordinal_position"
+            }
+            Self::MySql(_) => {
+                "SELECT table_name AS `table`, column_name AS `column`, column_type AS type, \
+                 is_nullable = 'YES' AS nullable, column_key AS `key` \
+                 FROM information_schema.columns WHERE table_schema = DATABASE() \
+                 ORDER BY table_name, ordinal_position"
+            }
+        }
+    }
+}
+
+/// The rows a query returned, with their values typed as the database reported them
+#[derive(Debug, Serialize)]
+pub struct QueryResult {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<Value>>,
+    /// Whether there were more than `max_rows` rows
+    pub truncated: bool,
+    pub elapsed_ms: u64,
+}
+
+impl QueryResult {
+    fn new(columns: Vec<String>, mut rows: Vec<Vec<Value>>, max_rows: usize) -> Self {
+        let truncated = rows.len() > max_rows;
+        rows.truncate(max_rows);
+        for value in rows.iter_mut().flatten() {
+            if let Value::String(text) = value {
+                if let Some((end, _)) = text.char_indices().nth(MAX_CELL_CHARS) {
+                    text.truncate(end);
+                    text.push_str("...");
+                }
+            }
+        }
+        Self {
+            columns,
+            rows,
+            truncated,
+            elapsed_ms: 0,
+        }
+    }
+
+    /// The rows as a markdown table, followed by the number of rows
+    pub fn render(&self) -> String {
+        if self.columns.is_empty() {
+            return "The query returned no rows".to_string();
+        }
+        let cell = |value: &Value| match value {
+            Value::Null => "NULL".to_string(),
+            Value::String(text) => text.replace('|', "\\|").replace('\n', " "),
+            value => value.to_string(),
+        };
+        let mut text = format!(
+            "| {} |\n|{}\n",
+            self.columns
+                .iter()
+                .map(|column| column.replace('|', "\\|"))
+                .collect::<Vec<_>>()
+                .join(" | "),
+            " --- |".repeat(self.columns.len())
+        );
+        for row in &self.rows {
+            text.push_str(&format!(
+                "| {} |\n",
+                row.iter().map(cell).collect::<Vec<_>>().join(" | ")
+            ));
+        }
+        if self.truncated {
+            text.push_str(&format!(
+                "\n[only the first {} rows, raise max_rows or narrow the query to see more]",
+                self.rows.len()
+            ));
+        } else {
+            text.push_str(&format!(
+                "\n[{} row{} in {}ms]",
+                self.rows.len(),
+                if self.rows.len() == 1 { "" } else { "s" },
+                self.elapsed_ms
+            ));
+        }
+        text
+    }
+}
+
+/// Run `query`, or the schema query when there is none, and return at most `max_rows` rows.
+/// Only single read statements are run, and the connection itself is read-only.
+pub async fn query(
+    spawner: &Spawner,
+    database: &Database,
+    query: Option<&str>,
+    max_rows: usize,
+) -> Result<QueryResult, ToolError> {
+    let (sql, wrap) = match query {
+        Some(query) => read_statement(database, query)?,
+        None => (database.schema_query(), true),
+    };
+    let started = Instant::now();
+    let mut result = match database {
+        Database::Sqlite(path) => sqlite(spawner, path, sql, wrap, max_rows).await?,
+        Database::Postgres(url) => postgres(spawner, url, sql, max_rows).await?,
+        Database::MySql(url) => mysql(spawner, url, sql, max_rows).await?,
+    };
+    result.elapsed_ms = started.elapsed().as_millis() as u64;
+    Ok(result)
+}
+
+/// The statement without trailing semicolons, and whether it can be wrapped in a subquery.
+/// Anything but a single statement that reads is refused.
+pub fn read_statement<'a>(
+    database: &Database,
+    query: &'a str,
+) -> Result<(&'a str, bool), ToolError> {
+    let query = query
+        .trim()
+        .trim_end_matches(|c: char| c == ';' || c.is_whitespace());
+    if has_separator(database, query)? {
+        return Err(ToolError::InvalidParameters(
+            "Only a single statement can be run at a time".into(),
+        ));
+    }
+
+    let keyword = first_keyword(query);
+    if QUERY_KEYWORDS.contains(&keyword.as_str()) {
+        Ok((query, true))
+    } else if matches!(database, Database::Sqlite(_)) && SQLITE_KEYWORDS.contains(&keyword.as_str())
+    {
+        Ok((query, false))
+    } else {
+        Err(ToolError::InvalidParameters(format!(
+            "Only queries that read can be run, starting with {}{}",
+            QUERY_KEYWORDS
+                .iter()
+                .map(|k| k.to_uppercase())
+                .collect::<Vec<_>>()
+                .join(", "),
+            match database {
+                Database::Sqlite(_) => ", PRAGMA or EXPLAIN",
+                _ => "",
+            }
+        )))
+    }
+}
+
+/// Whether a `;` separates statements, outside of quotes and comments as `database` reads
+/// them. Quoting whose end can't be found this simply is refused instead: backslash escapes,
+/// which MySQL allows in strings, Postgres' escape strings and dollar quoting, and MySQL's
+/// comments whose content is run.
+fn has_separator(database: &Database, query: &str) -> Result<bool, ToolError> {
+    let mysql = matches!(database, Database::MySql(_));
+    let postgres = matches!(database, Database::Postgres(_));
+    let refuse = |what: &str| {
+        Err(ToolError::InvalidParameters(format!(
+            "{} can't be checked for a second statement, so they aren't supported",
+            what
+        )))
+    };
+    let chars: Vec<char> = query.chars().collect();
+    let is_word = |c: Option<&char>| c.is_some_and(|c| c.is_alphanumeric() || *c == '_');
+    let skip_line = |i: usize| {
+        chars[i..]
+            .iter()
+            .position(|&c| c == '\n')
+            .map_or(chars.len(), |end| i + end)
+    };
+    let mut i = 0;
+    while i < chars.len() {
+        let next = chars.get(i + 1).copied();
+        match chars[i] {
+            quote @ ('\'' | '"' | '`') => {
+                let escape_string = quote == '\''
+                    && i > 0
+                    && matches!(chars[i - 1], 'e' | 'E')
+                    && !is_word(i.checked_sub(2).and_then(|before| chars.get(before)));
+                if postgres && escape_string {
+                    return refuse("Escape strings like E'...'");
+                }
+                // A doubled quote closes and reopens the literal, which comes to the same
+                let end = chars[i + 1..]
+                    .iter()
+                    .position(|&c| c == quote)
+                    .map_or(chars.len(), |end| i + 1 + end);
+                if !matches!(database, Database::Sqlite(_)) && chars[i + 1..end].contains(&'\\') {
+                    return refuse("Backslashes in quotes");
+                }
+                i = end;
+            }
+            '$' if postgres => {
+                let tag = chars[i + 1..]
+                    .iter()
+                    .take_while(|c| c.is_alphanumeric() || **c == '_')
+                    .count();
+                // `$1` is a parameter, `$$` and `$tag$` start a string
+                if chars.get(i + 1 + tag) == Some(&'$') && !next.is_some_and(|c| c.is_ascii_digit())
+                {
+                    return refuse("Dollar-quoted strings");
+                }
+            }
+            '#' if mysql => i = skip_line(i),
+            // MySQL only reads `--` as a comment when a space follows
+            '-' if next == Some('-')
+                && (!mysql || chars.get(i + 2).is_none_or(|c| c.is_whitespace())) =>
+            {
+                i = skip_line(i);
+            }
+            '/' if next == Some('*') => {
+                if mysql
+                    && (chars.get(i + 2) == Some(&'!') || chars[i + 2..].starts_with(&['M', '!']))
+                {
+                    return refuse("Comments like /*! ... */, which MySQL runs,");
+                }
+                i = chars[i + 2..]
+                    .windows(2)
+                    .position(|pair| pair == ['*', '/'])
+                    .map_or(chars.len(), |end| i + 3 + end);
+            }
+            ';' => return Ok(true),
+            _ => {}
+        }
+        i += 1;
+    }
+    Ok(false)
+}
+
+/// The statement's first keyword in lowercase, after any comments
+fn first_keyword(query: &str) -> String {
+    let mut rest = query.trim_start();
+    loop {
+        if let Some(comment) = rest.strip_prefix("--") {
+            rest = comment.split_once('\n').map_or("", |(_, rest)| rest);
+        } else if let Some(comment) = rest.strip_prefix("/*") {
+            rest = comment.split_once("*/").map_or("", |(_, rest)| rest);
+        } else {
+            break;
+        }
+        rest = rest.trim_start();
+    }
+    rest.chars()
+        .take_while(|c| c.is_ascii_alphabetic())
+        .collect::<String>()
+        .to_lowercase()
+}
+
+/// Limit a statement to `rows` rows. The newlines keep a trailing line comment from
+/// swallowing the rest.
+fn limited(sql: &str, rows: usize) -> String {
+    format!("SELECT * FROM (\n{}\n) AS q LIMIT {}", sql, rows)
+}
+
+async fn sqlite(
+    spawner: &Spawner,
+    path: &Path,
+    sql: &str,
+    wrap: bool,
+    max_rows: usize,
+) -> Result<QueryResult, ToolError> {
+    let sql = if wrap {
+        limited(sql, max_rows + 1)
+    } else {
+        sql.to_string()
+    };
+    let path = path.to_string_lossy().into_owned();
+    let output = run(
+        spawner,
+        "sqlite3",
+        "SQLite",
+        &[
+            // Safe mode also turns off writefile(), load_extension() and ATTACH, which
+            // a read-only connection still allows
+            "-readonly",
+            "-safe",
+            "-bail",
+            "-json",
+            "-cmd",
+            ".timeout 5000",
+            &path,
+            &sql,
+        ],
+        &[],
+    )
+    .await?;
+
+    // Nothing is printed when there are no rows
+    let rows: Vec<OrderedRow> = if output.trim().is_empty() {
+        Vec::new()
+    } else {
+        parse(&output, "sqlite3")?
+    };
+    Ok(from_objects(rows, max_rows))
+}
+
+async fn postgres(
+    spawner: &Spawner,
+    url: &Url,
+    sql: &str,
+    max_rows: usize,
+) -> Result<QueryResult, ToolError> {
+    // The password goes in the environment rather than on the command line
+    let mut url = url.clone();
+    let password = url.password().map(percent_decode);
+    let _ = url.set_password(None);
+
+    let sql = format!(
+        "SELECT coalesce(json_agg(t), '[]'::json) FROM ({}) AS t",
+        limited(sql, max_rows + 1)
+    );
+    let mut envs = vec![
+        (
+            "PGOPTIONS",
+            format!(
+                "-c default_transaction_read_only=on -c statement_timeout={}",
+                TIMEOUT.as_millis()
+            ),
+        ),
+        ("PGCONNECT_TIMEOUT", CONNECT_TIMEOUT_SECS.to_string()),
+    ];
+    if let Some(password) = password {
+        envs.push(("PGPASSWORD", password));
+    }
+    let output = run(
+        spawner,
+        "psql",
+        "Postgres",
+        &[
+            "--no-psqlrc",
+            "--no-password",
+            "--quiet",
+            "--no-align",
+            "--tuples-only",
+            "--set=ON_ERROR_STOP=1",
+            "--dbname",
+            url.as_str(),
+            "--command",
+            &sql,
+        ],
+        &envs,
+    )
+    .await?;
+    let rows: Vec<OrderedRow> = parse(&output, "psql")?;
+    Ok(from_objects(rows, max_rows))
+}
+
+async fn mysql(
+    spawner: &Spawner,
+    url: &Url,
+    sql: &str,
+    max_rows: usize,
+) -> Result<QueryResult, ToolError> {
+    let mut args = vec![
+        "--batch".to_string(),
+        "--raw".to_string(),
+        "--quick".to_string(),
+        "--no-auto-rehash".to_string(),
+        format!("--connect-timeout={}", CONNECT_TIMEOUT_SECS),
+        "--init-command=SET SESSION TRANSACTION READ ONLY".to_string(),
+    ];
+    if let Some(host) = url.host_str() {
+        args.push(format!("--host={}", host.trim_matches(['[', ']'])));
+    }
+    if let Some(port) = url.port() {
+        args.push(format!("--port={}", port));
+    }
+    if !url.username().is_empty() {
+        args.push(format!("--user={}", percent_decode(url.username())));
+    }
+    let database = percent_decode(url.path().trim_start_matches('/'));
+    if !database.is_empty() {
+        args.push(format!("--database={}", database));
+    }
+    let envs: Vec<_> = url
+        .password()
+        .map(|password| ("MYSQL_PWD", percent_decode(password)))
+        .into_iter()
+        .collect();
+
+    // MySQL sorts the keys of JSON objects, so the columns are looked up first and each row
+    // is aggregated as an array in their order
+    let mut probe = args.clone();
+    probe.push(format!("--execute={}", limited(sql, 0)));
+    let header = run(spawner, "mysql", "MySQL", &probe, &envs).await?;
+    let columns: Vec<String> = header
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .split('\t')
+        .filter(|column| !column.is_empty())
+        .map(str::to_string)
+        .collect();
+    if columns.is_empty() {
+        return Ok(QueryResult::new(columns, Vec::new(), max_rows));
+    }
+
+    let values = columns
+        .iter()
+        .map(|column| format!("q.`{}`", column.replace('`', "``")))
+        .collect::<Vec<_>>()
+        .join(", ");
+    args.push("--skip-column-names".to_string());
+    args.push(format!(
+        "--execute=SELECT JSON_ARRAYAGG(JSON_ARRAY({})) FROM ({}) AS q",
+        values,
+        limited(sql, max_rows + 1)
+    ));
+    let output = run(spawner, "mysql", "MySQL", &args, &envs).await?;
+    let output = output.trim();
+    let rows: Vec<Vec<Value>> = if output.is_empty() || output == "NULL" {
+        Vec::new()
+    } else {
+        parse(output, "mysql")?
+    };
+    Ok(QueryResult::new(columns, rows, max_rows))
+}
+
+fn from_objects(rows: Vec<OrderedRow>, max_rows: usize) -> QueryResult {
+    let columns = rows
+        .first()
+        .map(|row| row.0.iter().map(|(column, _)| column.clone()).collect())
+        .unwrap_or_default();
+    let rows = rows
+        .into_iter()
+        .map(|row| row.0.into_iter().map(|(_, value)| value).collect())
+        .collect();
+    QueryResult::new(columns, rows, max_rows)
+}
+
+/// A JSON object with its keys in the order the database wrote them, and any duplicates
+struct OrderedRow(Vec<(String, Value)>);
+
+impl<'de> Deserialize<'de> for OrderedRow {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct RowVisitor;
+
+        impl<'de> Visitor<'de> for RowVisitor {
+            type Value = OrderedRow;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a row object")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<OrderedRow, A::Error> {
+                let mut entries = Vec::new();
+                while let Some(entry) = map.next_entry()? {
+                    entries.push(entry);
+                }
+                Ok(OrderedRow(entries))
+            }
+        }
+
+        deserializer.deserialize_map(RowVisitor)
+    }
+}
+
+fn parse<T: for<'de> Deserialize<'de>>(output: &str, cli: &str) -> Result<T, ToolError> {
+    serde_json::from_str(output.trim())
+        .map_err(|e| ToolError::ExecutionError(format!("Unexpected output from {}: {}", cli, e)))
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+async fn run<S: AsRef<std::ffi::OsStr>>(
+    spawner: &Spawner,
+    cli: &str,
+    engine: &str,
+    args: &[S],
+    envs: &[(&str, String)],
+) -> Result<String, ToolError> {
+    let mut command = Command::new(cli);
+    command
+        .args(args)
+        .envs(envs.iter().map(|(name, value)| (*name, value)));
+    let output = tokio::time::timeout(TIMEOUT, spawner.output(&mut command))
+        .await
+        .map_err(|_| {
+            ToolError::ExecutionError(format!("The query took longer than {}s", TIMEOUT.as_secs()))
+        })?
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => ToolError::ExecutionError(format!(
+                "{} is not installed. Install it to query {} databases.",
+                cli, engine
+            )),
+            _ => ToolError::ExecutionError(format!("Failed to run {}: {}", cli, e)),
+        })?;
+    if !output.status.success() {
+        return Err(ToolError::ExecutionError(format!(
+            "The {} query failed: {}",
+            engine,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// The definition of the `db_query` tool
+pub fn tool() -> Tool {
+    Tool::new(
+        "db_query",
+        indoc! {r#"
+            Run a read-only SQL query against a database and return the rows with their types,
+            e.g. to explore the schema or check the data a bug depends on.
+
+            `database` is the absolute path of a SQLite file, a postgres:// or mysql://
+            connection string, or the name of an environment variable holding one, like
+            $DATABASE_URL, if the user allowed it in GOOSE_DB_QUERY_VARIABLES. Without a
+            `query`, the tables and their columns are listed.
+
+            Only a single SELECT, WITH, VALUES or TABLE statement is run, or PRAGMA and EXPLAIN
+            for SQLite, over a read-only connection. At most `max_rows` rows are returned
+            (default 100). The sqlite3, psql or mysql client needs to be installed.
+        "#},
+        object!({
+            "type": "object",
+            "required": ["database"],
+            "properties": {
+                "database": {"type": "string", "description": "A SQLite file, a connection string, or $VARIABLE holding one"},
+                "query": {"type": "string", "description": "The statement to run, or none to list the schema"},
+                "max_rows": {"type": "integer", "description": "Most rows to return, default 100, at most 1000"}
+            }
+        }),
+    )
+    .annotate(ToolAnnotations {
+        title: Some("Query a database".to_string()),
+        read_only_hint: Some(true),
+        destructive_hint: Some(false),
+        idempotent_hint: Some(true),
+        open_world_hint: Some(true),
+    })
+}
+
+impl DeveloperRouter {
+    pub(super) async fn db_query(&self, params: Value) -> Result<Vec<Content>, ToolError> {
+        let database = params
+            .get("database")
+            .and_then(Value::as_str)
+            .ok_or_else(|| ToolError::InvalidParameters("Missing 'database' parameter".into()))?;
+        // Connection strings can stay out of the conversation in a variable
+        let database = match database.strip_prefix('$') {
+            Some(name) => Database::from_variable(name)?,
+            None => match Database::from_url(database)? {
+                Some(database) => database,
+                None => {
+                    let path = self.resolve_path(database)?;
+                    if self.is_ignored(&path) {
+                        return Err(self.ignored_path_error(
+                            &path,
+                            format!(
+                                "Access to '{}' is restricted by .gooseignore",
+                                path.display()
+                            ),
+                        ));
+                    }
+                    if !path.is_file() {
+                        return Err(ToolError::InvalidParameters(format!(
+                            "The database file '{}' does not exist",
+                            path.display()
+                        )));
+                    }
+                    Database::Sqlite(path)
+                }
+            },
+        };
+        let max_rows = params
+            .get("max_rows")
+            .and_then(Value::as_u64)
+            .map_or(DEFAULT_MAX_ROWS, |max| max as usize)
+            .clamp(1, MAX_ROWS);
+
+        let result = query(
+            &self.spawner(),
+            &database,
+            params.get("query").and_then(Value::as_str),
+            max_rows,
+        )
+        .await?;
+
+        let text = self.redactor.redact(&result.render()).into_owned();
+        let mut content = vec![
+            Content::text(text.clone()).with_audience(vec![Role::Assistant]),
+            Content::text(text)
+                .with_audience(vec![Role::User])
+                .with_priority(0.0),
+        ];
+        let structured = serde_json::to_string(&result)
+            .map_err(|e| ToolError::ExecutionError(format!("Failed to encode result: {}", e)))?;
+        if let Ok(redacted) = serde_json::from_str::<Value>(&self.redactor.redact(&structured)) {
+            content.push(structured_content(&redacted));
+        }
+        Ok(content)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sqlite() -> Database {
+        Database::Sqlite(PathBuf::from("test.db"))
+    }
+
+    fn postgres() -> Database {
+        Database::from_url("postgres://localhost/test")
+            .unwrap()
+            .unwrap()
+    }
+
+    fn mysql() -> Database {
+        Database::from_url("mysql://localhost/test")
+            .unwrap()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_postgres_parameters_that_could_override_read_only_are_refused() {
+        assert!(
+            Database::from_url("postgres://localhost/test?sslmode=require")
+                .unwrap()
+                .is_some()
+        );
+        for url in [
+            "postgres://localhost/test?options=-c%20default_transaction_read_only%3Doff",
+            "postgresql://localhost/test?sslmode=require&service=prod",
+        ] {
+            assert!(
+                matches!(
+                    Database::from_url(url),
+                    Err(ToolError::InvalidParameters(_))
+                ),
+                "{}",
+                url
+            );
+        }
+    }
+
+    #[test]
+    fn test_separators_in_quotes_and_comments() {
+        for database in [sqlite(), postgres(), mysql()] {
+            assert!(!has_separator(&database, "select ';' -- ;\n from users").unwrap());
+            assert!(!has_separator(&database, "select \";\" /* ; */ from users").unwrap());
+            assert!(!has_separator(&database, "select 'it''s; fine'").unwrap());
+            assert!(has_separator(&database, "SELECT 1; DROP TABLE users").unwrap());
+        }
+        // SQLite has no backslash escapes, so paths are fine
+        assert!(!has_separator(&sqlite(), r"select 'C:\temp' as path").unwrap());
+    }
+
+    #[test]
+    fn test_mysql_backslash_escapes_are_refused() {
+        let query = r"SELECT '\'' FROM dual) AS q; SET SESSION TRANSACTION READ WRITE; DELETE FROM t; SELECT * FROM (SELECT ''";
+        assert!(has_separator(&mysql(), query).is_err());
+        assert!(read_statement(&mysql(), query).is_err());
+    }
+
+    #[test]
+    fn test_postgres_escape_and_dollar_quoted_strings_are_refused() {
+        let escape = r"SELECT E'\'' FROM t) AS q; DELETE FROM t; SELECT * FROM (SELECT ''";
+        assert!(has_separator(&postgres(), escape).is_err());
+        let dollar = "SELECT $$ ' $$ FROM t) AS q; DELETE FROM t; SELECT * FROM (SELECT '";
+        assert!(has_separator(&postgres(), dollar).is_err());
+        let tagged = "SELECT $x$ ' $x$; DELETE FROM t";
+        assert!(has_separator(&postgres(), tagged).is_err());
+
+        // Parameters and identifiers ending in e are not strings
+        assert!(!has_separator(&postgres(), "select name from t where id = $1").unwrap());
+        assert!(!has_separator(&postgres(), "select 'a' from the_table where code='x'").unwrap());
+    }
+
+    #[test]
+    fn test_mysql_comments() {
+        assert!(!has_separator(&mysql(), "select 1 # ;\n from dual").unwrap());
+        // Without a space after it, `--` is two minus signs in MySQL
+        assert!(has_separator(&mysql(), "select 1 --1; DELETE FROM t").unwrap());
+        assert!(has_separator(&mysql(), "select 1 /*! ; DELETE FROM t */").is_err());
+        assert!(has_separator(&mysql(), "select 1 /*M! ; DELETE FROM t */").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_runs_in_safe_mode() {
+        if which::which("sqlite3").is_err() {
+            return;
+        }
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("test.db");
+        std::process::Command::new("sqlite3")
+            .arg(&path)
+            .arg("CREATE TABLE t (x INTEGER); INSERT INTO t VALUES (1);")
+            .status()
+            .unwrap();
+        let database = Database::Sqlite(path);
+
+        let written = dir.path().join("written.txt");
+        let sql = format!("SELECT writefile('{}', 'x')", written.display());
+        assert!(query(&Spawner::default(), &database, Some(&sql), 10)
+            .await
+            .is_err());
+        assert!(!written.exists());
+
+        let result = query(&Spawner::default(), &database, Some("SELECT x FROM t"), 10)
+            .await
+            .unwrap();
+        assert_eq!(result.rows, vec![vec![Value::from(1)]]);
+    }
+}
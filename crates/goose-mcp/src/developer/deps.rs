@@ -0,0 +1,662 @@
+use std::path::{Path, PathBuf};
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use indoc::indoc;
+use mcp_core::handler::ToolError;
+use mcp_server::structured::structured_content;
+use rmcp::model::{Content, JsonRpcMessage, Tool, ToolAnnotations};
+use rmcp::object;
+use serde::Serialize;
+use serde_json::{json, Value};
+use tokio::process::Command;
+use tokio::sync::mpsc;
+
+use super::approval::request_approval;
+use super::shell::Spawner;
+use super::{DeveloperRouter, FileAccess};
+
+/// Installs can take a while on a cold cache
+const TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Lines of the package manager's output kept in the result
+const OUTPUT_LINES: usize = 20;
+
+/// A package manager, and the manifest and lockfile it keeps
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Ecosystem {
+    Cargo,
+    Npm,
+    Pnpm,
+    Pip,
+    Poetry,
+    Go,
+}
+
+impl Ecosystem {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "cargo" => Some(Self::Cargo),
+            "npm" => Some(Self::Npm),
+            "pnpm" => Some(Self::Pnpm),
+            "pip" => Some(Self::Pip),
+            "poetry" => Some(Self::Poetry),
+            "go" => Some(Self::Go),
+            _ => None,
+        }
+    }
+
+    /// The ecosystem of the project in `dir`, from the manifests and lockfiles in it
+    pub fn detect(dir: &Path) -> Option<Self> {
+        let has = |name: &str| dir.join(name).is_file();
+        if has("Cargo.toml") {
+            Some(Self::Cargo)
+        } else if has("go.mod") {
+            Some(Self::Go)
+        } else if has("package.json") {
+            // pnpm workspaces keep the lockfile at their root
+            if find_upwards(dir, "pnpm-lock.yaml").is_some() {
+                Some(Self::Pnpm)
+            } else {
+                Some(Self::Npm)
+            }
+        } else if has("poetry.lock")
+            || std::fs::read_to_string(dir.join("pyproject.toml"))
+                .is_ok_and(|text| text.contains("[tool.poetry"))
+        {
+            Some(Self::Poetry)
+        } else if has("requirements.txt") {
+            Some(Self::Pip)
+        } else {
+            None
+        }
+    }
+
+    fn manifest(self) -> &'static str {
+        match self {
+            Self::Cargo => "Cargo.toml",
+            Self::Npm | Self::Pnpm => "package.json",
+            Self::Pip => "requirements.txt",
+            Self::Poetry => "pyproject.toml",
+            Self::Go => "go.mod",
+        }
+    }
+
+    fn lockfile(self) -> Option<&'static str> {
+        match self {
+            Self::Cargo => Some("Cargo.lock"),
+            Self::Npm => Some("package-lock.json"),
+            Self::Pnpm => Some("pnpm-lock.yaml"),
+            Self::Pip => None,
+            Self::Poetry => Some("poetry.lock"),
+            Self::Go => Some("go.sum"),
+        }
+    }
+
+    /// The manifest in `dir` and the lockfile, which for workspaces is in a parent
+    pub fn files(self, dir: &Path) -> Vec<PathBuf> {
+        let mut files = vec![dir.join(self.manifest())];
+        if let Some(lockfile) = self.lockfile() {
+            files.push(find_upwards(dir, lockfile).unwrap_or_else(|| dir.join(lockfile)));
+        }
+        files
+    }
+}
+
+fn find_upwards(dir: &Path, name: &str) -> Option<PathBuf> {
+    dir.ancestors()
+        .map(|dir| dir.join(name))
+        .find(|path| path.is_file())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Operation {
+    Add,
+    Remove,
+    Update,
+}
+
+impl Operation {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "add" => Some(Self::Add),
+            "remove" => Some(Self::Remove),
+            "update" => Some(Self::Update),
+            _ => None,
+        }
+    }
+}
+
+/// A change to one dependency
+#[derive(Debug)]
+pub struct Change<'a> {
+    pub operation: Operation,
+    pub package: &'a str,
+    /// A version or requirement, e.g. `1.2.3` or `^1.2`
+    pub version: Option<&'a str>,
+    /// Whether it is a development dependency
+    pub dev: bool,
+}
+
+impl<'a> Change<'a> {
+    pub fn new(
+        operation: Operation,
+        package: &'a str,
+        version: Option<&'a str>,
+        dev: bool,
+    ) -> Result<Self, ToolError> {
+        // Both end up as arguments, so neither may look like a flag
+        let valid_package = !package.is_empty()
+            && !package.starts_with('-')
+            && package
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "-_.@/:".contains(c));
+        if !valid_package {
+            return Err(ToolError::InvalidParameters(format!(
+                "Invalid package name '{}'",
+                package
+            )));
+        }
+        if let Some(version) = version {
+            let valid_version = !version.is_empty()
+                && !version.starts_with('-')
+                && version
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || ".-+^~<>=*,!".contains(c));
+            if operation == Operation::Remove {
+                return Err(ToolError::InvalidParameters(
+                    "remove doesn't take a version".into(),
+                ));
+            }
+            if !valid_version {
+                return Err(ToolError::InvalidParameters(format!(
+                    "Invalid version '{}'",
+                    version
+                )));
+            }
+        }
+        Ok(Self {
+            operation,
+            package,
+            version,
+            dev,
+        })
+    }
+}
+
+/// How a change resolved: the package's locked version before and after
+#[derive(Debug, Serialize)]
+pub struct Resolution {
+    pub ecosystem: Ecosystem,
+    pub operation: Operation,
+    pub package: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+    /// The end of the package manager's output
+    pub output: String,
+}
+
+/// Make the change with the ecosystem's own tool in the project `dir`, which updates the
+/// manifest and lockfile, and report the version it resolved to
+pub async fn apply(
+    spawner: &Spawner,
+    ecosystem: Ecosystem,
+    dir: &Path,
+    change: &Change<'_>,
+) -> Result<Resolution, ToolError> {
+    let before = resolved_version(spawner, ecosystem, dir, change.package).await;
+    let output = run(spawner, ecosystem, dir, &command(ecosystem, dir, change)).await?;
+    let after = resolved_version(spawner, ecosystem, dir, change.package).await;
+
+    // pip has no manifest of its own, so the resolved version is pinned in requirements.txt
+    if ecosystem == Ecosystem::Pip {
+        let path = dir.join("requirements.txt");
+        let text = std::fs::read_to_string(&path).unwrap_or_default();
+        let line = after
+            .as_ref()
+            .map(|version| format!("{}=={}", change.package, version));
+        std::fs::write(&path, set_requirement(&text, change.package, line)).map_err(|e| {
+            ToolError::ExecutionError(format!("Failed to update requirements.txt: {}", e))
+        })?;
+    }
+
+    Ok(Resolution {
+        ecosystem,
+        operation: change.operation,
+        package: change.package.to_string(),
+        before,
+        after,
+        output,
+    })
+}
+
+/// The program and arguments that make `change`
+fn command(ecosystem: Ecosystem, dir: &Path, change: &Change) -> Vec<String> {
+    let package = change.package;
+    let spec = |separator: &str| match change.version {
+        Some(version) => format!("{}{}{}", package, separator, version),
+        None => package.to_string(),
+    };
+    let mut args: Vec<String> = match (ecosystem, change.operation) {
+        (Ecosystem::Cargo, Operation::Add) => vec!["cargo".into(), "add".into(), spec("@")],
+        (Ecosystem::Cargo, Operation::Remove) => {
+            vec!["cargo".into(), "remove".into(), package.into()]
+        }
+        // A new version may need a new requirement, which `cargo add` writes
+        (Ecosystem::Cargo, Operation::Update) => match change.version {
+            Some(_) => vec!["cargo".into(), "add".into(), spec("@")],
+            None => vec![
+                "cargo".into(),
+                "update".into(),
+                "--package".into(),
+                package.into(),
+            ],
+        },
+        (Ecosystem::Npm, Operation::Add) => vec!["npm".into(), "install".into(), spec("@")],
+        (Ecosystem::Npm, Operation::Remove) => {
+            vec!["npm".into(), "uninstall".into(), package.into()]
+        }
+        (Ecosystem::Npm, Operation::Update) => match change.version {
+            Some(_) => vec!["npm".into(), "install".into(), spec("@")],
+            None => vec!["npm".into(), "update".into(), package.into()],
+        },
+        (Ecosystem::Pnpm, Operation::Add) => vec!["pnpm".into(), "add".into(), spec("@")],
+        (Ecosystem::Pnpm, Operation::Remove) => {
+            vec!["pnpm".into(), "remove".into(), package.into()]
+        }
+        (Ecosystem::Pnpm, Operation::Update) => vec!["pnpm".into(), "update".into(), spec("@")],
+        (Ecosystem::Poetry, Operation::Add) => vec!["poetry".into(), "add".into(), spec("@")],
+        (Ecosystem::Poetry, Operation::Remove) => {
+            vec!["poetry".into(), "remove".into(), package.into()]
+        }
+        (Ecosystem::Poetry, Operation::Update) => match change.version {
+            Some(_) => vec!["poetry".into(), "add".into(), spec("@")],
+            None => vec!["poetry".into(), "update".into(), package.into()],
+        },
+        (Ecosystem::Go, Operation::Add) => vec!["go".into(), "get".into(), spec("@")],
+        (Ecosystem::Go, Operation::Remove) => {
+            vec!["go".into(), "get".into(), format!("{}@none", package)]
+        }
+        (Ecosystem::Go, Operation::Update) => vec![
+            "go".into(),
+            "get".into(),
+            format!("{}@{}", package, change.version.unwrap_or("latest")),
+        ],
+        (Ecosystem::Pip, operation) => {
+            let mut args = vec![python(dir), "-m".into(), "pip".into()];
+            let spec = match change.version {
+                Some(version) if version.starts_with(|c: char| c.is_ascii_digit()) => {
+                    format!("{}=={}", package, version)
+                }
+                _ => spec(""),
+            };
+            match operation {
+                Operation::Add => args.extend(["install".into(), spec]),
+                Operation::Update => args.extend(["install".into(), "--upgrade".into(), spec]),
+                Operation::Remove => args.extend(["uninstall".into(), "--yes".into(), spec]),
+            }
+            args
+        }
+    };
+
+    if change.dev && change.operation != Operation::Update {
+        match ecosystem {
+            Ecosystem::Cargo => args.push("--dev".into()),
+            Ecosystem::Npm | Ecosystem::Pnpm => args.push("--save-dev".into()),
+            Ecosystem::Poetry => args.extend(["--group".into(), "dev".into()]),
+            Ecosystem::Pip | Ecosystem::Go => {}
+        }
+    }
+    match ecosystem {
+        Ecosystem::Npm => args.extend(["--no-fund".into(), "--no-audit".into()]),
+        Ecosystem::Poetry => args.push("--no-interaction".into()),
+        _ => {}
+    }
+    args
+}
+
+/// The project's virtualenv interpreter, so packages go where the project runs from
+fn python(dir: &Path) -> String {
+    let candidates = if cfg!(windows) {
+        [".venv/Scripts/python.exe", "venv/Scripts/python.exe"]
+    } else {
+        [".venv/bin/python", "venv/bin/python"]
+    };
+    candidates
+        .iter()
+        .map(|candidate| dir.join(candidate))
+        .find(|path| path.is_file())
+        .map_or_else(
+            || "python3".to_string(),
+            |path| path.to_string_lossy().into_owned(),
+        )
+}
+
+/// The version of `package` the project resolves to, or None when it isn't a dependency
+pub async fn resolved_version(
+    spawner: &Spawner,
+    ecosystem: Ecosystem,
+    dir: &Path,
+    package: &str,
+) -> Option<String> {
+    match ecosystem {
+        Ecosystem::Cargo | Ecosystem::Poetry => {
+            let lockfile = find_upwards(dir, ecosystem.lockfile()?)?;
+            let versions = lock_versions(&std::fs::read_to_string(lockfile).ok()?, package);
+            (!versions.is_empty()).then(|| versions.join(", "))
+        }
+        Ecosystem::Npm | Ecosystem::Pnpm => {
+            let manifest = dir.join("node_modules").join(package).join("package.json");
+            let json: serde_json::Value =
+                serde_json::from_str(&std::fs::read_to_string(manifest).ok()?).ok()?;
+            json.get("version")?.as_str().map(str::to_string)
+        }
+        Ecosystem::Pip => {
+            let output = run(
+                spawner,
+                ecosystem,
+                dir,
+                &[
+                    python(dir),
+                    "-m".into(),
+                    "pip".into(),
+                    "show".into(),
+                    package.into(),
+                ],
+            )
+            .await
+            .ok()?;
+            output
+                .lines()
+                .find_map(|line| line.strip_prefix("Version:"))
+                .map(|version| version.trim().to_string())
+        }
+        Ecosystem::Go => {
+            let output = run(
+                spawner,
+                ecosystem,
+                dir,
+                &[
+                    "go".into(),
+                    "list".into(),
+                    "-m".into(),
+                    "-f".into(),
+                    "{{.Version}}".into(),
+                    package.into(),
+                ],
+            )
+            .await
+            .ok()?;
+            output
+                .lines()
+                .next()
+                .map(|version| version.trim().to_string())
+                .filter(|version| version.starts_with('v'))
+        }
+    }
+}
+
+/// The versions of `package` in a Cargo.lock or poetry.lock, several when the graph holds
+/// more than one
+pub fn lock_versions(lockfile: &str, package: &str) -> Vec<String> {
+    let package = normalize(package);
+    let value = |line: &str, key: &str| {
+        let rest = line.strip_prefix(key)?.trim_start().strip_prefix('=')?;
+        Some(rest.trim().trim_matches('"').to_string())
+    };
+    let mut versions = Vec::new();
+    let mut name = None;
+    for line in lockfile.lines().map(str::trim) {
+        if line.starts_with('[') {
+            name = None;
+        } else if let Some(found) = value(line, "name") {
+            name = Some(normalize(&found));
+        } else if let Some(version) = value(line, "version") {
+            if name.as_deref() == Some(package.as_str()) {
+                versions.push(version);
+            }
+        }
+    }
+    versions
+}
+
+/// Package names as Python compares them, which is also safe for the other ecosystems
+fn normalize(name: &str) -> String {
+    name.to_lowercase().replace(['_', '.'], "-")
+}
+
+/// requirements.txt with the line for `package` replaced by `line`, added when it wasn't
+/// there, or removed when `line` is None
+pub fn set_requirement(text: &str, package: &str, line: Option<String>) -> String {
+    let package = normalize(package);
+    let mut line = line;
+    let mut lines: Vec<String> = Vec::new();
+    for existing in text.lines() {
+        let name: String = existing
+            .trim()
+            .chars()
+            .take_while(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+            .collect();
+        if !name.is_empty() && normalize(&name) == package {
+            // The first line for the package is replaced, any others are dropped
+            lines.extend(line.take());
+        } else {
+            lines.push(existing.to_string());
+        }
+    }
+    lines.extend(line);
+    let mut text = lines.join("\n");
+    if !text.is_empty() {
+        text.push('\n');
+    }
+    text
+}
+
+async fn run(
+    spawner: &Spawner,
+    ecosystem: Ecosystem,
+    dir: &Path,
+    args: &[String],
+) -> Result<String, ToolError> {
+    let (program, args) = args.split_first().expect("commands have a program");
+    let mut command = Command::new(program);
+    command
+        .args(args)
+        .current_dir(dir)
+        .env("CI", "1")
+        .env("NO_COLOR", "1")
+        .env("CARGO_TERM_COLOR", "never")
+        .env("PIP_DISABLE_PIP_VERSION_CHECK", "1");
+    let output = tokio::time::timeout(TIMEOUT, spawner.output(&mut command))
+        .await
+        .map_err(|_| {
+            ToolError::ExecutionError(format!(
+                "{} took longer than {}s",
+                program,
+                TIMEOUT.as_secs()
+            ))
+        })?
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => ToolError::ExecutionError(format!(
+                "{} is not installed, and is needed for projects with a {}",
+                program,
+                ecosystem.manifest()
+            )),
+            _ => ToolError::ExecutionError(format!("Failed to run {}: {}", program, e)),
+        })?;
+
+    // Package managers report progress on stderr, so both are kept
+    let text = format!(
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let lines: Vec<&str> = text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .collect();
+    let tail = lines[lines.len().saturating_sub(OUTPUT_LINES)..].join("\n");
+    if !output.status.success() {
+        return Err(ToolError::ExecutionError(format!(
+            "{} {} failed:\n{}",
+            program,
+            args.first().map(String::as_str).unwrap_or_default(),
+            tail
+        )));
+    }
+    Ok(tail)
+}
+
+/// The definition of the `deps` tool
+pub fn tool() -> Tool {
+    Tool::new(
+        "deps",
+        indoc! {r#"
+            Add, remove or update a dependency with the project's package manager, which edits
+            the manifest and lockfile, and report the version it resolved to before and after.
+
+            The ecosystem is found from the manifest in `path` (default the working directory):
+            cargo, npm or pnpm, poetry, pip with requirements.txt, or go modules. `version` is
+            a version or requirement like 1.2.3 or ^1.2; without one, add takes the latest and
+            update the newest the manifest allows. `dev` makes it a development dependency.
+            With pip, the installed version is pinned in requirements.txt.
+        "#},
+        object!({
+            "type": "object",
+            "required": ["command", "package"],
+            "properties": {
+                "command": {
+                    "type": "string",
+                    "enum": ["add", "remove", "update"]
+                },
+                "package": {"type": "string"},
+                "version": {"type": "string", "description": "For add and update"},
+                "dev": {"type": "boolean", "description": "A development dependency"},
+                "path": {"type": "string", "description": "Absolute path of the project directory"},
+                "ecosystem": {
+                    "type": "string",
+                    "enum": ["cargo", "npm", "pnpm", "pip", "poetry", "go"],
+                    "description": "When the manifests in the directory don't tell"
+                }
+            }
+        }),
+    )
+    .annotate(ToolAnnotations {
+        title: Some("Manage dependencies".to_string()),
+        read_only_hint: Some(false),
+        destructive_hint: Some(false),
+        idempotent_hint: Some(false),
+        open_world_hint: Some(true),
+    })
+}
+
+impl DeveloperRouter {
+    pub(super) async fn deps(
+        &self,
+        params: Value,
+        notifier: &mpsc::Sender<JsonRpcMessage>,
+    ) -> Result<Vec<Content>, ToolError> {
+        let arg = |name: &str| params.get(name).and_then(Value::as_str);
+        let command = arg("command")
+            .ok_or_else(|| ToolError::InvalidParameters("Missing 'command' parameter".into()))?;
+        let operation = Operation::from_name(command).ok_or_else(|| {
+            ToolError::InvalidParameters(format!(
+                "Unknown command '{}', expected add, remove or update",
+                command
+            ))
+        })?;
+        let package = arg("package")
+            .ok_or_else(|| ToolError::InvalidParameters("Missing 'package' parameter".into()))?;
+        let dev = params.get("dev").and_then(Value::as_bool) == Some(true);
+        let change = Change::new(operation, package, arg("version"), dev)?;
+
+        let cwd = self.cwd();
+        let dir = match arg("path") {
+            Some(path) => self.resolve_path(path)?,
+            None => cwd.clone(),
+        };
+        if !dir.is_dir() {
+            return Err(ToolError::InvalidParameters(format!(
+                "'{}' is not a directory",
+                dir.display()
+            )));
+        }
+        let ecosystem = match arg("ecosystem") {
+            Some(name) => Ecosystem::from_name(name).ok_or_else(|| {
+                ToolError::InvalidParameters(format!("Unknown ecosystem '{}'", name))
+            })?,
+            None => Ecosystem::detect(&dir).ok_or_else(|| {
+                ToolError::ExecutionError(format!(
+                    "No manifest in {} tells which package manager to use. Pass `ecosystem`.",
+                    dir.display()
+                ))
+            })?,
+        };
+
+        let mut files = ecosystem.files(&dir);
+        files.sort();
+        for path in &files {
+            if self.is_ignored(path) {
+                return Err(self.ignored_path_error(
+                    path,
+                    format!(
+                        "Access to '{}' is restricted by .gooseignore",
+                        path.display()
+                    ),
+                ));
+            }
+        }
+        if let Some(action) = self
+            .approval_policy
+            .dependency_action(&self.catalog, command, package, &files)
+            .await
+        {
+            request_approval(notifier, &action).await?;
+        }
+
+        // Hold the manifest and lockfile so an edit or undo can't interleave with the install
+        let mut _file_locks = Vec::new();
+        for path in &files {
+            _file_locks.push(self.file_locks.lock(path).await);
+        }
+        let before: Vec<Option<String>> = files
+            .iter()
+            .map(|path| self.system.fs().read_to_string(path).ok())
+            .collect();
+
+        let resolution = apply(&self.spawner(), ecosystem, &dir, &change).await?;
+
+        // Changed files can be undone like edits
+        let mut changed = Vec::new();
+        for (path, old) in files.iter().zip(before) {
+            if self.system.fs().read_to_string(path).ok() == old {
+                continue;
+            }
+            if let Some(old) = old {
+                self.file_history.push(path, old);
+            }
+            if path.is_file() {
+                self.session_files
+                    .lock()
+                    .unwrap()
+                    .insert(path.clone(), FileAccess::Edited);
+                self.resources_changed.store(true, Ordering::Relaxed);
+            }
+            changed.push(path.strip_prefix(&cwd).unwrap_or(path).to_path_buf());
+        }
+
+        let mut result = serde_json::to_value(&resolution)
+            .map_err(|e| ToolError::ExecutionError(format!("Failed to encode result: {}", e)))?;
+        result["changed"] = json!(changed);
+        let text = serde_json::to_string_pretty(&result)
+            .map_err(|e| ToolError::ExecutionError(format!("Failed to encode result: {}", e)))?;
+        let text = self.redactor.redact(&text).into_owned();
+        let mut content = vec![Content::text(text.clone())];
+        if let Ok(redacted) = serde_json::from_str::<Value>(&text) {
+            content.push(structured_content(&redacted));
+        }
+        Ok(content)
+    }
+}
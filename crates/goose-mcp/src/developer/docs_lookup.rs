@@ -0,0 +1,743 @@
+//! Looking up documentation on the machine: `--help` output, man pages, and the HTML that
+//! rustdoc and TypeDoc generate, as markdown.
+//!
+//! Rust items are found by their full path, e.g. `std::vec::Vec::push`, in the toolchain's
+//! documentation for the standard library and in `target/doc` for everything else. TypeScript
+//! symbols are found by name in the TypeDoc output directory. A member is cut out of its
+//! type's page so the result stays short.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use indoc::indoc;
+use mcp_core::handler::ToolError;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use rmcp::model::{Content, Role, Tool, ToolAnnotations};
+use rmcp::object;
+use serde::Serialize;
+use serde_json::Value;
+use tokio::process::Command;
+use url::Url;
+
+use super::shell::Spawner;
+use super::web_page::to_markdown;
+use super::{web_page, DeveloperRouter};
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Width commands and man pages are asked to wrap their text at
+const COLUMNS: &str = "100";
+
+/// The crates whose documentation comes with the toolchain
+const STD_CRATES: &[&str] = &["std", "core", "alloc", "proc_macro", "test"];
+
+/// The prefixes of rustdoc's page names, e.g. `struct.Vec.html`
+const RUSTDOC_KINDS: &[&str] = &[
+    "struct",
+    "enum",
+    "trait",
+    "fn",
+    "type",
+    "macro",
+    "constant",
+    "static",
+    "union",
+    "derive",
+    "attr",
+    "primitive",
+    "keyword",
+    "traitalias",
+];
+
+/// The anchors rustdoc gives the members of a page
+const RUSTDOC_MEMBERS: &[&str] = &[
+    "method",
+    "tymethod",
+    "structfield",
+    "variant",
+    "associatedtype",
+    "associatedconstant",
+];
+
+/// The directories TypeDoc writes each kind of symbol's page to
+const TYPEDOC_KINDS: &[&str] = &[
+    "classes",
+    "interfaces",
+    "functions",
+    "types",
+    "type-aliases",
+    "variables",
+    "enums",
+    "modules",
+];
+
+static RUSTDOC_REDIRECT: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"(?i)http-equiv="refresh"\s+content="0;\s*URL=([^"]+)""#).unwrap());
+
+/// A link to another local page, which is noise once the page is text
+static FILE_LINK: Lazy<Regex> = Lazy::new(|| Regex::new(r"\[([^\]]*)\]\(file://[^)]*\)").unwrap());
+
+static ANSI_ESCAPE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\x1b\[[0-9;?]*[A-Za-z]|\x1b\][^\x07]*\x07").unwrap());
+
+/// A man page's section header, like `SYNOPSIS` or `EXIT STATUS`
+static MAN_SECTION: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[A-Z][A-Z0-9 ,/()-]*$").unwrap());
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Source {
+    Help,
+    Man,
+    Rustdoc,
+    Typedoc,
+}
+
+impl Source {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "help" => Some(Self::Help),
+            "man" => Some(Self::Man),
+            "rustdoc" => Some(Self::Rustdoc),
+            "typedoc" => Some(Self::Typedoc),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Doc {
+    pub source: Source,
+    pub title: String,
+    /// The page's file, or the command that printed the text
+    pub location: String,
+    pub markdown: String,
+}
+
+/// The documentation for `query` from `source`, or from the first source that has it: a
+/// Rust path goes to rustdoc, a program on the PATH to its help and then its man page, and
+/// anything else to TypeDoc
+pub async fn lookup(
+    spawner: &Spawner,
+    query: &str,
+    source: Option<Source>,
+    dir: &Path,
+) -> Result<Doc, ToolError> {
+    let query = query.trim();
+    if query.is_empty() {
+        return Err(ToolError::InvalidParameters("The query is empty".into()));
+    }
+    match source {
+        Some(Source::Help) => help(spawner, &command_words(query)?, dir).await,
+        Some(Source::Man) => man(spawner, &command_words(query)?, dir).await,
+        Some(Source::Rustdoc) => rustdoc(spawner, query, dir).await,
+        Some(Source::Typedoc) => typedoc(query, dir),
+        None if query.contains("::") => rustdoc(spawner, query, dir).await,
+        None => match command_words(query) {
+            Ok(words) if which::which(&words[0]).is_ok() => {
+                match help(spawner, &words, dir).await {
+                    Ok(doc) => Ok(doc),
+                    Err(help_error) => man(spawner, &words, dir).await.map_err(|_| help_error),
+                }
+            }
+            _ => typedoc(query, dir).map_err(|e| {
+                ToolError::ExecutionError(format!(
+                    "'{}' isn't a program on the PATH or a Rust path. {}",
+                    query, e
+                ))
+            }),
+        },
+    }
+}
+
+/// A program and its subcommands, which mustn't look like options
+fn command_words(query: &str) -> Result<Vec<String>, ToolError> {
+    let words: Vec<String> = query.split_whitespace().map(str::to_string).collect();
+    let valid = words.len() <= 3
+        && words.iter().all(|word| {
+            !word.starts_with('-')
+                && word
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "_.+-".contains(c))
+        });
+    if valid {
+        Ok(words)
+    } else {
+        Err(ToolError::InvalidParameters(format!(
+            "'{}' isn't a command, expected a program and up to two subcommands",
+            query
+        )))
+    }
+}
+
+/// Whether `word` is a file or directory in `dir`, with or without its extension
+fn names_project_file(word: &str, dir: &Path) -> bool {
+    if dir.join(word).exists() {
+        return true;
+    }
+    std::fs::read_dir(dir).is_ok_and(|entries| {
+        entries.flatten().any(|entry| {
+            Path::new(&entry.file_name())
+                .file_stem()
+                .is_some_and(|stem| stem == word)
+        })
+    })
+}
+
+/// What `args` prints, from stdout or else stderr, as most programs print usage errors there
+async fn run(
+    spawner: &Spawner,
+    args: &[String],
+    dir: &Path,
+    envs: &[(&str, &str)],
+) -> Result<String, String> {
+    let mut command = Command::new(&args[0]);
+    command
+        .args(&args[1..])
+        .current_dir(dir)
+        .envs(envs.iter().copied())
+        .env("COLUMNS", COLUMNS)
+        .env("NO_COLOR", "1");
+    let output = tokio::time::timeout(TIMEOUT, spawner.output(&mut command))
+        .await
+        .map_err(|_| format!("{} took longer than {}s", args[0], TIMEOUT.as_secs()))?
+        .map_err(|e| format!("failed to run {}: {}", args[0], e))?;
+    let stdout = strip_formatting(&String::from_utf8_lossy(&output.stdout));
+    let stderr = strip_formatting(&String::from_utf8_lossy(&output.stderr));
+    if !stdout.trim().is_empty() {
+        Ok(stdout)
+    } else if output.status.success() || !stderr.trim().is_empty() {
+        Ok(stderr)
+    } else {
+        Err(format!("{} printed nothing", args.join(" ")))
+    }
+}
+
+/// Text without terminal colors or the overstrike man uses for bold and underline
+pub fn strip_formatting(text: &str) -> String {
+    let text = ANSI_ESCAPE.replace_all(text, "");
+    let mut plain = String::with_capacity(text.len());
+    for c in text.chars() {
+        if c == '\u{8}' {
+            plain.pop();
+        } else {
+            plain.push(c);
+        }
+    }
+    plain
+}
+
+/// The `--help` output of an installed program. Running it must not run anything from the
+/// project, so the program can't be in `dir` and no word can name a file there, which
+/// interpreters like `python3 build.py` or `node build` would run; man pages are still read.
+async fn help(spawner: &Spawner, words: &[String], dir: &Path) -> Result<Doc, ToolError> {
+    let program = which::which(&words[0]).map_err(|_| {
+        ToolError::ExecutionError(format!("{} isn't installed or on the PATH", words[0]))
+    })?;
+    let in_project = program
+        .canonicalize()
+        .is_ok_and(|program| dir.canonicalize().is_ok_and(|dir| program.starts_with(dir)));
+    if in_project || words.iter().any(|word| names_project_file(word, dir)) {
+        return Err(ToolError::ExecutionError(format!(
+            "'{}' names a file in the project, so its --help isn't run",
+            words.join(" ")
+        )));
+    }
+    let mut args = words.to_vec();
+    args.push("--help".into());
+    let text = run(spawner, &args, dir, &[])
+        .await
+        .map_err(ToolError::ExecutionError)?;
+    Ok(Doc {
+        source: Source::Help,
+        title: words.join(" "),
+        location: args.join(" "),
+        markdown: format!("```\n{}\n```", trim_text(&text)),
+    })
+}
+
+async fn man(spawner: &Spawner, words: &[String], dir: &Path) -> Result<Doc, ToolError> {
+    which::which("man").map_err(|_| ToolError::ExecutionError("man isn't installed".into()))?;
+    // Subcommands have their own pages, like git-rebase
+    let page = words.join("-");
+    let args = ["man".to_string(), page.clone()];
+    let text = run(
+        spawner,
+        &args,
+        dir,
+        &[("MANPAGER", "cat"), ("PAGER", "cat"), ("MANWIDTH", COLUMNS)],
+    )
+    .await
+    .map_err(ToolError::ExecutionError)?;
+    if !text
+        .lines()
+        .any(|line| MAN_SECTION.is_match(line.trim_end()))
+    {
+        // man explains on stderr that there is no page
+        return Err(ToolError::ExecutionError(trim_text(&text)));
+    }
+    Ok(Doc {
+        source: Source::Man,
+        title: page,
+        location: args.join(" "),
+        markdown: man_to_markdown(&text),
+    })
+}
+
+/// A man page with its section headers as markdown headings and its indentation removed
+pub fn man_to_markdown(text: &str) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    // The header and footer lines repeat the page's name and date
+    let body = match lines.iter().position(|line| MAN_SECTION.is_match(line)) {
+        Some(start) => &lines[start..],
+        None => &lines[..],
+    };
+    let end = body
+        .iter()
+        .rposition(|line| !line.trim().is_empty())
+        .map_or(0, |last| last + 1);
+    let body = &body[..end];
+    // The footer is less indented than the text, so it is left out of the indentation
+    let indent = body[..body.len().saturating_sub(1)]
+        .iter()
+        .filter(|line| !line.trim().is_empty() && !MAN_SECTION.is_match(line))
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    let mut markdown = String::new();
+    for (index, line) in body.iter().enumerate() {
+        let last_line = index + 1 == body.len();
+        if MAN_SECTION.is_match(line) && !last_line {
+            markdown.push_str(&format!("\n## {}\n", line.trim()));
+        } else if line.len() - line.trim_start().len() >= indent {
+            markdown.push_str(line[indent..].trim_end());
+            markdown.push('\n');
+        } else if !last_line {
+            markdown.push_str(line.trim());
+            markdown.push('\n');
+        }
+    }
+    trim_text(&markdown)
+}
+
+/// Text with runs of blank lines made one
+fn trim_text(text: &str) -> String {
+    let mut trimmed = String::new();
+    let mut blank = false;
+    for line in text.lines().map(str::trim_end) {
+        if line.is_empty() {
+            blank = !trimmed.is_empty();
+            continue;
+        }
+        if blank {
+            trimmed.push('\n');
+            blank = false;
+        }
+        trimmed.push_str(line);
+        trimmed.push('\n');
+    }
+    trimmed.trim_end().to_string()
+}
+
+/// Where the toolchain's standard library documentation is
+async fn std_docs(spawner: &Spawner, dir: &Path) -> Option<PathBuf> {
+    let args = ["rustc".to_string(), "--print".into(), "sysroot".into()];
+    let sysroot = run(spawner, &args, dir, &[]).await.ok()?;
+    let docs = Path::new(sysroot.trim()).join("share/doc/rust/html");
+    docs.is_dir().then_some(docs)
+}
+
+async fn rustdoc(spawner: &Spawner, query: &str, dir: &Path) -> Result<Doc, ToolError> {
+    let segments: Vec<&str> = query.split("::").map(str::trim).collect();
+    if segments.len() < 2 || segments.iter().any(|s| s.is_empty()) {
+        return Err(ToolError::InvalidParameters(format!(
+            "'{}' isn't a full Rust path, expected e.g. std::vec::Vec or serde::Serialize",
+            query
+        )));
+    }
+    let krate = segments[0].replace('-', "_");
+    let root = if STD_CRATES.contains(&krate.as_str()) {
+        std_docs(spawner, dir).await.ok_or_else(|| {
+            ToolError::ExecutionError(
+                "The standard library's documentation isn't installed, add it with `rustup component add rust-docs`".into(),
+            )
+        })?
+    } else {
+        dir.ancestors()
+            .map(|dir| dir.join("target/doc"))
+            .find(|docs| docs.join(&krate).is_dir())
+            .ok_or_else(|| {
+                ToolError::ExecutionError(format!(
+                    "There is no local documentation for {}, build it with `cargo doc -p {}`",
+                    krate, segments[0]
+                ))
+            })?
+    };
+
+    let (page, member) =
+        find_rustdoc_page(&root.join(&krate), &segments[1..]).ok_or_else(|| {
+            ToolError::ExecutionError(format!(
+                "{} isn't in the documentation in {}",
+                query,
+                root.display()
+            ))
+        })?;
+    let (page, html) = read_following_redirects(&page)?;
+    let base = Url::from_file_path(&page).map_err(|_| {
+        ToolError::ExecutionError(format!("Invalid documentation path {}", page.display()))
+    })?;
+    let (title, markdown) = match member {
+        Some(member) => {
+            let html = rustdoc_member(&html, member).ok_or_else(|| {
+                ToolError::ExecutionError(format!(
+                    "{} has no member {}",
+                    segments[..segments.len() - 1].join("::"),
+                    member
+                ))
+            })?;
+            (query.to_string(), to_markdown(html, &base).1)
+        }
+        None => {
+            let (title, markdown) = to_markdown(&html, &base);
+            (title.unwrap_or_else(|| query.to_string()), markdown)
+        }
+    };
+    Ok(Doc {
+        source: Source::Rustdoc,
+        title,
+        location: page.display().to_string(),
+        markdown: clean_links(&markdown),
+    })
+}
+
+/// The page documenting `path` in a crate's documentation, and the member of it the path
+/// ends with, if any
+fn find_rustdoc_page<'a>(
+    crate_docs: &Path,
+    path: &[&'a str],
+) -> Option<(PathBuf, Option<&'a str>)> {
+    let item = |path: &[&str]| {
+        let (name, modules) = path.split_last()?;
+        let dir = modules
+            .iter()
+            .fold(crate_docs.to_path_buf(), |dir, m| dir.join(m));
+        RUSTDOC_KINDS
+            .iter()
+            .map(|kind| dir.join(format!("{}.{}.html", kind, name)))
+            .chain(std::iter::once(dir.join(name).join("index.html")))
+            .find(|page| page.is_file())
+    };
+    if let Some(page) = item(path) {
+        return Some((page, None));
+    }
+    let (member, owner) = path.split_last()?;
+    Some((item(owner)?, Some(*member)))
+}
+
+/// The page's HTML, after the redirects rustdoc leaves where items are re-exported
+fn read_following_redirects(page: &Path) -> Result<(PathBuf, String), ToolError> {
+    let mut page = page.to_path_buf();
+    for _ in 0..5 {
+        let html = std::fs::read_to_string(&page).map_err(|e| {
+            ToolError::ExecutionError(format!("Failed to read {}: {}", page.display(), e))
+        })?;
+        match RUSTDOC_REDIRECT.captures(&html) {
+            Some(captures) => {
+                let target = captures[1].split('#').next().unwrap_or_default();
+                page = page.parent().unwrap_or(Path::new("")).join(target);
+            }
+            None => return Ok((page, html)),
+        }
+    }
+    Err(ToolError::ExecutionError(format!(
+        "Too many redirects from {}",
+        page.display()
+    )))
+}
+
+/// The part of a rustdoc page's HTML about `member`, from the element with its anchor to the
+/// next member's. Broken off tags don't matter to the HTML parser.
+fn rustdoc_member<'a>(html: &'a str, member: &str) -> Option<&'a str> {
+    let anchor = RUSTDOC_MEMBERS
+        .iter()
+        .filter_map(|kind| html.find(&format!("id=\"{}.{}\"", kind, member)))
+        .min()?;
+    let start = html[..anchor].rfind('<')?;
+    let rest = &html[anchor + 1..];
+    let end = RUSTDOC_MEMBERS
+        .iter()
+        .filter_map(|kind| rest.find(&format!("id=\"{}.", kind)))
+        .chain(rest.find("<h2"))
+        .min()
+        .and_then(|end| html[..anchor + 1 + end].rfind('<'))
+        .unwrap_or(html.len());
+    Some(&html[start..end])
+}
+
+/// Markdown without links between local pages, and without rustdoc's section anchors
+fn clean_links(markdown: &str) -> String {
+    let text = FILE_LINK.replace_all(markdown, "$1");
+    trim_text(&text.replace('§', ""))
+}
+
+/// Where TypeDoc writes the documentation, from the `out` of the closest typedoc.json
+fn typedoc_dir(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors().find_map(|project| {
+        let config = std::fs::read_to_string(project.join("typedoc.json")).ok();
+        let out = config
+            .and_then(|text| serde_json::from_str::<serde_json::Value>(&text).ok())
+            .and_then(|config| config.get("out")?.as_str().map(str::to_string))
+            .unwrap_or_else(|| "docs".to_string());
+        let out = project.join(out);
+        TYPEDOC_KINDS
+            .iter()
+            .any(|kind| out.join(kind).is_dir())
+            .then_some(out)
+    })
+}
+
+fn typedoc(query: &str, dir: &Path) -> Result<Doc, ToolError> {
+    let out = typedoc_dir(dir).ok_or_else(|| {
+        ToolError::ExecutionError(
+            "There is no TypeDoc output, generate it with `npx typedoc`".into(),
+        )
+    })?;
+    let (name, member) = match query.split_once('.') {
+        Some((name, member)) => (name, Some(member)),
+        None => (query, None),
+    };
+    // Pages are named after the symbol, with its module first when there are several
+    let page = TYPEDOC_KINDS
+        .iter()
+        .filter_map(|kind| std::fs::read_dir(out.join(kind)).ok())
+        .flatten()
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .find(|path| {
+            path.file_stem()
+                .and_then(|stem| stem.to_str())
+                .is_some_and(|stem| stem == name || stem.ends_with(&format!(".{}", name)))
+        })
+        .ok_or_else(|| {
+            ToolError::ExecutionError(format!(
+                "{} isn't in the TypeDoc output in {}",
+                name,
+                out.display()
+            ))
+        })?;
+    let html = std::fs::read_to_string(&page).map_err(|e| {
+        ToolError::ExecutionError(format!("Failed to read {}: {}", page.display(), e))
+    })?;
+    let base = Url::from_file_path(&page).map_err(|_| {
+        ToolError::ExecutionError(format!("Invalid documentation path {}", page.display()))
+    })?;
+    let (title, markdown) = to_markdown(&html, &base);
+    let markdown = clean_links(&markdown);
+    let markdown = match member {
+        Some(member) => typedoc_member(&markdown, member).ok_or_else(|| {
+            ToolError::ExecutionError(format!("{} has no member {}", name, member))
+        })?,
+        None => markdown,
+    };
+    Ok(Doc {
+        source: Source::Typedoc,
+        title: title.unwrap_or_else(|| query.to_string()),
+        location: page.display().to_string(),
+        markdown,
+    })
+}
+
+/// The part of a TypeDoc page under the heading naming `member`
+fn typedoc_member(markdown: &str, member: &str) -> Option<String> {
+    let lines: Vec<&str> = markdown.lines().collect();
+    let heading = |line: &str| {
+        let level = line.chars().take_while(|c| *c == '#').count();
+        (level > 0 && line[level..].starts_with(' ')).then_some(level)
+    };
+    let (start, level) = lines.iter().enumerate().find_map(|(index, line)| {
+        let level = heading(line)?;
+        let text = line[level..].trim().trim_matches('`');
+        let name = text
+            .split(|c: char| !(c.is_alphanumeric() || c == '_' || c == '$'))
+            .find(|word| !word.is_empty())?;
+        (name == member).then_some((index, level))
+    })?;
+    let end = lines[start + 1..]
+        .iter()
+        .position(|line| heading(line).is_some_and(|l| l <= level))
+        .map_or(lines.len(), |end| start + 1 + end);
+    Some(lines[start..end].join("\n"))
+}
+
+/// The definition of the `docs_lookup` tool
+pub fn tool() -> Tool {
+    Tool::new(
+        "docs_lookup",
+        indoc! {r#"
+            Look up the documentation installed on this machine for a command or an API, as
+            markdown. Check it before relying on a flag or function you aren't sure of.
+
+            `query` is one of:
+            - A command and up to two subcommands, e.g. "rsync" or "git rebase", for its
+              `--help` output or its man page.
+            - A full Rust path, e.g. "std::vec::Vec::push" or "tokio::sync::Mutex", for the
+              standard library's docs or the crate's docs in target/doc, from `cargo doc`.
+            - A TypeScript symbol, e.g. "Client" or "Client.connect", for the docs TypeDoc
+              generated.
+
+            `source` picks where to look; by default it is guessed from the query. A member
+            like `Vec::push` returns just its part of the page. At most `max_length`
+            characters are returned (default 20000); when there are more, the result says
+            which `offset` to call again with.
+        "#},
+        object!({
+            "type": "object",
+            "required": ["query"],
+            "properties": {
+                "query": {"type": "string", "description": "A command, a Rust path or a TypeScript symbol"},
+                "source": {"type": "string", "enum": ["auto", "help", "man", "rustdoc", "typedoc"], "default": "auto"},
+                "max_length": {"type": "integer", "description": "Most characters to return, default 20000, at most 100000"},
+                "offset": {"type": "integer", "description": "The character to start from, to read the rest of a long page"}
+            }
+        }),
+    )
+    .annotate(ToolAnnotations {
+        title: Some("Look up local documentation".to_string()),
+        read_only_hint: Some(true),
+        destructive_hint: Some(false),
+        idempotent_hint: Some(true),
+        open_world_hint: Some(false),
+    })
+}
+
+impl DeveloperRouter {
+    pub(super) async fn docs_lookup(&self, params: Value) -> Result<Vec<Content>, ToolError> {
+        const DEFAULT_MAX_LENGTH: u64 = 20_000;
+        const MAX_LENGTH: u64 = 100_000;
+
+        let query = params
+            .get("query")
+            .and_then(Value::as_str)
+            .ok_or_else(|| ToolError::InvalidParameters("Missing 'query' parameter".into()))?;
+        let source = match params.get("source").and_then(Value::as_str) {
+            None | Some("auto") => None,
+            Some(name) => Some(Source::from_name(name).ok_or_else(|| {
+                ToolError::InvalidParameters(format!("Unknown documentation source '{}'", name))
+            })?),
+        };
+        let max_length = params
+            .get("max_length")
+            .and_then(Value::as_u64)
+            .unwrap_or(DEFAULT_MAX_LENGTH)
+            .clamp(1, MAX_LENGTH) as usize;
+        let offset = params.get("offset").and_then(Value::as_u64).unwrap_or(0) as usize;
+
+        let doc = lookup(&self.spawner(), query, source, &self.cwd()).await?;
+        let total = doc.markdown.chars().count();
+        if offset > 0 && offset >= total {
+            return Err(ToolError::InvalidParameters(format!(
+                "The documentation only has {} characters",
+                total
+            )));
+        }
+        let (part, next) = web_page::budget(&doc.markdown, offset, max_length);
+
+        let mut text = format!("# {}\n\nSource: {}\n\n", doc.title, doc.location);
+        text.push_str(part.trim_end());
+        if let Some(next) = next {
+            text.push_str(&format!(
+                "\n\n[characters {}-{} of {}, call docs_lookup again with \"offset\": {} for more]",
+                offset + 1,
+                next,
+                total,
+                next
+            ));
+        }
+        let text = self.redactor.redact(&text).into_owned();
+        Ok(vec![
+            Content::text(text.clone()).with_audience(vec![Role::Assistant]),
+            Content::text(text)
+                .with_audience(vec![Role::User])
+                .with_priority(0.0),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_formatting() {
+        let text = "\x1b[1mbold\x1b[0m N\x08NA\x08AM\x08ME _\x08u \x1b]8;;http://x\x07link";
+        assert_eq!(strip_formatting(text), "bold NAME u link");
+    }
+
+    #[test]
+    fn test_man_to_markdown() {
+        let page = "\
+LS(1)                       User Commands                       LS(1)
+
+NAME
+       ls - list directory contents
+
+
+SYNOPSIS
+       ls [OPTION]... [FILE]...
+
+   Sort
+       -t     sort by time
+
+GNU coreutils 9.1              2023                             LS(1)
+
+";
+        assert_eq!(
+            man_to_markdown(page),
+            "## NAME\n    ls - list directory contents\n\n## SYNOPSIS\n    ls [OPTION]... [FILE]...\n\nSort\n    -t     sort by time"
+        );
+    }
+
+    #[test]
+    fn test_command_words() {
+        assert_eq!(command_words(" git  rebase ").unwrap(), ["git", "rebase"]);
+        assert!(command_words("git --exec=rm").is_err());
+        assert!(command_words("a b c d").is_err());
+        assert!(command_words("ls;rm").is_err());
+    }
+
+    #[test]
+    fn test_rustdoc_member() {
+        let html = r#"<h1>Vec</h1><section id="method.push"><h4>push</h4><p>Appends</p></section><section id="method.pop"><h4>pop</h4></section><h2 id="trait-implementations">Traits</h2>"#;
+        assert_eq!(
+            rustdoc_member(html, "push"),
+            Some(r#"<section id="method.push"><h4>push</h4><p>Appends</p></section>"#)
+        );
+        let pop = rustdoc_member(html, "pop").unwrap();
+        assert!(pop.starts_with(r#"<section id="method.pop"><h4>pop</h4>"#));
+        assert!(!pop.contains("Traits"));
+        assert_eq!(rustdoc_member(html, "insert"), None);
+    }
+
+    #[test]
+    fn test_typedoc_member() {
+        let markdown = "# Class Foo\n\n## Methods\n\n### bar()\n\nDoes bar\n\n#### Parameters\n\nx\n\n### `baz`\n\nDoes baz";
+        assert_eq!(
+            typedoc_member(markdown, "bar").unwrap(),
+            "### bar()\n\nDoes bar\n\n#### Parameters\n\nx\n"
+        );
+        assert_eq!(
+            typedoc_member(markdown, "baz").unwrap(),
+            "### `baz`\n\nDoes baz"
+        );
+        assert_eq!(typedoc_member(markdown, "qux"), None);
+    }
+
+    #[test]
+    fn test_clean_links() {
+        assert_eq!(
+            clean_links(
+                "See [Vec](file:///doc/struct.Vec.html) and [web](https://x.dev)§\n\n\n\nend"
+            ),
+            "See Vec and [web](https://x.dev)\n\nend"
+        );
+    }
+}
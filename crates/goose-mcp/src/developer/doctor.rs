@@ -0,0 +1,292 @@
+use std::{fmt, path::Path, time::Duration};
+
+use etcetera::{choose_app_strategy, AppStrategy};
+use indoc::indoc;
+use mcp_core::handler::ToolError;
+use rmcp::model::{Content, Role, Tool, ToolAnnotations};
+use rmcp::object;
+use xcap::Monitor;
+
+use super::editor_models::EditorSettings;
+use super::shell::get_shell_config;
+use super::DeveloperRouter;
+
+/// How long to wait for an editor model's host to answer
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Binaries the developer tools work better with, and what they are used for
+const BINARIES: &[(&str, &str)] = &[
+    ("rg", "fast searches in the grep tool and shell"),
+    (
+        "git",
+        "approval of edits to tracked files, and most projects",
+    ),
+    ("rustfmt", "formatting Rust"),
+    ("prettier", "formatting JavaScript, TypeScript and CSS"),
+    ("black", "formatting Python"),
+    ("gofmt", "formatting Go"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Ok,
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Status::Ok => "ok",
+            Status::Warning => "warning",
+            Status::Error => "error",
+        })
+    }
+}
+
+/// The outcome of one diagnostic check
+#[derive(Debug, Clone)]
+pub struct Check {
+    pub name: String,
+    pub status: Status,
+    pub detail: String,
+}
+
+impl Check {
+    fn new(name: impl Into<String>, status: Status, detail: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            status,
+            detail: detail.into(),
+        }
+    }
+}
+
+/// Check everything the developer tools depend on, for the project in `cwd`
+pub async fn run_checks(cwd: &Path) -> Vec<Check> {
+    let mut checks = Vec::new();
+    checks.extend(check_binaries());
+    checks.push(check_shell());
+    checks.extend(check_directories());
+    // Capturing a display blocks until the image is taken
+    checks.push(
+        tokio::task::spawn_blocking(check_screen_capture)
+            .await
+            .unwrap_or_else(|e| Check::new("screen capture", Status::Error, e.to_string())),
+    );
+    checks.extend(check_editor_models(cwd).await);
+    checks
+}
+
+/// The checks as a report, problems first
+pub fn render(checks: &[Check]) -> String {
+    let mut sorted: Vec<&Check> = checks.iter().collect();
+    sorted.sort_by_key(|check| match check.status {
+        Status::Error => 0,
+        Status::Warning => 1,
+        Status::Ok => 2,
+    });
+    let problems = checks.iter().filter(|c| c.status != Status::Ok).count();
+
+    let mut report = match problems {
+        0 => "All checks passed.\n".to_string(),
+        n => format!("{} of {} checks found a problem.\n", n, checks.len()),
+    };
+    for check in sorted {
+        report.push_str(&format!(
+            "\n[{}] {}: {}",
+            check.status, check.name, check.detail
+        ));
+    }
+    report
+}
+
+fn check_binaries() -> Vec<Check> {
+    BINARIES
+        .iter()
+        .map(|(binary, purpose)| match which::which(binary) {
+            Ok(path) => Check::new(*binary, Status::Ok, path.display().to_string()),
+            Err(_) => Check::new(
+                *binary,
+                Status::Warning,
+                format!("not found on PATH, used for {}", purpose),
+            ),
+        })
+        .collect()
+}
+
+fn check_shell() -> Check {
+    let shell = get_shell_config();
+    let command = std::iter::once(shell.executable.as_str())
+        .chain(shell.args.iter().map(String::as_str))
+        .collect::<Vec<_>>()
+        .join(" ");
+    match which::which(&shell.executable) {
+        Ok(path) => Check::new(
+            "shell",
+            Status::Ok,
+            format!("commands run with `{}` ({})", command, path.display()),
+        ),
+        Err(_) => Check::new(
+            "shell",
+            Status::Error,
+            format!("`{}` was detected but can't be found", shell.executable),
+        ),
+    }
+}
+
+fn check_directories() -> Vec<Check> {
+    let Ok(strategy) = choose_app_strategy(crate::APP_STRATEGY.clone()) else {
+        return vec![Check::new(
+            "config and data directories",
+            Status::Error,
+            "the home directory could not be determined",
+        )];
+    };
+
+    let mut dirs = vec![
+        ("config directory", strategy.config_dir()),
+        ("data directory", strategy.data_dir()),
+    ];
+    if let Some(state_dir) = strategy.state_dir() {
+        dirs.push(("state directory", state_dir));
+    }
+    dirs.into_iter()
+        .map(|(name, dir)| match check_writable(&dir) {
+            Ok(()) => Check::new(name, Status::Ok, format!("{} is writable", dir.display())),
+            Err(e) => Check::new(
+                name,
+                Status::Error,
+                format!("{} is not writable: {}", dir.display(), e),
+            ),
+        })
+        .collect()
+}
+
+fn check_writable(dir: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    tempfile::tempfile_in(dir).map(|_| ())
+}
+
+fn check_screen_capture() -> Check {
+    let monitors = match Monitor::all() {
+        Ok(monitors) if !monitors.is_empty() => monitors,
+        Ok(_) => {
+            return Check::new(
+                "screen capture",
+                Status::Warning,
+                "no displays found, screen_capture will not work",
+            )
+        }
+        Err(e) => {
+            return Check::new(
+                "screen capture",
+                Status::Warning,
+                format!("displays can't be listed: {}", e),
+            )
+        }
+    };
+
+    match monitors[0].capture_image() {
+        Ok(_) => Check::new(
+            "screen capture",
+            Status::Ok,
+            format!("{} display(s) can be captured", monitors.len()),
+        ),
+        Err(e) => Check::new(
+            "screen capture",
+            Status::Warning,
+            format!(
+                "capturing failed, the terminal may need screen recording permission: {}",
+                e
+            ),
+        ),
+    }
+}
+
+async fn check_editor_models(cwd: &Path) -> Vec<Check> {
+    let settings = EditorSettings::load(cwd);
+    let configs: Vec<_> = settings
+        .chain
+        .iter()
+        .chain(settings.routes.iter().map(|route| &route.config))
+        .collect();
+    if configs.is_empty() {
+        return vec![Check::new(
+            "editor model",
+            Status::Ok,
+            "none configured, edits use string replacement",
+        )];
+    }
+
+    let client = match reqwest::Client::builder().timeout(CONNECT_TIMEOUT).build() {
+        Ok(client) => client,
+        Err(e) => {
+            return vec![Check::new(
+                "editor model",
+                Status::Error,
+                format!("no HTTP client: {}", e),
+            )]
+        }
+    };
+
+    let mut checks = Vec::new();
+    for config in configs {
+        let name = format!("editor model {}", config.model);
+        // Any HTTP response means the host is reachable; authentication is checked on use
+        let check = match client.get(&config.host).send().await {
+            Ok(response) => Check::new(
+                name,
+                Status::Ok,
+                format!("{} answered with HTTP {}", config.host, response.status()),
+            ),
+            Err(e) => Check::new(
+                name,
+                Status::Error,
+                format!("{} can't be reached: {}", config.host, e),
+            ),
+        };
+        checks.push(check);
+    }
+    checks
+}
+
+/// The definition of the `doctor` tool
+pub fn tool() -> Tool {
+    Tool::new(
+        "doctor",
+        indoc! {r#"
+            Diagnose problems with the developer tools.
+
+            Reports missing binaries (rg, git and formatters), the shell commands run with,
+            whether the goose config and data directories are writable, whether the screen
+            can be captured, and whether configured editor models can be reached. Use it when
+            a tool fails in a way that suggests the environment rather than the request.
+        "#},
+        object!({
+            "type": "object",
+            "required": [],
+            "properties": {}
+        }),
+    )
+    .annotate(ToolAnnotations {
+        title: Some("Diagnose the developer tools".to_string()),
+        read_only_hint: Some(true),
+        destructive_hint: Some(false),
+        idempotent_hint: Some(true),
+        open_world_hint: Some(true),
+    })
+}
+
+impl DeveloperRouter {
+    pub(super) async fn doctor(&self) -> Result<Vec<Content>, ToolError> {
+        let checks = run_checks(&self.cwd()).await;
+        let report = render(&checks);
+        Ok(vec![
+            Content::text(report.clone()).with_audience(vec![Role::Assistant]),
+            Content::text(report)
+                .with_audience(vec![Role::User])
+                .with_priority(0.0),
+        ])
+    }
+}
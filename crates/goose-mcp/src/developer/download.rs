@@ -0,0 +1,326 @@
+use std::path::{Path, PathBuf};
+use std::sync::atomic::Ordering;
+use std::time::{Duration, Instant};
+
+use indoc::indoc;
+use mcp_core::handler::ToolError;
+use mcp_server::structured::structured_content;
+use rmcp::model::{Content, JsonRpcMessage, Tool, ToolAnnotations};
+use rmcp::object;
+use serde::Serialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::mpsc;
+use url::Url;
+
+use super::approval::request_approval;
+use super::editor_models::load_user_setting;
+use super::http_request::DomainAllowlist;
+use super::progress::ProgressReporter;
+use super::{DeveloperRouter, FileAccess};
+
+/// Config key for the largest file that is downloaded. Only read from the environment and
+/// the global config, so a project can't raise it.
+pub const MAX_BYTES_KEY: &str = "GOOSE_DOWNLOAD_MAX_BYTES";
+
+/// Config key listing the hosts files may be downloaded from, like
+/// [`super::http_request::ALLOWED_DOMAINS_KEY`]. Any public host when it isn't set.
+pub const ALLOWED_DOMAINS_KEY: &str = "GOOSE_DOWNLOAD_ALLOWED_DOMAINS";
+
+const DEFAULT_MAX_BYTES: u64 = 100 * 1024 * 1024;
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How long the server may send nothing before the download is given up
+const STALL_TIMEOUT: Duration = Duration::from_secs(60);
+const MAX_REDIRECTS: usize = 10;
+
+#[derive(Debug, Serialize)]
+pub struct Download {
+    /// The URL the file came from, after redirects
+    pub url: String,
+    pub path: PathBuf,
+    pub bytes: u64,
+    pub sha256: String,
+    /// Whether the file matched the checksum it was expected to have
+    pub verified: bool,
+    pub elapsed_ms: u64,
+}
+
+/// The most bytes a download may have: the configured cap, or less if the caller asks
+pub fn max_bytes(requested: Option<u64>) -> u64 {
+    let cap = load_user_setting(MAX_BYTES_KEY)
+        .and_then(|value| value.trim().parse().ok())
+        .unwrap_or(DEFAULT_MAX_BYTES);
+    requested.map_or(cap, |max| max.min(cap))
+}
+
+/// A SHA-256 checksum as lowercase hex, accepting a `sha256:` prefix and the `<hash>  <file>`
+/// lines of `sha256sum` output
+pub fn parse_sha256(checksum: &str) -> Result<String, ToolError> {
+    let hash = checksum.trim();
+    let hash = hash.strip_prefix("sha256:").unwrap_or(hash);
+    let hash = hash.split_whitespace().next().unwrap_or_default();
+    if hash.len() == 64 && hash.chars().all(|c| c.is_ascii_hexdigit()) {
+        Ok(hash.to_lowercase())
+    } else {
+        Err(ToolError::InvalidParameters(format!(
+            "'{}' is not a SHA-256 checksum, expected 64 hex characters",
+            checksum
+        )))
+    }
+}
+
+/// Download `url` to `path`, failing without touching `path` when the file is larger than
+/// `max_bytes` or doesn't match `sha256`. The file is written next to `path` first and only
+/// moved into place once it is complete and verified. The URL and every redirect must be to
+/// a host in `allowlist`.
+pub async fn download(
+    url: &Url,
+    path: &Path,
+    max_bytes: u64,
+    sha256: Option<&str>,
+    progress: Option<&ProgressReporter>,
+    allowlist: &DomainAllowlist,
+) -> Result<Download, ToolError> {
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(ToolError::InvalidParameters(format!(
+            "Only http and https URLs can be downloaded, not '{}'",
+            url
+        )));
+    }
+    allowlist.check(url)?;
+    let client = allowlist
+        .client_builder(MAX_REDIRECTS)
+        .connect_timeout(CONNECT_TIMEOUT)
+        .build()
+        .map_err(|e| ToolError::ExecutionError(format!("Failed to create HTTP client: {}", e)))?;
+
+    let started = Instant::now();
+    let mut response = client
+        .get(url.clone())
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| ToolError::ExecutionError(format!("Download failed: {}", e)))?;
+    let too_large = |bytes: u64| {
+        ToolError::ExecutionError(format!(
+            "{} has at least {} bytes, more than the {} allowed; raise max_bytes or {} to download it",
+            url, bytes, max_bytes, MAX_BYTES_KEY
+        ))
+    };
+    let total = response.content_length();
+    if let Some(total) = total.filter(|total| *total > max_bytes) {
+        return Err(too_large(total));
+    }
+
+    let dir = path.parent().unwrap_or(Path::new("."));
+    tokio::fs::create_dir_all(dir).await.map_err(|e| {
+        ToolError::ExecutionError(format!("Failed to create {}: {}", dir.display(), e))
+    })?;
+    // The path is dropped, and so the file deleted, on every early return
+    let (file, temp_path) = tempfile::NamedTempFile::new_in(dir)
+        .map_err(|e| {
+            ToolError::ExecutionError(format!(
+                "Failed to create a file in {}: {}",
+                dir.display(),
+                e
+            ))
+        })?
+        .into_parts();
+    let mut file = tokio::fs::File::from_std(file);
+    let write_error = |e: std::io::Error| {
+        ToolError::ExecutionError(format!("Failed to write {}: {}", path.display(), e))
+    };
+    let mut hasher = Sha256::new();
+    let mut bytes = 0;
+    loop {
+        let chunk = tokio::time::timeout(STALL_TIMEOUT, response.chunk())
+            .await
+            .map_err(|_| {
+                ToolError::ExecutionError(format!(
+                    "The server sent nothing for {}s after {} bytes",
+                    STALL_TIMEOUT.as_secs(),
+                    bytes
+                ))
+            })?
+            .map_err(|e| ToolError::ExecutionError(format!("Download failed: {}", e)))?;
+        let Some(chunk) = chunk else { break };
+        bytes += chunk.len() as u64;
+        if bytes > max_bytes {
+            return Err(too_large(bytes));
+        }
+        hasher.update(&chunk);
+        file.write_all(&chunk).await.map_err(write_error)?;
+        if let Some(progress) = progress {
+            progress.report(bytes as f64, total.map(|total| total as f64));
+        }
+    }
+
+    file.flush().await.map_err(write_error)?;
+    drop(file);
+
+    let actual: String = hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    if let Some(expected) = sha256 {
+        if actual != expected {
+            return Err(ToolError::ExecutionError(format!(
+                "The download's SHA-256 is {} but {} was expected, so it wasn't saved",
+                actual, expected
+            )));
+        }
+    }
+    // Temporary files are only readable by their owner, unlike the file they replace
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = tokio::fs::metadata(path)
+            .await
+            .map_or(0o644, |metadata| metadata.permissions().mode());
+        let _ = tokio::fs::set_permissions(&temp_path, std::fs::Permissions::from_mode(mode)).await;
+    }
+    temp_path.persist(path).map_err(|e| {
+        ToolError::ExecutionError(format!("Failed to save {}: {}", path.display(), e.error))
+    })?;
+
+    Ok(Download {
+        url: response.url().to_string(),
+        path: path.to_path_buf(),
+        bytes,
+        sha256: actual,
+        verified: sha256.is_some(),
+        elapsed_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+/// The definition of the `download` tool
+pub fn tool() -> Tool {
+    Tool::new(
+        "download",
+        indoc! {r#"
+            Download a URL to a file, for datasets, release archives or vendored assets.
+            Use this rather than curl or wget in the shell so the size is capped and the
+            checksum is checked before anything is saved.
+
+            `path` is the absolute path to save to; an existing file is only replaced with
+            `overwrite`. Pass `sha256`, e.g. from the release's checksums file, and the file
+            is only saved when it matches. Downloads stop at `max_bytes`, at most the
+            configured limit (100 MB unless set otherwise). Returns the file's size and
+            SHA-256. Only hosts the user allows can be downloaded from, by default any public
+            host but nothing local, and redirects must stay on them.
+        "#},
+        object!({
+            "type": "object",
+            "required": ["url", "path"],
+            "properties": {
+                "url": {"type": "string", "description": "http or https URL"},
+                "path": {"type": "string", "description": "Absolute path to save the file to"},
+                "sha256": {"type": "string", "description": "Expected SHA-256 as hex"},
+                "max_bytes": {"type": "integer", "minimum": 1},
+                "overwrite": {"type": "boolean", "default": false}
+            }
+        }),
+    )
+    .annotate(ToolAnnotations {
+        title: Some("Download a file".to_string()),
+        read_only_hint: Some(false),
+        destructive_hint: Some(false),
+        idempotent_hint: Some(true),
+        open_world_hint: Some(true),
+    })
+}
+
+impl DeveloperRouter {
+    pub(super) async fn download(
+        &self,
+        params: Value,
+        notifier: &mpsc::Sender<JsonRpcMessage>,
+    ) -> Result<Vec<Content>, ToolError> {
+        let arg = |name: &str| params.get(name).and_then(Value::as_str);
+        let url = arg("url")
+            .ok_or_else(|| ToolError::InvalidParameters("Missing 'url' parameter".into()))?;
+        let url = Url::parse(url)
+            .map_err(|e| ToolError::InvalidParameters(format!("Invalid URL '{}': {}", url, e)))?;
+        let path = self
+            .resolve_path(arg("path").ok_or_else(|| {
+                ToolError::InvalidParameters("Missing 'path' parameter".into())
+            })?)?;
+        if self.is_ignored(&path) {
+            return Err(self.ignored_path_error(
+                &path,
+                format!(
+                    "Access to '{}' is restricted by .gooseignore",
+                    path.display()
+                ),
+            ));
+        }
+        if path.is_dir() {
+            return Err(ToolError::InvalidParameters(format!(
+                "'{}' is a directory, pass the path of the file to save",
+                path.display()
+            )));
+        }
+        let overwrite = params
+            .get("overwrite")
+            .and_then(Value::as_bool)
+            .unwrap_or(false);
+        if path.exists() && !overwrite {
+            return Err(ToolError::InvalidParameters(format!(
+                "'{}' already exists, pass \"overwrite\": true to replace it",
+                path.display()
+            )));
+        }
+        let sha256 = arg("sha256").map(parse_sha256).transpose()?;
+        let max_bytes = max_bytes(params.get("max_bytes").and_then(Value::as_u64));
+
+        if let Some(action) = self
+            .approval_policy
+            .write_action(&self.catalog, &path, "download")
+            .await
+        {
+            request_approval(notifier, &action).await?;
+        }
+        let _file_lock = self.file_locks.lock(&path).await;
+        // Text files replaced by a download can be restored with undo_edit
+        let old = tokio::fs::read_to_string(&path).await.ok();
+        let progress =
+            ProgressReporter::requested(&params, notifier, || format!("Downloading {}", url));
+        let downloaded = download(
+            &url,
+            &path,
+            max_bytes,
+            sha256.as_deref(),
+            progress.as_ref(),
+            &DomainAllowlist::load_or_public(ALLOWED_DOMAINS_KEY),
+        )
+        .await?;
+        if let Some(old) = old {
+            self.file_history.push(&path, old);
+        }
+        self.session_files
+            .lock()
+            .unwrap()
+            .insert(path.clone(), FileAccess::Edited);
+        self.resources_changed.store(true, Ordering::Relaxed);
+
+        let result = serde_json::to_value(&downloaded)
+            .map_err(|e| ToolError::ExecutionError(format!("Failed to encode result: {}", e)))?;
+        Ok(vec![
+            Content::text(format!(
+                "Downloaded {} bytes to {}{} (sha256 {})",
+                downloaded.bytes,
+                path.display(),
+                if downloaded.verified {
+                    ", checksum verified"
+                } else {
+                    ""
+                },
+                downloaded.sha256
+            )),
+            structured_content(&result),
+        ])
+    }
+}
@@ -0,0 +1,65 @@
+use std::collections::{HashMap, VecDeque};
+
+use sha2::{Digest, Sha256};
+
+/// Applies remembered unless `GOOSE_EDITOR_CACHE_SIZE` says otherwise
+const DEFAULT_CAPACITY: usize = 64;
+
+pub type ApplyKey = [u8; 32];
+
+/// Results of recent editor model applies, so an agent retrying the same edit on the same
+/// file doesn't pay for another API call. The least recently stored entry is evicted first.
+#[derive(Debug)]
+pub struct ApplyCache {
+    capacity: usize,
+    entries: HashMap<ApplyKey, String>,
+    order: VecDeque<ApplyKey>,
+}
+
+impl ApplyCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    /// Sized from `GOOSE_EDITOR_CACHE_SIZE`, where 0 disables caching
+    pub fn from_env() -> Self {
+        let capacity = std::env::var("GOOSE_EDITOR_CACHE_SIZE")
+            .ok()
+            .and_then(|v| v.trim().parse().ok())
+            .unwrap_or(DEFAULT_CAPACITY);
+        Self::new(capacity)
+    }
+
+    /// Identify an apply by the file content and the requested change
+    pub fn key(content: &str, old_str: &str, new_str: &str) -> ApplyKey {
+        let mut hasher = Sha256::new();
+        // Length prefixes keep ("ab", "c") and ("a", "bc") apart
+        for part in [content, old_str, new_str] {
+            hasher.update((part.len() as u64).to_le_bytes());
+            hasher.update(part.as_bytes());
+        }
+        hasher.finalize().into()
+    }
+
+    pub fn get(&self, key: &ApplyKey) -> Option<&str> {
+        self.entries.get(key).map(String::as_str)
+    }
+
+    pub fn insert(&mut self, key: ApplyKey, updated_content: String) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.insert(key, updated_content).is_none() {
+            self.order.push_back(key);
+        }
+        while self.order.len() > self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.entries.remove(&oldest);
+            }
+        }
+    }
+}
@@ -0,0 +1,462 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use etcetera::{choose_app_strategy, AppStrategy};
+use keyring::Entry;
+use serde::{de::DeserializeOwned, Deserialize};
+
+use super::ollama_editor::DEFAULT_OLLAMA_HOST;
+
+/// Where goose keeps its secrets, shared with the goose config
+const KEYRING_SERVICE: &str = "goose";
+const KEYRING_USERNAME: &str = "secrets";
+
+/// Project-level overrides, relative to the working directory
+const PROJECT_CONFIG_PATH: &str = ".goose/config.yaml";
+
+/// Applies sent to one provider at the same time unless configured otherwise
+pub const DEFAULT_MAX_CONCURRENCY: usize = 4;
+
+/// Providers tried in order when the primary editor model fails
+const FALLBACKS_KEY: &str = "GOOSE_EDITOR_FALLBACKS";
+/// Providers used instead of the primary one for files in particular languages
+const ROUTES_KEY: &str = "GOOSE_EDITOR_ROUTES";
+
+/// Editor model settings, resolved from (highest priority first) environment variables, the
+/// project's `.goose/config.yaml` and the goose `config.yaml`, using the same keys in each.
+/// The host, the key and the fallbacks and routes say where file contents are sent and with
+/// which credentials, so they are only read from the environment and the goose `config.yaml`:
+/// the project's config is in the repository and the tools can edit it.
+///
+/// - `GOOSE_EDITOR_PROVIDER`: `openai_compatible`, `morphllm`, `relace` or `ollama` (inferred
+///   from the host when unset)
+/// - `GOOSE_EDITOR_HOST` and `GOOSE_EDITOR_MODEL`, where the host defaults to a local server
+///   for `ollama`
+/// - `GOOSE_EDITOR_API_KEY`, or `GOOSE_EDITOR_API_KEY_REF` naming an environment variable or
+///   goose secret that holds the key so it doesn't have to be written into a config file. Local
+///   servers don't need a key.
+/// - `GOOSE_EDITOR_MAX_CONCURRENCY` and `GOOSE_EDITOR_REQUESTS_PER_MINUTE`: limits on calls to
+///   the provider
+/// - `GOOSE_EDITOR_FALLBACKS`: providers to try in order when the primary one fails, as a
+///   list of `{provider, host, model, api_key or api_key_ref, max_concurrency,
+///   requests_per_minute}` (JSON in the environment)
+/// - `GOOSE_EDITOR_ROUTES`: providers for files in particular languages, as a list of the same
+///   entries with a `languages` list of file extensions or language names
+#[derive(Debug, Clone, PartialEq)]
+pub struct EditorConfig {
+    pub provider: Option<String>,
+    pub host: String,
+    pub model: String,
+    pub api_key: String,
+    /// Most applies in flight at once, defaulting to [`DEFAULT_MAX_CONCURRENCY`]
+    pub max_concurrency: Option<usize>,
+    /// Most applies started in any minute, unlimited by default
+    pub requests_per_minute: Option<u32>,
+}
+
+/// All configured editor models
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EditorSettings {
+    /// The primary editor model followed by its fallbacks
+    pub chain: Vec<EditorConfig>,
+    /// Editor models for particular languages, first match wins
+    pub routes: Vec<EditorRoute>,
+}
+
+/// An editor model used for files in some languages instead of the primary one
+#[derive(Debug, Clone, PartialEq)]
+pub struct EditorRoute {
+    /// Lowercase file extensions or language names, e.g. `rs` or `markdown`
+    pub languages: Vec<String>,
+    pub config: EditorConfig,
+}
+
+impl EditorSettings {
+    /// Load every editor model, skipping any that are incomplete
+    pub fn load(cwd: &Path) -> Self {
+        ConfigSources::load(cwd).settings()
+    }
+}
+
+/// One entry of `GOOSE_EDITOR_ROUTES`
+#[derive(Debug, Deserialize)]
+struct RouteEntry {
+    languages: Vec<String>,
+    #[serde(flatten)]
+    provider: ProviderEntry,
+}
+
+/// One entry of `GOOSE_EDITOR_FALLBACKS`, or the provider of a route
+#[derive(Debug, Deserialize)]
+struct ProviderEntry {
+    #[serde(default)]
+    provider: Option<String>,
+    #[serde(default)]
+    host: String,
+    model: String,
+    #[serde(default)]
+    api_key: Option<String>,
+    #[serde(default)]
+    api_key_ref: Option<String>,
+    #[serde(default)]
+    max_concurrency: Option<usize>,
+    #[serde(default)]
+    requests_per_minute: Option<u32>,
+}
+
+impl ProviderEntry {
+    fn resolve(self) -> Option<EditorConfig> {
+        let host = if self.host.is_empty() {
+            default_host(self.provider.as_deref())?
+        } else {
+            self.host
+        };
+        if self.model.is_empty() {
+            return None;
+        }
+        let api_key = self
+            .api_key
+            .filter(|key| !key.is_empty())
+            .or_else(|| self.api_key_ref.and_then(|name| resolve_secret(&name)));
+        let api_key = key_or_local(api_key, self.provider.as_deref(), &host)?;
+        Some(EditorConfig {
+            provider: self.provider,
+            host,
+            model: self.model,
+            api_key,
+            max_concurrency: self.max_concurrency,
+            requests_per_minute: self.requests_per_minute,
+        })
+    }
+}
+
+struct ConfigSources {
+    project: HashMap<String, serde_yaml::Value>,
+    global: HashMap<String, serde_yaml::Value>,
+}
+
+impl ConfigSources {
+    fn load(cwd: &Path) -> Self {
+        Self {
+            project: read_yaml(&cwd.join(PROJECT_CONFIG_PATH)),
+            global: read_yaml(&global_config_path()),
+        }
+    }
+
+    /// The environment and the global config only, for settings that widen what the tools may
+    /// do and so mustn't come from the project's config, which the tools can edit
+    fn user(&self) -> Self {
+        Self {
+            project: HashMap::new(),
+            global: self.global.clone(),
+        }
+    }
+
+    /// Every editor model, skipping any that are incomplete
+    fn settings(&self) -> EditorSettings {
+        let user = self.user();
+        let chain = self
+            .primary()
+            .into_iter()
+            .chain(
+                user.entries::<ProviderEntry>(FALLBACKS_KEY)
+                    .into_iter()
+                    .filter_map(ProviderEntry::resolve),
+            )
+            .collect();
+        let routes = user
+            .entries::<RouteEntry>(ROUTES_KEY)
+            .into_iter()
+            .filter_map(|route| {
+                let languages = route
+                    .languages
+                    .iter()
+                    .map(|language| language.trim_start_matches('.').to_lowercase())
+                    .collect();
+                Some(EditorRoute {
+                    languages,
+                    config: route.provider.resolve()?,
+                })
+            })
+            .collect();
+
+        EditorSettings { chain, routes }
+    }
+
+    fn primary(&self) -> Option<EditorConfig> {
+        let user = self.user();
+        let provider = self.get("GOOSE_EDITOR_PROVIDER");
+        let host = user
+            .get("GOOSE_EDITOR_HOST")
+            .or_else(|| default_host(provider.as_deref()))?;
+        let model = self.get("GOOSE_EDITOR_MODEL")?;
+        let api_key = user
+            .get("GOOSE_EDITOR_API_KEY")
+            .or_else(|| {
+                user.get("GOOSE_EDITOR_API_KEY_REF")
+                    .and_then(|name| resolve_secret(&name))
+            })
+            .or_else(|| keyring_secret("GOOSE_EDITOR_API_KEY"));
+        let api_key = key_or_local(api_key, provider.as_deref(), &host)?;
+
+        Some(EditorConfig {
+            provider,
+            host,
+            model,
+            api_key,
+            max_concurrency: self.parse("GOOSE_EDITOR_MAX_CONCURRENCY"),
+            requests_per_minute: self.parse("GOOSE_EDITOR_REQUESTS_PER_MINUTE"),
+        })
+    }
+
+    fn parse<T: std::str::FromStr>(&self, key: &str) -> Option<T> {
+        let value = self.get(key)?;
+        let parsed = value.trim().parse().ok();
+        if parsed.is_none() {
+            tracing::warn!("Ignoring invalid {} '{}'", key, value);
+        }
+        parsed
+    }
+
+    /// A list of entries, from JSON in the environment or YAML in the config files
+    fn entries<T: DeserializeOwned>(&self, key: &str) -> Vec<T> {
+        let entries: Result<Vec<T>, String> = match std::env::var(key) {
+            Ok(json) => serde_json::from_str(&json).map_err(|e| e.to_string()),
+            Err(_) => match self.project.get(key).or_else(|| self.global.get(key)) {
+                Some(value) => serde_yaml::from_value(value.clone()).map_err(|e| e.to_string()),
+                None => Ok(Vec::new()),
+            },
+        };
+
+        entries.unwrap_or_else(|e| {
+            tracing::warn!("Ignoring invalid {}: {}", key, e);
+            Vec::new()
+        })
+    }
+
+    /// A non-empty string setting from the first source that has it
+    fn get(&self, key: &str) -> Option<String> {
+        std::env::var(key)
+            .ok()
+            .or_else(|| yaml_string(&self.project, key))
+            .or_else(|| yaml_string(&self.global, key))
+            .filter(|value| !value.is_empty())
+    }
+}
+
+fn default_host(provider: Option<&str>) -> Option<String> {
+    (provider == Some("ollama")).then(|| DEFAULT_OLLAMA_HOST.to_string())
+}
+
+/// The API key, or an empty one for local servers which don't need it
+fn key_or_local(api_key: Option<String>, provider: Option<&str>, host: &str) -> Option<String> {
+    let is_local = provider == Some("ollama")
+        || ["localhost", "127.0.0.1", "[::1]"]
+            .iter()
+            .any(|local| host.contains(local));
+    api_key.or_else(|| is_local.then(String::new))
+}
+
+/// The config files settings are read from, the project's first
+pub fn config_paths(cwd: &Path) -> Vec<PathBuf> {
+    vec![cwd.join(PROJECT_CONFIG_PATH), global_config_path()]
+}
+
+/// A list setting from the same sources as the editor settings, e.g. the developer tools
+/// to turn off
+pub fn load_list(cwd: &Path, key: &str) -> Vec<String> {
+    ConfigSources::load(cwd).entries(key)
+}
+
+/// A string setting from the same sources as the editor settings, e.g. the locale
+pub fn load_setting(cwd: &Path, key: &str) -> Option<String> {
+    ConfigSources::load(cwd).get(key)
+}
+
+/// A list from the environment or the global config only, for settings that widen what the
+/// tools may do and so mustn't come from the project's config, which the tools can edit
+pub fn load_user_list(key: &str) -> Vec<String> {
+    let sources = ConfigSources {
+        project: HashMap::new(),
+        global: read_yaml(&global_config_path()),
+    };
+    sources.entries(key)
+}
+
+/// A string setting from the environment or the global config only, like [`load_user_list`]
+pub fn load_user_setting(key: &str) -> Option<String> {
+    let sources = ConfigSources {
+        project: HashMap::new(),
+        global: read_yaml(&global_config_path()),
+    };
+    sources.get(key)
+}
+
+fn global_config_path() -> PathBuf {
+    choose_app_strategy(crate::APP_STRATEGY.clone())
+        .map(|strategy| strategy.in_config_dir("config.yaml"))
+        .unwrap_or_else(|_| {
+            PathBuf::from(shellexpand::tilde("~/.config/goose/config.yaml").to_string())
+        })
+}
+
+fn read_yaml(path: &Path) -> HashMap<String, serde_yaml::Value> {
+    let Ok(content) = std::fs::read_to_string(path) else {
+        return HashMap::new();
+    };
+    serde_yaml::from_str(&content).unwrap_or_else(|e| {
+        tracing::warn!("Ignoring invalid config file {}: {}", path.display(), e);
+        HashMap::new()
+    })
+}
+
+fn yaml_string(values: &HashMap<String, serde_yaml::Value>, key: &str) -> Option<String> {
+    match values.get(key)? {
+        serde_yaml::Value::String(value) => Some(value.clone()),
+        serde_yaml::Value::Number(value) => Some(value.to_string()),
+        serde_yaml::Value::Bool(value) => Some(value.to_string()),
+        _ => None,
+    }
+}
+
+/// Look up a secret by name in the environment, then in the goose secrets in the keyring
+fn resolve_secret(name: &str) -> Option<String> {
+    std::env::var(name)
+        .ok()
+        .filter(|value| !value.is_empty())
+        .or_else(|| keyring_secret(name))
+}
+
+fn keyring_secret(name: &str) -> Option<String> {
+    let content = Entry::new(KEYRING_SERVICE, KEYRING_USERNAME)
+        .ok()?
+        .get_password()
+        .ok()?;
+    let secrets: HashMap<String, serde_json::Value> = serde_json::from_str(&content).ok()?;
+    secrets
+        .get(name)
+        .and_then(|v| v.as_str())
+        .filter(|value| !value.is_empty())
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serial_test::serial;
+
+    const KEYS: &[&str] = &[
+        "GOOSE_EDITOR_PROVIDER",
+        "GOOSE_EDITOR_HOST",
+        "GOOSE_EDITOR_MODEL",
+        "GOOSE_EDITOR_API_KEY",
+        "GOOSE_EDITOR_API_KEY_REF",
+        "GOOSE_EDITOR_MAX_CONCURRENCY",
+        "GOOSE_EDITOR_REQUESTS_PER_MINUTE",
+        FALLBACKS_KEY,
+        ROUTES_KEY,
+    ];
+
+    fn sources(project: &str, global: &str) -> ConfigSources {
+        for key in KEYS {
+            std::env::remove_var(key);
+        }
+        ConfigSources {
+            project: serde_yaml::from_str(project).unwrap(),
+            global: serde_yaml::from_str(global).unwrap(),
+        }
+    }
+
+    const GLOBAL: &str = "
+GOOSE_EDITOR_HOST: https://api.example.com/v1
+GOOSE_EDITOR_MODEL: global-model
+GOOSE_EDITOR_API_KEY: global-key
+GOOSE_EDITOR_MAX_CONCURRENCY: 8
+";
+
+    #[test]
+    #[serial]
+    fn test_environment_then_project_then_global() {
+        let sources = sources(
+            "GOOSE_EDITOR_MODEL: project-model\nGOOSE_EDITOR_MAX_CONCURRENCY: 2\n",
+            GLOBAL,
+        );
+        assert_eq!(
+            sources.primary(),
+            Some(EditorConfig {
+                provider: None,
+                host: "https://api.example.com/v1".to_string(),
+                model: "project-model".to_string(),
+                api_key: "global-key".to_string(),
+                max_concurrency: Some(2),
+                requests_per_minute: None,
+            })
+        );
+
+        std::env::set_var("GOOSE_EDITOR_MODEL", "env-model");
+        std::env::set_var("GOOSE_EDITOR_API_KEY", "env-key");
+        let primary = sources.primary().unwrap();
+        std::env::remove_var("GOOSE_EDITOR_MODEL");
+        std::env::remove_var("GOOSE_EDITOR_API_KEY");
+        assert_eq!(primary.model, "env-model");
+        assert_eq!(primary.api_key, "env-key");
+        assert_eq!(primary.max_concurrency, Some(2));
+    }
+
+    #[test]
+    #[serial]
+    fn test_project_config_cannot_choose_where_files_are_sent() {
+        let project = "
+GOOSE_EDITOR_HOST: https://attacker.example.com
+GOOSE_EDITOR_API_KEY_REF: HOME
+GOOSE_EDITOR_FALLBACKS:
+  - host: https://attacker.example.com
+    model: m
+    api_key_ref: HOME
+GOOSE_EDITOR_ROUTES:
+  - languages: [rs]
+    host: https://attacker.example.com
+    model: m
+    api_key: k
+";
+        let settings = sources(project, GLOBAL).settings();
+        assert_eq!(settings.chain.len(), 1);
+        assert_eq!(settings.chain[0].host, "https://api.example.com/v1");
+        assert_eq!(settings.chain[0].api_key, "global-key");
+        assert!(settings.routes.is_empty());
+
+        // Without a host of the user's, there is no editor model at all
+        let settings = sources(
+            "GOOSE_EDITOR_HOST: https://attacker.example.com\nGOOSE_EDITOR_MODEL: m\nGOOSE_EDITOR_API_KEY: k\n",
+            "{}",
+        )
+        .settings();
+        assert_eq!(settings, EditorSettings::default());
+    }
+
+    #[test]
+    #[serial]
+    fn test_fallbacks_and_routes_from_the_global_config() {
+        let global = format!(
+            "{}
+GOOSE_EDITOR_FALLBACKS:
+  - provider: ollama
+    model: qwen
+GOOSE_EDITOR_ROUTES:
+  - languages: [.RS, markdown]
+    host: http://localhost:8080
+    model: local
+",
+            GLOBAL
+        );
+        let settings = sources("{}", &global).settings();
+        assert_eq!(settings.chain.len(), 2);
+        assert_eq!(settings.chain[1].host, DEFAULT_OLLAMA_HOST);
+        assert_eq!(settings.chain[1].api_key, "");
+        assert_eq!(settings.routes.len(), 1);
+        assert_eq!(settings.routes[0].languages, vec!["rs", "markdown"]);
+        assert_eq!(settings.routes[0].config.host, "http://localhost:8080");
+    }
+}
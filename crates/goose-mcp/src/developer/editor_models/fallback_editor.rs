@@ -0,0 +1,108 @@
+use super::{EditorModelImpl, Progress};
+use anyhow::Result;
+use async_trait::async_trait;
+
+/// Tries each editor in order until one succeeds, so a failing or rate limited primary
+/// provider doesn't immediately degrade to plain string replacement
+pub struct FallbackEditor {
+    editors: Vec<Box<dyn EditorModelImpl>>,
+}
+
+impl FallbackEditor {
+    pub fn new(editors: Vec<Box<dyn EditorModelImpl>>) -> Self {
+        Self { editors }
+    }
+}
+
+#[async_trait]
+impl EditorModelImpl for FallbackEditor {
+    async fn edit_code(
+        &self,
+        original_code: &str,
+        old_str: &str,
+        update_snippet: &str,
+        progress: Progress<'_>,
+    ) -> Result<String, String> {
+        let mut errors = Vec::new();
+        for (index, editor) in self.editors.iter().enumerate() {
+            match editor
+                .edit_code(original_code, old_str, update_snippet, progress)
+                .await
+            {
+                Ok(updated) => return Ok(updated),
+                Err(e) => {
+                    tracing::warn!(
+                        "Editor model {} of {} failed: {}",
+                        index + 1,
+                        self.editors.len(),
+                        e
+                    );
+                    errors.push(e);
+                }
+            }
+        }
+        Err(format!("All editor models failed: {}", errors.join("; ")))
+    }
+
+    // The primary editor decides how str_replace is described
+    fn get_str_replace_description(&self) -> &'static str {
+        self.editors
+            .first()
+            .map(|editor| editor.get_str_replace_description())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Fixed(Result<&'static str, &'static str>, &'static str);
+
+    #[async_trait]
+    impl EditorModelImpl for Fixed {
+        async fn edit_code(
+            &self,
+            _: &str,
+            _: &str,
+            _: &str,
+            _: Progress<'_>,
+        ) -> Result<String, String> {
+            self.0.map(str::to_string).map_err(str::to_string)
+        }
+
+        fn get_str_replace_description(&self) -> &'static str {
+            self.1
+        }
+    }
+
+    #[tokio::test]
+    async fn test_editors_are_tried_in_order() {
+        let editor = FallbackEditor::new(vec![
+            Box::new(Fixed(Err("rate limited"), "primary")),
+            Box::new(Fixed(Ok("updated"), "fallback")),
+            Box::new(Fixed(Ok("unused"), "last")),
+        ]);
+        assert_eq!(
+            editor.edit_code("code", "old", "new", None).await.unwrap(),
+            "updated"
+        );
+        assert_eq!(editor.get_str_replace_description(), "primary");
+
+        let failing = FallbackEditor::new(vec![
+            Box::new(Fixed(Err("timeout"), "")),
+            Box::new(Fixed(Err("HTTP 500"), "")),
+        ]);
+        assert_eq!(
+            failing
+                .edit_code("code", "old", "new", None)
+                .await
+                .unwrap_err(),
+            "All editor models failed: timeout; HTTP 500"
+        );
+        assert_eq!(
+            FallbackEditor::new(Vec::new()).get_str_replace_description(),
+            ""
+        );
+    }
+}
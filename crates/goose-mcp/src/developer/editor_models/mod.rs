@@ -1,98 +1,198 @@
+mod cache;
+mod config;
+mod fallback_editor;
 mod morphllm_editor;
+mod ollama_editor;
 mod openai_compatible_editor;
+mod rate_limit;
+mod registry;
 mod relace_editor;
+mod streaming;
+mod validate;
+
+use std::path::Path;
 
 use anyhow::Result;
+use async_trait::async_trait;
 
+pub use cache::ApplyCache;
+pub(crate) use config::{config_paths, load_list, load_setting, load_user_list, load_user_setting};
+pub use config::{EditorConfig, EditorRoute, EditorSettings, DEFAULT_MAX_CONCURRENCY};
+pub use fallback_editor::FallbackEditor;
 pub use morphllm_editor::MorphLLMEditor;
+pub use ollama_editor::OllamaEditor;
 pub use openai_compatible_editor::OpenAICompatibleEditor;
+pub use rate_limit::RateLimitedEditor;
+pub use registry::{editor_providers, register_editor_provider, EditorFactory};
 pub use relace_editor::RelaceEditor;
+pub use validate::validate_edit;
 
-/// Enum for different editor models that can perform intelligent code editing
-#[derive(Debug)]
-pub enum EditorModel {
-    MorphLLM(MorphLLMEditor),
-    OpenAICompatible(OpenAICompatibleEditor),
-    Relace(RelaceEditor),
-}
+use super::lang;
+use registry::build_editor;
 
-impl EditorModel {
-    /// Call the editor API to perform intelligent code replacement
-    pub async fn edit_code(
-        &self,
-        original_code: &str,
-        old_str: &str,
-        update_snippet: &str,
-    ) -> Result<String, String> {
-        match self {
-            EditorModel::MorphLLM(editor) => {
-                editor
-                    .edit_code(original_code, old_str, update_snippet)
-                    .await
-            }
-            EditorModel::OpenAICompatible(editor) => {
-                editor
-                    .edit_code(original_code, old_str, update_snippet)
-                    .await
-            }
-            EditorModel::Relace(editor) => {
-                editor
-                    .edit_code(original_code, old_str, update_snippet)
-                    .await
-            }
-        }
-    }
+/// Called with the number of bytes of the edited file received so far, when the caller
+/// wants the response streamed
+pub type Progress<'a> = Option<&'a (dyn Fn(usize) + Send + Sync)>;
 
-    /// Get the description for the str_replace command when this editor is active
-    pub fn get_str_replace_description(&self) -> &'static str {
-        match self {
-            EditorModel::MorphLLM(editor) => editor.get_str_replace_description(),
-            EditorModel::OpenAICompatible(editor) => editor.get_str_replace_description(),
-            EditorModel::Relace(editor) => editor.get_str_replace_description(),
-        }
-    }
-}
+/// An editor model that can perform intelligent code editing
+pub type EditorModel = dyn EditorModelImpl;
 
-/// Trait for individual editor implementations
-pub trait EditorModelImpl {
+/// Trait for individual editor implementations. Implement it and call
+/// [`register_editor_provider`] to add a custom fast-apply provider.
+#[async_trait]
+pub trait EditorModelImpl: Send + Sync {
     /// Call the editor API to perform intelligent code replacement
     async fn edit_code(
         &self,
         original_code: &str,
         old_str: &str,
         update_snippet: &str,
+        progress: Progress<'_>,
     ) -> Result<String, String>;
 
     /// Get the description for the str_replace command when this editor is active
     fn get_str_replace_description(&self) -> &'static str;
 }
 
-/// Factory function to create the appropriate editor model based on environment variables
-pub fn create_editor_model() -> Option<EditorModel> {
+/// The configured editor models, picked per file so some languages can use a different
+/// model than the primary one
+pub struct EditorModels {
+    default: Option<Box<EditorModel>>,
+    routes: Vec<(Vec<String>, Box<EditorModel>)>,
+}
+
+impl EditorModels {
+    /// The editor model for a file, matched on its extension or language name
+    pub fn for_path(&self, path: &Path) -> Option<&EditorModel> {
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(str::to_lowercase);
+        let language = lang::get_language_identifier(path);
+        self.routes
+            .iter()
+            .find(|(languages, _)| {
+                languages.iter().any(|l| {
+                    Some(l.as_str()) == extension.as_deref()
+                        || (!language.is_empty() && l == language)
+                })
+            })
+            .map(|(_, editor)| editor.as_ref())
+            .or(self.default.as_deref())
+    }
+
+    /// Get the description for the str_replace command, from the primary editor model
+    pub fn get_str_replace_description(&self) -> &'static str {
+        self.default
+            .as_ref()
+            .or_else(|| self.routes.first().map(|(_, editor)| editor))
+            .map(|editor| editor.get_str_replace_description())
+            .unwrap_or_default()
+    }
+}
+
+/// Factory function to create the appropriate editor models from the environment, the
+/// `.goose/config.yaml` of the project in `cwd` and the goose config file, chaining any
+/// fallbacks after the primary editor
+pub fn create_editor_model(cwd: &Path) -> Option<EditorModels> {
     // Don't use Editor API during tests
     if cfg!(test) {
         return None;
     }
 
-    // Check if basic editor API variables are set
-    let api_key = std::env::var("GOOSE_EDITOR_API_KEY").ok()?;
-    let host = std::env::var("GOOSE_EDITOR_HOST").ok()?;
-    let model = std::env::var("GOOSE_EDITOR_MODEL").ok()?;
+    let settings = EditorSettings::load(cwd);
+
+    let mut editors: Vec<Box<EditorModel>> = settings
+        .chain
+        .into_iter()
+        .filter_map(limited_editor)
+        .collect();
+    let default: Option<Box<EditorModel>> = match editors.len() {
+        0 => None,
+        1 => editors.pop(),
+        _ => Some(Box::new(FallbackEditor::new(editors))),
+    };
+    let routes: Vec<_> = settings
+        .routes
+        .into_iter()
+        .filter_map(|route| Some((route.languages, limited_editor(route.config)?)))
+        .collect();
 
-    if api_key.is_empty() || host.is_empty() || model.is_empty() {
+    if default.is_none() && routes.is_empty() {
         return None;
     }
+    Some(EditorModels { default, routes })
+}
+
+// Each provider gets its own limits, so a throttled primary doesn't hold up fallbacks
+fn limited_editor(config: EditorConfig) -> Option<Box<EditorModel>> {
+    let max_concurrency = config.max_concurrency.unwrap_or(DEFAULT_MAX_CONCURRENCY);
+    let requests_per_minute = config.requests_per_minute;
+    let editor = build_editor(config)?;
+    Some(Box::new(RateLimitedEditor::new(
+        editor,
+        max_concurrency,
+        requests_per_minute,
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Named(&'static str);
+
+    #[async_trait]
+    impl EditorModelImpl for Named {
+        async fn edit_code(
+            &self,
+            _: &str,
+            _: &str,
+            _: &str,
+            _: Progress<'_>,
+        ) -> Result<String, String> {
+            Ok(self.0.to_string())
+        }
+
+        fn get_str_replace_description(&self) -> &'static str {
+            self.0
+        }
+    }
+
+    fn picked(models: &EditorModels, path: &str) -> Option<&'static str> {
+        models
+            .for_path(Path::new(path))
+            .map(|editor| editor.get_str_replace_description())
+    }
+
+    #[test]
+    fn test_for_path_matches_extensions_and_languages() {
+        let models = EditorModels {
+            default: Some(Box::new(Named("default"))),
+            routes: vec![
+                (
+                    vec!["py".to_string()],
+                    Box::new(Named("python")) as Box<EditorModel>,
+                ),
+                (
+                    vec!["rust".to_string(), "toml".to_string()],
+                    Box::new(Named("rust")),
+                ),
+            ],
+        };
+        assert_eq!(picked(&models, "src/app.py"), Some("python"));
+        assert_eq!(picked(&models, "src/main.rs"), Some("rust"));
+        assert_eq!(picked(&models, "Cargo.toml"), Some("rust"));
+        assert_eq!(picked(&models, "SRC/APP.PY"), Some("python"));
+        assert_eq!(picked(&models, "README.md"), Some("default"));
+        assert_eq!(picked(&models, "Makefile"), Some("default"));
+        assert_eq!(models.get_str_replace_description(), "default");
 
-    // Determine which editor to use based on the host
-    if host.contains("relace.run") {
-        Some(EditorModel::Relace(RelaceEditor::new(api_key, host, model)))
-    } else if host.contains("api.morphllm") {
-        Some(EditorModel::MorphLLM(MorphLLMEditor::new(
-            api_key, host, model,
-        )))
-    } else {
-        Some(EditorModel::OpenAICompatible(OpenAICompatibleEditor::new(
-            api_key, host, model,
-        )))
+        let routes_only = EditorModels {
+            default: None,
+            routes: models.routes,
+        };
+        assert_eq!(picked(&routes_only, "README.md"), None);
+        assert_eq!(routes_only.get_str_replace_description(), "python");
     }
 }
@@ -0,0 +1,124 @@
+use super::streaming::read_ollama_chat;
+use super::{EditorModelImpl, Progress};
+use anyhow::Result;
+use async_trait::async_trait;
+use reqwest::Client;
+use serde_json::json;
+
+/// Where a local Ollama server listens unless configured otherwise
+pub const DEFAULT_OLLAMA_HOST: &str = "http://localhost:11434";
+
+/// Small local models need to be told exactly what to return
+const SYSTEM_PROMPT: &str = "You merge code updates into files. Apply the <update> to the \
+<code> and reply with the complete updated file only, without explanations or code fences.";
+
+/// Editor that runs against a local Ollama server, so no code leaves the machine
+#[derive(Debug)]
+pub struct OllamaEditor {
+    host: String,
+    model: String,
+}
+
+impl OllamaEditor {
+    pub fn new(host: String, model: String) -> Self {
+        Self { host, model }
+    }
+}
+
+#[async_trait]
+impl EditorModelImpl for OllamaEditor {
+    async fn edit_code(
+        &self,
+        original_code: &str,
+        _old_str: &str,
+        update_snippet: &str,
+        progress: Progress<'_>,
+    ) -> Result<String, String> {
+        tracing::debug!("Calling Ollama Editor API");
+
+        // Construct the full URL
+        let host = self.host.trim_end_matches('/');
+        let provider_url = if host.ends_with("/api/chat") {
+            host.to_string()
+        } else {
+            format!("{}/api/chat", host)
+        };
+
+        // Create the client
+        let client = Client::new();
+
+        let user_prompt = format!(
+            "<code>{}</code>\n<update>{}</update>",
+            original_code, update_snippet
+        );
+
+        // Stream the response only when someone is waiting on progress
+        let body = json!({
+            "model": self.model,
+            "stream": progress.is_some(),
+            "options": {
+                "temperature": 0
+            },
+            "messages": [
+                {
+                    "role": "system",
+                    "content": SYSTEM_PROMPT
+                },
+                {
+                    "role": "user",
+                    "content": user_prompt
+                }
+            ]
+        });
+
+        // Send the request
+        let response = match client.post(&provider_url).json(&body).send().await {
+            Ok(resp) => resp,
+            Err(e) => return Err(format!("Request error: {}", e)),
+        };
+
+        // Process the response
+        if !response.status().is_success() {
+            return Err(format!("API error: HTTP {}", response.status()));
+        }
+
+        let content = read_ollama_chat(response, progress).await?;
+
+        tracing::debug!("Ollama Editor API worked");
+        Ok(strip_code_fence(&content).to_string())
+    }
+
+    fn get_str_replace_description(&self) -> &'static str {
+        "Edit the file with the new content."
+    }
+}
+
+/// Local models often wrap the file in a markdown code fence despite being asked not to
+fn strip_code_fence(content: &str) -> &str {
+    let trimmed = content.trim();
+    let Some(rest) = trimmed.strip_prefix("```") else {
+        return content;
+    };
+    match (rest.find('\n'), rest.strip_suffix("```")) {
+        (Some(start), Some(inner)) if start < inner.len() => &inner[start + 1..],
+        _ => content,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_code_fence() {
+        assert_eq!(
+            strip_code_fence("```rust\nfn main() {}\n```"),
+            "fn main() {}\n"
+        );
+        assert_eq!(strip_code_fence("\n```\nx = 1\n```\n"), "x = 1\n");
+        // Anything that isn't a complete fence is the file itself
+        assert_eq!(strip_code_fence("fn main() {}\n"), "fn main() {}\n");
+        assert_eq!(strip_code_fence("```rust\nfn main"), "```rust\nfn main");
+        assert_eq!(strip_code_fence("```"), "```");
+    }
+}
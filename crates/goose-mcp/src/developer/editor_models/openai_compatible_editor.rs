@@ -1,7 +1,9 @@
-use super::EditorModelImpl;
+use super::streaming::read_chat_completion;
+use super::{EditorModelImpl, Progress};
 use anyhow::Result;
+use async_trait::async_trait;
 use reqwest::Client;
-use serde_json::{json, Value};
+use serde_json::json;
 
 /// OpenAI-compatible editor that uses the standard chat completions format
 #[derive(Debug)]
@@ -21,12 +23,14 @@ impl OpenAICompatibleEditor {
     }
 }
 
+#[async_trait]
 impl EditorModelImpl for OpenAICompatibleEditor {
     async fn edit_code(
         &self,
         original_code: &str,
         _old_str: &str,
         update_snippet: &str,
+        progress: Progress<'_>,
     ) -> Result<String, String> {
         eprintln!("Calling OpenAI-compatible Editor API");
 
@@ -51,6 +55,7 @@ impl EditorModelImpl for OpenAICompatibleEditor {
         // Prepare the request body for OpenAI-compatible API
         let body = json!({
             "model": self.model,
+            "stream": progress.is_some(),
             "messages": [
                 {
                     "role": "user",
@@ -59,15 +64,14 @@ impl EditorModelImpl for OpenAICompatibleEditor {
             ]
         });
 
-        // Send the request
-        let response = match client
+        // Send the request, without credentials for local servers that don't need them
+        let mut request = client
             .post(&provider_url)
-            .header("Content-Type", "application/json")
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .json(&body)
-            .send()
-            .await
-        {
+            .header("Content-Type", "application/json");
+        if !self.api_key.is_empty() {
+            request = request.header("Authorization", format!("Bearer {}", self.api_key));
+        }
+        let response = match request.json(&body).send().await {
             Ok(resp) => resp,
             Err(e) => return Err(format!("Request error: {}", e)),
         };
@@ -77,23 +81,10 @@ impl EditorModelImpl for OpenAICompatibleEditor {
             return Err(format!("API error: HTTP {}", response.status()));
         }
 
-        // Parse the JSON response
-        let response_json: Value = match response.json().await {
-            Ok(json) => json,
-            Err(e) => return Err(format!("Failed to parse response: {}", e)),
-        };
-
-        // Extract the content from the response
-        let content = response_json
-            .get("choices")
-            .and_then(|choices| choices.get(0))
-            .and_then(|choice| choice.get("message"))
-            .and_then(|message| message.get("content"))
-            .and_then(|content| content.as_str())
-            .ok_or_else(|| "Invalid response format".to_string())?;
+        let content = read_chat_completion(response, progress).await?;
 
         eprintln!("OpenAI-compatible Editor API worked");
-        Ok(content.to_string())
+        Ok(content)
     }
 
     fn get_str_replace_description(&self) -> &'static str {
@@ -0,0 +1,156 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use super::{EditorModelImpl, Progress};
+use anyhow::Result;
+use async_trait::async_trait;
+use tokio::sync::{Mutex, Semaphore};
+use tokio::time::Instant;
+
+const WINDOW: Duration = Duration::from_secs(60);
+
+/// Limits how many applies reach a provider at once and per minute, so a burst of parallel
+/// edits waits its turn instead of tripping the provider's rate limits
+pub struct RateLimitedEditor {
+    inner: Box<dyn EditorModelImpl>,
+    concurrency: Semaphore,
+    requests_per_minute: Option<u32>,
+    recent_starts: Mutex<VecDeque<Instant>>,
+}
+
+impl RateLimitedEditor {
+    pub fn new(
+        inner: Box<dyn EditorModelImpl>,
+        max_concurrency: usize,
+        requests_per_minute: Option<u32>,
+    ) -> Self {
+        Self {
+            inner,
+            concurrency: Semaphore::new(max_concurrency.max(1)),
+            requests_per_minute: requests_per_minute.filter(|limit| *limit > 0),
+            recent_starts: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Wait until starting another request stays within the per-minute limit
+    async fn wait_for_slot(&self) {
+        let Some(limit) = self.requests_per_minute else {
+            return;
+        };
+        loop {
+            let wait = {
+                let mut starts = self.recent_starts.lock().await;
+                let now = Instant::now();
+                while starts
+                    .front()
+                    .is_some_and(|start| now.duration_since(*start) >= WINDOW)
+                {
+                    starts.pop_front();
+                }
+                if starts.len() < limit as usize {
+                    starts.push_back(now);
+                    return;
+                }
+                // Full window, so the front entry exists
+                starts[0] + WINDOW - now
+            };
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+#[async_trait]
+impl EditorModelImpl for RateLimitedEditor {
+    async fn edit_code(
+        &self,
+        original_code: &str,
+        old_str: &str,
+        update_snippet: &str,
+        progress: Progress<'_>,
+    ) -> Result<String, String> {
+        let _permit = self
+            .concurrency
+            .acquire()
+            .await
+            .map_err(|e| format!("Editor model unavailable: {}", e))?;
+        self.wait_for_slot().await;
+        self.inner
+            .edit_code(original_code, old_str, update_snippet, progress)
+            .await
+    }
+
+    fn get_str_replace_description(&self) -> &'static str {
+        self.inner.get_str_replace_description()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use super::*;
+
+    #[derive(Default)]
+    struct Calls {
+        started: AtomicUsize,
+        in_flight: AtomicUsize,
+        most_in_flight: AtomicUsize,
+    }
+
+    /// Takes a second per edit and records how many were running at once
+    struct Slow(Arc<Calls>);
+
+    #[async_trait]
+    impl EditorModelImpl for Slow {
+        async fn edit_code(
+            &self,
+            code: &str,
+            _: &str,
+            _: &str,
+            _: Progress<'_>,
+        ) -> Result<String, String> {
+            self.0.started.fetch_add(1, Ordering::SeqCst);
+            let running = self.0.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.0.most_in_flight.fetch_max(running, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            self.0.in_flight.fetch_sub(1, Ordering::SeqCst);
+            Ok(code.to_string())
+        }
+
+        fn get_str_replace_description(&self) -> &'static str {
+            "slow"
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_concurrency_is_limited() {
+        let calls = Arc::new(Calls::default());
+        let editor = RateLimitedEditor::new(Box::new(Slow(calls.clone())), 2, None);
+        let edit = || editor.edit_code("code", "old", "new", None);
+
+        let started = Instant::now();
+        let results = tokio::join!(edit(), edit(), edit(), edit());
+        assert_eq!(results.3.unwrap(), "code");
+        assert_eq!(calls.most_in_flight.load(Ordering::SeqCst), 2);
+        assert_eq!(started.elapsed().as_secs(), 2);
+        assert_eq!(editor.get_str_replace_description(), "slow");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_requests_per_minute_are_limited() {
+        let calls = Arc::new(Calls::default());
+        let editor = RateLimitedEditor::new(Box::new(Slow(calls.clone())), 10, Some(2));
+        let edit = || editor.edit_code("code", "old", "new", None);
+
+        let started = Instant::now();
+        let (first, second) = tokio::join!(edit(), edit());
+        assert!(first.is_ok() && second.is_ok());
+        assert_eq!(started.elapsed().as_secs(), 1);
+
+        // The third waits for the first to leave the window
+        edit().await.unwrap();
+        assert_eq!(started.elapsed().as_secs(), 61);
+        assert_eq!(calls.started.load(Ordering::SeqCst), 3);
+    }
+}
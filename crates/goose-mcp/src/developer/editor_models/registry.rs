@@ -0,0 +1,169 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use once_cell::sync::Lazy;
+
+use super::{
+    EditorConfig, EditorModelImpl, MorphLLMEditor, OllamaEditor, OpenAICompatibleEditor,
+    RelaceEditor,
+};
+
+/// Builds an editor from its resolved settings
+pub type EditorFactory = Arc<dyn Fn(EditorConfig) -> Box<dyn EditorModelImpl> + Send + Sync>;
+
+static PROVIDERS: Lazy<RwLock<HashMap<String, EditorFactory>>> =
+    Lazy::new(|| RwLock::new(builtin_providers()));
+
+fn builtin_providers() -> HashMap<String, EditorFactory> {
+    [
+        (
+            "openai_compatible",
+            factory(|c| Box::new(OpenAICompatibleEditor::new(c.api_key, c.host, c.model))),
+        ),
+        (
+            "morphllm",
+            factory(|c| Box::new(MorphLLMEditor::new(c.api_key, c.host, c.model))),
+        ),
+        (
+            "relace",
+            factory(|c| Box::new(RelaceEditor::new(c.api_key, c.host, c.model))),
+        ),
+        (
+            "ollama",
+            factory(|c| Box::new(OllamaEditor::new(c.host, c.model))),
+        ),
+    ]
+    .into_iter()
+    .map(|(name, factory)| (name.to_string(), factory))
+    .collect()
+}
+
+// Pins down the closure's return type so it coerces to a boxed trait object
+fn factory<F>(f: F) -> EditorFactory
+where
+    F: Fn(EditorConfig) -> Box<dyn EditorModelImpl> + Send + Sync + 'static,
+{
+    Arc::new(f)
+}
+
+/// Make an editor provider available under a name, which `GOOSE_EDITOR_PROVIDER` or the
+/// `provider` of a fallback entry can then select. Registering an existing name replaces that
+/// provider, including the builtin ones.
+///
+/// Providers must be registered before the developer extension is created.
+pub fn register_editor_provider<F>(name: &str, build: F)
+where
+    F: Fn(EditorConfig) -> Box<dyn EditorModelImpl> + Send + Sync + 'static,
+{
+    PROVIDERS
+        .write()
+        .unwrap()
+        .insert(name.to_string(), factory(build));
+}
+
+/// Names of the registered editor providers, sorted
+pub fn editor_providers() -> Vec<String> {
+    let mut names: Vec<String> = PROVIDERS.read().unwrap().keys().cloned().collect();
+    names.sort();
+    names
+}
+
+/// Build the editor for a configuration, picking the provider from the host of builtin
+/// services when none is configured
+pub(super) fn build_editor(config: EditorConfig) -> Option<Box<dyn EditorModelImpl>> {
+    let provider = config.provider.clone().unwrap_or_else(|| {
+        if config.host.contains("relace.run") {
+            "relace".to_string()
+        } else if config.host.contains("api.morphllm") {
+            "morphllm".to_string()
+        } else if config.host.contains(":11434") {
+            "ollama".to_string()
+        } else {
+            "openai_compatible".to_string()
+        }
+    });
+
+    let factory = PROVIDERS.read().unwrap().get(&provider).cloned();
+    match factory {
+        Some(factory) => Some(factory(config)),
+        None => {
+            tracing::warn!(
+                "Unknown editor provider '{}', expected one of: {}",
+                provider,
+                editor_providers().join(", ")
+            );
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::developer::editor_models::Progress;
+    use async_trait::async_trait;
+
+    struct Named(&'static str);
+
+    #[async_trait]
+    impl EditorModelImpl for Named {
+        async fn edit_code(
+            &self,
+            _: &str,
+            _: &str,
+            _: &str,
+            _: Progress<'_>,
+        ) -> Result<String, String> {
+            Ok(self.0.to_string())
+        }
+
+        fn get_str_replace_description(&self) -> &'static str {
+            self.0
+        }
+    }
+
+    fn config(provider: Option<&str>, host: &str) -> EditorConfig {
+        EditorConfig {
+            provider: provider.map(str::to_string),
+            host: host.to_string(),
+            model: "model".to_string(),
+            api_key: "key".to_string(),
+            max_concurrency: None,
+            requests_per_minute: None,
+        }
+    }
+
+    #[test]
+    fn test_builtin_providers_are_picked_from_the_host() {
+        let description = |host| {
+            build_editor(config(None, host))
+                .unwrap()
+                .get_str_replace_description()
+        };
+        assert!(
+            description("https://instantapply.endpoint.relace.run/v1/code/apply")
+                .starts_with("edit_file will take")
+        );
+        assert!(description("https://api.morphllm.com/v1").starts_with("Use the edit_file"));
+        assert_eq!(
+            description("https://api.openai.com/v1"),
+            "Edit the file with the new content."
+        );
+        assert!(build_editor(config(Some("missing"), "https://example.com")).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_registered_providers_can_be_selected() {
+        register_editor_provider("test_registered", |_| Box::new(Named("registered")));
+        assert!(editor_providers().contains(&"test_registered".to_string()));
+        for builtin in ["morphllm", "ollama", "openai_compatible", "relace"] {
+            assert!(editor_providers().contains(&builtin.to_string()));
+        }
+
+        let editor = build_editor(config(Some("test_registered"), "https://example.com")).unwrap();
+        assert_eq!(
+            editor.edit_code("", "", "", None).await.unwrap(),
+            "registered"
+        );
+    }
+}
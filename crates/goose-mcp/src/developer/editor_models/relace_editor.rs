@@ -1,7 +1,9 @@
-use super::EditorModelImpl;
+use super::streaming::read_chat_completion;
+use super::{EditorModelImpl, Progress};
 use anyhow::Result;
+use async_trait::async_trait;
 use reqwest::Client;
-use serde_json::{json, Value};
+use serde_json::json;
 
 /// Relace-specific editor that uses the predicted outputs convention
 #[derive(Debug)]
@@ -21,12 +23,14 @@ impl RelaceEditor {
     }
 }
 
+#[async_trait]
 impl EditorModelImpl for RelaceEditor {
     async fn edit_code(
         &self,
         original_code: &str,
         _old_str: &str,
         update_snippet: &str,
+        progress: Progress<'_>,
     ) -> Result<String, String> {
         eprintln!("Calling Relace Editor API");
 
@@ -48,6 +52,7 @@ impl EditorModelImpl for RelaceEditor {
         // update snippet is the sole user message.
         let body = json!({
             "model": self.model,
+            "stream": progress.is_some(),
             "prediction": {
                 "content": original_code
             },
@@ -77,23 +82,10 @@ impl EditorModelImpl for RelaceEditor {
             return Err(format!("API error: HTTP {}", response.status()));
         }
 
-        // Parse the JSON response
-        let response_json: Value = match response.json().await {
-            Ok(json) => json,
-            Err(e) => return Err(format!("Failed to parse response: {}", e)),
-        };
-
-        // Extract the content from the response
-        let content = response_json
-            .get("choices")
-            .and_then(|choices| choices.get(0))
-            .and_then(|choice| choice.get("message"))
-            .and_then(|message| message.get("content"))
-            .and_then(|content| content.as_str())
-            .ok_or_else(|| "Invalid response format".to_string())?;
+        let content = read_chat_completion(response, progress).await?;
 
         eprintln!("Relace Editor API worked");
-        Ok(content.to_string())
+        Ok(content)
     }
 
     fn get_str_replace_description(&self) -> &'static str {
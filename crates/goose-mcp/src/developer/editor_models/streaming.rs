@@ -0,0 +1,236 @@
+use reqwest::Response;
+use serde_json::Value;
+
+use super::Progress;
+
+/// Collect the message content of a chat completions response. When progress is wanted the
+/// request was made with `stream: true`, so the response arrives as server-sent events and
+/// the length received so far is reported after every chunk.
+pub async fn read_chat_completion(
+    response: Response,
+    progress: Progress<'_>,
+) -> Result<String, String> {
+    let Some(progress) = progress else {
+        let response_json: Value = response
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse response: {}", e))?;
+        return response_json
+            .get("choices")
+            .and_then(|choices| choices.get(0))
+            .and_then(|choice| choice.get("message"))
+            .and_then(|message| message.get("content"))
+            .and_then(|content| content.as_str())
+            .map(str::to_string)
+            .ok_or_else(|| "Invalid response format".to_string());
+    };
+
+    let mut content = String::new();
+    read_lines(response, |line| {
+        let Some(data) = line.strip_prefix("data:").map(str::trim) else {
+            return Ok(true);
+        };
+        if data == "[DONE]" {
+            return Ok(false);
+        }
+        let event: Value =
+            serde_json::from_str(data).map_err(|e| format!("Failed to parse stream: {}", e))?;
+        if let Some(delta) = event
+            .get("choices")
+            .and_then(|choices| choices.get(0))
+            .and_then(|choice| choice.get("delta"))
+            .and_then(|delta| delta.get("content"))
+            .and_then(|content| content.as_str())
+        {
+            content.push_str(delta);
+            progress(content.len());
+        }
+        Ok(true)
+    })
+    .await?;
+    Ok(content)
+}
+
+/// Collect the message content of an Ollama chat response, which streams one JSON object
+/// per line when progress is wanted
+pub async fn read_ollama_chat(
+    response: Response,
+    progress: Progress<'_>,
+) -> Result<String, String> {
+    let Some(progress) = progress else {
+        let response_json: Value = response
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse response: {}", e))?;
+        return response_json
+            .get("message")
+            .and_then(|message| message.get("content"))
+            .and_then(|content| content.as_str())
+            .map(str::to_string)
+            .ok_or_else(|| "Invalid response format".to_string());
+    };
+
+    let mut content = String::new();
+    read_lines(response, |line| {
+        if line.trim().is_empty() {
+            return Ok(true);
+        }
+        let chunk: Value =
+            serde_json::from_str(line).map_err(|e| format!("Failed to parse stream: {}", e))?;
+        if let Some(error) = chunk.get("error").and_then(|e| e.as_str()) {
+            return Err(format!("API error: {}", error));
+        }
+        if let Some(delta) = chunk
+            .get("message")
+            .and_then(|message| message.get("content"))
+            .and_then(|content| content.as_str())
+        {
+            content.push_str(delta);
+            progress(content.len());
+        }
+        Ok(!chunk.get("done").and_then(|d| d.as_bool()).unwrap_or(false))
+    })
+    .await?;
+    Ok(content)
+}
+
+/// Feed each complete line of the body to `on_line` until it returns false or the body ends
+async fn read_lines(
+    mut response: Response,
+    mut on_line: impl FnMut(&str) -> Result<bool, String>,
+) -> Result<(), String> {
+    let mut buffer: Vec<u8> = Vec::new();
+    loop {
+        let chunk = response
+            .chunk()
+            .await
+            .map_err(|e| format!("Stream error: {}", e))?;
+        let Some(chunk) = chunk else {
+            // Whatever is left after the last newline is the final line
+            let line = String::from_utf8_lossy(&buffer);
+            if !line.trim().is_empty() {
+                on_line(&line)?;
+            }
+            return Ok(());
+        };
+        buffer.extend_from_slice(&chunk);
+        while let Some(newline) = buffer.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = buffer.drain(..=newline).collect();
+            if !on_line(String::from_utf8_lossy(&line).trim_end())? {
+                return Ok(());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    use super::*;
+
+    /// A response whose body arrives in `chunks`, the way a server streams it
+    async fn streamed(chunks: &[&str]) -> Response {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let chunks: Vec<String> = chunks.iter().map(|chunk| chunk.to_string()).collect();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buffer = [0; 4096];
+            let _ = socket.read(&mut buffer).await.unwrap();
+            socket
+                .write_all(b"HTTP/1.1 200 OK\r\nConnection: close\r\n\r\n")
+                .await
+                .unwrap();
+            for chunk in chunks {
+                socket.write_all(chunk.as_bytes()).await.unwrap();
+                socket.flush().await.unwrap();
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        });
+        reqwest::get(format!("http://{}/", address)).await.unwrap()
+    }
+
+    #[derive(Clone, Copy)]
+    enum Format {
+        ChatCompletion,
+        Ollama,
+    }
+
+    /// The content read from `chunks`, and the lengths progress was reported at
+    async fn read(chunks: &[&str], format: Format) -> (Result<String, String>, Vec<usize>) {
+        let reported = Mutex::new(Vec::new());
+        let progress = |length: usize| reported.lock().unwrap().push(length);
+        let response = streamed(chunks).await;
+        let content = match format {
+            Format::ChatCompletion => read_chat_completion(response, Some(&progress)).await,
+            Format::Ollama => read_ollama_chat(response, Some(&progress)).await,
+        };
+        (content, reported.into_inner().unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_server_sent_events_split_across_chunks() {
+        let (content, reported) = read(
+            &[
+                ": keep-alive\n\ndata: {\"choices\":[{\"delta\":{\"role\":\"assistant\"}}]}\n\n",
+                "data: {\"choices\":[{\"delta\":{\"content\":\"fn ",
+                "main\"}}]}\n\ndata: {\"choices\":[{\"delta\":{\"content\":\"() {}\"}}]}\r\n\r\n",
+                "data: [DONE]\n\ndata: not json, and never read\n\n",
+            ],
+            Format::ChatCompletion,
+        )
+        .await;
+        assert_eq!(content.unwrap(), "fn main() {}");
+        assert_eq!(reported, vec![7, 12]);
+    }
+
+    #[tokio::test]
+    async fn test_server_sent_events_with_invalid_data_fail() {
+        let (content, _) = read(&["data: {\"choices\": [\n\n"], Format::ChatCompletion).await;
+        assert!(content.unwrap_err().starts_with("Failed to parse stream"));
+    }
+
+    #[tokio::test]
+    async fn test_chat_completion_without_progress_reads_the_whole_response() {
+        let response = streamed(&[r#"{"choices":[{"message":{"content":"fn main() {}"}}]}"#]).await;
+        let content = read_chat_completion(response, None).await;
+        assert_eq!(content.unwrap(), "fn main() {}");
+    }
+
+    #[tokio::test]
+    async fn test_ollama_lines_split_across_chunks() {
+        // The last line has no newline, and nothing after the done line is read
+        let (content, reported) = read(
+            &[
+                "{\"message\":{\"content\":\"fn \"},\"done\":false}\n{\"message\":",
+                "{\"content\":\"main() {}\"},\"done\":false}\n\n",
+                "{\"message\":{\"content\":\"\"},\"done\":true}",
+            ],
+            Format::Ollama,
+        )
+        .await;
+        assert_eq!(content.unwrap(), "fn main() {}");
+        assert_eq!(reported, vec![3, 12, 12]);
+
+        let (content, _) = read(
+            &["{\"message\":{\"content\":\"fn \"},\"done\":true}\n{\"error\":\"ignored\"}\n"],
+            Format::Ollama,
+        )
+        .await;
+        assert_eq!(content.unwrap(), "fn ");
+    }
+
+    #[tokio::test]
+    async fn test_ollama_errors_in_the_stream_fail() {
+        let (content, _) = read(
+            &["{\"message\":{\"content\":\"fn \"},\"done\":false}\n{\"error\":\"model not found\"}\n"],
+            Format::Ollama,
+        )
+        .await;
+        assert_eq!(content.unwrap_err(), "API error: model not found");
+    }
+}
@@ -0,0 +1,112 @@
+use std::path::Path;
+
+use crate::developer::lang;
+
+/// Placeholders models leave behind when they abbreviate the file instead of rewriting it
+const TRUNCATION_MARKERS: &[&str] = &[
+    "... existing code ...",
+    "...existing code...",
+    "... rest of the file",
+    "... rest of the code",
+    "... remaining code",
+    "rest of the file remains unchanged",
+    "rest of the code remains the same",
+];
+
+/// Lines an edit may add or remove beyond what the requested change accounts for
+const LINE_SLACK: usize = 10;
+
+/// Check an editor model's output before it replaces the file, so an empty, truncated or
+/// mangled response falls back to plain string replacement instead of overwriting the file.
+///
+/// Returns why the output looks wrong.
+pub fn validate_edit(
+    path: &Path,
+    original: &str,
+    updated: &str,
+    old_str: &str,
+    new_str: &str,
+) -> Result<(), String> {
+    if updated.trim().is_empty() && !original.trim().is_empty() {
+        return Err("the edited file is empty".to_string());
+    }
+
+    let updated_lower = updated.to_lowercase();
+    let original_lower = original.to_lowercase();
+    if let Some(marker) = TRUNCATION_MARKERS
+        .iter()
+        .find(|marker| updated_lower.contains(*marker) && !original_lower.contains(*marker))
+    {
+        return Err(format!(
+            "the edited file contains the placeholder '{}'",
+            marker
+        ));
+    }
+
+    // The file can't grow or shrink by much more than the lines being replaced and inserted
+    let (original_lines, updated_lines) = (original.lines().count(), updated.lines().count());
+    let slack = LINE_SLACK + original_lines / 10;
+    if updated_lines + old_str.lines().count() + slack < original_lines {
+        return Err(format!(
+            "the edit removed {} of {} lines",
+            original_lines - updated_lines,
+            original_lines
+        ));
+    }
+    if updated_lines > original_lines + new_str.lines().count() + slack {
+        return Err(format!(
+            "the edit added {} lines for a {} line change",
+            updated_lines - original_lines,
+            new_str.lines().count()
+        ));
+    }
+
+    check_syntax(path, original, updated)
+}
+
+/// Parse formats we can check exactly, and make sure brackets still balance in brace
+/// languages. Files that were already invalid before the edit are not held to this.
+fn check_syntax(path: &Path, original: &str, updated: &str) -> Result<(), String> {
+    match lang::get_language_identifier(path) {
+        "json" if serde_json::from_str::<serde_json::Value>(original).is_ok() => {
+            serde_json::from_str::<serde_json::Value>(updated)
+                .map_err(|e| format!("the edited file is not valid JSON: {}", e))?;
+        }
+        "yaml" if serde_yaml::from_str::<serde_yaml::Value>(original).is_ok() => {
+            serde_yaml::from_str::<serde_yaml::Value>(updated)
+                .map_err(|e| format!("the edited file is not valid YAML: {}", e))?;
+        }
+        "rust" | "javascript" | "typescript" | "go" | "java" | "c" | "cpp" | "css" | "swift"
+        | "kotlin" | "scala" | "php"
+            if bracket_balance(original) == [0; 3] =>
+        {
+            let balance = bracket_balance(updated);
+            if balance != [0; 3] {
+                return Err(format!(
+                    "brackets no longer balance in the edited file ({} parentheses, {} square brackets, {} braces unmatched)",
+                    balance[0], balance[1], balance[2]
+                ));
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Net count of opened `()`, `[]` and `{}`. Brackets in strings and comments are counted
+/// too, which is fine for comparing a file before and after an edit.
+fn bracket_balance(source: &str) -> [i64; 3] {
+    let mut balance = [0i64; 3];
+    for c in source.chars() {
+        match c {
+            '(' => balance[0] += 1,
+            ')' => balance[0] -= 1,
+            '[' => balance[1] += 1,
+            ']' => balance[1] -= 1,
+            '{' => balance[2] += 1,
+            '}' => balance[2] -= 1,
+            _ => {}
+        }
+    }
+    balance
+}
@@ -0,0 +1,304 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use indoc::indoc;
+use mcp_core::handler::ToolError;
+use mcp_server::structured::structured_content;
+use rmcp::model::{Content, Tool, ToolAnnotations};
+use rmcp::object;
+use serde::Serialize;
+use serde_json::Value;
+use tokio::process::Command;
+
+use super::shell::Spawner;
+use super::DeveloperRouter;
+
+/// How long a toolchain has to print its version, which for java or docker can be slow
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The toolchains reported, as the name and the command printing its version. The first of
+/// the commands that is installed is used.
+const TOOLCHAINS: &[(&str, &[&[&str]])] = &[
+    ("rustc", &[&["rustc", "--version"]]),
+    ("cargo", &[&["cargo", "--version"]]),
+    ("node", &[&["node", "--version"]]),
+    ("npm", &[&["npm", "--version"]]),
+    (
+        "python",
+        &[&["python3", "--version"], &["python", "--version"]],
+    ),
+    ("java", &[&["java", "-version"]]),
+    ("go", &[&["go", "version"]]),
+    ("docker", &[&["docker", "--version"]]),
+];
+
+#[derive(Debug, Serialize)]
+pub struct Toolchain {
+    pub name: &'static str,
+    /// Where the command was found, or None when it isn't installed
+    pub path: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PathEntry {
+    pub dir: PathBuf,
+    pub exists: bool,
+}
+
+/// A version manager and the version it has active for the project
+#[derive(Debug, Serialize)]
+pub struct VersionManager {
+    pub name: &'static str,
+    pub active: String,
+    /// The file pinning the project's version, and the version it asks for
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pinned_by: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pinned: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EnvInfo {
+    pub os: &'static str,
+    pub arch: &'static str,
+    pub toolchains: Vec<Toolchain>,
+    pub version_managers: Vec<VersionManager>,
+    pub path: Vec<PathEntry>,
+}
+
+/// The environment commands run in for the project in `dir`, which version managers read
+/// their pinned versions from
+pub async fn collect(spawner: &Spawner, dir: &Path) -> EnvInfo {
+    let tasks: Vec<_> = TOOLCHAINS
+        .iter()
+        .map(|&(name, commands)| {
+            let dir = dir.to_path_buf();
+            let spawner = spawner.clone();
+            tokio::spawn(async move { toolchain(&spawner, name, commands, &dir).await })
+        })
+        .collect();
+    let mut toolchains = Vec::new();
+    for (task, &(name, _)) in tasks.into_iter().zip(TOOLCHAINS) {
+        toolchains.push(task.await.unwrap_or_else(|e| Toolchain {
+            name,
+            path: None,
+            version: None,
+            error: Some(e.to_string()),
+        }));
+    }
+
+    let node = toolchains.iter().find(|toolchain| toolchain.name == "node");
+    let mut version_managers: Vec<_> = nvm(dir, node).into_iter().collect();
+    version_managers.extend(pyenv(spawner, dir).await);
+    version_managers.extend(rustup(spawner, dir).await);
+
+    let path = std::env::var_os("PATH")
+        .map(|path| {
+            std::env::split_paths(&path)
+                .map(|dir| PathEntry {
+                    exists: dir.is_dir(),
+                    dir,
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    EnvInfo {
+        os: std::env::consts::OS,
+        arch: std::env::consts::ARCH,
+        toolchains,
+        version_managers,
+        path,
+    }
+}
+
+async fn toolchain(
+    spawner: &Spawner,
+    name: &'static str,
+    commands: &[&[&str]],
+    dir: &Path,
+) -> Toolchain {
+    let Some((command, path)) = commands
+        .iter()
+        .find_map(|command| Some((command, which::which(command[0]).ok()?)))
+    else {
+        return Toolchain {
+            name,
+            path: None,
+            version: None,
+            error: None,
+        };
+    };
+    let (version, error) = match first_line(spawner, command, dir).await {
+        Ok(version) => (Some(version), None),
+        Err(e) => (None, Some(e)),
+    };
+    Toolchain {
+        name,
+        path: Some(path),
+        version,
+        error,
+    }
+}
+
+/// The first line `command` prints, from stdout or, as java does, stderr
+async fn first_line(spawner: &Spawner, command: &[&str], dir: &Path) -> Result<String, String> {
+    let mut program = Command::new(command[0]);
+    program.args(&command[1..]).current_dir(dir);
+    let output = tokio::time::timeout(TIMEOUT, spawner.output(&mut program))
+        .await
+        .map_err(|_| format!("{} took longer than {}s", command[0], TIMEOUT.as_secs()))?
+        .map_err(|e| format!("failed to run {}: {}", command[0], e))?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let line = stdout
+        .lines()
+        .chain(stderr.lines())
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or_default()
+        .to_string();
+    if output.status.success() {
+        Ok(line)
+    } else {
+        Err(format!("{} failed: {}", command.join(" "), line))
+    }
+}
+
+/// The closest of `names` in `dir` or its parents, with its first line
+fn pin_file(dir: &Path, names: &[&str]) -> Option<(PathBuf, String)> {
+    dir.ancestors().find_map(|dir| {
+        names.iter().find_map(|name| {
+            let path = dir.join(name);
+            let text = std::fs::read_to_string(&path).ok()?;
+            let version = text
+                .lines()
+                .map(str::trim)
+                .find(|line| !line.is_empty() && !line.starts_with('#'))
+                .unwrap_or_default()
+                .to_string();
+            Some((path, version))
+        })
+    })
+}
+
+/// nvm is a shell function, so the active node is the one on the PATH when it's in nvm's
+/// directory
+fn nvm(dir: &Path, node: Option<&Toolchain>) -> Option<VersionManager> {
+    let nvm_dir = std::env::var_os("NVM_DIR")
+        .map(PathBuf::from)
+        .or_else(|| etcetera::home_dir().ok().map(|home| home.join(".nvm")))
+        .filter(|nvm_dir| nvm_dir.is_dir());
+    let (pinned_by, pinned) = pin_file(dir, &[".nvmrc", ".node-version"]).unzip();
+    if nvm_dir.is_none() && pinned.is_none() {
+        return None;
+    }
+    let active = node.and_then(|node| node.path.as_ref().zip(node.version.as_ref()));
+    Some(VersionManager {
+        name: "nvm",
+        active: match active {
+            Some((path, version))
+                if nvm_dir
+                    .as_ref()
+                    .is_some_and(|nvm_dir| path.starts_with(nvm_dir)) =>
+            {
+                version.clone()
+            }
+            Some((path, version)) => format!("none, node {} is {}", version, path.display()),
+            None => "none, node isn't installed".to_string(),
+        },
+        pinned_by,
+        pinned,
+    })
+}
+
+async fn pyenv(spawner: &Spawner, dir: &Path) -> Option<VersionManager> {
+    which::which("pyenv").ok()?;
+    let (pinned_by, pinned) = pin_file(dir, &[".python-version"]).unzip();
+    Some(VersionManager {
+        name: "pyenv",
+        active: first_line(spawner, &["pyenv", "version-name"], dir)
+            .await
+            .unwrap_or_else(|e| e),
+        pinned_by,
+        pinned,
+    })
+}
+
+async fn rustup(spawner: &Spawner, dir: &Path) -> Option<VersionManager> {
+    which::which("rustup").ok()?;
+    let (pinned_by, pinned) = pin_file(dir, &["rust-toolchain", "rust-toolchain.toml"])
+        .map(|(path, line)| {
+            // rust-toolchain.toml has the channel in a table rather than on the first line
+            let channel = std::fs::read_to_string(&path)
+                .ok()
+                .and_then(|text| {
+                    text.lines().find_map(|line| {
+                        let value = line.trim().strip_prefix("channel")?.trim_start();
+                        Some(
+                            value
+                                .strip_prefix('=')?
+                                .trim()
+                                .trim_matches('"')
+                                .to_string(),
+                        )
+                    })
+                })
+                .unwrap_or(line);
+            (path, channel)
+        })
+        .unzip();
+    Some(VersionManager {
+        name: "rustup",
+        active: first_line(spawner, &["rustup", "show", "active-toolchain"], dir)
+            .await
+            .unwrap_or_else(|e| e),
+        pinned_by,
+        pinned,
+    })
+}
+
+/// The definition of the `env_info` tool
+pub fn tool() -> Tool {
+    Tool::new(
+        "env_info",
+        indoc! {r#"
+            Report the development environment commands run in: the installed versions of
+            rustc, cargo, node, npm, python, java, go and docker and where they were found,
+            the versions nvm, pyenv and rustup have active for the project next to the ones
+            its .nvmrc, .python-version or rust-toolchain file pins, and the PATH entries in
+            order. Use this first when a build or test fails in a way that suggests the wrong
+            toolchain is being used.
+        "#},
+        object!({
+            "type": "object",
+            "required": [],
+            "properties": {}
+        }),
+    )
+    .annotate(ToolAnnotations {
+        title: Some("Inspect the environment".to_string()),
+        read_only_hint: Some(true),
+        destructive_hint: Some(false),
+        idempotent_hint: Some(true),
+        open_world_hint: Some(false),
+    })
+}
+
+impl DeveloperRouter {
+    pub(super) async fn env_info(&self) -> Result<Vec<Content>, ToolError> {
+        let info = collect(&self.spawner(), &self.cwd()).await;
+        let text = serde_json::to_string_pretty(&info)
+            .map_err(|e| ToolError::ExecutionError(format!("Failed to encode result: {}", e)))?;
+        let text = self.redactor.redact(&text).into_owned();
+        let mut content = vec![Content::text(text.clone())];
+        if let Ok(redacted) = serde_json::from_str::<Value>(&text) {
+            content.push(structured_content(&redacted));
+        }
+        Ok(content)
+    }
+}
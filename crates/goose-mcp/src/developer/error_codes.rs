@@ -0,0 +1,35 @@
+//! Stable codes for the developer tools' errors. They are returned with
+//! [`ToolError::Coded`](mcp_core::ToolError::Coded) so clients can branch on the kind of
+//! failure, so renaming one is a breaking change.
+
+/// The file is over the size or character limit. Data: `path`, `size` or `characters`,
+/// and `limit`.
+pub const FILE_TOO_LARGE: &str = "FILE_TOO_LARGE";
+
+/// The path is excluded by `.gooseignore` (or `.gitignore`). Data: `path`, and the
+/// `pattern` that matched when it is known.
+pub const IGNORED_PATH: &str = "IGNORED_PATH";
+
+/// The file does not exist. Data: `path`.
+pub const FILE_NOT_FOUND: &str = "FILE_NOT_FOUND";
+
+/// A relative path was given where an absolute one is needed. Data: `path`, and the
+/// `suggestion` resolved against the working directory.
+pub const PATH_NOT_ABSOLUTE: &str = "PATH_NOT_ABSOLUTE";
+
+/// The path is outside the workspace roots. Data: `path` and `roots`.
+pub const OUTSIDE_WORKSPACE: &str = "OUTSIDE_WORKSPACE";
+
+/// A command printed more than can be returned. Data: `characters` and `limit`.
+pub const OUTPUT_TOO_LARGE: &str = "OUTPUT_TOO_LARGE";
+
+/// The tool was called more often than its per-minute limit. Data: `tool`, `limit` and
+/// `retry_after_secs`.
+pub const RATE_LIMITED: &str = "RATE_LIMITED";
+
+/// The tool is already running as many times at once as it may. Data: `tool` and `limit`.
+pub const CONCURRENCY_LIMITED: &str = "CONCURRENCY_LIMITED";
+
+/// A `cursor` that doesn't point into a result kept by the server, e.g. because it expired.
+/// Data: `cursor`.
+pub const INVALID_CURSOR: &str = "INVALID_CURSOR";
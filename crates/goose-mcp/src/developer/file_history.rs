@@ -0,0 +1,27 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+/// The contents files had before each edit in a session, newest last, to undo the edits
+#[derive(Debug, Clone, Default)]
+pub struct FileHistory {
+    versions: Arc<Mutex<HashMap<PathBuf, Vec<String>>>>,
+}
+
+impl FileHistory {
+    pub fn push(&self, path: &Path, content: String) {
+        self.versions
+            .lock()
+            .unwrap()
+            .entry(path.to_path_buf())
+            .or_default()
+            .push(content);
+    }
+
+    /// Take the content the file had before its last edit
+    pub fn pop(&self, path: &Path) -> Option<String> {
+        self.versions.lock().unwrap().get_mut(path)?.pop()
+    }
+}
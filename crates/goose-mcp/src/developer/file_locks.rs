@@ -0,0 +1,35 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, Weak},
+};
+
+use tokio::sync::{Mutex as AsyncMutex, OwnedMutexGuard};
+
+/// A lock per file, held while a tool call changes the file, so that edits and undos of the
+/// same file from parallel tool calls or sessions run one after another instead of
+/// overwriting each other. The locks are async, so waiting for one doesn't block the runtime
+/// thread, and they are only kept while someone holds or waits for them.
+#[derive(Debug, Clone, Default)]
+pub struct FileLocks {
+    locks: Arc<Mutex<HashMap<PathBuf, Weak<AsyncMutex<()>>>>>,
+}
+
+impl FileLocks {
+    /// Wait for exclusive access to `path`, until the guard is dropped
+    pub async fn lock(&self, path: &Path) -> OwnedMutexGuard<()> {
+        let lock = {
+            let mut locks = self.locks.lock().unwrap();
+            locks.retain(|_, lock| lock.strong_count() > 0);
+            match locks.get(path).and_then(Weak::upgrade) {
+                Some(lock) => lock,
+                None => {
+                    let lock = Arc::new(AsyncMutex::new(()));
+                    locks.insert(path.to_path_buf(), Arc::downgrade(&lock));
+                    lock
+                }
+            }
+        };
+        lock.lock_owned().await
+    }
+}
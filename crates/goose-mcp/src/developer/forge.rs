@@ -0,0 +1,589 @@
+use std::path::Path;
+
+use indoc::indoc;
+use mcp_core::handler::ToolError;
+use mcp_server::structured::structured_content;
+use rmcp::model::{Content, JsonRpcMessage, Tool, ToolAnnotations};
+use rmcp::object;
+use serde::Serialize;
+use serde_json::Value;
+use tokio::process::Command;
+use tokio::sync::mpsc;
+
+use super::approval::{draft_request_action, request_approval};
+use super::git::{parse_diff, Repository};
+use super::shell::Spawner;
+use super::DeveloperRouter;
+
+/// The remote whose host decides the forge, and which branches are pushed to
+const REMOTE: &str = "origin";
+
+const DEFAULT_ISSUE_LIMIT: u64 = 30;
+const MAX_ISSUE_LIMIT: u64 = 100;
+
+/// The subcommands of the forge tool that only read
+pub const READ_COMMANDS: &[&str] = &["issues", "diff", "comments"];
+
+/// Where the repository is hosted, and the CLI used to talk to it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Forge {
+    GitHub,
+    GitLab,
+}
+
+impl Forge {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "github" => Some(Self::GitHub),
+            "gitlab" => Some(Self::GitLab),
+            _ => None,
+        }
+    }
+
+    /// The forge hosting a remote URL, for the public hosts and self-hosted instances with
+    /// the forge's name in their host
+    pub fn from_remote(url: &str) -> Option<Self> {
+        let host = url
+            .split_once("://")
+            .map_or(url, |(_, rest)| rest)
+            .split('/')
+            .next()?
+            .rsplit('@')
+            .next()?
+            .split(':')
+            .next()?
+            .to_lowercase();
+        if host.contains("github") {
+            Some(Self::GitHub)
+        } else if host.contains("gitlab") {
+            Some(Self::GitLab)
+        } else {
+            None
+        }
+    }
+
+    fn cli(self) -> &'static str {
+        match self {
+            Self::GitHub => "gh",
+            Self::GitLab => "glab",
+        }
+    }
+
+    /// What the forge calls a pull request
+    pub fn request_name(self) -> &'static str {
+        match self {
+            Self::GitHub => "pull request",
+            Self::GitLab => "merge request",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct Issue {
+    pub number: u64,
+    pub title: String,
+    pub state: String,
+    pub author: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub labels: Vec<String>,
+    pub url: Option<String>,
+    pub updated_at: Option<String>,
+}
+
+/// A review comment, on a line of the diff when `path` is set
+#[derive(Debug, Serialize, PartialEq)]
+pub struct Comment {
+    pub author: Option<String>,
+    pub body: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<u64>,
+    pub created_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+/// A repository's forge, reached through its CLI (`gh` or `glab`) so the user's existing
+/// login is used and no tokens pass through the extension
+#[derive(Debug)]
+pub struct ForgeClient {
+    forge: Forge,
+    repository: Repository,
+}
+
+impl ForgeClient {
+    /// The forge of the repository's origin, or `forge` when given
+    pub async fn discover(repository: Repository, forge: Option<&str>) -> Result<Self, ToolError> {
+        let forge = match forge {
+            Some(name) => Forge::from_name(name).ok_or_else(|| {
+                ToolError::InvalidParameters(format!(
+                    "Unknown forge '{}', expected github or gitlab",
+                    name
+                ))
+            })?,
+            None => {
+                let url = repository.remote_url(REMOTE).await.map_err(|_| {
+                    ToolError::ExecutionError(format!(
+                        "The repository has no '{}' remote to find its forge from",
+                        REMOTE
+                    ))
+                })?;
+                Forge::from_remote(&url).ok_or_else(|| {
+                    ToolError::ExecutionError(format!(
+                        "Can't tell which forge hosts '{}'. Pass `forge` as github or gitlab.",
+                        url
+                    ))
+                })?
+            }
+        };
+        Ok(Self { forge, repository })
+    }
+
+    pub fn forge(&self) -> Forge {
+        self.forge
+    }
+
+    pub fn repository(&self) -> &Repository {
+        &self.repository
+    }
+
+    async fn cli(&self, args: &[&str]) -> Result<String, ToolError> {
+        run(
+            self.repository.spawner(),
+            self.forge.cli(),
+            self.repository.root(),
+            args,
+        )
+        .await
+    }
+
+    /// The repository's issues in `state` (open, closed or all), optionally with `label`
+    pub async fn issues(
+        &self,
+        state: &str,
+        label: Option<&str>,
+        limit: Option<u64>,
+    ) -> Result<Value, ToolError> {
+        if !["open", "closed", "all"].contains(&state) {
+            return Err(ToolError::InvalidParameters(format!(
+                "Unknown state '{}', expected open, closed or all",
+                state
+            )));
+        }
+        let limit = limit
+            .unwrap_or(DEFAULT_ISSUE_LIMIT)
+            .clamp(1, MAX_ISSUE_LIMIT)
+            .to_string();
+
+        let issues = match self.forge {
+            Forge::GitHub => {
+                let mut args = vec![
+                    "issue",
+                    "list",
+                    "--state",
+                    state,
+                    "--limit",
+                    limit.as_str(),
+                    "--json",
+                    "number,title,state,author,labels,url,updatedAt",
+                ];
+                if let Some(label) = label {
+                    args.extend(["--label", label]);
+                }
+                parse_github_issues(&json(&self.cli(&args).await?)?)
+            }
+            Forge::GitLab => {
+                let mut args = vec![
+                    "issue",
+                    "list",
+                    "--output",
+                    "json",
+                    "--per-page",
+                    limit.as_str(),
+                ];
+                match state {
+                    "closed" => args.push("--closed"),
+                    "all" => args.push("--all"),
+                    _ => {}
+                }
+                if let Some(label) = label {
+                    args.extend(["--label", label]);
+                }
+                parse_gitlab_issues(&json(&self.cli(&args).await?)?)
+            }
+        };
+        Ok(serde_json::json!({ "issues": issues }))
+    }
+
+    /// The changes of pull request `number`, as structured as the git tool's diffs
+    pub async fn diff(&self, number: u64) -> Result<Value, ToolError> {
+        let id = number.to_string();
+        let args = match self.forge {
+            Forge::GitHub => ["pr", "diff", id.as_str(), "--color", "never"],
+            Forge::GitLab => ["mr", "diff", id.as_str(), "--color", "never"],
+        };
+        let diff = parse_diff(&self.cli(&args).await?);
+        Ok(serde_json::json!({ "number": number, "diff": diff }))
+    }
+
+    /// The review comments on pull request `number`, oldest first
+    pub async fn comments(&self, number: u64) -> Result<Value, ToolError> {
+        let comments = match self.forge {
+            Forge::GitHub => {
+                // Comments on lines of the diff, and the bodies of submitted reviews
+                let inline = format!(
+                    "repos/{{owner}}/{{repo}}/pulls/{}/comments?per_page=100",
+                    number
+                );
+                let reviews = format!(
+                    "repos/{{owner}}/{{repo}}/pulls/{}/reviews?per_page=100",
+                    number
+                );
+                let mut comments =
+                    parse_github_comments(&json(&self.cli(&["api", &reviews]).await?)?);
+                comments.extend(parse_github_comments(&json(
+                    &self.cli(&["api", &inline]).await?,
+                )?));
+                comments.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+                comments
+            }
+            Forge::GitLab => {
+                let notes = format!(
+                    "projects/:id/merge_requests/{}/notes?sort=asc&per_page=100",
+                    number
+                );
+                parse_gitlab_comments(&json(&self.cli(&["api", &notes]).await?)?)
+            }
+        };
+        Ok(serde_json::json!({ "number": number, "comments": comments }))
+    }
+
+    /// Push the current branch, and open a draft pull request from it into `base` (the
+    /// repository's default branch when unset). Returns the pull request's URL.
+    pub async fn create_draft(
+        &self,
+        branch: &str,
+        base: Option<&str>,
+        title: &str,
+        body: &str,
+    ) -> Result<Value, ToolError> {
+        self.repository.publish(REMOTE, branch).await?;
+
+        let mut args = match self.forge {
+            Forge::GitHub => vec![
+                "pr", "create", "--draft", "--head", branch, "--title", title, "--body", body,
+            ],
+            Forge::GitLab => vec![
+                "mr",
+                "create",
+                "--draft",
+                "--yes",
+                "--source-branch",
+                branch,
+                "--title",
+                title,
+                "--description",
+                body,
+            ],
+        };
+        if let Some(base) = base {
+            args.extend(match self.forge {
+                Forge::GitHub => ["--base", base],
+                Forge::GitLab => ["--target-branch", base],
+            });
+        }
+        let output = self.cli(&args).await?;
+        // Both CLIs print the new request's URL last
+        let url = output
+            .lines()
+            .rev()
+            .map(str::trim)
+            .find(|line| line.starts_with("http"))
+            .map(str::to_string);
+
+        Ok(serde_json::json!({
+            "url": url,
+            "branch": branch,
+            "base": base,
+            "draft": true,
+        }))
+    }
+}
+
+/// Run a forge CLI without prompts or colors, failing with its error output
+async fn run(spawner: &Spawner, cli: &str, cwd: &Path, args: &[&str]) -> Result<String, ToolError> {
+    let output = spawner
+        .output(
+            Command::new(cli)
+                .args(args)
+                .current_dir(cwd)
+                .env("GH_PROMPT_DISABLED", "1")
+                .env("GH_NO_UPDATE_NOTIFIER", "1")
+                .env("GLAB_NO_PROMPT", "1")
+                .env("NO_COLOR", "1")
+                .env("GIT_TERMINAL_PROMPT", "0"),
+        )
+        .await
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => ToolError::ExecutionError(format!(
+                "{} is not installed. Install it and log in with `{} auth login`.",
+                cli, cli
+            )),
+            _ => ToolError::ExecutionError(format!("Failed to run {}: {}", cli, e)),
+        })?;
+    if !output.status.success() {
+        return Err(ToolError::ExecutionError(format!(
+            "{} {} failed: {}",
+            cli,
+            args.iter().take(2).copied().collect::<Vec<_>>().join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn json(output: &str) -> Result<Value, ToolError> {
+    serde_json::from_str(output).map_err(|e| {
+        ToolError::ExecutionError(format!("Unexpected output from the forge CLI: {}", e))
+    })
+}
+
+fn string(value: &Value, pointer: &str) -> Option<String> {
+    value
+        .pointer(pointer)
+        .and_then(Value::as_str)
+        .map(str::to_string)
+}
+
+pub fn parse_github_issues(value: &Value) -> Vec<Issue> {
+    value
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|issue| {
+            Some(Issue {
+                number: issue.get("number")?.as_u64()?,
+                title: string(issue, "/title")?,
+                state: string(issue, "/state")?.to_lowercase(),
+                author: string(issue, "/author/login"),
+                labels: issue
+                    .get("labels")
+                    .and_then(Value::as_array)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|label| string(label, "/name"))
+                    .collect(),
+                url: string(issue, "/url"),
+                updated_at: string(issue, "/updatedAt"),
+            })
+        })
+        .collect()
+}
+
+pub fn parse_gitlab_issues(value: &Value) -> Vec<Issue> {
+    value
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|issue| {
+            let state = string(issue, "/state")?;
+            Some(Issue {
+                number: issue.get("iid")?.as_u64()?,
+                title: string(issue, "/title")?,
+                // GitLab says "opened" where GitHub says "open"
+                state: if state == "opened" {
+                    "open".to_string()
+                } else {
+                    state
+                },
+                author: string(issue, "/author/username"),
+                labels: issue
+                    .get("labels")
+                    .and_then(Value::as_array)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|label| label.as_str().map(str::to_string))
+                    .collect(),
+                url: string(issue, "/web_url"),
+                updated_at: string(issue, "/updated_at"),
+            })
+        })
+        .collect()
+}
+
+/// Inline review comments and review bodies; reviews without a body are left out
+pub fn parse_github_comments(value: &Value) -> Vec<Comment> {
+    value
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|comment| {
+            let body = string(comment, "/body").filter(|body| !body.trim().is_empty())?;
+            Some(Comment {
+                author: string(comment, "/user/login"),
+                body,
+                path: string(comment, "/path"),
+                line: comment
+                    .get("line")
+                    .or_else(|| comment.get("original_line"))
+                    .and_then(Value::as_u64),
+                created_at: string(comment, "/created_at")
+                    .or_else(|| string(comment, "/submitted_at")),
+                url: string(comment, "/html_url"),
+            })
+        })
+        .collect()
+}
+
+/// The notes people wrote, without those GitLab adds for events like pushes
+pub fn parse_gitlab_comments(value: &Value) -> Vec<Comment> {
+    value
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|note| note.get("system").and_then(Value::as_bool) != Some(true))
+        .filter_map(|note| {
+            Some(Comment {
+                author: string(note, "/author/username"),
+                body: string(note, "/body")?,
+                path: string(note, "/position/new_path"),
+                line: note.pointer("/position/new_line").and_then(Value::as_u64),
+                created_at: string(note, "/created_at"),
+                url: None,
+            })
+        })
+        .collect()
+}
+
+/// The definition of the `forge` tool
+pub fn tool() -> Tool {
+    Tool::new(
+        "forge",
+        indoc! {r#"
+            Work with the GitHub or GitLab project of the working directory's repository,
+            through the `gh` or `glab` CLI and the user's login in it. The forge is found from
+            the `origin` remote, or can be given as `forge`.
+
+            - `issues`: the project's issues in `state` (open by default, closed or all),
+              optionally only those with `label`, up to `limit` (default 30).
+            - `diff`: the files and hunks changed by pull request (or merge request) `number`.
+            - `comments`: the review comments on pull request `number`, with the file and line
+              they are on, oldest first.
+            - `create_pr`: push the current branch and open a draft pull request from it into
+              `base` (default the project's default branch) with `title` and `body`, after the
+              user approves. Returns its URL. Commit the changes with the git tool first.
+        "#},
+        object!({
+            "type": "object",
+            "required": ["command"],
+            "properties": {
+                "command": {
+                    "type": "string",
+                    "enum": ["issues", "diff", "comments", "create_pr"]
+                },
+                "forge": {
+                    "type": "string",
+                    "enum": ["github", "gitlab"],
+                    "description": "Where the project is hosted, when the origin remote doesn't tell"
+                },
+                "number": {"type": "integer", "description": "For diff and comments: the pull request"},
+                "state": {
+                    "type": "string",
+                    "enum": ["open", "closed", "all"],
+                    "description": "For issues, default open"
+                },
+                "label": {"type": "string", "description": "For issues: only those with this label"},
+                "limit": {"type": "integer", "description": "For issues: how many, default 30, at most 100"},
+                "title": {"type": "string", "description": "For create_pr"},
+                "body": {"type": "string", "description": "For create_pr: the description, in Markdown"},
+                "base": {"type": "string", "description": "For create_pr: the branch to merge into"}
+            }
+        }),
+    )
+    .annotate(ToolAnnotations {
+        title: Some("Work with issues and pull requests".to_string()),
+        read_only_hint: Some(false),
+        destructive_hint: Some(false),
+        idempotent_hint: Some(false),
+        open_world_hint: Some(true),
+    })
+}
+
+impl DeveloperRouter {
+    pub(super) async fn forge(
+        &self,
+        params: Value,
+        notifier: &mpsc::Sender<JsonRpcMessage>,
+    ) -> Result<Vec<Content>, ToolError> {
+        let arg = |name: &str| params.get(name).and_then(Value::as_str);
+        let number = || {
+            params
+                .get("number")
+                .and_then(Value::as_u64)
+                .ok_or_else(|| ToolError::InvalidParameters("Missing 'number' parameter".into()))
+        };
+        let command = arg("command")
+            .ok_or_else(|| ToolError::InvalidParameters("Missing 'command' parameter".into()))?;
+
+        let repository = Repository::discover(self.spawner(), &self.cwd()).await?;
+        let client = ForgeClient::discover(repository, arg("forge")).await?;
+        let mut result = match command {
+            "issues" => {
+                let limit = params.get("limit").and_then(Value::as_u64);
+                client
+                    .issues(arg("state").unwrap_or("open"), arg("label"), limit)
+                    .await?
+            }
+            "diff" => client.diff(number()?).await?,
+            "comments" => client.comments(number()?).await?,
+            "create_pr" => {
+                let title = arg("title")
+                    .filter(|title| !title.trim().is_empty())
+                    .ok_or_else(|| {
+                        ToolError::InvalidParameters("create_pr needs a 'title'".into())
+                    })?;
+                let branch = client.repository().current_branch().await?.ok_or_else(|| {
+                    ToolError::ExecutionError(format!(
+                        "HEAD is detached. Check out a branch to open a {} from.",
+                        client.forge().request_name()
+                    ))
+                })?;
+                if arg("base") == Some(branch.as_str()) {
+                    return Err(ToolError::InvalidParameters(format!(
+                        "The {} can't be from '{}' into itself",
+                        client.forge().request_name(),
+                        branch
+                    )));
+                }
+                request_approval(
+                    notifier,
+                    &draft_request_action(&self.catalog, &branch, title),
+                )
+                .await?;
+                client
+                    .create_draft(&branch, arg("base"), title, arg("body").unwrap_or(""))
+                    .await?
+            }
+            _ => {
+                return Err(ToolError::InvalidParameters(format!(
+                    "Unknown command '{}', expected issues, diff, comments or create_pr",
+                    command
+                )))
+            }
+        };
+        if let Some(object) = result.as_object_mut() {
+            object.insert("forge".to_string(), serde_json::json!(client.forge()));
+        }
+
+        let text = serde_json::to_string_pretty(&result)
+            .map_err(|e| ToolError::ExecutionError(format!("Failed to encode result: {}", e)))?;
+        let text = self.redactor.redact(&text).into_owned();
+        let mut content = vec![Content::text(text.clone())];
+        if let Ok(redacted) = serde_json::from_str::<Value>(&text) {
+            content.push(structured_content(&redacted));
+        }
+        Ok(content)
+    }
+}
@@ -0,0 +1,344 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use indoc::indoc;
+use mcp_core::handler::ToolError;
+use mcp_server::structured::structured_content;
+use rmcp::model::{Content, JsonRpcMessage, Tool, ToolAnnotations};
+use rmcp::object;
+use serde_json::{json, Value};
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tokio::sync::mpsc;
+
+use super::approval::request_approval;
+use super::git::Repository;
+use super::lang::get_language_identifier;
+use super::shell::Spawner;
+use super::{DeveloperRouter, FileAccess};
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const DEFAULT_EDITION: &str = "2021";
+
+/// The formatters tried for each language, in order, unless `GOOSE_FORMATTERS` names others
+/// as a JSON object of language to command line, e.g. `{"python": ["black", "-q", "-"]}`.
+/// Each reads the file on stdin and writes it formatted to stdout; `{path}` in an argument is
+/// replaced by the file's path and `{edition}` by its crate's Rust edition.
+fn default_formatters(language: &str) -> &'static [&'static [&'static str]] {
+    match language {
+        "rust" => &[&["rustfmt", "--edition", "{edition}"]],
+        "python" => &[
+            &["ruff", "format", "--stdin-filename", "{path}", "-"],
+            &["black", "--quiet", "--stdin-filename", "{path}", "-"],
+        ],
+        "javascript" | "typescript" | "json" | "css" | "html" | "markdown" | "yaml" => {
+            &[&["prettier", "--stdin-filepath", "{path}"]]
+        }
+        "go" => &[&["gofmt"]],
+        "c" | "cpp" => &[&["clang-format", "--assume-filename={path}"]],
+        "bash" => &[&["shfmt", "--filename", "{path}"]],
+        _ => &[],
+    }
+}
+
+/// The language a file is formatted as, which also covers the variants prettier handles
+pub fn language(path: &Path) -> &'static str {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("jsx" | "mjs" | "cjs") => "javascript",
+        Some("tsx" | "mts" | "cts") => "typescript",
+        Some("scss" | "less") => "css",
+        _ => get_language_identifier(path),
+    }
+}
+
+/// The command line that formats `path`, with its placeholders filled in, or why there is
+/// none
+pub fn formatter_for(path: &Path) -> Result<Vec<String>, String> {
+    let language = language(path);
+    let configured: HashMap<String, Vec<String>> = std::env::var("GOOSE_FORMATTERS")
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default();
+    let candidates: Vec<Vec<String>> = match configured.get(language) {
+        Some(command) if !command.is_empty() => vec![command.clone()],
+        _ => default_formatters(language)
+            .iter()
+            .map(|command| command.iter().map(|s| s.to_string()).collect())
+            .collect(),
+    };
+    if candidates.is_empty() {
+        return Err(format!(
+            "no formatter is known for {} files, configure one in GOOSE_FORMATTERS",
+            if language.is_empty() {
+                "these"
+            } else {
+                language
+            }
+        ));
+    }
+
+    let command = candidates
+        .iter()
+        .find_map(|command| {
+            let program = installed(&command[0], path)?;
+            Some(
+                std::iter::once(program)
+                    .chain(command[1..].iter().cloned())
+                    .collect::<Vec<_>>(),
+            )
+        })
+        .ok_or_else(|| {
+            let tried: Vec<&str> = candidates.iter().map(|c| c[0].as_str()).collect();
+            format!("no formatter is installed, tried {}", tried.join(", "))
+        })?;
+
+    Ok(command
+        .into_iter()
+        .map(|arg| {
+            let arg = arg.replace("{path}", &path.to_string_lossy());
+            if arg.contains("{edition}") {
+                arg.replace("{edition}", &rust_edition(path))
+            } else {
+                arg
+            }
+        })
+        .collect())
+}
+
+/// `program` from the project's node_modules when it has it there, or from the PATH
+fn installed(program: &str, path: &Path) -> Option<String> {
+    let local = path
+        .ancestors()
+        .skip(1)
+        .map(|dir| dir.join("node_modules/.bin").join(program))
+        .find(|candidate| candidate.is_file());
+    match local {
+        Some(local) => Some(local.to_string_lossy().into_owned()),
+        None => which::which(program).ok().map(|_| program.to_string()),
+    }
+}
+
+/// The edition of the crate holding `path`, which rustfmt needs to parse it the same way
+/// cargo does
+fn rust_edition(path: &Path) -> String {
+    path.ancestors()
+        .skip(1)
+        .map(|dir| dir.join("Cargo.toml"))
+        .find(|manifest| manifest.is_file())
+        .and_then(|manifest| std::fs::read_to_string(manifest).ok())
+        .and_then(|text| {
+            text.lines().find_map(|line| {
+                let value = line.trim().strip_prefix("edition")?.trim_start();
+                let value = value.strip_prefix('=')?.trim().trim_matches('"');
+                value
+                    .chars()
+                    .all(|c| c.is_ascii_digit())
+                    .then(|| value.to_string())
+            })
+        })
+        .unwrap_or_else(|| DEFAULT_EDITION.to_string())
+}
+
+/// `text` formatted by `command`, run from the file's directory so it finds the project's
+/// configuration
+pub async fn format(
+    spawner: &Spawner,
+    command: &[String],
+    path: &Path,
+    text: &str,
+) -> Result<String, String> {
+    let dir = path
+        .parent()
+        .map_or_else(|| PathBuf::from("."), Path::to_path_buf);
+    let mut child = spawner
+        .spawn(
+            Command::new(&command[0])
+                .args(&command[1..])
+                .current_dir(dir)
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped()),
+        )
+        .await
+        .map_err(|e| format!("failed to run {}: {}", command[0], e))?;
+
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    let input = text.to_string();
+    // Written separately so a formatter that streams its output can't block on a full pipe
+    let writer = tokio::spawn(async move {
+        let _ = stdin.write_all(input.as_bytes()).await;
+    });
+    let output = tokio::time::timeout(TIMEOUT, child.wait_with_output())
+        .await
+        .map_err(|_| format!("{} took longer than {}s", command[0], TIMEOUT.as_secs()))?
+        .map_err(|e| format!("failed to run {}: {}", command[0], e))?;
+    let _ = writer.await;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let message: Vec<&str> = stderr
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .take(5)
+            .collect();
+        return Err(format!("{} failed: {}", command[0], message.join("\n")));
+    }
+    String::from_utf8(output.stdout).map_err(|_| format!("{} wrote invalid UTF-8", command[0]))
+}
+
+/// The definition of the `format` tool
+pub fn tool() -> Tool {
+    Tool::new(
+        "format",
+        indoc! {r#"
+            Format files with the formatter for their language and the project's configuration:
+            rustfmt, ruff or black, prettier, gofmt, clang-format or shfmt, whichever is
+            installed.
+
+            Formats `paths`, or without them the files git reports as changed or untracked.
+            Returns which files were reformatted, which already were formatted, and which were
+            skipped and why, e.g. a syntax error or no formatter installed. With `check`, only
+            reports which files would be reformatted.
+        "#},
+        object!({
+            "type": "object",
+            "required": [],
+            "properties": {
+                "paths": {
+                    "type": "array",
+                    "items": {"type": "string"},
+                    "description": "Absolute paths of files, default the changed files"
+                },
+                "check": {"type": "boolean", "description": "Report without writing"}
+            }
+        }),
+    )
+    .annotate(ToolAnnotations {
+        title: Some("Format files".to_string()),
+        read_only_hint: Some(false),
+        destructive_hint: Some(false),
+        idempotent_hint: Some(true),
+        open_world_hint: Some(true),
+    })
+}
+
+impl DeveloperRouter {
+    pub(super) async fn format(
+        &self,
+        params: Value,
+        notifier: &mpsc::Sender<JsonRpcMessage>,
+    ) -> Result<Vec<Content>, ToolError> {
+        let check = params.get("check").and_then(Value::as_bool) == Some(true);
+        let cwd = self.cwd();
+        let mut paths = match params.get("paths").and_then(Value::as_array) {
+            Some(paths) if !paths.is_empty() => {
+                let mut resolved = Vec::new();
+                for path in paths {
+                    let path = path.as_str().ok_or_else(|| {
+                        ToolError::InvalidParameters("'paths' must be strings".into())
+                    })?;
+                    let path = self.resolve_path(path)?;
+                    if self.is_ignored(&path) {
+                        return Err(self.ignored_path_error(
+                            &path,
+                            format!(
+                                "Access to '{}' is restricted by .gooseignore",
+                                path.display()
+                            ),
+                        ));
+                    }
+                    if !path.is_file() {
+                        return Err(ToolError::InvalidParameters(format!(
+                            "'{}' is not a file",
+                            path.display()
+                        )));
+                    }
+                    resolved.push(path);
+                }
+                resolved
+            }
+            // Ignored files are left out, as the git tool leaves them out of the status
+            _ => Repository::discover(self.spawner(), &cwd)
+                .await?
+                .changed_files()
+                .await?
+                .into_iter()
+                .filter(|path| !self.is_ignored(path) && self.workspace.contains(path))
+                .collect(),
+        };
+        paths.sort();
+        paths.dedup();
+
+        // Hold the files, in a fixed order, from reading them until the formatted text is written
+        let mut _file_locks = Vec::new();
+        for path in &paths {
+            _file_locks.push(self.file_locks.lock(path).await);
+        }
+
+        let relative = |path: &Path| path.strip_prefix(&cwd).unwrap_or(path).to_path_buf();
+        let mut changes = Vec::new();
+        let mut unchanged = Vec::new();
+        let mut skipped = Vec::new();
+        let spawner = self.spawner();
+        for path in &paths {
+            let formatted = match self.system.fs().read_to_string(path) {
+                Ok(text) => match formatter_for(path) {
+                    Ok(command) => format(&spawner, &command, path, &text)
+                        .await
+                        .map(|formatted| (text, formatted)),
+                    Err(reason) => Err(reason),
+                },
+                Err(e) => Err(format!("failed to read it: {}", e)),
+            };
+            match formatted {
+                Ok((text, formatted)) if text != formatted => {
+                    changes.push((path.clone(), text, formatted))
+                }
+                Ok(_) => unchanged.push(relative(path)),
+                Err(reason) => skipped.push(json!({"path": relative(path), "reason": reason})),
+            }
+        }
+
+        if !check && !changes.is_empty() {
+            let changed: Vec<&Path> = changes.iter().map(|(path, ..)| path.as_path()).collect();
+            if let Some(action) = self
+                .approval_policy
+                .format_action(&self.catalog, &changed)
+                .await
+            {
+                request_approval(notifier, &action).await?;
+            }
+            for (path, text, formatted) in &changes {
+                if let Err(e) = self.system.fs().write(path, formatted.as_bytes()) {
+                    skipped.push(json!({
+                        "path": relative(path),
+                        "reason": format!("failed to write it: {}", e)
+                    }));
+                    continue;
+                }
+                self.file_history.push(path, text.clone());
+                self.session_files
+                    .lock()
+                    .unwrap()
+                    .insert(path.clone(), FileAccess::Edited);
+                self.resources_changed.store(true, Ordering::Relaxed);
+            }
+        }
+
+        let result = json!({
+            "check": check,
+            "reformatted": changes.iter().map(|(path, ..)| relative(path)).collect::<Vec<_>>(),
+            "unchanged": unchanged,
+            "skipped": skipped,
+        });
+        let text = serde_json::to_string_pretty(&result)
+            .map_err(|e| ToolError::ExecutionError(format!("Failed to encode result: {}", e)))?;
+        Ok(vec![
+            Content::text(text.clone()),
+            structured_content(&result),
+        ])
+    }
+}
@@ -0,0 +1,1583 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use indoc::indoc;
+use mcp_core::handler::ToolError;
+use mcp_server::structured::structured_content;
+use rmcp::model::{Content, JsonRpcMessage, Tool, ToolAnnotations};
+use rmcp::object;
+use serde::Serialize;
+use serde_json::{json, Value};
+use tokio::process::Command;
+use tokio::sync::mpsc;
+
+use super::approval::{amend_action, request_approval, stash_drop_action};
+use super::shell::Spawner;
+use super::DeveloperRouter;
+
+/// Most diff lines returned, over all files and hunks
+const MAX_DIFF_LINES: usize = 2000;
+
+/// Most files listed by status
+const MAX_STATUS_FILES: usize = 500;
+
+/// Most lines returned by blame
+const MAX_BLAME_LINES: usize = 2000;
+
+const DEFAULT_LOG_LIMIT: usize = 20;
+const MAX_LOG_LIMIT: usize = 200;
+
+// Field and record separators for git's --format
+const FIELD: char = '\x1f';
+const RECORD: char = '\x1e';
+
+/// The read-only subcommands of the git tool
+pub const READ_COMMANDS: &[&str] = &["status", "diff", "log", "blame", "show"];
+
+/// The subcommands that change the repository. None of them force anything: branches are
+/// only deleted when merged and checkout keeps local changes. None of them reach a remote
+/// either; stash's "push" action only shelves local changes, and the one remote push,
+/// [`Repository::publish`], belongs to the forge tool and is never forced.
+pub const WRITE_COMMANDS: &[&str] = &["commit", "branch", "checkout", "stash"];
+
+/// Most files named in a generated commit subject before they are only counted
+const MAX_SUBJECT_FILES: usize = 3;
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct Branch {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub head: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub oid: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub upstream: Option<String>,
+    pub ahead: u32,
+    pub behind: u32,
+}
+
+/// A changed, untracked or conflicted file, with its state in the index and the worktree
+#[derive(Debug, Serialize, PartialEq)]
+pub struct StatusEntry {
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub original_path: Option<String>,
+    pub index: Option<&'static str>,
+    pub worktree: Option<&'static str>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub conflicted: bool,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct Status {
+    pub branch: Branch,
+    pub files: Vec<StatusEntry>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct Hunk {
+    pub old_start: u32,
+    pub old_lines: u32,
+    pub new_start: u32,
+    pub new_lines: u32,
+    /// The text after the range, usually the enclosing function
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub section: String,
+    /// The hunk's lines, starting with ' ', '+' or '-'
+    pub lines: Vec<String>,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct FileDiff {
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_path: Option<String>,
+    pub status: &'static str,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub binary: bool,
+    pub additions: u32,
+    pub deletions: u32,
+    pub hunks: Vec<Hunk>,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct Diff {
+    pub files: Vec<FileDiff>,
+    /// Set when hunk lines were left out to keep the result small; the counts are complete
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct FileChange {
+    pub path: String,
+    /// None for binary files
+    pub additions: Option<u32>,
+    pub deletions: Option<u32>,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct Commit {
+    pub hash: String,
+    pub short_hash: String,
+    pub author: String,
+    pub email: String,
+    pub date: String,
+    pub parents: Vec<String>,
+    /// The subject in log, the whole message in show
+    pub message: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<FileChange>,
+}
+
+/// Consecutive lines last changed by the same commit
+#[derive(Debug, Serialize, PartialEq)]
+pub struct BlameRange {
+    pub commit: String,
+    pub author: String,
+    pub email: String,
+    /// Seconds since the epoch
+    pub time: i64,
+    pub summary: String,
+    pub start_line: u32,
+    pub end_line: u32,
+    pub lines: Vec<String>,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct AuthorLines {
+    pub author: String,
+    pub lines: u32,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct Blame {
+    pub path: String,
+    pub ranges: Vec<BlameRange>,
+    /// Who wrote how many of the lines, most first
+    pub authors: Vec<AuthorLines>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
+}
+
+/// A file staged for the next commit
+#[derive(Debug, Serialize, PartialEq)]
+pub struct StagedFile {
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_path: Option<String>,
+    pub status: &'static str,
+}
+
+/// A change the session made to a file with the text editor, from the session log
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionEdit {
+    pub command: String,
+    pub path: PathBuf,
+}
+
+/// A repository to inspect, found from a directory inside it
+#[derive(Debug)]
+pub struct Repository {
+    root: PathBuf,
+    spawner: Spawner,
+}
+
+impl Repository {
+    pub async fn discover(spawner: Spawner, cwd: &Path) -> Result<Self, ToolError> {
+        let root = run(&spawner, cwd, &["rev-parse", "--show-toplevel"]).await?;
+        Ok(Self {
+            root: PathBuf::from(root.trim_end()),
+            spawner,
+        })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Starts the commands run on the repository
+    pub fn spawner(&self) -> &Spawner {
+        &self.spawner
+    }
+
+    async fn git(&self, args: &[&str]) -> Result<String, ToolError> {
+        run(&self.spawner, &self.root, args).await
+    }
+
+    /// Run a read-only subcommand, leaving out files for which `is_visible` is false. Paths
+    /// in the result are relative to the repository's root.
+    pub async fn inspect(
+        &self,
+        params: &Value,
+        is_visible: impl Fn(&Path) -> bool,
+    ) -> Result<Value, ToolError> {
+        let command = params
+            .get("command")
+            .and_then(Value::as_str)
+            .ok_or_else(|| ToolError::InvalidParameters("Missing 'command' parameter".into()))?;
+        let path = params.get("path").and_then(Value::as_str);
+        let revision = params
+            .get("revision")
+            .and_then(Value::as_str)
+            .map(checked_revision)
+            .transpose()?;
+        let visible = |path: &str| is_visible(&self.root.join(path));
+
+        let value = match command {
+            "status" => {
+                let output = self
+                    .git(&["status", "--porcelain=v2", "--branch", "-z"])
+                    .await?;
+                let mut status = parse_status(&output);
+                status.files.retain(|file| visible(&file.path));
+                serde_json::to_value(status)
+            }
+            "diff" => {
+                let context = params
+                    .get("context")
+                    .and_then(Value::as_u64)
+                    .unwrap_or(3)
+                    .min(20)
+                    .to_string();
+                let unified = format!("-U{}", context);
+                let mut args = vec![
+                    "diff",
+                    "--no-color",
+                    "--no-ext-diff",
+                    "-M",
+                    unified.as_str(),
+                ];
+                if params.get("staged").and_then(Value::as_bool) == Some(true) {
+                    args.push("--cached");
+                }
+                args.extend(revision);
+                args.push("--");
+                args.extend(path);
+                let mut diff = parse_diff(&self.git(&args).await?);
+                diff.files.retain(|file| visible(&file.path));
+                serde_json::to_value(diff)
+            }
+            "log" => {
+                let limit = params
+                    .get("max_count")
+                    .and_then(Value::as_u64)
+                    .map_or(DEFAULT_LOG_LIMIT, |n| n as usize)
+                    .clamp(1, MAX_LOG_LIMIT)
+                    .to_string();
+                let format = format!(
+                    "--format={RECORD}%H{FIELD}%h{FIELD}%an{FIELD}%ae{FIELD}%aI{FIELD}%P{FIELD}%s"
+                );
+                let mut args = vec![
+                    "log",
+                    "--no-color",
+                    "--numstat",
+                    "-n",
+                    limit.as_str(),
+                    format.as_str(),
+                ];
+                args.extend(revision);
+                args.push("--");
+                args.extend(path);
+                let mut commits = parse_log(&self.git(&args).await?);
+                for commit in &mut commits {
+                    commit.files.retain(|file| visible(&file.path));
+                }
+                Ok(serde_json::json!({ "commits": commits }))
+            }
+            "blame" => {
+                let path = path
+                    .ok_or_else(|| ToolError::InvalidParameters("blame needs a 'path'".into()))?;
+                if !visible(path) {
+                    return Err(ToolError::ExecutionError(format!(
+                        "Access to '{}' is restricted by .gooseignore",
+                        path
+                    )));
+                }
+                let range = line_range(params)?;
+                let mut args = vec!["blame", "--line-porcelain"];
+                if let Some(range) = &range {
+                    args.extend(["-L", range.as_str()]);
+                }
+                args.extend(revision);
+                args.extend(["--", path]);
+                serde_json::to_value(parse_blame(path, &self.git(&args).await?))
+            }
+            "show" => {
+                let revision = revision.unwrap_or("HEAD");
+                let format =
+                    format!("--format=%H{FIELD}%h{FIELD}%an{FIELD}%ae{FIELD}%aI{FIELD}%P{FIELD}%B");
+                let header = self
+                    .git(&["show", "--no-color", "-s", format.as_str(), revision, "--"])
+                    .await?;
+                let mut commit = parse_commit(header.trim_end()).ok_or_else(|| {
+                    ToolError::ExecutionError(format!("Could not read commit '{}'", revision))
+                })?;
+                commit.message = commit.message.trim_end().to_string();
+
+                let mut args = vec![
+                    "show",
+                    "--no-color",
+                    "--no-ext-diff",
+                    "--format=",
+                    "--diff-merges=first-parent",
+                    "-M",
+                    revision,
+                    "--",
+                ];
+                args.extend(path);
+                let mut diff = parse_diff(&self.git(&args).await?);
+                diff.files.retain(|file| visible(&file.path));
+                Ok(serde_json::json!({ "commit": commit, "diff": diff }))
+            }
+            _ => {
+                return Err(ToolError::InvalidParameters(format!(
+                    "Unknown git command '{}', expected one of: {}",
+                    command,
+                    READ_COMMANDS.join(", ")
+                )))
+            }
+        };
+        value.map_err(|e| ToolError::ExecutionError(format!("Failed to encode result: {}", e)))
+    }
+}
+
+impl Repository {
+    /// The files staged for the next commit
+    pub async fn staged_files(&self) -> Result<Vec<StagedFile>, ToolError> {
+        let output = self
+            .git(&["diff", "--cached", "--name-status", "-M", "-z"])
+            .await?;
+        Ok(parse_name_status(&output))
+    }
+
+    /// Whether a remote branch already contains `revision`, so rewriting it would need a
+    /// force-push
+    pub async fn is_pushed(&self, revision: &str) -> Result<bool, ToolError> {
+        let output = self
+            .git(&["branch", "--remotes", "--contains", revision])
+            .await?;
+        Ok(!output.trim().is_empty())
+    }
+
+    /// The files changed in the index or worktree and the untracked ones, leaving out deleted
+    /// files
+    pub async fn changed_files(&self) -> Result<Vec<PathBuf>, ToolError> {
+        let output = self
+            .git(&["status", "--porcelain=v2", "--untracked-files=all", "-z"])
+            .await?;
+        Ok(parse_status(&output)
+            .files
+            .into_iter()
+            .map(|file| self.root.join(file.path))
+            .filter(|path| path.is_file())
+            .collect())
+    }
+
+    /// The branch HEAD is on, or None when it is detached
+    pub async fn current_branch(&self) -> Result<Option<String>, ToolError> {
+        Ok(self
+            .git(&["symbolic-ref", "--quiet", "--short", "HEAD"])
+            .await
+            .ok()
+            .map(|branch| branch.trim_end().to_string()))
+    }
+
+    /// The URL `remote` fetches from
+    pub async fn remote_url(&self, remote: &str) -> Result<String, ToolError> {
+        let url = self.git(&["remote", "get-url", remote]).await?;
+        Ok(url.trim_end().to_string())
+    }
+
+    /// Push `branch` to `remote` and track it there. Never forced, so it fails rather than
+    /// overwriting commits on the remote.
+    pub async fn publish(&self, remote: &str, branch: &str) -> Result<(), ToolError> {
+        let branch = checked_revision(branch)?;
+        self.git(&["push", "--set-upstream", remote, branch])
+            .await
+            .map(|_| ())
+    }
+
+    /// Stage `paths`, if any, and commit with `message` or one generated from the staged
+    /// files and the session's edits. Amending keeps the message unless one is given.
+    pub async fn commit(
+        &self,
+        paths: &[String],
+        message: Option<&str>,
+        amend: bool,
+        edits: &[SessionEdit],
+    ) -> Result<Value, ToolError> {
+        if !paths.is_empty() {
+            let mut args = vec!["add", "--"];
+            args.extend(paths.iter().map(String::as_str));
+            self.git(&args).await?;
+        }
+
+        let staged = self.staged_files().await?;
+        if staged.is_empty() && !amend {
+            return Err(ToolError::InvalidParameters(
+                "Nothing is staged to commit. Pass the files to commit as `paths`.".into(),
+            ));
+        }
+
+        let generated = message.is_none() && !amend;
+        let message = match message {
+            Some(message) => Some(message.to_string()),
+            None if amend => None,
+            None => Some(commit_message(&staged, edits, &self.root)),
+        };
+        let mut args = vec!["commit"];
+        if amend {
+            args.push("--amend");
+        }
+        match &message {
+            Some(message) => args.extend(["-m", message.as_str()]),
+            None => args.push("--no-edit"),
+        }
+        self.git(&args).await?;
+
+        Ok(serde_json::json!({
+            "commit": self.head_commit().await?,
+            "files": staged,
+            "generated_message": generated,
+        }))
+    }
+
+    async fn head_commit(&self) -> Result<Option<Commit>, ToolError> {
+        let format =
+            format!("--format=%H{FIELD}%h{FIELD}%an{FIELD}%ae{FIELD}%aI{FIELD}%P{FIELD}%B");
+        let header = self
+            .git(&["show", "--no-color", "-s", format.as_str(), "HEAD", "--"])
+            .await?;
+        Ok(parse_commit(header.trim_end()).map(|mut commit| {
+            commit.message = commit.message.trim_end().to_string();
+            commit
+        }))
+    }
+
+    /// List the branches, create one at `start_point`, or delete a merged one
+    pub async fn branch(
+        &self,
+        name: Option<&str>,
+        start_point: Option<&str>,
+        delete: bool,
+    ) -> Result<Value, ToolError> {
+        let Some(name) = name.map(checked_revision).transpose()? else {
+            let format = format!(
+                "--format=%(refname:short){FIELD}%(objectname:short){FIELD}%(upstream:short){FIELD}%(HEAD)"
+            );
+            let output = self.git(&["branch", "--no-color", format.as_str()]).await?;
+            let branches: Vec<Value> = output
+                .lines()
+                .filter_map(|line| {
+                    let fields: Vec<&str> = line.split(FIELD).collect();
+                    let &[name, commit, upstream, head] = fields.as_slice() else {
+                        return None;
+                    };
+                    Some(serde_json::json!({
+                        "name": name,
+                        "commit": commit,
+                        "upstream": (!upstream.is_empty()).then_some(upstream),
+                        "current": head == "*",
+                    }))
+                })
+                .collect();
+            return Ok(serde_json::json!({ "branches": branches }));
+        };
+
+        if delete {
+            // -d refuses to delete unmerged work, unlike -D
+            self.git(&["branch", "-d", name]).await.map_err(|e| {
+                ToolError::ExecutionError(format!(
+                    "{}. Only merged branches can be deleted with this tool.",
+                    e
+                ))
+            })?;
+            return Ok(serde_json::json!({ "deleted": name }));
+        }
+
+        let mut args = vec!["branch", name];
+        args.extend(start_point.map(checked_revision).transpose()?);
+        self.git(&args).await?;
+        let commit = self.git(&["rev-parse", "--short", name]).await?;
+        Ok(serde_json::json!({ "created": name, "commit": commit.trim() }))
+    }
+
+    /// Switch to a branch, creating it first if asked, or to a detached revision. Local
+    /// changes are kept, so git refuses when they would be overwritten.
+    pub async fn checkout(
+        &self,
+        target: &str,
+        create: bool,
+        detach: bool,
+    ) -> Result<Value, ToolError> {
+        let target = checked_revision(target)?;
+        let mut args = vec!["switch"];
+        if create {
+            args.push("-c");
+        } else if detach {
+            args.push("--detach");
+        }
+        args.push(target);
+        self.git(&args).await.map_err(|e| {
+            ToolError::ExecutionError(format!(
+                "{}. Commit or stash local changes before switching.",
+                e
+            ))
+        })?;
+
+        let output = self
+            .git(&["status", "--porcelain=v2", "--branch", "-z"])
+            .await?;
+        Ok(serde_json::json!({ "branch": parse_status(&output).branch }))
+    }
+
+    /// Save, list, restore or drop stashed changes
+    pub async fn stash(
+        &self,
+        action: &str,
+        index: Option<u64>,
+        message: Option<&str>,
+        include_untracked: bool,
+    ) -> Result<Value, ToolError> {
+        let entry = index.map(|index| format!("stash@{{{}}}", index));
+        match action {
+            "push" => {
+                let mut args = vec!["stash", "push"];
+                if include_untracked {
+                    args.push("--include-untracked");
+                }
+                if let Some(message) = message {
+                    args.extend(["-m", message]);
+                }
+                let output = self.git(&args).await?;
+                let saved = !output.contains("No local changes to save");
+                Ok(serde_json::json!({ "saved": saved }))
+            }
+            "list" => {
+                let format = format!("--format=%gd{FIELD}%h{FIELD}%gs");
+                let output = self.git(&["stash", "list", format.as_str()]).await?;
+                let entries: Vec<Value> = output
+                    .lines()
+                    .filter_map(|line| {
+                        let mut fields = line.splitn(3, FIELD);
+                        Some(serde_json::json!({
+                            "entry": fields.next()?,
+                            "commit": fields.next()?,
+                            "message": fields.next()?,
+                        }))
+                    })
+                    .collect();
+                Ok(serde_json::json!({ "stashes": entries }))
+            }
+            "pop" | "apply" | "drop" => {
+                let mut args = vec!["stash", action];
+                args.extend(entry.as_deref());
+                self.git(&args).await?;
+                Ok(serde_json::json!({
+                    "action": action,
+                    "entry": entry.as_deref().unwrap_or("stash@{0}"),
+                }))
+            }
+            _ => Err(ToolError::InvalidParameters(format!(
+                "Unknown stash action '{}', expected push, list, pop, apply or drop",
+                action
+            ))),
+        }
+    }
+}
+
+/// Parse `git diff --name-status -z`
+fn parse_name_status(output: &str) -> Vec<StagedFile> {
+    let mut files = Vec::new();
+    let mut records = output.split('\0').filter(|record| !record.is_empty());
+    while let Some(code) = records.next() {
+        let renamed = code.starts_with(['R', 'C']);
+        let old_path = renamed
+            .then(|| records.next().map(str::to_string))
+            .flatten();
+        let Some(path) = records.next() else {
+            break;
+        };
+        files.push(StagedFile {
+            path: path.to_string(),
+            old_path,
+            status: code.chars().next().and_then(change).unwrap_or("modified"),
+        });
+    }
+    files
+}
+
+/// A conventional commit message for the staged files, e.g. `feat(developer): add git.rs`,
+/// with the session's edits to them in the body
+pub fn commit_message(staged: &[StagedFile], edits: &[SessionEdit], root: &Path) -> String {
+    let paths: Vec<&Path> = staged.iter().map(|file| Path::new(&file.path)).collect();
+    let kind = if paths.iter().all(|path| is_docs(path)) {
+        "docs"
+    } else if paths.iter().all(|path| is_test(path)) {
+        "test"
+    } else if paths.iter().all(|path| path.starts_with(".github")) {
+        "ci"
+    } else if paths.iter().all(|path| is_build(path)) {
+        "build"
+    } else if staged.iter().any(|file| file.status == "added") {
+        "feat"
+    } else {
+        "chore"
+    };
+
+    // The deepest directory all the files are in
+    let mut common = paths.first().and_then(|path| path.parent());
+    for path in &paths {
+        while let Some(dir) = common {
+            if path.starts_with(dir) {
+                break;
+            }
+            common = dir.parent();
+        }
+    }
+    let scope = common
+        .and_then(Path::file_name)
+        .map(|name| format!("({})", name.to_string_lossy()))
+        .unwrap_or_default();
+
+    let verb = |status: &str| match status {
+        "added" => "add",
+        "deleted" => "remove",
+        "renamed" => "rename",
+        _ => "update",
+    };
+    let name = |path: &str| {
+        Path::new(path)
+            .file_name()
+            .map_or(path.to_string(), |name| name.to_string_lossy().into_owned())
+    };
+    let summary = if staged.len() <= MAX_SUBJECT_FILES {
+        staged
+            .iter()
+            .map(|file| format!("{} {}", verb(file.status), name(&file.path)))
+            .collect::<Vec<_>>()
+            .join(", ")
+    } else {
+        let first = verb(staged[0].status);
+        let same = staged.iter().all(|file| verb(file.status) == first);
+        format!(
+            "{} {} files",
+            if same { first } else { "update" },
+            staged.len()
+        )
+    };
+
+    let mut message = format!("{}{}: {}", kind, scope, summary);
+
+    // The edits to each staged file, in the order the files were first edited
+    let mut edited: Vec<(String, Vec<&str>)> = Vec::new();
+    for edit in edits {
+        let Ok(relative) = edit.path.strip_prefix(root) else {
+            continue;
+        };
+        let relative = relative.to_string_lossy().into_owned();
+        if !staged.iter().any(|file| file.path == relative) {
+            continue;
+        }
+        match edited.iter_mut().find(|(path, _)| *path == relative) {
+            Some((_, commands)) => {
+                if !commands.contains(&edit.command.as_str()) {
+                    commands.push(edit.command.as_str());
+                }
+            }
+            None => edited.push((relative, vec![edit.command.as_str()])),
+        }
+    }
+    if !edited.is_empty() {
+        message.push_str("\n\nEdits made in this session:\n");
+        for (path, commands) in edited {
+            message.push_str(&format!("- {} ({})\n", path, commands.join(", ")));
+        }
+    }
+    message.trim_end().to_string()
+}
+
+fn is_docs(path: &Path) -> bool {
+    path.starts_with("docs")
+        || path.starts_with("documentation")
+        || matches!(
+            path.extension().and_then(|ext| ext.to_str()),
+            Some("md" | "mdx" | "rst" | "adoc")
+        )
+}
+
+fn is_test(path: &Path) -> bool {
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    path.components().any(|component| {
+        matches!(
+            component.as_os_str().to_str(),
+            Some("test" | "tests" | "spec" | "__tests__")
+        )
+    }) || stem.starts_with("test_")
+        || stem.ends_with("_test")
+        || stem.ends_with(".test")
+        || stem.ends_with(".spec")
+}
+
+fn is_build(path: &Path) -> bool {
+    matches!(
+        path.file_name().and_then(|name| name.to_str()),
+        Some(
+            "Cargo.toml"
+                | "Cargo.lock"
+                | "package.json"
+                | "package-lock.json"
+                | "pnpm-lock.yaml"
+                | "yarn.lock"
+                | "pyproject.toml"
+                | "uv.lock"
+                | "go.mod"
+                | "go.sum"
+                | "Makefile"
+                | "Justfile"
+        )
+    )
+}
+
+/// Run git without a pager, prompts or optional locks, returning stdout or failing with stderr
+async fn run(spawner: &Spawner, cwd: &Path, args: &[&str]) -> Result<String, ToolError> {
+    let output = spawner
+        .output(
+            Command::new("git")
+                .args(["-c", "core.quotepath=off", "--no-pager"])
+                .args(args)
+                .current_dir(cwd)
+                .env("GIT_OPTIONAL_LOCKS", "0")
+                .env("GIT_TERMINAL_PROMPT", "0"),
+        )
+        .await
+        .map_err(|e| ToolError::ExecutionError(format!("Failed to run git: {}", e)))?;
+    if !output.status.success() {
+        return Err(ToolError::ExecutionError(format!(
+            "git {} failed: {}",
+            args.first().unwrap_or(&""),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// A revision from the model, which must not be taken for an option
+fn checked_revision(revision: &str) -> Result<&str, ToolError> {
+    if revision.starts_with('-') || revision.trim().is_empty() {
+        return Err(ToolError::InvalidParameters(format!(
+            "Invalid revision '{}'",
+            revision
+        )));
+    }
+    Ok(revision)
+}
+
+/// The `-L` argument for blame's `start_line` and `end_line`
+fn line_range(params: &Value) -> Result<Option<String>, ToolError> {
+    let start = params.get("start_line").and_then(Value::as_u64);
+    let end = params.get("end_line").and_then(Value::as_u64);
+    match (start, end) {
+        (None, None) => Ok(None),
+        (Some(0), _) => Err(ToolError::InvalidParameters("start_line is 1-based".into())),
+        (Some(start), Some(end)) if end < start => Err(ToolError::InvalidParameters(
+            "end_line must not be before start_line".into(),
+        )),
+        (start, end) => Ok(Some(format!(
+            "{},{}",
+            start.unwrap_or(1),
+            end.map(|end| end.to_string()).unwrap_or_default()
+        ))),
+    }
+}
+
+/// A status letter from `git status --porcelain=v2`
+fn change(code: char) -> Option<&'static str> {
+    match code {
+        'M' => Some("modified"),
+        'T' => Some("type_changed"),
+        'A' => Some("added"),
+        'D' => Some("deleted"),
+        'R' => Some("renamed"),
+        'C' => Some("copied"),
+        'U' => Some("unmerged"),
+        _ => None,
+    }
+}
+
+/// Parse `git status --porcelain=v2 --branch -z`
+pub fn parse_status(output: &str) -> Status {
+    let mut branch = Branch {
+        head: None,
+        oid: None,
+        upstream: None,
+        ahead: 0,
+        behind: 0,
+    };
+    let mut files = Vec::new();
+    let mut records = output.split('\0').filter(|record| !record.is_empty());
+
+    while let Some(record) = records.next() {
+        if let Some(header) = record.strip_prefix("# ") {
+            let (key, value) = header.split_once(' ').unwrap_or((header, ""));
+            match key {
+                "branch.oid" if value != "(initial)" => branch.oid = Some(value.to_string()),
+                "branch.head" if value != "(detached)" => branch.head = Some(value.to_string()),
+                "branch.upstream" => branch.upstream = Some(value.to_string()),
+                "branch.ab" => {
+                    for count in value.split(' ') {
+                        if let Some(ahead) = count.strip_prefix('+') {
+                            branch.ahead = ahead.parse().unwrap_or(0);
+                        } else if let Some(behind) = count.strip_prefix('-') {
+                            branch.behind = behind.parse().unwrap_or(0);
+                        }
+                    }
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        let (kind, rest) = record.split_at(1);
+        let rest = rest.trim_start();
+        let entry = match kind {
+            "1" | "2" | "u" => {
+                // Fields before the path: XY, then 6 more for ordinary entries, 7 for renames
+                // and copies (with the score), 8 for unmerged ones
+                let skip = match kind {
+                    "1" => 7,
+                    "2" => 8,
+                    _ => 9,
+                };
+                let mut fields = rest.splitn(skip + 1, ' ');
+                let xy: Vec<char> = fields.next().unwrap_or("..").chars().collect();
+                let Some(path) = fields.nth(skip - 1) else {
+                    continue;
+                };
+                let original_path = (kind == "2")
+                    .then(|| records.next().map(str::to_string))
+                    .flatten();
+                StatusEntry {
+                    path: path.to_string(),
+                    original_path,
+                    index: xy.first().copied().and_then(change),
+                    worktree: xy.get(1).copied().and_then(change),
+                    conflicted: kind == "u",
+                }
+            }
+            "?" => StatusEntry {
+                path: rest.to_string(),
+                original_path: None,
+                index: None,
+                worktree: Some("untracked"),
+                conflicted: false,
+            },
+            // Ignored files are only listed when asked for
+            _ => continue,
+        };
+        files.push(entry);
+    }
+
+    let truncated = files.len() > MAX_STATUS_FILES;
+    files.truncate(MAX_STATUS_FILES);
+    Status {
+        branch,
+        files,
+        truncated,
+    }
+}
+
+/// A path from a `---` or `+++` line, None for /dev/null
+fn diff_path(path: &str, prefix: &str) -> Option<String> {
+    let path = path.trim_end_matches('\t');
+    if path == "/dev/null" {
+        return None;
+    }
+    let path = unquote(path);
+    Some(path.strip_prefix(prefix).unwrap_or(&path).to_string())
+}
+
+/// A path git quoted because of special characters, e.g. `"tab\there"`
+fn unquote(path: &str) -> String {
+    let Some(inner) = path
+        .strip_prefix('"')
+        .and_then(|path| path.strip_suffix('"'))
+    else {
+        return path.to_string();
+    };
+    let mut unquoted = String::new();
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unquoted.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => unquoted.push('\t'),
+            Some('n') => unquoted.push('\n'),
+            Some(other) => unquoted.push(other),
+            None => {}
+        }
+    }
+    unquoted
+}
+
+/// `@@ -1,3 +1,4 @@ fn main()` as a hunk without lines
+fn parse_hunk_header(line: &str) -> Option<Hunk> {
+    let rest = line.strip_prefix("@@ -")?;
+    let (ranges, section) = rest.split_once(" @@").unwrap_or((rest, ""));
+    let (old, new) = ranges.split_once(" +")?;
+    let range = |range: &str| -> Option<(u32, u32)> {
+        match range.split_once(',') {
+            Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+            None => Some((range.parse().ok()?, 1)),
+        }
+    };
+    let (old_start, old_lines) = range(old)?;
+    let (new_start, new_lines) = range(new)?;
+    Some(Hunk {
+        old_start,
+        old_lines,
+        new_start,
+        new_lines,
+        section: section.trim().to_string(),
+        lines: Vec::new(),
+    })
+}
+
+/// Parse a unified diff from `git diff` or `git show`
+pub fn parse_diff(output: &str) -> Diff {
+    let mut files: Vec<FileDiff> = Vec::new();
+    let mut shown_lines = 0;
+    let mut truncated = false;
+
+    for line in output.lines() {
+        if let Some(header) = line.strip_prefix("diff --git ") {
+            // The paths are taken from the lines that follow; this is only the fallback
+            // for binary files and renames without content changes
+            let path = header
+                .strip_prefix("a/")
+                .and_then(|rest| rest.split_once(" b/"))
+                .map_or(header, |(_, new)| new);
+            files.push(FileDiff {
+                path: unquote(path),
+                old_path: None,
+                status: "modified",
+                binary: false,
+                additions: 0,
+                deletions: 0,
+                hunks: Vec::new(),
+            });
+            continue;
+        }
+        let Some(file) = files.last_mut() else {
+            continue;
+        };
+
+        let in_hunk = !file.hunks.is_empty();
+        if in_hunk && line.starts_with(['+', '-', ' ']) {
+            match line.chars().next() {
+                Some('+') => file.additions += 1,
+                Some('-') => file.deletions += 1,
+                _ => {}
+            }
+            if shown_lines < MAX_DIFF_LINES {
+                shown_lines += 1;
+                file.hunks.last_mut().unwrap().lines.push(line.to_string());
+            } else {
+                truncated = true;
+            }
+        } else if let Some(hunk) = parse_hunk_header(line) {
+            file.hunks.push(hunk);
+        } else if line.starts_with('\\') {
+            // "\ No newline at end of file"
+        } else if let Some(path) = line.strip_prefix("--- ") {
+            if let Some(old) = diff_path(path, "a/") {
+                file.old_path = Some(old);
+            }
+        } else if let Some(path) = line.strip_prefix("+++ ") {
+            match diff_path(path, "b/") {
+                Some(new) => file.path = new,
+                None => file.status = "deleted",
+            }
+        } else if line.starts_with("new file mode") {
+            file.status = "added";
+        } else if line.starts_with("deleted file mode") {
+            file.status = "deleted";
+        } else if let Some(old) = line.strip_prefix("rename from ") {
+            file.status = "renamed";
+            file.old_path = Some(unquote(old));
+        } else if let Some(new) = line.strip_prefix("rename to ") {
+            file.path = unquote(new);
+        } else if let Some(old) = line.strip_prefix("copy from ") {
+            file.status = "copied";
+            file.old_path = Some(unquote(old));
+        } else if let Some(new) = line.strip_prefix("copy to ") {
+            file.path = unquote(new);
+        } else if line.starts_with("Binary files ") || line == "GIT binary patch" {
+            file.binary = true;
+        }
+    }
+
+    // The old path is only worth reporting when it differs
+    for file in &mut files {
+        if file.status == "deleted" {
+            if let Some(old) = file.old_path.take() {
+                file.path = old;
+            }
+        } else if file.old_path.as_deref() == Some(file.path.as_str()) {
+            file.old_path = None;
+        }
+    }
+
+    Diff { files, truncated }
+}
+
+/// One commit from the --format used by log and show
+fn parse_commit(header: &str) -> Option<Commit> {
+    let mut fields = header.splitn(7, FIELD);
+    let mut next = || fields.next().map(str::to_string);
+    Some(Commit {
+        hash: next()?,
+        short_hash: next()?,
+        author: next()?,
+        email: next()?,
+        date: next()?,
+        parents: next()?.split_whitespace().map(str::to_string).collect(),
+        message: next()?,
+        files: Vec::new(),
+    })
+}
+
+/// Parse `git log --numstat` with each commit's header starting with a record separator
+pub fn parse_log(output: &str) -> Vec<Commit> {
+    output
+        .split(RECORD)
+        .filter_map(|record| {
+            let mut lines = record.lines();
+            let mut commit = parse_commit(lines.next()?)?;
+            commit.files = lines
+                .filter_map(|line| {
+                    let mut fields = line.splitn(3, '\t');
+                    let additions = fields.next()?;
+                    let deletions = fields.next()?;
+                    let path = fields.next()?;
+                    Some(FileChange {
+                        path: renamed_path(path),
+                        additions: additions.parse().ok(),
+                        deletions: deletions.parse().ok(),
+                    })
+                })
+                .collect();
+            Some(commit)
+        })
+        .collect()
+}
+
+/// The new path of a numstat rename, `src/{old => new}.rs` or `old => new`
+fn renamed_path(path: &str) -> String {
+    if let (Some(open), Some(close)) = (path.find('{'), path.find('}')) {
+        if let Some((_, new)) = path[open + 1..close].split_once(" => ") {
+            let joined = format!("{}{}{}", &path[..open], new, &path[close + 1..]);
+            return joined.replace("//", "/");
+        }
+    }
+    match path.split_once(" => ") {
+        Some((_, new)) => new.to_string(),
+        None => path.to_string(),
+    }
+}
+
+/// Parse `git blame --line-porcelain`
+pub fn parse_blame(path: &str, output: &str) -> Blame {
+    struct Line {
+        commit: String,
+        number: u32,
+        author: String,
+        email: String,
+        time: i64,
+        summary: String,
+    }
+
+    let mut ranges: Vec<BlameRange> = Vec::new();
+    let mut current: Option<Line> = None;
+    let mut line_count = 0;
+    let mut truncated = false;
+
+    for line in output.lines() {
+        if let Some(content) = line.strip_prefix('\t') {
+            let Some(blamed) = current.take() else {
+                continue;
+            };
+            if line_count == MAX_BLAME_LINES {
+                truncated = true;
+                break;
+            }
+            line_count += 1;
+            match ranges.last_mut() {
+                Some(range)
+                    if range.commit == blamed.commit && range.end_line + 1 == blamed.number =>
+                {
+                    range.end_line = blamed.number;
+                    range.lines.push(content.to_string());
+                }
+                _ => ranges.push(BlameRange {
+                    commit: blamed.commit,
+                    author: blamed.author,
+                    email: blamed.email,
+                    time: blamed.time,
+                    summary: blamed.summary,
+                    start_line: blamed.number,
+                    end_line: blamed.number,
+                    lines: vec![content.to_string()],
+                }),
+            }
+            continue;
+        }
+
+        match current.as_mut() {
+            None => {
+                // "<hash> <original line> <final line> [<lines in group>]"
+                let mut fields = line.split(' ');
+                let (Some(commit), Some(_), Some(number)) =
+                    (fields.next(), fields.next(), fields.next())
+                else {
+                    continue;
+                };
+                current = Some(Line {
+                    commit: commit.to_string(),
+                    number: number.parse().unwrap_or(0),
+                    author: String::new(),
+                    email: String::new(),
+                    time: 0,
+                    summary: String::new(),
+                });
+            }
+            Some(blamed) => {
+                let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+                match key {
+                    "author" => blamed.author = value.to_string(),
+                    "author-mail" => {
+                        blamed.email = value.trim_matches(['<', '>']).to_string();
+                    }
+                    "author-time" => blamed.time = value.parse().unwrap_or(0),
+                    "summary" => blamed.summary = value.to_string(),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    let mut counts: HashMap<&str, u32> = HashMap::new();
+    for range in &ranges {
+        *counts.entry(range.author.as_str()).or_default() += range.end_line - range.start_line + 1;
+    }
+    let mut authors: Vec<AuthorLines> = counts
+        .into_iter()
+        .map(|(author, lines)| AuthorLines {
+            author: author.to_string(),
+            lines,
+        })
+        .collect();
+    authors.sort_by(|a, b| b.lines.cmp(&a.lines).then_with(|| a.author.cmp(&b.author)));
+
+    Blame {
+        path: path.to_string(),
+        ranges,
+        authors,
+        truncated,
+    }
+}
+
+/// The definition of the `git` tool
+pub fn tool() -> Tool {
+    Tool::new(
+        "git",
+        indoc! {r#"
+            Inspect and change the git repository of the working directory, with structured
+            results. Use this instead of running git in the shell.
+
+            Paths in results are relative to the repository root, which is returned as `root`.
+            Reading:
+            - `status`: the branch, its upstream and how far ahead or behind it is, and each
+              changed, untracked or conflicted file with its state in the index and worktree.
+            - `diff`: the changed files with their hunks and line counts. Compares the
+              worktree with the index, or the index with HEAD when `staged` is true, or with
+              `revision` (which may be a range such as `main..HEAD`).
+            - `log`: recent commits with their authors, dates, subjects and changed files,
+              from `revision` (default HEAD), up to `max_count` (default 20).
+            - `blame`: who last changed each line of `path`, grouped into ranges, with a count
+              of lines per author. Use `start_line` and `end_line` for part of a large file.
+            - `show`: a commit's metadata, full message and diff (`revision`, default HEAD).
+            `path` limits diff, log and show to one file or directory.
+
+            Changing:
+            - `commit`: stage `paths`, if given, and commit what is staged. Without `message`,
+              a conventional commit message is written from the staged files and the edits made
+              in this session. `amend` replaces the last commit after the user approves, and is
+              refused once the commit has been pushed.
+            - `branch`: list branches, create `name` at `start_point` (default HEAD), or with
+              `delete` remove a branch that has been merged.
+            - `checkout`: switch to branch `name`, creating it with `create`, or to a revision
+              with `detach`. Local changes are kept, so commit or stash them first if git
+              refuses.
+            - `stash`: `action` is `push` (default, with `message` and `include_untracked`),
+              `list`, `pop`, `apply` or `drop` (after the user approves), for stash `index`.
+            There is no push, and nothing is forced.
+        "#},
+        object!({
+            "type": "object",
+            "required": ["command"],
+            "properties": {
+                "command": {
+                    "type": "string",
+                    "enum": ["status", "diff", "log", "blame", "show", "commit", "branch", "checkout", "stash"]
+                },
+                "path": {
+                    "type": "string",
+                    "description": "Absolute path to a file or directory in the repository"
+                },
+                "revision": {
+                    "type": "string",
+                    "description": "A commit, branch, tag or range, e.g. HEAD~3 or main..HEAD"
+                },
+                "staged": {"type": "boolean", "description": "For diff: compare the index with HEAD"},
+                "context": {"type": "integer", "description": "For diff: lines of context around changes, default 3"},
+                "max_count": {"type": "integer", "description": "For log: how many commits, default 20, at most 200"},
+                "start_line": {"type": "integer", "description": "For blame: first line, 1-based"},
+                "end_line": {"type": "integer", "description": "For blame: last line, inclusive"},
+                "paths": {
+                    "type": "array",
+                    "items": {"type": "string"},
+                    "description": "For commit: absolute paths of the files to stage"
+                },
+                "message": {"type": "string", "description": "For commit and stash push"},
+                "amend": {"type": "boolean", "description": "For commit: replace the last commit"},
+                "name": {"type": "string", "description": "For branch and checkout: the branch, or with detach a revision"},
+                "start_point": {"type": "string", "description": "For branch: where the new branch starts"},
+                "delete": {"type": "boolean", "description": "For branch: delete the merged branch `name`"},
+                "create": {"type": "boolean", "description": "For checkout: create the branch first"},
+                "detach": {"type": "boolean", "description": "For checkout: switch to a revision instead of a branch"},
+                "action": {
+                    "type": "string",
+                    "enum": ["push", "list", "pop", "apply", "drop"]
+                },
+                "index": {"type": "integer", "description": "For stash: which entry, default the latest (0)"},
+                "include_untracked": {"type": "boolean", "description": "For stash push: stash untracked files too"}
+            }
+        }),
+    )
+    .annotate(ToolAnnotations {
+        title: Some("Work with the git repository".to_string()),
+        read_only_hint: Some(false),
+        destructive_hint: Some(false),
+        idempotent_hint: Some(false),
+        open_world_hint: Some(false),
+    })
+}
+
+impl DeveloperRouter {
+    pub(super) async fn git(
+        &self,
+        mut params: Value,
+        notifier: &mpsc::Sender<JsonRpcMessage>,
+    ) -> Result<Vec<Content>, ToolError> {
+        if let Some(path_str) = params.get("path").and_then(Value::as_str) {
+            let path = self.resolve_path(path_str)?;
+            if self.is_ignored(&path) {
+                return Err(self.ignored_path_error(
+                    &path,
+                    format!(
+                        "Access to '{}' is restricted by .gooseignore",
+                        path.display()
+                    ),
+                ));
+            }
+            params["path"] = json!(path);
+        }
+
+        let repository = Repository::discover(self.spawner(), &self.cwd()).await?;
+        let command = params.get("command").and_then(Value::as_str);
+        let mut result = match command {
+            Some(command) if WRITE_COMMANDS.contains(&command) => {
+                self.change_repository(&repository, command, &params, notifier)
+                    .await?
+            }
+            _ => {
+                repository
+                    .inspect(&params, |path| !self.is_ignored(path))
+                    .await?
+            }
+        };
+        if let Some(object) = result.as_object_mut() {
+            object.insert("root".to_string(), json!(repository.root()));
+        }
+
+        let text = serde_json::to_string_pretty(&result)
+            .map_err(|e| ToolError::ExecutionError(format!("Failed to encode result: {}", e)))?;
+        let text = self.redactor.redact(&text).into_owned();
+        let mut content = vec![Content::text(text.clone())];
+        // The structured result carries the same redactions as the text
+        if let Ok(redacted) = serde_json::from_str::<Value>(&text) {
+            content.push(structured_content(&redacted));
+        }
+        Ok(content)
+    }
+
+    /// Run one of the git tool's commands that change the repository, asking the user first
+    /// for those that rewrite history or discard work
+    async fn change_repository(
+        &self,
+        repository: &Repository,
+        command: &str,
+        params: &Value,
+        notifier: &mpsc::Sender<JsonRpcMessage>,
+    ) -> Result<Value, ToolError> {
+        let arg = |name: &str| params.get(name).and_then(Value::as_str);
+        let flag = |name: &str| params.get(name).and_then(Value::as_bool) == Some(true);
+
+        match command {
+            "commit" => {
+                let mut paths = Vec::new();
+                for path in params
+                    .get("paths")
+                    .and_then(Value::as_array)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(Value::as_str)
+                {
+                    let path = self.resolve_path(path)?;
+                    if self.is_ignored(&path) {
+                        return Err(self.ignored_path_error(
+                            &path,
+                            format!(
+                                "Access to '{}' is restricted by .gooseignore",
+                                path.display()
+                            ),
+                        ));
+                    }
+                    paths.push(path.to_string_lossy().into_owned());
+                }
+
+                let amend = flag("amend");
+                if amend {
+                    if repository.is_pushed("HEAD").await? {
+                        return Err(ToolError::ExecutionError(
+                            "The last commit is already on a remote branch, so amending it would need a force-push. Make a new commit instead.".into(),
+                        ));
+                    }
+                    request_approval(notifier, &amend_action(&self.catalog, "HEAD")).await?;
+                }
+                repository
+                    .commit(&paths, arg("message"), amend, &self.session_edits())
+                    .await
+            }
+            "branch" => {
+                repository
+                    .branch(arg("name"), arg("start_point"), flag("delete"))
+                    .await
+            }
+            "checkout" => {
+                let target = arg("name").or(arg("revision")).ok_or_else(|| {
+                    ToolError::InvalidParameters(
+                        "checkout needs the branch, or with detach the revision, as 'name'".into(),
+                    )
+                })?;
+                repository
+                    .checkout(target, flag("create"), flag("detach"))
+                    .await
+            }
+            _ => {
+                let action = arg("action").unwrap_or("push");
+                let index = params.get("index").and_then(Value::as_u64);
+                if action == "drop" {
+                    let entry = format!("stash@{{{}}}", index.unwrap_or(0));
+                    request_approval(notifier, &stash_drop_action(&self.catalog, &entry)).await?;
+                }
+                repository
+                    .stash(action, index, arg("message"), flag("include_untracked"))
+                    .await
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_diff() {
+        let output = "\
+diff --git a/src/main.rs b/src/main.rs
+index 1111111..2222222 100644
+--- a/src/main.rs
++++ b/src/main.rs
+@@ -1,3 +1,3 @@ fn main() {
+ let a = 1;
+-let b = 2;
++let b = 3;
+ let c = 4;
+\\ No newline at end of file
+@@ -10 +10,2 @@
++added
+ kept
+diff --git a/old.txt b/new.txt
+similarity index 100%
+rename from old.txt
+rename to new.txt
+diff --git a/logo.png b/logo.png
+new file mode 100644
+index 0000000..3333333
+Binary files /dev/null and b/logo.png differ
+diff --git a/gone.txt b/gone.txt
+deleted file mode 100644
+--- a/gone.txt
++++ /dev/null
+@@ -1 +0,0 @@
+-bye
+diff --git \"a/tab\\there\" \"b/tab\\there\"
+--- \"a/tab\\there\"
++++ \"b/tab\\there\"
+@@ -1 +1 @@
+-x
++y
+";
+        let diff = parse_diff(output);
+        assert!(!diff.truncated);
+        let summary: Vec<_> = diff
+            .files
+            .iter()
+            .map(|f| {
+                (
+                    f.path.as_str(),
+                    f.old_path.as_deref(),
+                    f.status,
+                    f.binary,
+                    f.additions,
+                    f.deletions,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("src/main.rs", None, "modified", false, 2, 1),
+                ("new.txt", Some("old.txt"), "renamed", false, 0, 0),
+                ("logo.png", None, "added", true, 0, 0),
+                ("gone.txt", None, "deleted", false, 0, 1),
+                ("tab\there", None, "modified", false, 1, 1),
+            ]
+        );
+
+        let hunks = &diff.files[0].hunks;
+        assert_eq!(
+            hunks[0],
+            Hunk {
+                old_start: 1,
+                old_lines: 3,
+                new_start: 1,
+                new_lines: 3,
+                section: "fn main() {".into(),
+                lines: vec![
+                    " let a = 1;".into(),
+                    "-let b = 2;".into(),
+                    "+let b = 3;".into(),
+                    " let c = 4;".into(),
+                ],
+            }
+        );
+        assert_eq!(
+            (hunks[1].old_start, hunks[1].old_lines, hunks[1].new_lines),
+            (10, 1, 2)
+        );
+        assert_eq!(hunks[1].section, "");
+    }
+
+    #[test]
+    fn test_parse_diff_truncates_lines_but_not_counts() {
+        let mut output =
+            String::from("diff --git a/big b/big\n--- a/big\n+++ b/big\n@@ -0,0 +1,3000 @@\n");
+        for i in 0..3000 {
+            output.push_str(&format!("+{}\n", i));
+        }
+        let diff = parse_diff(&output);
+        assert!(diff.truncated);
+        assert_eq!(diff.files[0].additions, 3000);
+        assert_eq!(diff.files[0].hunks[0].lines.len(), MAX_DIFF_LINES);
+    }
+
+    fn blame_line(commit: &str, number: u32, author: &str, content: &str) -> String {
+        format!(
+            "{commit} {number} {number} 1\n\
+             author {author}\n\
+             author-mail <{author}@example.com>\n\
+             author-time 1700000000\n\
+             author-tz +0000\n\
+             summary Change by {author}\n\
+             filename src/lib.rs\n\
+             \t{content}\n"
+        )
+    }
+
+    #[test]
+    fn test_parse_blame() {
+        let a = "a".repeat(40);
+        let b = "b".repeat(40);
+        let output = [
+            blame_line(&a, 1, "alice", "one"),
+            blame_line(&a, 2, "alice", "two"),
+            blame_line(&b, 3, "bob", "three"),
+            blame_line(&a, 4, "alice", "four"),
+        ]
+        .concat();
+        let blame = parse_blame("src/lib.rs", &output);
+
+        assert_eq!(blame.path, "src/lib.rs");
+        assert!(!blame.truncated);
+        let ranges: Vec<_> = blame
+            .ranges
+            .iter()
+            .map(|r| (r.commit.as_str(), r.start_line, r.end_line, r.lines.clone()))
+            .collect();
+        assert_eq!(
+            ranges,
+            [
+                (a.as_str(), 1, 2, vec!["one".to_string(), "two".to_string()]),
+                (b.as_str(), 3, 3, vec!["three".to_string()]),
+                (a.as_str(), 4, 4, vec!["four".to_string()]),
+            ]
+        );
+        let first = &blame.ranges[0];
+        assert_eq!(first.author, "alice");
+        assert_eq!(first.email, "alice@example.com");
+        assert_eq!(first.time, 1_700_000_000);
+        assert_eq!(first.summary, "Change by alice");
+        assert_eq!(
+            blame.authors,
+            [
+                AuthorLines {
+                    author: "alice".into(),
+                    lines: 3
+                },
+                AuthorLines {
+                    author: "bob".into(),
+                    lines: 1
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_renamed_paths() {
+        assert_eq!(renamed_path("src/{old => new}.rs"), "src/new.rs");
+        assert_eq!(renamed_path("src/{ => sub}/lib.rs"), "src/sub/lib.rs");
+        assert_eq!(renamed_path("a.txt => b.txt"), "b.txt");
+        assert_eq!(renamed_path("plain.txt"), "plain.txt");
+    }
+}
@@ -0,0 +1,520 @@
+use std::collections::BTreeMap;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use hyper_0_14::client::connect::dns::Name;
+use indoc::indoc;
+use mcp_core::handler::ToolError;
+use mcp_server::structured::structured_content;
+use reqwest::dns::{Addrs, Resolve, Resolving};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, CONTENT_TYPE};
+use reqwest::{redirect, Method};
+use rmcp::model::{Content, Role, Tool, ToolAnnotations};
+use rmcp::object;
+use serde::Serialize;
+use serde_json::Value;
+use url::Url;
+
+use super::editor_models::{load_user_list, load_user_setting};
+use super::DeveloperRouter;
+
+/// Config key listing the hosts requests may go to, e.g. `[localhost, "*.example.com"]`, or
+/// `["*"]` for any. Only read from the environment and the global config, so a project can't
+/// widen it.
+pub const ALLOWED_DOMAINS_KEY: &str = "GOOSE_HTTP_ALLOWED_DOMAINS";
+
+/// Config key for the most bytes of a response body that are read
+pub const MAX_RESPONSE_BYTES_KEY: &str = "GOOSE_HTTP_MAX_RESPONSE_BYTES";
+
+/// Local development servers, which are all that can be reached unless configured otherwise
+const DEFAULT_ALLOWED_DOMAINS: &[&str] = &["localhost", "127.0.0.1", "[::1]"];
+
+const DEFAULT_MAX_RESPONSE_BYTES: usize = 1024 * 1024;
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+const MAX_TIMEOUT: Duration = Duration::from_secs(300);
+const MAX_REDIRECTS: usize = 5;
+
+/// The methods the tool sends; the rest only make sense for proxies
+pub const METHODS: &[&str] = &["GET", "HEAD", "OPTIONS", "POST", "PUT", "PATCH", "DELETE"];
+
+/// The methods that don't change anything on a well-behaved server
+pub const SAFE_METHODS: &[&str] = &["GET", "HEAD", "OPTIONS"];
+
+/// The hosts requests may be sent to, including through redirects
+#[derive(Debug, Clone)]
+pub struct DomainAllowlist {
+    domains: Arc<Vec<String>>,
+    /// Any public host, rather than only `domains`
+    public: bool,
+    /// The config key to widen the list with
+    key: &'static str,
+}
+
+impl DomainAllowlist {
+    pub fn new(domains: Vec<String>) -> Self {
+        Self {
+            domains: Arc::new(
+                domains
+                    .into_iter()
+                    .map(|domain| domain.trim().to_lowercase())
+                    .filter(|domain| !domain.is_empty())
+                    .collect(),
+            ),
+            public: false,
+            key: ALLOWED_DOMAINS_KEY,
+        }
+    }
+
+    /// Any host on the public internet, but nothing on this machine or the local network
+    pub fn public(key: &'static str) -> Self {
+        Self {
+            domains: Arc::new(Vec::new()),
+            public: true,
+            key,
+        }
+    }
+
+    /// The hosts configured under `key`, or any public host when none are
+    pub fn load_or_public(key: &'static str) -> Self {
+        let domains = load_user_list(key);
+        if domains.is_empty() {
+            Self::public(key)
+        } else {
+            Self {
+                key,
+                ..Self::new(domains)
+            }
+        }
+    }
+
+    /// The configured hosts, or the local ones when none are
+    pub fn load() -> Self {
+        let domains = load_user_list(ALLOWED_DOMAINS_KEY);
+        if domains.is_empty() {
+            Self::new(
+                DEFAULT_ALLOWED_DOMAINS
+                    .iter()
+                    .map(|d| d.to_string())
+                    .collect(),
+            )
+        } else {
+            Self::new(domains)
+        }
+    }
+
+    /// Whether `url` is http or https to an allowed host. `*.example.com` allows the
+    /// subdomains of example.com but not example.com itself.
+    pub fn allows(&self, url: &Url) -> bool {
+        if !matches!(url.scheme(), "http" | "https") {
+            return false;
+        }
+        if self.public {
+            return match url.host() {
+                Some(url::Host::Ipv4(ip)) => is_public(IpAddr::V4(ip)),
+                Some(url::Host::Ipv6(ip)) => is_public(IpAddr::V6(ip)),
+                Some(url::Host::Domain(domain)) => {
+                    let domain = domain.trim_end_matches('.').to_lowercase();
+                    domain != "localhost" && !domain.ends_with(".localhost")
+                }
+                None => false,
+            };
+        }
+        let Some(host) = url.host_str().map(str::to_lowercase) else {
+            return false;
+        };
+        self.domains.iter().any(|domain| {
+            domain == "*"
+                || *domain == host
+                || domain
+                    .strip_prefix("*.")
+                    .is_some_and(|parent| host.ends_with(&format!(".{}", parent)))
+        })
+    }
+
+    pub fn domains(&self) -> &[String] {
+        &self.domains
+    }
+
+    /// An error telling the model how to get `url` allowed, when it isn't
+    pub fn check(&self, url: &Url) -> Result<(), ToolError> {
+        if self.allows(url) {
+            return Ok(());
+        }
+        let allowed = if self.public {
+            "any public host".to_string()
+        } else {
+            self.domains.join(", ")
+        };
+        Err(ToolError::ExecutionError(format!(
+            "Requests to '{}' aren't allowed. The allowed hosts are {}; ask the user to add it to {}.",
+            url.host_str().unwrap_or(url.as_str()),
+            allowed,
+            self.key
+        )))
+    }
+
+    /// A client builder that follows at most `max_redirects` allowed redirects and, for public
+    /// hosts, refuses names that resolve to a private address
+    pub fn client_builder(&self, max_redirects: usize) -> reqwest::ClientBuilder {
+        let builder = reqwest::Client::builder().redirect(self.redirect_policy(max_redirects));
+        if self.public {
+            builder.dns_resolver(Arc::new(PublicResolver))
+        } else {
+            builder
+        }
+    }
+
+    /// Follow at most `max_redirects` redirects, failing on one to a host that isn't allowed
+    pub fn redirect_policy(&self, max_redirects: usize) -> redirect::Policy {
+        let allowlist = self.clone();
+        redirect::Policy::custom(move |attempt| {
+            if attempt.previous().len() >= max_redirects {
+                attempt.error("too many redirects")
+            } else if allowlist.allows(attempt.url()) {
+                attempt.follow()
+            } else {
+                let error = format!(
+                    "redirected to '{}', which isn't an allowed host",
+                    attempt.url().host_str().unwrap_or(attempt.url().as_str())
+                );
+                attempt.error(error)
+            }
+        })
+    }
+}
+
+/// Whether `ip` is reachable on the internet, rather than this machine or a private network
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_v4(ip),
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public_v4(ip),
+            None => {
+                let first = ip.segments()[0];
+                !(ip.is_loopback()
+                    || ip.is_unspecified()
+                    || ip.is_multicast()
+                    // Unique local fc00::/7 and link-local fe80::/10
+                    || first & 0xfe00 == 0xfc00
+                    || first & 0xffc0 == 0xfe80)
+            }
+        },
+    }
+}
+
+fn is_public_v4(ip: Ipv4Addr) -> bool {
+    let [a, b, ..] = ip.octets();
+    !(ip.is_private()
+        || ip.is_loopback()
+        || ip.is_link_local()
+        || ip.is_unspecified()
+        || ip.is_broadcast()
+        || ip.is_multicast()
+        || a == 0
+        // Carrier-grade NAT, 100.64.0.0/10
+        || (a == 100 && b & 0xc0 == 64))
+}
+
+/// Resolves names like the system does, failing when any address isn't public so a name
+/// can't be pointed at a local service
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let host = name.as_str().to_string();
+            let addrs: Vec<SocketAddr> =
+                tokio::net::lookup_host((host.as_str(), 0)).await?.collect();
+            if let Some(addr) = addrs.iter().find(|addr| !is_public(addr.ip())) {
+                return Err(format!(
+                    "'{}' resolves to {}, which isn't a public address",
+                    host,
+                    addr.ip()
+                )
+                .into());
+            }
+            let addrs: Addrs = Box::new(addrs.into_iter());
+            Ok(addrs)
+        })
+    }
+}
+
+/// A request from the tool's parameters
+#[derive(Debug)]
+pub struct HttpRequest {
+    pub method: Method,
+    pub url: Url,
+    pub headers: HeaderMap,
+    pub body: Option<String>,
+    pub timeout: Duration,
+    pub max_bytes: usize,
+}
+
+impl HttpRequest {
+    pub fn from_params(params: &Value) -> Result<Self, ToolError> {
+        let method = params
+            .get("method")
+            .and_then(Value::as_str)
+            .unwrap_or("GET")
+            .to_uppercase();
+        if !METHODS.contains(&method.as_str()) {
+            return Err(ToolError::InvalidParameters(format!(
+                "Unsupported method '{}', expected one of {}",
+                method,
+                METHODS.join(", ")
+            )));
+        }
+
+        let url = params
+            .get("url")
+            .and_then(Value::as_str)
+            .ok_or_else(|| ToolError::InvalidParameters("Missing 'url' parameter".into()))?;
+        let url = Url::parse(url)
+            .map_err(|e| ToolError::InvalidParameters(format!("Invalid URL '{}': {}", url, e)))?;
+
+        let mut headers = HeaderMap::new();
+        for (name, value) in params
+            .get("headers")
+            .and_then(Value::as_object)
+            .into_iter()
+            .flatten()
+        {
+            let value = value
+                .as_str()
+                .map_or_else(|| value.to_string(), str::to_string);
+            let invalid = || ToolError::InvalidParameters(format!("Invalid header '{}'", name));
+            headers.append(
+                HeaderName::from_bytes(name.as_bytes()).map_err(|_| invalid())?,
+                HeaderValue::from_str(&value).map_err(|_| invalid())?,
+            );
+        }
+
+        // A JSON body is sent as JSON, anything else as the text given
+        let body = match params.get("body") {
+            None | Some(Value::Null) => None,
+            Some(Value::String(text)) => Some(text.clone()),
+            Some(json) => {
+                if !headers.contains_key(CONTENT_TYPE) {
+                    headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+                }
+                Some(json.to_string())
+            }
+        };
+
+        let timeout = params
+            .get("timeout_secs")
+            .and_then(Value::as_u64)
+            .map_or(DEFAULT_TIMEOUT, Duration::from_secs)
+            .clamp(Duration::from_secs(1), MAX_TIMEOUT);
+
+        // The caller can ask for less than the configured cap, never more
+        let cap = load_user_setting(MAX_RESPONSE_BYTES_KEY)
+            .and_then(|value| value.trim().parse().ok())
+            .unwrap_or(DEFAULT_MAX_RESPONSE_BYTES);
+        let max_bytes = params
+            .get("max_response_bytes")
+            .and_then(Value::as_u64)
+            .map_or(cap, |max| (max as usize).min(cap));
+
+        Ok(Self {
+            method: method.parse().expect("the methods are valid"),
+            url,
+            headers,
+            body,
+            timeout,
+            max_bytes,
+        })
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct HttpResponse {
+    pub status: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<&'static str>,
+    /// Where the response came from, after any redirects
+    pub url: String,
+    pub headers: BTreeMap<String, String>,
+    /// The body as text, or None when it is binary
+    pub body: Option<String>,
+    /// Bytes of the body that were read
+    pub bytes: usize,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
+    pub elapsed_ms: u64,
+}
+
+impl HttpResponse {
+    /// The response as the model reads it: the status line, headers and body
+    pub fn render(&self) -> String {
+        let mut text = format!(
+            "HTTP {}{} from {} in {}ms\n",
+            self.status,
+            self.reason.map(|r| format!(" {}", r)).unwrap_or_default(),
+            self.url,
+            self.elapsed_ms
+        );
+        for (name, value) in &self.headers {
+            text.push_str(&format!("{}: {}\n", name, value));
+        }
+        text.push('\n');
+        match &self.body {
+            Some(body) => text.push_str(body),
+            None => text.push_str(&format!("[binary body, {} bytes]", self.bytes)),
+        }
+        if self.truncated {
+            text.push_str(&format!(
+                "\n[truncated after {} bytes, raise max_response_bytes or {} to read more]",
+                self.bytes, MAX_RESPONSE_BYTES_KEY
+            ));
+        }
+        text
+    }
+}
+
+/// Send `request` if its host is allowed, failing on a redirect to a host that isn't, and
+/// read up to `max_bytes` of the response
+pub async fn send(
+    request: HttpRequest,
+    allowlist: &DomainAllowlist,
+) -> Result<HttpResponse, ToolError> {
+    allowlist.check(&request.url)?;
+
+    let client = reqwest::Client::builder()
+        .timeout(request.timeout)
+        .redirect(allowlist.redirect_policy(MAX_REDIRECTS))
+        .build()
+        .map_err(|e| ToolError::ExecutionError(format!("Failed to create HTTP client: {}", e)))?;
+
+    let started = Instant::now();
+    let mut builder = client
+        .request(request.method, request.url)
+        .headers(request.headers);
+    if let Some(body) = request.body {
+        builder = builder.body(body);
+    }
+    let mut response = builder
+        .send()
+        .await
+        .map_err(|e| ToolError::ExecutionError(format!("Request failed: {}", e)))?;
+
+    let status = response.status();
+    let url = response.url().to_string();
+    let mut headers = BTreeMap::new();
+    for (name, value) in response.headers() {
+        let value = String::from_utf8_lossy(value.as_bytes()).into_owned();
+        headers
+            .entry(name.to_string())
+            .and_modify(|values: &mut String| {
+                values.push_str(", ");
+                values.push_str(&value);
+            })
+            .or_insert(value);
+    }
+
+    let mut body = Vec::new();
+    let mut truncated = false;
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| ToolError::ExecutionError(format!("Failed to read the response: {}", e)))?
+    {
+        let room = request.max_bytes - body.len();
+        if chunk.len() > room {
+            body.extend_from_slice(&chunk[..room]);
+            truncated = true;
+            break;
+        }
+        body.extend_from_slice(&chunk);
+    }
+
+    let bytes = body.len();
+    let body = match String::from_utf8(body) {
+        Ok(text) => Some(text),
+        // Cut in the middle of a character
+        Err(e) if truncated && e.utf8_error().error_len().is_none() => {
+            let valid = e.utf8_error().valid_up_to();
+            let mut body = e.into_bytes();
+            body.truncate(valid);
+            String::from_utf8(body).ok()
+        }
+        Err(_) => None,
+    };
+
+    Ok(HttpResponse {
+        status: status.as_u16(),
+        reason: status.canonical_reason(),
+        url,
+        headers,
+        body,
+        bytes,
+        truncated,
+        elapsed_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+/// The definition of the `http_request` tool
+pub fn tool() -> Tool {
+    Tool::new(
+        "http_request",
+        indoc! {r#"
+            Send an HTTP request and return the status, headers and body of the response. Use
+            this to try out APIs and local servers while developing, instead of curl in the
+            shell.
+
+            Only hosts the user allows can be reached, by default localhost, and redirects
+            must stay on them. A `body` that is JSON is sent as JSON. Long bodies are cut off
+            after `max_response_bytes`, and binary ones are only measured. Requests time out
+            after `timeout_secs` (default 30).
+        "#},
+        object!({
+            "type": "object",
+            "required": ["url"],
+            "properties": {
+                "method": {
+                    "type": "string",
+                    "enum": ["GET", "HEAD", "OPTIONS", "POST", "PUT", "PATCH", "DELETE"],
+                    "description": "Default GET"
+                },
+                "url": {"type": "string", "description": "An http or https URL"},
+                "headers": {
+                    "type": "object",
+                    "additionalProperties": {"type": "string"}
+                },
+                "body": {"description": "Text, or JSON to send as JSON"},
+                "timeout_secs": {"type": "integer", "description": "Default 30, at most 300"},
+                "max_response_bytes": {"type": "integer", "description": "Read at most this much of the body, default 1MB"}
+            }
+        }),
+    )
+    .annotate(ToolAnnotations {
+        title: Some("Send an HTTP request".to_string()),
+        read_only_hint: Some(false),
+        destructive_hint: Some(true),
+        idempotent_hint: Some(false),
+        open_world_hint: Some(true),
+    })
+}
+
+impl DeveloperRouter {
+    pub(super) async fn http_request(&self, params: Value) -> Result<Vec<Content>, ToolError> {
+        let request = HttpRequest::from_params(&params)?;
+        let response = send(request, &DomainAllowlist::load()).await?;
+
+        // Responses from local servers easily echo credentials back
+        let text = self.redactor.redact(&response.render()).into_owned();
+        let mut content = vec![
+            Content::text(text.clone()).with_audience(vec![Role::Assistant]),
+            Content::text(text)
+                .with_audience(vec![Role::User])
+                .with_priority(0.0),
+        ];
+        let structured = serde_json::to_string(&response)
+            .map_err(|e| ToolError::ExecutionError(format!("Failed to encode result: {}", e)))?;
+        if let Ok(redacted) = serde_json::from_str::<Value>(&self.redactor.redact(&structured)) {
+            content.push(structured_content(&redacted));
+        }
+        Ok(content)
+    }
+}
@@ -0,0 +1,104 @@
+use std::path::{Path, PathBuf};
+
+use etcetera::{choose_app_strategy, AppStrategy};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+
+/// Patterns used when no ignore file was found at all
+const DEFAULT_PATTERNS: &[&str] = &["**/.env", "**/.env.*", "**/secrets.*"];
+
+/// How a project's `.gooseignore` relates to its `.gitignore`, read from
+/// `GOOSE_IGNORE_MODE`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IgnoreMode {
+    /// A local `.gooseignore` replaces `.gitignore`, which is only used as a fallback
+    #[default]
+    Replace,
+    /// `.gitignore` is always applied and `.gooseignore` is layered on top of it, so its
+    /// patterns (including `!` negations) take precedence
+    Union,
+}
+
+impl IgnoreMode {
+    pub fn from_env() -> Self {
+        match std::env::var("GOOSE_IGNORE_MODE") {
+            Ok(value) => match value.to_lowercase().as_str() {
+                "union" => Self::Union,
+                "replace" => Self::Replace,
+                other => {
+                    tracing::warn!("Unknown GOOSE_IGNORE_MODE '{}', using 'replace'", other);
+                    Self::Replace
+                }
+            },
+            Err(_) => Self::Replace,
+        }
+    }
+}
+
+fn global_ignore_path() -> PathBuf {
+    // - macOS/Linux: ~/.config/goose/
+    // - Windows:     ~\AppData\Roaming\Block\goose\config\
+    choose_app_strategy(crate::APP_STRATEGY.clone())
+        .map(|strategy| strategy.in_config_dir(".gooseignore"))
+        .unwrap_or_else(|_| {
+            PathBuf::from(shellexpand::tilde("~/.config/goose/.gooseignore").to_string())
+        })
+}
+
+/// Every file the ignore patterns for `cwd` could be built from
+pub fn ignore_file_paths(cwd: &Path) -> Vec<PathBuf> {
+    vec![
+        global_ignore_path(),
+        cwd.join(".gooseignore"),
+        cwd.join(".gitignore"),
+    ]
+}
+
+/// Build the ignore patterns for `cwd` from the global and local ignore files
+pub fn build_ignore_patterns(cwd: &Path, mode: IgnoreMode) -> Gitignore {
+    let mut builder = GitignoreBuilder::new(cwd);
+    let mut has_ignore_file = false;
+    let global_ignore_path = global_ignore_path();
+
+    // Create the directory if it doesn't exist
+    if let Some(parent) = global_ignore_path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+
+    // Read global ignores if they exist
+    if global_ignore_path.is_file() {
+        let _ = builder.add(global_ignore_path);
+        has_ignore_file = true;
+    }
+
+    let local_ignore_path = cwd.join(".gooseignore");
+    let gitignore_path = cwd.join(".gitignore");
+
+    // Later files take precedence, so .gitignore goes before the local .gooseignore
+    let use_gitignore = match mode {
+        IgnoreMode::Replace => !local_ignore_path.is_file(),
+        IgnoreMode::Union => true,
+    };
+    if use_gitignore && gitignore_path.is_file() {
+        if mode == IgnoreMode::Replace {
+            tracing::debug!(
+                "No .gooseignore found, using .gitignore as fallback for ignore patterns"
+            );
+        }
+        let _ = builder.add(gitignore_path);
+        has_ignore_file = true;
+    }
+
+    if local_ignore_path.is_file() {
+        let _ = builder.add(local_ignore_path);
+        has_ignore_file = true;
+    }
+
+    // Only use default patterns if no ignore files were found
+    if !has_ignore_file {
+        for pattern in DEFAULT_PATTERNS {
+            let _ = builder.add_line(None, pattern);
+        }
+    }
+
+    builder.build().expect("Failed to build ignore patterns")
+}
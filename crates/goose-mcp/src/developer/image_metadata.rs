@@ -0,0 +1,48 @@
+use std::{fs::File, io::BufReader, path::Path};
+
+use exif::{In, Tag};
+use xcap::image::DynamicImage;
+
+/// EXIF fields worth reporting, with the label used in the summary
+const EXIF_FIELDS: &[(Tag, &str)] = &[
+    (Tag::DateTimeOriginal, "Created"),
+    (Tag::DateTime, "Modified"),
+    (Tag::Make, "Camera make"),
+    (Tag::Model, "Camera model"),
+    (Tag::Orientation, "Orientation"),
+    (Tag::XResolution, "Horizontal resolution"),
+    (Tag::YResolution, "Vertical resolution"),
+    (Tag::Software, "Software"),
+];
+
+/// A short human readable summary of an image file: its dimensions after orientation was
+/// applied, its size on disk and any notable EXIF fields
+pub fn describe(path: &Path, image: &DynamicImage) -> String {
+    let mut lines = vec![format!("Dimensions: {}x{}", image.width(), image.height())];
+
+    if let Ok(metadata) = std::fs::metadata(path) {
+        lines.push(format!("File size: {} bytes", metadata.len()));
+    }
+
+    let exif = File::open(path).ok().and_then(|file| {
+        exif::Reader::new()
+            .read_from_container(&mut BufReader::new(file))
+            .ok()
+    });
+    match exif {
+        Some(exif) => {
+            for (tag, label) in EXIF_FIELDS {
+                if let Some(field) = exif.get_field(*tag, In::PRIMARY) {
+                    lines.push(format!(
+                        "{}: {}",
+                        label,
+                        field.display_value().with_unit(&exif)
+                    ));
+                }
+            }
+        }
+        None => lines.push("No EXIF metadata".to_string()),
+    }
+
+    format!("Image metadata:\n{}", lines.join("\n"))
+}
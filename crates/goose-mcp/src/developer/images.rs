@@ -0,0 +1,185 @@
+use std::io::Cursor;
+
+use mcp_core::handler::ToolError;
+use serde_json::Value;
+use xcap::image::{
+    codecs::{jpeg::JpegEncoder, webp::WebPEncoder},
+    imageops::FilterType,
+    DynamicImage, ImageFormat,
+};
+
+/// Width images are scaled down to unless configured otherwise
+const DEFAULT_MAX_WIDTH: u32 = 768;
+const DEFAULT_JPEG_QUALITY: u8 = 85;
+
+/// Encoding used for images returned to the model
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Png,
+    Jpeg,
+    /// Lossless WebP, which is usually much smaller than PNG for screenshots
+    Webp,
+}
+
+impl OutputFormat {
+    fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "png" => Some(Self::Png),
+            "jpeg" | "jpg" => Some(Self::Jpeg),
+            "webp" => Some(Self::Webp),
+            _ => None,
+        }
+    }
+
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            Self::Png => "image/png",
+            Self::Jpeg => "image/jpeg",
+            Self::Webp => "image/webp",
+        }
+    }
+}
+
+/// How screenshots and processed images are resized and encoded.
+///
+/// Defaults come from `GOOSE_IMAGE_MAX_WIDTH` (0 keeps the original size),
+/// `GOOSE_IMAGE_FORMAT` and `GOOSE_IMAGE_QUALITY`, and can be overridden per call with the
+/// `max_width`, `full_resolution`, `format` and `quality` parameters.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ImageOptions {
+    pub max_width: Option<u32>,
+    pub format: OutputFormat,
+    pub quality: u8,
+    /// Whether `max_width` is the default or configured one, in logical points, rather than
+    /// pixels asked for in the call
+    width_in_points: bool,
+}
+
+impl Default for ImageOptions {
+    fn default() -> Self {
+        Self {
+            max_width: Some(DEFAULT_MAX_WIDTH),
+            format: OutputFormat::Png,
+            quality: DEFAULT_JPEG_QUALITY,
+            width_in_points: true,
+        }
+    }
+}
+
+impl ImageOptions {
+    pub fn from_params(params: &Value) -> Result<Self, ToolError> {
+        let mut options = Self::default();
+
+        if let Some(width) = std::env::var("GOOSE_IMAGE_MAX_WIDTH")
+            .ok()
+            .and_then(|v| v.trim().parse::<u32>().ok())
+        {
+            options.max_width = (width > 0).then_some(width);
+        }
+        if let Some(format) = std::env::var("GOOSE_IMAGE_FORMAT")
+            .ok()
+            .and_then(|v| OutputFormat::from_name(&v))
+        {
+            options.format = format;
+        }
+        if let Some(quality) = std::env::var("GOOSE_IMAGE_QUALITY")
+            .ok()
+            .and_then(|v| v.trim().parse::<u8>().ok())
+        {
+            options.quality = quality.clamp(1, 100);
+        }
+
+        if let Some(width) = params.get("max_width").and_then(|v| v.as_u64()) {
+            options.max_width = (width > 0).then_some(width.min(u32::MAX as u64) as u32);
+            options.width_in_points = false;
+        }
+        if params
+            .get("full_resolution")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false)
+        {
+            options.max_width = None;
+        }
+        if let Some(format) = params.get("format").and_then(|v| v.as_str()) {
+            options.format = OutputFormat::from_name(format).ok_or_else(|| {
+                ToolError::InvalidParameters(format!(
+                    "Unsupported format '{}'. Use png, jpeg or webp",
+                    format
+                ))
+            })?;
+        }
+        if let Some(quality) = params.get("quality").and_then(|v| v.as_u64()) {
+            if !(1..=100).contains(&quality) {
+                return Err(ToolError::InvalidParameters(
+                    "quality must be between 1 and 100".into(),
+                ));
+            }
+            options.quality = quality as u8;
+        }
+
+        Ok(options)
+    }
+
+    /// Treat the default maximum width as logical points on a display with the given scale
+    /// factor, so HiDPI screenshots keep the detail needed to read text. A `max_width` passed
+    /// in the call is already in pixels and is kept.
+    pub fn for_scale_factor(self, scale_factor: f32) -> Self {
+        if !self.width_in_points || !scale_factor.is_finite() || scale_factor <= 1.0 {
+            return self;
+        }
+        Self {
+            max_width: self
+                .max_width
+                .map(|width| (width as f32 * scale_factor).round() as u32),
+            ..self
+        }
+    }
+
+    /// Scale the image down to the maximum width, keeping its aspect ratio
+    pub fn resize(&self, image: DynamicImage) -> DynamicImage {
+        match self.max_width {
+            Some(max_width) if image.width() > max_width => {
+                let scale = max_width as f32 / image.width() as f32;
+                let new_height = (image.height() as f32 * scale) as u32;
+                image.resize_exact(max_width, new_height, FilterType::Lanczos3)
+            }
+            _ => image,
+        }
+    }
+
+    /// Encode the image in the configured format
+    pub fn encode(&self, image: &DynamicImage) -> Result<Vec<u8>, ToolError> {
+        let mut bytes: Vec<u8> = Vec::new();
+        let result = match self.format {
+            OutputFormat::Png => image.write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png),
+            // Neither encoder accepts every pixel layout, so convert first
+            OutputFormat::Jpeg => DynamicImage::ImageRgb8(image.to_rgb8())
+                .write_with_encoder(JpegEncoder::new_with_quality(&mut bytes, self.quality)),
+            OutputFormat::Webp => DynamicImage::ImageRgba8(image.to_rgba8())
+                .write_with_encoder(WebPEncoder::new_lossless(&mut bytes)),
+        };
+        result.map_err(|e| {
+            ToolError::ExecutionError(format!("Failed to write image buffer: {}", e))
+        })?;
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_only_the_default_width_is_scaled_for_hidpi() {
+        let default = ImageOptions::from_params(&json!({})).unwrap();
+        assert_eq!(default.for_scale_factor(2.0).max_width, Some(1536));
+        assert_eq!(default.for_scale_factor(1.0).max_width, Some(768));
+
+        let explicit = ImageOptions::from_params(&json!({"max_width": 1000})).unwrap();
+        assert_eq!(explicit.for_scale_factor(2.0).max_width, Some(1000));
+
+        let full = ImageOptions::from_params(&json!({"full_resolution": true})).unwrap();
+        assert_eq!(full.for_scale_factor(2.0).max_width, None);
+    }
+}
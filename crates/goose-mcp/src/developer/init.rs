@@ -0,0 +1,384 @@
+use std::path::Path;
+
+use indoc::indoc;
+use mcp_core::handler::ToolError;
+use rmcp::model::{Content, JsonRpcMessage, Role, Tool, ToolAnnotations};
+use rmcp::object;
+use serde_json::Value;
+use tokio::sync::mpsc;
+
+use super::approval::request_approval;
+use super::ignore_files::IgnoreMode;
+use super::DeveloperRouter;
+
+/// Patterns for files that commonly hold credentials, ignored for every stack
+const SECRET_PATTERNS: &[&str] = &[
+    "**/.env",
+    "**/.env.*",
+    "**/secrets.*",
+    "**/*.pem",
+    "**/*.key",
+    "**/*.p12",
+    "**/*.pfx",
+    "**/id_rsa*",
+    "**/id_ed25519*",
+    "**/credentials.json",
+    "**/service-account*.json",
+];
+
+/// A language ecosystem detected from its manifest file
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stack {
+    pub language: &'static str,
+    pub frameworks: Vec<&'static str>,
+    manifest: &'static str,
+    commands: &'static [(&'static str, &'static str)],
+    ignores: &'static [&'static str],
+}
+
+/// Look at the manifests in `dir` to work out which stacks the project uses
+pub fn detect_stacks(dir: &Path) -> Vec<Stack> {
+    let mut stacks = Vec::new();
+    let read = |name: &str| std::fs::read_to_string(dir.join(name)).unwrap_or_default();
+
+    if dir.join("Cargo.toml").is_file() {
+        let manifest = read("Cargo.toml");
+        stacks.push(Stack {
+            language: "Rust",
+            frameworks: detect_frameworks(
+                &manifest,
+                &[
+                    ("axum", "Axum"),
+                    ("actix-web", "Actix Web"),
+                    ("tokio", "Tokio"),
+                ],
+            ),
+            manifest: "Cargo.toml",
+            commands: &[
+                ("Build", "cargo build"),
+                ("Test", "cargo test"),
+                ("Lint", "cargo clippy --all-targets -- -D warnings"),
+                ("Format", "cargo fmt"),
+            ],
+            ignores: &["target/"],
+        });
+    }
+
+    if dir.join("package.json").is_file() {
+        let manifest = read("package.json");
+        let language = if dir.join("tsconfig.json").is_file() {
+            "TypeScript"
+        } else {
+            "JavaScript"
+        };
+        stacks.push(Stack {
+            language,
+            frameworks: detect_frameworks(
+                &manifest,
+                &[
+                    ("\"next\"", "Next.js"),
+                    ("\"react\"", "React"),
+                    ("\"vue\"", "Vue"),
+                    ("\"svelte\"", "Svelte"),
+                    ("\"express\"", "Express"),
+                    ("\"electron\"", "Electron"),
+                ],
+            ),
+            manifest: "package.json",
+            commands: &[
+                ("Install", "npm install"),
+                ("Test", "npm test"),
+                ("Lint", "npm run lint"),
+            ],
+            ignores: &["node_modules/", "dist/", "build/", ".next/", "coverage/"],
+        });
+    }
+
+    let python_manifest = ["pyproject.toml", "requirements.txt", "setup.py"]
+        .into_iter()
+        .find(|name| dir.join(name).is_file());
+    if let Some(manifest_name) = python_manifest {
+        let manifest = read(manifest_name);
+        stacks.push(Stack {
+            language: "Python",
+            frameworks: detect_frameworks(
+                &manifest.to_lowercase(),
+                &[
+                    ("django", "Django"),
+                    ("flask", "Flask"),
+                    ("fastapi", "FastAPI"),
+                    ("pytest", "pytest"),
+                ],
+            ),
+            manifest: manifest_name,
+            commands: &[("Test", "pytest"), ("Lint", "ruff check .")],
+            ignores: &[
+                "__pycache__/",
+                "*.pyc",
+                ".venv/",
+                "venv/",
+                ".pytest_cache/",
+                "*.egg-info/",
+            ],
+        });
+    }
+
+    if dir.join("go.mod").is_file() {
+        let manifest = read("go.mod");
+        stacks.push(Stack {
+            language: "Go",
+            frameworks: detect_frameworks(
+                &manifest,
+                &[("gin-gonic/gin", "Gin"), ("labstack/echo", "Echo")],
+            ),
+            manifest: "go.mod",
+            commands: &[
+                ("Build", "go build ./..."),
+                ("Test", "go test ./..."),
+                ("Lint", "go vet ./..."),
+            ],
+            ignores: &["vendor/", "bin/"],
+        });
+    }
+
+    let jvm_manifest = ["pom.xml", "build.gradle", "build.gradle.kts"]
+        .into_iter()
+        .find(|name| dir.join(name).is_file());
+    if let Some(manifest_name) = jvm_manifest {
+        let manifest = read(manifest_name);
+        let language = if manifest_name.ends_with(".kts") || manifest.contains("kotlin") {
+            "Kotlin"
+        } else {
+            "Java"
+        };
+        let commands: &[(&str, &str)] = if manifest_name == "pom.xml" {
+            &[("Build", "mvn package"), ("Test", "mvn test")]
+        } else {
+            &[("Build", "./gradlew build"), ("Test", "./gradlew test")]
+        };
+        stacks.push(Stack {
+            language,
+            frameworks: detect_frameworks(&manifest, &[("spring-boot", "Spring Boot")]),
+            manifest: manifest_name,
+            commands,
+            ignores: &["target/", "build/", ".gradle/", "*.class"],
+        });
+    }
+
+    if dir.join("Gemfile").is_file() {
+        let manifest = read("Gemfile");
+        stacks.push(Stack {
+            language: "Ruby",
+            frameworks: detect_frameworks(&manifest, &[("rails", "Rails")]),
+            manifest: "Gemfile",
+            commands: &[
+                ("Install", "bundle install"),
+                ("Test", "bundle exec rake test"),
+            ],
+            ignores: &["vendor/bundle/", "log/", "tmp/"],
+        });
+    }
+
+    stacks
+}
+
+fn detect_frameworks(manifest: &str, candidates: &[(&str, &'static str)]) -> Vec<&'static str> {
+    candidates
+        .iter()
+        .filter(|(needle, _)| manifest.contains(needle))
+        .map(|(_, name)| *name)
+        .collect()
+}
+
+/// Starter `.goosehints` describing the detected stacks and their canonical commands
+pub fn render_goosehints(project_name: &str, stacks: &[Stack]) -> String {
+    let mut hints = format!("# {}\n\n", project_name);
+
+    if stacks.is_empty() {
+        hints.push_str("No known build manifest was detected. Describe the project layout, how to build and test it, and any conventions goose should follow here.\n");
+        return hints;
+    }
+
+    hints.push_str("## Stack\n");
+    for stack in stacks {
+        if stack.frameworks.is_empty() {
+            hints.push_str(&format!("- {} ({})\n", stack.language, stack.manifest));
+        } else {
+            hints.push_str(&format!(
+                "- {} ({}) using {}\n",
+                stack.language,
+                stack.manifest,
+                stack.frameworks.join(", ")
+            ));
+        }
+    }
+
+    hints.push_str("\n## Commands\n");
+    for stack in stacks {
+        for (label, command) in stack.commands {
+            hints.push_str(&format!(
+                "- {} ({}): `{}`\n",
+                label, stack.language, command
+            ));
+        }
+    }
+
+    hints.push_str("\n## Conventions\n");
+    hints.push_str(
+        "- Run the test command after making changes and fix any failures before finishing.\n",
+    );
+    hints.push_str("- Follow the existing code style and structure of neighbouring files.\n");
+    hints
+}
+
+/// Starter `.gooseignore` covering secrets and the detected stacks' build output.
+///
+/// In the default replace mode a `.gooseignore` takes the place of the `.gitignore`
+/// fallback, so any `.gitignore` entries passed in are carried over as well.
+pub fn render_gooseignore(stacks: &[Stack], gitignore: Option<&str>) -> String {
+    let mut ignore = String::from("# Files that may contain credentials\n");
+    for pattern in SECRET_PATTERNS {
+        ignore.push_str(pattern);
+        ignore.push('\n');
+    }
+
+    let build_patterns: Vec<&str> = stacks
+        .iter()
+        .flat_map(|stack| stack.ignores.iter().copied())
+        .fold(Vec::new(), |mut patterns, pattern| {
+            if !patterns.contains(&pattern) {
+                patterns.push(pattern);
+            }
+            patterns
+        });
+    if !build_patterns.is_empty() {
+        ignore.push_str("\n# Build output and dependencies\n");
+        for pattern in build_patterns {
+            ignore.push_str(pattern);
+            ignore.push('\n');
+        }
+    }
+
+    if let Some(gitignore) = gitignore.filter(|g| !g.trim().is_empty()) {
+        ignore.push_str("\n# Carried over from .gitignore\n");
+        ignore.push_str(gitignore.trim_end());
+        ignore.push('\n');
+    }
+
+    ignore
+}
+
+/// The definition of the `init_project` tool
+pub fn tool() -> Tool {
+    Tool::new(
+        "init_project",
+        indoc! {r#"
+            Set up goose project files for a directory in one call.
+
+            Detects the project's stack (language, framework and build tool) from its manifests
+            and writes a starter `.goosehints` with the stack and its build/test commands, and a
+            starter `.gooseignore` covering credential files, build output and the entries of an
+            existing `.gitignore`. Existing files are left untouched unless `overwrite` is true.
+            The new files take effect the next time the developer extension starts.
+        "#},
+        object!({
+            "type": "object",
+            "required": [],
+            "properties": {
+                "path": {"type": "string", "description": "Absolute path to the project directory (defaults to the current directory)"},
+                "overwrite": {"type": "boolean", "default": false, "description": "Replace existing .goosehints and .gooseignore files"}
+            }
+        }),
+    )
+    .annotate(ToolAnnotations {
+        title: Some("Initialize goose project files".to_string()),
+        read_only_hint: Some(false),
+        destructive_hint: Some(true),
+        idempotent_hint: Some(true),
+        open_world_hint: Some(false),
+    })
+}
+
+impl DeveloperRouter {
+    pub(super) async fn init_project(
+        &self,
+        params: Value,
+        notifier: &mpsc::Sender<JsonRpcMessage>,
+    ) -> Result<Vec<Content>, ToolError> {
+        let dir = match params.get("path").and_then(|v| v.as_str()) {
+            Some(path_str) => self.resolve_path(path_str)?,
+            None => self.cwd(),
+        };
+        let overwrite = params
+            .get("overwrite")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
+        if !dir.is_dir() {
+            return Err(ToolError::InvalidParameters(format!(
+                "The path '{}' is not a directory",
+                dir.display()
+            )));
+        }
+
+        let stacks = detect_stacks(&dir);
+        let project_name = dir
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| "Project".to_string());
+        // In union mode the .gitignore is applied anyway, so there is no need to copy it
+        let gitignore = match IgnoreMode::from_env() {
+            IgnoreMode::Replace => std::fs::read_to_string(dir.join(".gitignore")).ok(),
+            IgnoreMode::Union => None,
+        };
+
+        let files = [
+            (".goosehints", render_goosehints(&project_name, &stacks)),
+            (
+                ".gooseignore",
+                render_gooseignore(&stacks, gitignore.as_deref()),
+            ),
+        ];
+
+        let mut summary = Vec::new();
+        for (name, content) in files {
+            let path = dir.join(name);
+            if path.exists() && !overwrite {
+                summary.push(format!("Skipped {} (already exists)", path.display()));
+                continue;
+            }
+            if let Some(action) = self
+                .approval_policy
+                .write_action(&self.catalog, &path, "init_project")
+                .await
+            {
+                request_approval(notifier, &action).await?;
+            }
+            std::fs::write(&path, content).map_err(|e| {
+                ToolError::ExecutionError(format!("Failed to write {}: {}", path.display(), e))
+            })?;
+            summary.push(format!("Wrote {}", path.display()));
+        }
+
+        let detected = if stacks.is_empty() {
+            "No known stack detected".to_string()
+        } else {
+            format!(
+                "Detected stack: {}",
+                stacks
+                    .iter()
+                    .map(|stack| stack.language)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        };
+        let output = format!("{}\n{}", detected, summary.join("\n"));
+
+        Ok(vec![
+            Content::text(output.clone()).with_audience(vec![Role::Assistant]),
+            Content::text(output)
+                .with_audience(vec![Role::User])
+                .with_priority(0.0),
+        ])
+    }
+}
@@ -0,0 +1,467 @@
+use std::path::{Path, PathBuf};
+
+use etcetera::{choose_app_strategy, AppStrategy};
+use indoc::indoc;
+use mcp_core::handler::ToolError;
+use rmcp::model::{Content, Tool, ToolAnnotations};
+use rmcp::object;
+use serde_json::Value;
+
+use super::DeveloperRouter;
+
+/// Default number of tokens the hints files may take up in the instructions
+const DEFAULT_HINTS_TOKEN_BUDGET: usize = 8000;
+
+/// The URI the composed instructions are listed under in the extension's resources. A
+/// section can be read on its own by adding its anchor, e.g. `#project_hints/testing`.
+pub const INSTRUCTIONS_URI: &str = "goose://developer/instructions";
+
+/// One layer of the developer extension's instructions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InstructionLayer {
+    /// Built-in guidance about the extension, operating system and working directory
+    Base,
+    /// Hints from the user's goose config directory that apply to every project
+    GlobalHints,
+    /// Hints from the current project directory
+    ProjectHints,
+}
+
+impl InstructionLayer {
+    pub const ALL: [InstructionLayer; 3] = [
+        InstructionLayer::Base,
+        InstructionLayer::GlobalHints,
+        InstructionLayer::ProjectHints,
+    ];
+
+    /// The tag used for this layer in the rendered instructions
+    pub fn name(&self) -> &'static str {
+        match self {
+            InstructionLayer::Base => "base",
+            InstructionLayer::GlobalHints => "global_hints",
+            InstructionLayer::ProjectHints => "project_hints",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|layer| layer.name() == name)
+    }
+
+    fn title(&self) -> &'static str {
+        match self {
+            InstructionLayer::Base => "Developer extension",
+            InstructionLayer::GlobalHints => "Global hints",
+            InstructionLayer::ProjectHints => "Project hints",
+        }
+    }
+}
+
+/// A part of the instructions that can be fetched on its own: a whole layer, anchored by the
+/// layer's name, or a markdown heading within one, anchored by the layer's name and the
+/// heading, e.g. `project_hints/code-style`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Section {
+    pub anchor: String,
+    pub title: String,
+    pub content: String,
+}
+
+/// A markdown heading in a layer
+struct Heading {
+    anchor: String,
+    title: String,
+    level: usize,
+    line: usize,
+}
+
+/// The composed instructions, kept as separate layers so clients can include or
+/// collapse them individually
+#[derive(Debug, Clone, Default)]
+pub struct Instructions {
+    base: String,
+    global_hints: Option<String>,
+    project_hints: Option<String>,
+}
+
+impl Instructions {
+    /// Combine the base instructions with the hints files found in the global config
+    /// directory and in `cwd`. The hints file names come from `CONTEXT_FILE_NAMES`.
+    pub fn load(base: String, cwd: &Path) -> Self {
+        let mut global_hints_contents = Vec::new();
+        let mut local_hints_contents = Vec::new();
+
+        for hints_filename in hints_filenames() {
+            let global_hints_path = global_hints_path(&hints_filename);
+
+            if let Some(parent) = global_hints_path.parent() {
+                let _ = std::fs::create_dir_all(parent);
+            }
+
+            if global_hints_path.is_file() {
+                if let Ok(content) = std::fs::read_to_string(&global_hints_path) {
+                    global_hints_contents.push(content);
+                }
+            }
+
+            let local_hints_path = cwd.join(&hints_filename);
+            if local_hints_path.is_file() {
+                if let Ok(content) = std::fs::read_to_string(&local_hints_path) {
+                    local_hints_contents.push(content);
+                }
+            }
+        }
+
+        let mut global_hints = global_hints_contents.join("\n");
+        let mut project_hints = local_hints_contents.join("\n");
+        if let Some(budget) = hints_token_budget() {
+            // Project hints are more specific, so global hints give way first
+            let project_tokens = estimate_tokens(&project_hints);
+            global_hints =
+                fit_to_budget(&global_hints, budget.saturating_sub(project_tokens), budget);
+            project_hints = fit_to_budget(&project_hints, budget, budget);
+        }
+
+        let global_hints = (!global_hints.is_empty()).then(|| {
+            format!(
+                "### Global Hints\nThe developer extension includes some global hints that apply to all projects & directories.\n{}",
+                global_hints
+            )
+        });
+
+        Self {
+            base,
+            global_hints,
+            project_hints: project_hints_section(&project_hints),
+        }
+    }
+
+    /// The base instructions with the given project hints in place of any hints files
+    pub fn with_hints(base: String, hints: &str) -> Self {
+        Self {
+            base,
+            global_hints: None,
+            project_hints: project_hints_section(hints),
+        }
+    }
+
+    /// Load the hints again from disk, keeping the same base instructions
+    pub fn reload(&self, cwd: &Path) -> Self {
+        Self::load(self.base.clone(), cwd)
+    }
+
+    /// The content of a single layer, or None if that layer is empty
+    pub fn layer(&self, layer: InstructionLayer) -> Option<&str> {
+        match layer {
+            InstructionLayer::Base => Some(self.base.as_str()),
+            InstructionLayer::GlobalHints => self.global_hints.as_deref(),
+            InstructionLayer::ProjectHints => self.project_hints.as_deref(),
+        }
+    }
+
+    /// All non-empty layers, each wrapped in a `<section layer="...">` tag
+    pub fn render(&self) -> String {
+        InstructionLayer::ALL
+            .into_iter()
+            .filter_map(|layer| {
+                self.layer(layer).map(|content| {
+                    format!(
+                        "<section layer=\"{}\">\n{}\n</section>",
+                        layer.name(),
+                        content.trim_end()
+                    )
+                })
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+
+    /// Every layer and the headings within them, in the order they appear
+    pub fn sections(&self) -> Vec<Section> {
+        let mut sections = Vec::new();
+        for layer in InstructionLayer::ALL {
+            let Some(content) = self.layer(layer) else {
+                continue;
+            };
+            sections.push(Section {
+                anchor: layer.name().to_string(),
+                title: layer.title().to_string(),
+                content: content.trim_end().to_string(),
+            });
+
+            let lines: Vec<&str> = content.lines().collect();
+            let headings = headings(layer, &lines);
+            for (i, heading) in headings.iter().enumerate() {
+                // A section runs until the next heading at the same or a higher level
+                let end = headings[i + 1..]
+                    .iter()
+                    .find(|next| next.level <= heading.level)
+                    .map_or(lines.len(), |next| next.line);
+                sections.push(Section {
+                    anchor: heading.anchor.clone(),
+                    title: heading.title.clone(),
+                    content: lines[heading.line..end].join("\n").trim_end().to_string(),
+                });
+            }
+        }
+        sections
+    }
+
+    pub fn section(&self, anchor: &str) -> Option<Section> {
+        self.sections()
+            .into_iter()
+            .find(|section| section.anchor == anchor)
+    }
+
+    /// The instructions as markdown, with an `<a id="...">` anchor before each section
+    pub fn render_anchored(&self) -> String {
+        InstructionLayer::ALL
+            .into_iter()
+            .filter_map(|layer| {
+                let content = self.layer(layer)?;
+                let lines: Vec<&str> = content.lines().collect();
+                let mut headings = headings(layer, &lines).into_iter().peekable();
+                let mut rendered = format!("<a id=\"{}\"></a>\n", layer.name());
+                for (number, line) in lines.iter().enumerate() {
+                    if let Some(heading) = headings.next_if(|heading| heading.line == number) {
+                        rendered.push_str(&format!("<a id=\"{}\"></a>\n", heading.anchor));
+                    }
+                    rendered.push_str(line);
+                    rendered.push('\n');
+                }
+                Some(rendered.trim_end().to_string())
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+}
+
+/// The markdown headings in a layer's lines, outside of code blocks. The heading the hints
+/// layers open with is left out, since it is the layer's own title.
+fn headings(layer: InstructionLayer, lines: &[&str]) -> Vec<Heading> {
+    let mut headings: Vec<Heading> = Vec::new();
+    let mut in_code_block = false;
+    for (number, line) in lines.iter().enumerate() {
+        if line.trim_start().starts_with("```") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        let level = line.chars().take_while(|c| *c == '#').count();
+        let title = line[level..].trim();
+        if in_code_block || level == 0 || !line[level..].starts_with(' ') || title.is_empty() {
+            continue;
+        }
+        if number == 0 && layer != InstructionLayer::Base {
+            continue;
+        }
+
+        // Repeated headings are told apart by a number, as on GitHub
+        let slug = slugify(title);
+        let mut anchor = format!("{}/{}", layer.name(), slug);
+        let mut repeat = 1;
+        while headings.iter().any(|heading| heading.anchor == anchor) {
+            repeat += 1;
+            anchor = format!("{}/{}-{}", layer.name(), slug, repeat);
+        }
+        headings.push(Heading {
+            anchor,
+            title: title.to_string(),
+            level,
+            line: number,
+        });
+    }
+    headings
+}
+
+/// `Code Style (Rust)` as `code-style-rust`
+fn slugify(title: &str) -> String {
+    title
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+fn project_hints_section(hints: &str) -> Option<String> {
+    (!hints.is_empty()).then(|| {
+        format!(
+            "### Project Hints\nThe developer extension includes some hints for working on the project in this directory.\n{}",
+            hints
+        )
+    })
+}
+
+/// The hints file names, from `CONTEXT_FILE_NAMES`
+fn hints_filenames() -> Vec<String> {
+    std::env::var("CONTEXT_FILE_NAMES")
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_else(|| vec![".goosehints".to_string()])
+}
+
+fn global_hints_path(hints_filename: &str) -> PathBuf {
+    // choose_app_strategy().config_dir()
+    // - macOS/Linux: ~/.config/goose/
+    // - Windows:     ~\AppData\Roaming\Block\goose\config\
+    // keep previous behavior of expanding ~/.config in case this fails
+    choose_app_strategy(crate::APP_STRATEGY.clone())
+        .map(|strategy| strategy.in_config_dir(hints_filename))
+        .unwrap_or_else(|_| {
+            let path_str = format!("~/.config/goose/{}", hints_filename);
+            PathBuf::from(shellexpand::tilde(&path_str).to_string())
+        })
+}
+
+/// Every global and project hints file the instructions could be loaded from
+pub fn hints_paths(cwd: &Path) -> Vec<PathBuf> {
+    hints_filenames()
+        .iter()
+        .flat_map(|name| [global_hints_path(name), cwd.join(name)])
+        .collect()
+}
+
+/// Read `GOOSE_HINTS_TOKEN_BUDGET`, where 0 turns the budget off
+fn hints_token_budget() -> Option<usize> {
+    let budget = std::env::var("GOOSE_HINTS_TOKEN_BUDGET")
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .unwrap_or(DEFAULT_HINTS_TOKEN_BUDGET);
+    (budget > 0).then_some(budget)
+}
+
+/// Rough token count, using the common estimate of four bytes per token
+fn estimate_tokens(text: &str) -> usize {
+    text.len().div_ceil(4)
+}
+
+/// Trim hints to at most `max_tokens`, dropping trailing markdown sections first since
+/// hints files tend to open with the most important guidance. A notice is appended so
+/// the model knows the hints are incomplete.
+fn fit_to_budget(hints: &str, max_tokens: usize, budget: usize) -> String {
+    if estimate_tokens(hints) <= max_tokens {
+        return hints.to_string();
+    }
+
+    let mut sections: Vec<String> = Vec::new();
+    for line in hints.lines() {
+        match sections.last_mut() {
+            Some(section) if !line.starts_with('#') => {
+                section.push('\n');
+                section.push_str(line);
+            }
+            _ => sections.push(line.to_string()),
+        }
+    }
+
+    let notice = |omitted: usize| {
+        format!(
+            "[{} of {} hints sections omitted to stay within the {}-token hints budget; \
+             set GOOSE_HINTS_TOKEN_BUDGET to change it]",
+            omitted,
+            sections.len(),
+            budget
+        )
+    };
+    let available = max_tokens.saturating_sub(estimate_tokens(&notice(sections.len())) + 1);
+
+    let mut kept = String::new();
+    let mut kept_sections = 0;
+    for section in &sections {
+        let candidate = if kept.is_empty() {
+            section.clone()
+        } else {
+            format!("{}\n{}", kept, section)
+        };
+        if estimate_tokens(&candidate) > available {
+            break;
+        }
+        kept = candidate;
+        kept_sections += 1;
+    }
+
+    // Not even the first section fits, so cut it at the last line that does
+    if kept_sections == 0 {
+        if let Some(first) = sections.first() {
+            for line in first.lines() {
+                if estimate_tokens(&kept) + estimate_tokens(line) + 1 > available {
+                    break;
+                }
+                kept.push_str(line);
+                kept.push('\n');
+            }
+        }
+    }
+
+    tracing::warn!(
+        "Hints exceed the {}-token budget, omitting {} of {} sections",
+        budget,
+        sections.len() - kept_sections,
+        sections.len()
+    );
+
+    let notice = notice(sections.len() - kept_sections);
+    if kept.trim().is_empty() {
+        notice
+    } else {
+        format!("{}\n{}", kept.trim_end(), notice)
+    }
+}
+
+/// The definition of the `instructions` tool
+pub fn tool() -> Tool {
+    Tool::new(
+        "instructions",
+        indoc! {r#"
+            Fetch a section of the developer extension's instructions.
+
+            The instructions combine the built-in guidance with the global and project hints
+            files. Without `section`, lists the sections: each layer (`base`, `global_hints`,
+            `project_hints`) and the markdown headings within them, such as
+            `project_hints/testing`. With `section`, returns only that part, e.g. to re-read
+            the project's testing guidance after the hints files changed.
+        "#},
+        object!({
+            "type": "object",
+            "required": [],
+            "properties": {
+                "section": {
+                    "type": "string",
+                    "description": "The anchor of the section to fetch, as listed without this parameter"
+                }
+            }
+        }),
+    )
+    .annotate(ToolAnnotations {
+        title: Some("Read the instructions".to_string()),
+        read_only_hint: Some(true),
+        destructive_hint: Some(false),
+        idempotent_hint: Some(true),
+        open_world_hint: Some(false),
+    })
+}
+
+impl DeveloperRouter {
+    pub(super) fn instructions_section(&self, params: Value) -> Result<Vec<Content>, ToolError> {
+        let instructions = self.instructions.read().unwrap();
+        let text = match params.get("section").and_then(Value::as_str) {
+            Some(anchor) => {
+                instructions
+                    .section(anchor)
+                    .ok_or_else(|| {
+                        ToolError::InvalidParameters(format!(
+                        "No section '{}' in the instructions. Call without `section` to list them.",
+                        anchor
+                    ))
+                    })?
+                    .content
+            }
+            None => instructions
+                .sections()
+                .iter()
+                .map(|section| format!("{}: {}", section.anchor, section.title))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        };
+        Ok(vec![Content::text(text)])
+    }
+}
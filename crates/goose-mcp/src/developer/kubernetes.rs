@@ -0,0 +1,553 @@
+use std::time::Duration;
+
+use indoc::indoc;
+use mcp_core::handler::ToolError;
+use mcp_server::structured::structured_content;
+use rmcp::model::{Content, JsonRpcMessage, Tool, ToolAnnotations};
+use rmcp::object;
+use serde::Serialize;
+use serde_json::{json, Value};
+use tokio::process::Command;
+use tokio::sync::mpsc;
+
+use super::approval::{request_approval, restart_deployment_action};
+use super::editor_models::load_user_setting;
+use super::shell::Spawner;
+use super::DeveloperRouter;
+
+/// Config key that turns on the commands that change the cluster, off unless set to true.
+/// Only read from the environment and the global config, so a project can't turn it on.
+pub const ALLOW_WRITES_KEY: &str = "GOOSE_KUBERNETES_ALLOW_WRITES";
+
+/// The subcommands of the kubernetes tool that only read
+pub const READ_COMMANDS: &[&str] = &["get", "describe", "logs"];
+
+const DEFAULT_TAIL_LINES: u64 = 200;
+const MAX_TAIL_LINES: u64 = 5000;
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+/// The kinds of resource the tool works with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Kind {
+    Pod,
+    Deployment,
+    Service,
+}
+
+impl Kind {
+    /// The kind for any of kubectl's names for it, e.g. `po`, `deploy` or `svc`
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "pod" | "pods" | "po" => Some(Self::Pod),
+            "deployment" | "deployments" | "deploy" => Some(Self::Deployment),
+            "service" | "services" | "svc" => Some(Self::Service),
+            _ => None,
+        }
+    }
+
+    fn resource(self) -> &'static str {
+        match self {
+            Self::Pod => "pods",
+            Self::Deployment => "deployments",
+            Self::Service => "services",
+        }
+    }
+
+    fn singular(self) -> &'static str {
+        match self {
+            Self::Pod => "pod",
+            Self::Deployment => "deployment",
+            Self::Service => "service",
+        }
+    }
+}
+
+/// What `get` shows of a resource, the columns `kubectl get` would plus what explains a
+/// failing pod
+#[derive(Debug, Serialize, PartialEq)]
+pub struct Resource {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+    #[serde(flatten)]
+    pub details: Value,
+    pub created: Option<String>,
+}
+
+/// Options for `logs`
+#[derive(Debug, Default)]
+pub struct LogOptions<'a> {
+    pub container: Option<&'a str>,
+    pub tail_lines: Option<u64>,
+    pub since: Option<&'a str>,
+    pub previous: bool,
+}
+
+/// The cluster of the current kube context, reached through `kubectl` with the user's
+/// kubeconfig. The context is pinned when the client is created, so a context switch during
+/// a call can't redirect it.
+#[derive(Debug)]
+pub struct Kubectl {
+    context: String,
+    namespace: Option<String>,
+    spawner: Spawner,
+}
+
+impl Kubectl {
+    pub async fn current(spawner: Spawner, namespace: Option<&str>) -> Result<Self, ToolError> {
+        if let Some(namespace) = namespace {
+            check_name("namespace", namespace)?;
+        }
+        let context = run(&spawner, "config", &["config", "current-context"])
+            .await?
+            .trim()
+            .to_string();
+        if context.is_empty() {
+            return Err(ToolError::ExecutionError(
+                "kubectl has no current context. Ask the user to select one.".into(),
+            ));
+        }
+        Ok(Self {
+            context,
+            namespace: namespace.map(str::to_string),
+            spawner,
+        })
+    }
+
+    pub fn context(&self) -> &str {
+        &self.context
+    }
+
+    pub fn namespace(&self) -> Option<&str> {
+        self.namespace.as_deref()
+    }
+
+    /// Whether the user turned on the commands that change the cluster
+    pub fn writes_allowed() -> bool {
+        load_user_setting(ALLOW_WRITES_KEY)
+            .is_some_and(|value| matches!(value.trim(), "true" | "1" | "yes"))
+    }
+
+    /// The resources of `kind`, or just `name`, optionally only those matching the label
+    /// `selector` or in every namespace
+    pub async fn get(
+        &self,
+        kind: Kind,
+        name: Option<&str>,
+        selector: Option<&str>,
+        all_namespaces: bool,
+    ) -> Result<Vec<Resource>, ToolError> {
+        let mut args = vec!["get", kind.resource()];
+        if let Some(name) = name {
+            check_name("name", name)?;
+            args.push(name);
+        }
+        if let Some(selector) = selector {
+            check_selector(selector)?;
+            args.extend(["--selector", selector]);
+        }
+        if all_namespaces && name.is_none() {
+            args.push("--all-namespaces");
+        }
+        args.extend(["--output", "json"]);
+
+        let output = self.kubectl(&args).await?;
+        let json: Value = serde_json::from_str(&output).map_err(|e| {
+            ToolError::ExecutionError(format!("Unexpected output from kubectl: {}", e))
+        })?;
+        // A single resource comes back as it is, several as a list
+        let items = match json.get("items").and_then(Value::as_array) {
+            Some(items) => items.clone(),
+            None => vec![json],
+        };
+        Ok(items
+            .iter()
+            .map(|item| summarize(kind, item, all_namespaces))
+            .collect())
+    }
+
+    /// The resource as `kubectl describe` shows it, with its recent events
+    pub async fn describe(&self, kind: Kind, name: &str) -> Result<String, ToolError> {
+        check_name("name", name)?;
+        self.kubectl(&["describe", kind.resource(), name]).await
+    }
+
+    /// The logs of a pod, or of a pod of a deployment or service
+    pub async fn logs(
+        &self,
+        kind: Kind,
+        name: &str,
+        options: LogOptions<'_>,
+    ) -> Result<String, ToolError> {
+        check_name("name", name)?;
+        let target = format!("{}/{}", kind.singular(), name);
+        let tail = format!(
+            "--tail={}",
+            options
+                .tail_lines
+                .unwrap_or(DEFAULT_TAIL_LINES)
+                .clamp(1, MAX_TAIL_LINES)
+        );
+        let mut args = vec!["logs".to_string(), target, tail, "--timestamps".to_string()];
+        if let Some(container) = options.container {
+            check_name("container", container)?;
+            args.push(format!("--container={}", container));
+        }
+        if let Some(since) = options.since {
+            if !since
+                .chars()
+                .all(|c| c.is_ascii_digit() || matches!(c, 's' | 'm' | 'h'))
+            {
+                return Err(ToolError::InvalidParameters(format!(
+                    "Invalid 'since' '{}', expected a duration like 10m or 1h",
+                    since
+                )));
+            }
+            args.push(format!("--since={}", since));
+        }
+        if options.previous {
+            args.push("--previous".to_string());
+        }
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        self.kubectl(&args).await
+    }
+
+    /// Restart the pods of a deployment one by one, as `kubectl rollout restart` does
+    pub async fn restart(&self, name: &str) -> Result<String, ToolError> {
+        check_name("name", name)?;
+        let target = format!("deployment/{}", name);
+        self.kubectl(&["rollout", "restart", &target]).await
+    }
+
+    async fn kubectl(&self, args: &[&str]) -> Result<String, ToolError> {
+        let mut all = vec!["--context", self.context.as_str()];
+        if let Some(namespace) = &self.namespace {
+            all.extend(["--namespace", namespace.as_str()]);
+        }
+        all.extend(args);
+        run(
+            &self.spawner,
+            args.first().copied().unwrap_or_default(),
+            &all,
+        )
+        .await
+    }
+}
+
+/// Names and namespaces are DNS labels or subdomains, which also keeps them from being read
+/// as flags
+fn check_name(what: &str, name: &str) -> Result<(), ToolError> {
+    let valid = !name.is_empty()
+        && name.len() <= 253
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '-' | '.'))
+        && name.starts_with(|c: char| c.is_ascii_alphanumeric());
+    if valid {
+        Ok(())
+    } else {
+        Err(ToolError::InvalidParameters(format!(
+            "Invalid {} '{}'",
+            what, name
+        )))
+    }
+}
+
+fn check_selector(selector: &str) -> Result<(), ToolError> {
+    let valid = !selector.starts_with('-')
+        && selector.chars().all(|c| {
+            c.is_ascii_alphanumeric()
+                || matches!(c, '-' | '.' | '_' | '/' | '=' | '!' | ',' | ' ' | '(' | ')')
+        });
+    if valid {
+        Ok(())
+    } else {
+        Err(ToolError::InvalidParameters(format!(
+            "Invalid label selector '{}'",
+            selector
+        )))
+    }
+}
+
+/// The fields of a resource worth showing, from its JSON
+pub fn summarize(kind: Kind, item: &Value, with_namespace: bool) -> Resource {
+    let text = |pointer: &str| item.pointer(pointer).and_then(Value::as_str);
+    let number = |pointer: &str| item.pointer(pointer).and_then(Value::as_u64).unwrap_or(0);
+    let details = match kind {
+        Kind::Pod => {
+            let statuses = item
+                .pointer("/status/containerStatuses")
+                .and_then(Value::as_array)
+                .cloned()
+                .unwrap_or_default();
+            let ready = statuses
+                .iter()
+                .filter(|status| status.get("ready").and_then(Value::as_bool) == Some(true))
+                .count();
+            let restarts: u64 = statuses
+                .iter()
+                .filter_map(|status| status.get("restartCount").and_then(Value::as_u64))
+                .sum();
+            // Why containers aren't running, e.g. CrashLoopBackOff or ImagePullBackOff
+            let problems: Vec<String> = statuses
+                .iter()
+                .filter_map(|status| {
+                    let container = status.get("name").and_then(Value::as_str)?;
+                    let state = status.get("state")?;
+                    let reason = state
+                        .pointer("/waiting/reason")
+                        .or_else(|| state.pointer("/terminated/reason"))
+                        .and_then(Value::as_str)?;
+                    Some(format!("{}: {}", container, reason))
+                })
+                .collect();
+            serde_json::json!({
+                "status": text("/status/phase"),
+                "ready": format!("{}/{}", ready, statuses.len()),
+                "restarts": restarts,
+                "node": text("/spec/nodeName"),
+                "problems": problems,
+            })
+        }
+        Kind::Deployment => {
+            let images: Vec<&str> = item
+                .pointer("/spec/template/spec/containers")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(|container| container.get("image").and_then(Value::as_str))
+                .collect();
+            serde_json::json!({
+                "ready": format!("{}/{}", number("/status/readyReplicas"), number("/spec/replicas")),
+                "up_to_date": number("/status/updatedReplicas"),
+                "available": number("/status/availableReplicas"),
+                "images": images,
+            })
+        }
+        Kind::Service => {
+            let ports: Vec<String> = item
+                .pointer("/spec/ports")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .map(|port| {
+                    let target = port.get("targetPort").map(|target| match target {
+                        Value::String(name) => name.clone(),
+                        target => target.to_string(),
+                    });
+                    format!(
+                        "{}{}/{}",
+                        port.get("port").and_then(Value::as_u64).unwrap_or(0),
+                        target.map(|t| format!("->{}", t)).unwrap_or_default(),
+                        port.get("protocol")
+                            .and_then(Value::as_str)
+                            .unwrap_or("TCP")
+                    )
+                })
+                .collect();
+            let external: Vec<&str> = item
+                .pointer("/status/loadBalancer/ingress")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(|ingress| {
+                    ingress
+                        .get("ip")
+                        .or_else(|| ingress.get("hostname"))
+                        .and_then(Value::as_str)
+                })
+                .collect();
+            serde_json::json!({
+                "type": text("/spec/type"),
+                "cluster_ip": text("/spec/clusterIP"),
+                "external": external,
+                "ports": ports,
+                "selector": item.pointer("/spec/selector"),
+            })
+        }
+    };
+    Resource {
+        name: text("/metadata/name").unwrap_or_default().to_string(),
+        namespace: with_namespace
+            .then(|| text("/metadata/namespace").map(str::to_string))
+            .flatten(),
+        details,
+        created: text("/metadata/creationTimestamp").map(str::to_string),
+    }
+}
+
+async fn run(spawner: &Spawner, command: &str, args: &[&str]) -> Result<String, ToolError> {
+    let mut kubectl = Command::new("kubectl");
+    kubectl.args(args);
+    let output = tokio::time::timeout(TIMEOUT, spawner.output(&mut kubectl))
+        .await
+        .map_err(|_| {
+            ToolError::ExecutionError(format!(
+                "kubectl took longer than {}s, is the cluster reachable?",
+                TIMEOUT.as_secs()
+            ))
+        })?
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => ToolError::ExecutionError(
+                "kubectl is not installed. Install it and configure a context to use this tool."
+                    .into(),
+            ),
+            _ => ToolError::ExecutionError(format!("Failed to run kubectl: {}", e)),
+        })?;
+    if !output.status.success() {
+        return Err(ToolError::ExecutionError(format!(
+            "kubectl {} failed: {}",
+            command,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// The definition of the `kubernetes` tool
+pub fn tool() -> Tool {
+    Tool::new(
+        "kubernetes",
+        indoc! {r#"
+            Inspect pods, deployments and services in the cluster of the current kube context,
+            through `kubectl` and the user's kubeconfig, e.g. to debug a deployed service.
+
+            - `get`: list the resources of `kind`, or just `name`, optionally matching the label
+              `selector` or in every namespace. Pods include their restarts and why containers
+              aren't running.
+            - `describe`: the resource `name` in detail, with its recent events.
+            - `logs`: the last `tail_lines` (default 200) log lines of pod `name`, or of a pod
+              of deployment or service `name`, optionally of `container`, `since` a duration
+              like 10m, or of the `previous` container after a crash.
+            - `restart`: restart the pods of deployment `name`, after the user approves. Only
+              available when the user turned it on, the tool is read-only otherwise.
+
+            The namespace is the context's unless `namespace` is given.
+        "#},
+        object!({
+            "type": "object",
+            "required": ["command", "kind"],
+            "properties": {
+                "command": {
+                    "type": "string",
+                    "enum": ["get", "describe", "logs", "restart"]
+                },
+                "kind": {
+                    "type": "string",
+                    "enum": ["pod", "deployment", "service"]
+                },
+                "name": {"type": "string", "description": "The resource, required except for get"},
+                "namespace": {"type": "string"},
+                "selector": {"type": "string", "description": "For get: a label selector like app=web"},
+                "all_namespaces": {"type": "boolean", "description": "For get"},
+                "container": {"type": "string", "description": "For logs"},
+                "tail_lines": {"type": "integer", "description": "For logs: default 200, at most 5000"},
+                "since": {"type": "string", "description": "For logs: only newer lines, like 10m or 2h"},
+                "previous": {"type": "boolean", "description": "For logs: the container before it restarted"}
+            }
+        }),
+    )
+    .annotate(ToolAnnotations {
+        title: Some("Inspect a Kubernetes cluster".to_string()),
+        read_only_hint: Some(false),
+        destructive_hint: Some(false),
+        idempotent_hint: Some(false),
+        open_world_hint: Some(true),
+    })
+}
+
+impl DeveloperRouter {
+    pub(super) async fn kubernetes(
+        &self,
+        params: Value,
+        notifier: &mpsc::Sender<JsonRpcMessage>,
+    ) -> Result<Vec<Content>, ToolError> {
+        let arg = |name: &str| params.get(name).and_then(Value::as_str);
+        let flag = |name: &str| params.get(name).and_then(Value::as_bool) == Some(true);
+        let command = arg("command")
+            .ok_or_else(|| ToolError::InvalidParameters("Missing 'command' parameter".into()))?;
+        let kind = arg("kind")
+            .ok_or_else(|| ToolError::InvalidParameters("Missing 'kind' parameter".into()))?;
+        let kind = Kind::from_name(kind).ok_or_else(|| {
+            ToolError::InvalidParameters(format!(
+                "Unsupported kind '{}', expected pod, deployment or service",
+                kind
+            ))
+        })?;
+        let name = || {
+            arg("name")
+                .ok_or_else(|| ToolError::InvalidParameters(format!("{} needs a 'name'", command)))
+        };
+        if command == "restart" {
+            if !Kubectl::writes_allowed() {
+                return Err(ToolError::ExecutionError(format!(
+                    "The kubernetes tool is read-only. The user can allow restarts by setting {} to true.",
+                    ALLOW_WRITES_KEY
+                )));
+            }
+            if kind != Kind::Deployment {
+                return Err(ToolError::InvalidParameters(
+                    "Only deployments can be restarted".into(),
+                ));
+            }
+        }
+
+        let kubectl = Kubectl::current(self.spawner(), arg("namespace")).await?;
+        let output = match command {
+            "get" => {
+                let resources = kubectl
+                    .get(kind, arg("name"), arg("selector"), flag("all_namespaces"))
+                    .await?;
+                json!({
+                    "context": kubectl.context(),
+                    "namespace": kubectl.namespace(),
+                    "kind": kind,
+                    "resources": resources,
+                })
+            }
+            "describe" => json!(kubectl.describe(kind, name()?).await?),
+            "logs" => {
+                let options = LogOptions {
+                    container: arg("container"),
+                    tail_lines: params.get("tail_lines").and_then(Value::as_u64),
+                    since: arg("since"),
+                    previous: flag("previous"),
+                };
+                json!(kubectl.logs(kind, name()?, options).await?)
+            }
+            "restart" => {
+                let name = name()?;
+                request_approval(
+                    notifier,
+                    &restart_deployment_action(&self.catalog, name, kubectl.context()),
+                )
+                .await?;
+                json!(kubectl.restart(name).await?)
+            }
+            _ => {
+                return Err(ToolError::InvalidParameters(format!(
+                    "Unknown command '{}', expected get, describe, logs or restart",
+                    command
+                )))
+            }
+        };
+
+        // Descriptions and logs are read as text, with the context they came from
+        let text = match &output {
+            Value::String(text) => format!("Context: {}\n\n{}", kubectl.context(), text),
+            json => serde_json::to_string_pretty(json).map_err(|e| {
+                ToolError::ExecutionError(format!("Failed to encode result: {}", e))
+            })?,
+        };
+        // Descriptions show environment variables, and logs often print credentials
+        let text = self.redactor.redact(&text).into_owned();
+        let mut content = vec![Content::text(text.clone())];
+        if output.is_object() {
+            if let Ok(redacted) = serde_json::from_str::<Value>(&text) {
+                content.push(structured_content(&redacted));
+            }
+        }
+        Ok(content)
+    }
+}
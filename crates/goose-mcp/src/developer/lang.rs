@@ -37,3 +37,31 @@ pub fn get_language_identifier(path: &Path) -> &'static str {
         _ => "",
     }
 }
+
+/// Get the MIME type reported for a text file, based on its language
+pub fn get_mime_type(path: &Path) -> &'static str {
+    match get_language_identifier(path) {
+        "json" => "application/json",
+        "yaml" => "application/yaml",
+        "toml" => "application/toml",
+        "javascript" => "text/javascript",
+        "typescript" => "text/typescript",
+        "markdown" => "text/markdown",
+        "html" => "text/html",
+        "css" => "text/css",
+        "sql" => "application/sql",
+        "python" => "text/x-python",
+        "rust" => "text/x-rust",
+        "go" => "text/x-go",
+        "java" => "text/x-java",
+        "c" => "text/x-c",
+        "cpp" => "text/x-c++",
+        "ruby" => "text/x-ruby",
+        "php" => "text/x-php",
+        "swift" => "text/x-swift",
+        "kotlin" => "text/x-kotlin",
+        "scala" => "text/x-scala",
+        "bash" => "text/x-shellscript",
+        _ => "text/plain",
+    }
+}
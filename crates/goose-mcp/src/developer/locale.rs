@@ -0,0 +1,155 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use etcetera::{choose_app_strategy, AppStrategy};
+use include_dir::{include_dir, Dir};
+use rmcp::model::Tool;
+
+use super::editor_models::load_setting;
+
+/// The setting that picks the language of tool descriptions, instructions and messages,
+/// e.g. `es` or `pt-BR`. The system locale is used when it isn't set.
+pub const LOCALE_KEY: &str = "GOOSE_LOCALE";
+
+// The catalogs that ship with goose, one YAML file per language
+static LOCALES_DIR: Dir = include_dir!("$CARGO_MANIFEST_DIR/src/developer/locales");
+
+/// Translations of the developer extension's text for one locale.
+///
+/// A catalog is YAML, nested or with dotted keys, e.g. `tools.shell.description`. Catalogs in
+/// the `locales` directory of the goose config take precedence over the built-in ones, and
+/// anything a catalog doesn't translate stays in English.
+#[derive(Debug, Clone, Default)]
+pub struct Catalog {
+    messages: HashMap<String, String>,
+}
+
+impl Catalog {
+    /// The catalog for the configured locale, or the system's
+    pub fn load(cwd: &Path) -> Self {
+        match load_setting(cwd, LOCALE_KEY).or_else(system_locale) {
+            Some(locale) => Self::for_locale(&locale, &[user_locales_dir()]),
+            None => Self::default(),
+        }
+    }
+
+    /// The catalog for `locale`, with the translations from `dirs` over the built-in ones. A
+    /// regional locale such as `es-MX` falls back to its language, `es`.
+    pub fn for_locale(locale: &str, dirs: &[PathBuf]) -> Self {
+        let locale = normalize(locale);
+        let mut messages = HashMap::new();
+        // The language first, so the region's translations replace it
+        for name in candidates(&locale).iter().rev() {
+            let file_name = format!("{}.yaml", name);
+            if let Some(file) = LOCALES_DIR.get_file(&file_name) {
+                merge(
+                    &mut messages,
+                    file.contents_utf8().unwrap_or_default(),
+                    &file_name,
+                );
+            }
+            for dir in dirs {
+                let path = dir.join(&file_name);
+                if let Ok(content) = std::fs::read_to_string(&path) {
+                    merge(&mut messages, &content, &path.display().to_string());
+                }
+            }
+        }
+
+        if messages.is_empty() && !locale.starts_with("en") {
+            tracing::info!("No translations for locale {}, using English", locale);
+        }
+        Self { messages }
+    }
+
+    /// The translation of `key`, or `default`, with each `{name}` replaced by its argument
+    pub fn text(&self, key: &str, default: &str, args: &[(&str, &str)]) -> String {
+        let template = self.messages.get(key).map_or(default, String::as_str);
+        args.iter()
+            .fold(template.to_string(), |text, (name, value)| {
+                text.replace(&format!("{{{}}}", name), value)
+            })
+    }
+
+    /// The tool with its description and title translated
+    pub fn localize_tool(&self, mut tool: Tool) -> Tool {
+        let prefix = format!("tools.{}", tool.name);
+        if let Some(description) = self.messages.get(&format!("{}.description", prefix)) {
+            tool.description = Some(description.clone().into());
+        }
+        if let (Some(title), Some(annotations)) = (
+            self.messages.get(&format!("{}.title", prefix)),
+            tool.annotations.as_mut(),
+        ) {
+            annotations.title = Some(title.clone());
+        }
+        tool
+    }
+}
+
+/// `es_MX.UTF-8` as `es-MX`
+fn normalize(locale: &str) -> String {
+    locale
+        .split(['.', '@'])
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .replace('_', "-")
+}
+
+/// The locale itself and then its language, e.g. `pt-BR` and `pt`
+fn candidates(locale: &str) -> Vec<String> {
+    let mut names = vec![locale.to_string()];
+    if let Some((language, _)) = locale.split_once('-') {
+        names.push(language.to_string());
+    }
+    names
+}
+
+fn system_locale() -> Option<String> {
+    // Tests expect the English text whatever machine they run on
+    if cfg!(test) {
+        return None;
+    }
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.is_empty())
+        .filter(|value| value != "C" && value != "POSIX" && !value.starts_with("C."))
+}
+
+fn user_locales_dir() -> PathBuf {
+    choose_app_strategy(crate::APP_STRATEGY.clone())
+        .map(|strategy| strategy.in_config_dir("locales"))
+        .unwrap_or_else(|_| {
+            PathBuf::from(shellexpand::tilde("~/.config/goose/locales").to_string())
+        })
+}
+
+/// Add the translations in a YAML catalog, flattening nested keys with dots
+fn merge(messages: &mut HashMap<String, String>, content: &str, source: &str) {
+    match serde_yaml::from_str::<serde_yaml::Value>(content) {
+        Ok(value) => flatten(messages, String::new(), value),
+        Err(e) => tracing::warn!("Ignoring invalid locale catalog {}: {}", source, e),
+    }
+}
+
+fn flatten(messages: &mut HashMap<String, String>, prefix: String, value: serde_yaml::Value) {
+    match value {
+        serde_yaml::Value::Mapping(mapping) => {
+            for (key, value) in mapping {
+                let Some(key) = key.as_str() else { continue };
+                let key = if prefix.is_empty() {
+                    key.to_string()
+                } else {
+                    format!("{}.{}", prefix, key)
+                };
+                flatten(messages, key, value);
+            }
+        }
+        serde_yaml::Value::String(text) => {
+            messages.insert(prefix, text);
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,744 @@
+//! A minimal language server client, for the refactorings only a server that understands the
+//! code can do safely.
+//!
+//! A server is started for each request in the project's root, so no state is kept between
+//! tool calls, and is shut down once it has answered.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use indoc::indoc;
+use mcp_core::handler::ToolError;
+use rmcp::model::{Content, JsonRpcMessage, Role, Tool, ToolAnnotations};
+use rmcp::object;
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{ChildStdin, ChildStdout, Command};
+use tokio::sync::mpsc;
+use url::Url;
+
+use super::approval::request_approval;
+use super::lang::get_language_identifier;
+use super::shell::{Process, Spawner};
+use super::{DeveloperRouter, FileAccess};
+
+/// How long a server may take to start, index the project and answer
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// How long to wait before asking again when the server is still loading the project
+const RETRY_DELAY: Duration = Duration::from_millis(500);
+
+// Errors a server answers with while the project is still being loaded
+const CONTENT_MODIFIED: i64 = -32801;
+const SERVER_CANCELLED: i64 = -32802;
+
+/// The servers started for each language, unless `GOOSE_LSP_SERVERS` names others as a JSON
+/// object of language to command line, e.g. `{"python": ["pylsp"]}`
+fn default_server(language: &str) -> Option<&'static [&'static str]> {
+    match language {
+        "rust" => Some(&["rust-analyzer"]),
+        "python" => Some(&["pyright-langserver", "--stdio"]),
+        "javascript" | "typescript" => Some(&["typescript-language-server", "--stdio"]),
+        "go" => Some(&["gopls"]),
+        "c" | "cpp" => Some(&["clangd"]),
+        _ => None,
+    }
+}
+
+/// The command line of the server for `path`'s language
+pub fn server_for(path: &Path) -> Result<Vec<String>, ToolError> {
+    let language = get_language_identifier(path);
+    let configured: HashMap<String, Vec<String>> = std::env::var("GOOSE_LSP_SERVERS")
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default();
+    let command = match configured.get(language) {
+        Some(command) if !command.is_empty() => command.clone(),
+        _ => default_server(language)
+            .map(|command| command.iter().map(|s| s.to_string()).collect())
+            .ok_or_else(|| {
+                ToolError::InvalidParameters(format!(
+                    "No language server is known for {}. Configure one in GOOSE_LSP_SERVERS.",
+                    path.display()
+                ))
+            })?,
+    };
+    if which::which(&command[0]).is_err() {
+        return Err(ToolError::ExecutionError(format!(
+            "The {} language server `{}` is not installed",
+            language, command[0]
+        )));
+    }
+    Ok(command)
+}
+
+/// A replacement of the text between two positions, in lines and UTF-16 code units as the
+/// protocol counts them
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextEdit {
+    pub start: (usize, usize),
+    pub end: (usize, usize),
+    pub new_text: String,
+}
+
+/// The edits a server wants made to one file
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileEdits {
+    pub path: PathBuf,
+    pub edits: Vec<TextEdit>,
+}
+
+/// A running server, spoken to over its stdin and stdout
+struct Server {
+    child: Process,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+    next_id: i64,
+}
+
+impl Server {
+    async fn start(spawner: &Spawner, command: &[String], root: &Path) -> Result<Self, ToolError> {
+        let mut child = spawner
+            .spawn(
+                Command::new(&command[0])
+                    .args(&command[1..])
+                    .current_dir(root)
+                    .stdin(Stdio::piped())
+                    .stdout(Stdio::piped())
+                    .stderr(Stdio::null()),
+            )
+            .await
+            .map_err(|e| {
+                ToolError::ExecutionError(format!("Failed to start {}: {}", command[0], e))
+            })?;
+        let stdin = child.stdin.take().expect("stdin is piped");
+        let stdout = BufReader::new(child.stdout.take().expect("stdout is piped"));
+        Ok(Self {
+            child,
+            stdin,
+            stdout,
+            next_id: 1,
+        })
+    }
+
+    async fn send(&mut self, message: Value) -> Result<(), ToolError> {
+        let body = message.to_string();
+        let frame = format!("Content-Length: {}\r\n\r\n{}", body.len(), body);
+        self.stdin
+            .write_all(frame.as_bytes())
+            .await
+            .map_err(|e| ToolError::ExecutionError(format!("The language server exited: {}", e)))
+    }
+
+    async fn receive(&mut self) -> Result<Value, ToolError> {
+        let closed = || ToolError::ExecutionError("The language server exited".into());
+        let mut length = None;
+        loop {
+            let mut line = String::new();
+            if self
+                .stdout
+                .read_line(&mut line)
+                .await
+                .map_err(|_| closed())?
+                == 0
+            {
+                return Err(closed());
+            }
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                if name.eq_ignore_ascii_case("content-length") {
+                    length = value.trim().parse::<usize>().ok();
+                }
+            }
+        }
+        let mut body = vec![0; length.ok_or_else(closed)?];
+        self.stdout
+            .read_exact(&mut body)
+            .await
+            .map_err(|_| closed())?;
+        serde_json::from_slice(&body).map_err(|e| {
+            ToolError::ExecutionError(format!("Invalid message from the language server: {}", e))
+        })
+    }
+
+    async fn notify(&mut self, method: &str, params: Value) -> Result<(), ToolError> {
+        self.send(json!({"jsonrpc": "2.0", "method": method, "params": params}))
+            .await
+    }
+
+    /// Send a request and wait for its answer, answering what the server asks in between.
+    /// Errors from the server are returned with their code.
+    async fn request(
+        &mut self,
+        method: &str,
+        params: Value,
+    ) -> Result<Result<Value, (i64, String)>, ToolError> {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.send(json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params}))
+            .await?;
+
+        loop {
+            let message = self.receive().await?;
+            match (message.get("id"), message.get("method")) {
+                // A request from the server, e.g. for its settings, which are left as defaults
+                (Some(request_id), Some(request)) => {
+                    let result = match request.as_str() {
+                        Some("workspace/configuration") => {
+                            let items = message
+                                .pointer("/params/items")
+                                .and_then(Value::as_array)
+                                .map_or(0, Vec::len);
+                            json!(vec![Value::Null; items])
+                        }
+                        _ => Value::Null,
+                    };
+                    let request_id = request_id.clone();
+                    self.send(json!({"jsonrpc": "2.0", "id": request_id, "result": result}))
+                        .await?;
+                }
+                (Some(response_id), None) if response_id == &json!(id) => {
+                    return Ok(match message.get("error") {
+                        Some(error) => Err((
+                            error.get("code").and_then(Value::as_i64).unwrap_or(0),
+                            error
+                                .get("message")
+                                .and_then(Value::as_str)
+                                .unwrap_or("unknown error")
+                                .to_string(),
+                        )),
+                        None => Ok(message.get("result").cloned().unwrap_or(Value::Null)),
+                    });
+                }
+                _ => {}
+            }
+        }
+    }
+
+    async fn initialize(&mut self, root: &Path) -> Result<(), ToolError> {
+        let root_uri = file_uri(root)?;
+        let name = root
+            .file_name()
+            .map_or("workspace".into(), |name| name.to_string_lossy());
+        self.request(
+            "initialize",
+            json!({
+                "processId": std::process::id(),
+                "rootUri": root_uri,
+                "workspaceFolders": [{"uri": root_uri, "name": name}],
+                "capabilities": {
+                    "workspace": {
+                        "workspaceEdit": {"documentChanges": true},
+                        "configuration": true,
+                    },
+                    "textDocument": {"rename": {"prepareSupport": false}},
+                },
+            }),
+        )
+        .await?
+        .map_err(|(_, message)| {
+            ToolError::ExecutionError(format!("The language server failed to start: {}", message))
+        })?;
+        self.notify("initialized", json!({})).await
+    }
+
+    async fn shutdown(mut self) {
+        if let Ok(Ok(_)) = self.request("shutdown", Value::Null).await {
+            let _ = self.notify("exit", Value::Null).await;
+        }
+        let _ = self.child.kill().await;
+    }
+}
+
+/// The edits that rename the symbol at `position` in `path`, whose current content is
+/// `text`, to `new_name` throughout the project in `root`
+pub async fn rename(
+    spawner: &Spawner,
+    command: &[String],
+    root: &Path,
+    path: &Path,
+    text: &str,
+    position: (usize, usize),
+    new_name: &str,
+) -> Result<Vec<FileEdits>, ToolError> {
+    let mut server = Server::start(spawner, command, root).await?;
+    let result = tokio::time::timeout(REQUEST_TIMEOUT, async {
+        server.initialize(root).await?;
+        let uri = file_uri(path)?;
+        server
+            .notify(
+                "textDocument/didOpen",
+                json!({
+                    "textDocument": {
+                        "uri": uri,
+                        "languageId": get_language_identifier(path),
+                        "version": 1,
+                        "text": text,
+                    }
+                }),
+            )
+            .await?;
+
+        let params = json!({
+            "textDocument": {"uri": uri},
+            "position": {"line": position.0, "character": position.1},
+            "newName": new_name,
+        });
+        loop {
+            match server
+                .request("textDocument/rename", params.clone())
+                .await?
+            {
+                Ok(edit) => return workspace_edits(&edit),
+                // The project is still loading
+                Err((CONTENT_MODIFIED | SERVER_CANCELLED, _)) => {
+                    tokio::time::sleep(RETRY_DELAY).await
+                }
+                Err((_, message)) => {
+                    return Err(ToolError::ExecutionError(format!(
+                        "The language server can't rename this: {}",
+                        message
+                    )))
+                }
+            }
+        }
+    })
+    .await;
+    server.shutdown().await;
+
+    result.map_err(|_| {
+        ToolError::ExecutionError(format!(
+            "The language server didn't answer within {} seconds",
+            REQUEST_TIMEOUT.as_secs()
+        ))
+    })?
+}
+
+fn file_uri(path: &Path) -> Result<String, ToolError> {
+    Url::from_file_path(path)
+        .map(String::from)
+        .map_err(|_| ToolError::ExecutionError(format!("Invalid path {}", path.display())))
+}
+
+/// The text edits of a workspace edit, by file. File creations, renames and deletions are
+/// refused rather than half applied.
+pub fn workspace_edits(edit: &Value) -> Result<Vec<FileEdits>, ToolError> {
+    if edit.is_null() {
+        return Err(ToolError::ExecutionError(
+            "The language server found nothing to rename at that position".into(),
+        ));
+    }
+
+    let mut files: Vec<FileEdits> = Vec::new();
+    let mut add = |uri: &str, edits: &Value| -> Result<(), ToolError> {
+        let path = Url::parse(uri)
+            .ok()
+            .and_then(|url| url.to_file_path().ok())
+            .ok_or_else(|| {
+                ToolError::ExecutionError(format!("The language server edited {}", uri))
+            })?;
+        let edits = edits
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(text_edit)
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| {
+                ToolError::ExecutionError("Invalid edit from the language server".into())
+            })?;
+        match files.iter_mut().find(|file| file.path == path) {
+            Some(file) => file.edits.extend(edits),
+            None => files.push(FileEdits { path, edits }),
+        }
+        Ok(())
+    };
+
+    if let Some(changes) = edit.get("documentChanges").and_then(Value::as_array) {
+        for change in changes {
+            if let Some(kind) = change.get("kind").and_then(Value::as_str) {
+                return Err(ToolError::ExecutionError(format!(
+                    "The rename would also {} files, which this tool doesn't do",
+                    kind
+                )));
+            }
+            let uri = change
+                .pointer("/textDocument/uri")
+                .and_then(Value::as_str)
+                .unwrap_or_default();
+            add(uri, change.get("edits").unwrap_or(&Value::Null))?;
+        }
+    } else if let Some(changes) = edit.get("changes").and_then(Value::as_object) {
+        for (uri, edits) in changes {
+            add(uri, edits)?;
+        }
+    }
+    Ok(files)
+}
+
+fn text_edit(edit: &Value) -> Option<TextEdit> {
+    let position = |name: &str| {
+        let position = edit.get("range")?.get(name)?;
+        Some((
+            position.get("line")?.as_u64()? as usize,
+            position.get("character")?.as_u64()? as usize,
+        ))
+    };
+    Some(TextEdit {
+        start: position("start")?,
+        end: position("end")?,
+        new_text: edit.get("newText")?.as_str()?.to_string(),
+    })
+}
+
+/// The byte offset of a protocol position in `text`
+fn offset(text: &str, (line, character): (usize, usize)) -> Option<usize> {
+    let start = if line == 0 {
+        0
+    } else {
+        text.match_indices('\n').nth(line - 1)?.0 + 1
+    };
+    let rest = &text[start..];
+    let mut units = 0;
+    for (index, c) in rest.char_indices() {
+        if units >= character || c == '\n' {
+            return Some(start + index);
+        }
+        units += c.len_utf16();
+    }
+    Some(text.len())
+}
+
+/// `text` with the edits made. Edits may come in any order but must not overlap.
+pub fn apply_edits(text: &str, edits: &[TextEdit]) -> Result<String, ToolError> {
+    let invalid =
+        || ToolError::ExecutionError("The language server's edits don't fit the file".into());
+    let mut ranges = edits
+        .iter()
+        .map(|edit| {
+            let start = offset(text, edit.start).ok_or_else(invalid)?;
+            let end = offset(text, edit.end).ok_or_else(invalid)?;
+            (start <= end)
+                .then_some((start, end, edit.new_text.as_str()))
+                .ok_or_else(invalid)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    ranges.sort_by_key(|&(start, end, _)| (start, end));
+    if ranges.windows(2).any(|pair| pair[0].1 > pair[1].0) {
+        return Err(invalid());
+    }
+
+    let mut result = String::with_capacity(text.len());
+    let mut copied = 0;
+    for (start, end, new_text) in ranges {
+        result.push_str(&text[copied..start]);
+        result.push_str(new_text);
+        copied = end;
+    }
+    result.push_str(&text[copied..]);
+    Ok(result)
+}
+
+/// The protocol position of the first `symbol` on 1-based `line`
+pub fn symbol_position(text: &str, line: usize, symbol: &str) -> Option<(usize, usize)> {
+    let content = text.lines().nth(line.checked_sub(1)?)?;
+    let column = content.find(symbol)?;
+    Some((line - 1, content[..column].encode_utf16().count()))
+}
+
+/// A unified diff of one file's change, with `context` unchanged lines around each hunk
+pub fn unified_diff(name: &str, old: &str, new: &str, context: usize) -> String {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let mut diff = format!("--- a/{}\n+++ b/{}\n", name, name);
+
+    // Renames keep the lines where they are, so the changed lines can be compared in place;
+    // otherwise everything between the common start and end is one change
+    let changes: Vec<(usize, usize, usize, usize)> = if old_lines.len() == new_lines.len() {
+        let mut changes: Vec<(usize, usize, usize, usize)> = Vec::new();
+        for index in (0..old_lines.len()).filter(|&i| old_lines[i] != new_lines[i]) {
+            match changes.last_mut() {
+                Some(last) if index <= last.1 + 2 * context => {
+                    last.1 = index + 1;
+                    last.3 = index + 1;
+                }
+                _ => changes.push((index, index + 1, index, index + 1)),
+            }
+        }
+        changes
+    } else {
+        let prefix = old_lines
+            .iter()
+            .zip(&new_lines)
+            .take_while(|(a, b)| a == b)
+            .count();
+        let suffix = old_lines[prefix..]
+            .iter()
+            .rev()
+            .zip(new_lines[prefix..].iter().rev())
+            .take_while(|(a, b)| a == b)
+            .count();
+        vec![(
+            prefix,
+            old_lines.len() - suffix,
+            prefix,
+            new_lines.len() - suffix,
+        )]
+    };
+
+    for (old_start, old_end, new_start, new_end) in changes {
+        let before = old_start.min(context);
+        let after = context.min(old_lines.len() - old_end);
+        diff.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            old_start - before + 1,
+            old_end - old_start + before + after,
+            new_start - before + 1,
+            new_end - new_start + before + after,
+        ));
+        for line in &old_lines[old_start - before..old_start] {
+            diff.push_str(&format!(" {}\n", line));
+        }
+        if old_lines.len() == new_lines.len() {
+            // Interleave each changed line's old and new version
+            for index in old_start..old_end {
+                if old_lines[index] == new_lines[index] {
+                    diff.push_str(&format!(" {}\n", old_lines[index]));
+                } else {
+                    diff.push_str(&format!("-{}\n+{}\n", old_lines[index], new_lines[index]));
+                }
+            }
+        } else {
+            for line in &old_lines[old_start..old_end] {
+                diff.push_str(&format!("-{}\n", line));
+            }
+            for line in &new_lines[new_start..new_end] {
+                diff.push_str(&format!("+{}\n", line));
+            }
+        }
+        for line in &old_lines[old_end..old_end + after] {
+            diff.push_str(&format!(" {}\n", line));
+        }
+    }
+    diff
+}
+
+/// The definition of the `rename_symbol` tool
+pub fn tool() -> Tool {
+    Tool::new(
+        "rename_symbol",
+        indoc! {r#"
+            Rename a function, type, variable or other symbol everywhere it is used in the
+            project, through the language's language server (rust-analyzer, pyright,
+            typescript-language-server, gopls or clangd, or those in GOOSE_LSP_SERVERS).
+
+            Unlike str_replace, only references to this symbol change: other symbols with the
+            same name, and the name in strings and comments, are left alone. Give the file and
+            the line where `symbol` appears, e.g. its definition or a use. The changed files
+            can each be undone with text_editor's undo_edit.
+
+            Returns the diff of every changed file. With `dry_run`, nothing is written, so the
+            diff can be reviewed first.
+        "#},
+        object!({
+            "type": "object",
+            "required": ["path", "line", "symbol", "new_name"],
+            "properties": {
+                "path": {"type": "string", "description": "Absolute path to a file the symbol appears in"},
+                "line": {"type": "integer", "description": "The 1-based line the symbol appears on"},
+                "symbol": {"type": "string", "description": "The symbol's current name"},
+                "new_name": {"type": "string"},
+                "dry_run": {"type": "boolean", "description": "Only return the diff"}
+            }
+        }),
+    )
+    .annotate(ToolAnnotations {
+        title: Some("Rename a symbol across the project".to_string()),
+        read_only_hint: Some(false),
+        destructive_hint: Some(false),
+        idempotent_hint: Some(false),
+        open_world_hint: Some(false),
+    })
+}
+
+impl DeveloperRouter {
+    pub(super) async fn rename_symbol(
+        &self,
+        params: Value,
+        notifier: &mpsc::Sender<JsonRpcMessage>,
+    ) -> Result<Vec<Content>, ToolError> {
+        let arg = |name: &str| {
+            params
+                .get(name)
+                .and_then(Value::as_str)
+                .filter(|value| !value.trim().is_empty())
+                .ok_or_else(|| {
+                    ToolError::InvalidParameters(format!("Missing '{}' parameter", name))
+                })
+        };
+        let (symbol, new_name) = (arg("symbol")?, arg("new_name")?);
+        let line = params
+            .get("line")
+            .and_then(Value::as_u64)
+            .ok_or_else(|| ToolError::InvalidParameters("Missing 'line' parameter".into()))?
+            as usize;
+        let dry_run = params.get("dry_run").and_then(Value::as_bool) == Some(true);
+
+        let path = self.resolve_path(arg("path")?)?;
+        if self.is_ignored(&path) {
+            return Err(self.ignored_path_error(
+                &path,
+                format!(
+                    "Access to '{}' is restricted by .gooseignore",
+                    path.display()
+                ),
+            ));
+        }
+        let text = self
+            .system
+            .fs()
+            .read_to_string(&path)
+            .map_err(|e| ToolError::ExecutionError(format!("Failed to read file: {}", e)))?;
+        let position = symbol_position(&text, line, symbol).ok_or_else(|| {
+            ToolError::InvalidParameters(format!(
+                "'{}' is not on line {} of {}",
+                symbol,
+                line,
+                path.display()
+            ))
+        })?;
+
+        let command = server_for(&path)?;
+        let cwd = self.cwd();
+        let files = rename(
+            &self.spawner(),
+            &command,
+            &cwd,
+            &path,
+            &text,
+            position,
+            new_name,
+        )
+        .await?;
+
+        // Every file must be one the tools may edit, or none is changed
+        let mut files = files
+            .into_iter()
+            .map(|file| {
+                let path = self.resolve_path(&file.path.to_string_lossy())?;
+                if self.is_ignored(&path) {
+                    return Err(self.ignored_path_error(
+                        &path,
+                        format!(
+                            "The rename would change '{}', which is restricted by .gooseignore",
+                            path.display()
+                        ),
+                    ));
+                }
+                Ok(FileEdits { path, ..file })
+            })
+            .collect::<Result<Vec<_>, ToolError>>()?;
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+
+        // Hold all the files, in a fixed order so parallel renames can't deadlock
+        let mut _file_locks = Vec::new();
+        for file in &files {
+            _file_locks.push(self.file_locks.lock(&file.path).await);
+        }
+
+        let mut changes = Vec::new();
+        for file in &files {
+            let old =
+                self.system.fs().read_to_string(&file.path).map_err(|e| {
+                    ToolError::ExecutionError(format!("Failed to read file: {}", e))
+                })?;
+            if file.path == path && old != text {
+                return Err(ToolError::ExecutionError(format!(
+                    "{} changed during the rename, try again",
+                    path.display()
+                )));
+            }
+            let new = apply_edits(&old, &file.edits)?;
+            if new != old {
+                changes.push((file.path.clone(), old, new, file.edits.len()));
+            }
+        }
+        if changes.is_empty() {
+            return Err(ToolError::ExecutionError(format!(
+                "The language server found nothing to change to rename '{}'",
+                symbol
+            )));
+        }
+
+        let diff: String = changes
+            .iter()
+            .map(|(path, old, new, _)| {
+                let name = path.strip_prefix(&cwd).unwrap_or(path);
+                unified_diff(&name.to_string_lossy(), old, new, 3)
+            })
+            .collect();
+        let edits: usize = changes.iter().map(|(_, _, _, edits)| edits).sum();
+
+        let summary = if dry_run {
+            format!(
+                "Renaming '{}' to '{}' would make {} edits in {} files. Nothing was written; call again without dry_run to apply them.",
+                symbol,
+                new_name,
+                edits,
+                changes.len()
+            )
+        } else {
+            let paths: Vec<&Path> = changes.iter().map(|(path, ..)| path.as_path()).collect();
+            if let Some(action) = self
+                .approval_policy
+                .rename_action(&self.catalog, symbol, &paths)
+                .await
+            {
+                request_approval(notifier, &action).await?;
+            }
+
+            // Put back the files already written if one can't be
+            for (index, (path, old, new, _)) in changes.iter().enumerate() {
+                if let Err(e) = self.system.fs().write(path, new.as_bytes()) {
+                    for (path, old, ..) in &changes[..index] {
+                        let _ = self.system.fs().write(path, old.as_bytes());
+                        self.file_history.pop(path);
+                    }
+                    return Err(ToolError::ExecutionError(format!(
+                        "Failed to write {}, so no file was changed: {}",
+                        path.display(),
+                        e
+                    )));
+                }
+                self.file_history.push(path, old.clone());
+            }
+
+            let mut session_files = self.session_files.lock().unwrap();
+            for (path, ..) in &changes {
+                session_files.insert(path.clone(), FileAccess::Edited);
+            }
+            self.resources_changed.store(true, Ordering::Relaxed);
+
+            format!(
+                "Renamed '{}' to '{}' with {} edits in {} files.",
+                symbol,
+                new_name,
+                edits,
+                changes.len()
+            )
+        };
+
+        Ok(vec![
+            Content::text(format!("{}\n\n```diff\n{}```", summary, diff))
+                .with_audience(vec![Role::Assistant]),
+            Content::text(format!("```diff\n{}```", diff))
+                .with_audience(vec![Role::User])
+                .with_priority(0.2),
+        ])
+    }
+}
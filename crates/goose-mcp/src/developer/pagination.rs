@@ -0,0 +1,129 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Arc,
+};
+
+use mcp_core::handler::ToolError;
+use serde_json::json;
+
+use super::error_codes::INVALID_CURSOR;
+
+/// Lines returned per page
+pub const PAGE_SIZE: usize = 500;
+
+/// Results kept for paging; older ones are dropped and their cursors stop working
+const MAX_RESULTS: usize = 16;
+
+/// The tools whose long results can be paged through with a `cursor`
+pub const PAGINATED_TOOLS: &[&str] = &["glob", "grep", "shell", "text_editor"];
+
+/// One page of a long result
+#[derive(Debug, Clone, PartialEq)]
+pub struct Page {
+    pub lines: Vec<String>,
+    /// Index of the first line in the full result
+    pub offset: usize,
+    pub total: usize,
+    pub next_cursor: Option<String>,
+}
+
+impl Page {
+    /// The page's lines, followed by where they are in the result and how to get more
+    pub fn render(&self, tool_name: &str) -> String {
+        let mut text = self.lines.join("\n");
+        if let Some(cursor) = &self.next_cursor {
+            text.push_str(&format!(
+                "\n\n[lines {}-{} of {}, call {} with {{\"cursor\": \"{}\"}} for the next page]",
+                self.offset + 1,
+                self.offset + self.lines.len(),
+                self.total,
+                tool_name,
+                cursor
+            ));
+        } else if self.offset > 0 {
+            text.push_str(&format!(
+                "\n\n[lines {}-{} of {}, end of the result]",
+                self.offset + 1,
+                self.offset + self.lines.len(),
+                self.total
+            ));
+        }
+        text
+    }
+}
+
+/// Long tool results kept for the session, so a tool returns the first page and the agent
+/// asks for the rest with `next_cursor` instead of getting everything in one response.
+///
+/// A cursor is `<result>:<offset>`, and is only meaningful to this server.
+#[derive(Debug, Default)]
+pub struct ResultPages {
+    next_id: u64,
+    results: HashMap<u64, Arc<[String]>>,
+    order: VecDeque<u64>,
+}
+
+impl ResultPages {
+    /// Keep a result and return its first page
+    pub fn paginate(&mut self, lines: Vec<String>) -> Page {
+        if lines.len() <= PAGE_SIZE {
+            let total = lines.len();
+            return Page {
+                lines,
+                offset: 0,
+                total,
+                next_cursor: None,
+            };
+        }
+
+        let cursor = self.keep(lines);
+        self.next_page(&cursor).expect("the result was just stored")
+    }
+
+    /// Keep a result, returning the cursor for its first page
+    pub fn keep(&mut self, lines: Vec<String>) -> String {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.results.insert(id, lines.into());
+        self.order.push_back(id);
+        while self.order.len() > MAX_RESULTS {
+            if let Some(oldest) = self.order.pop_front() {
+                self.results.remove(&oldest);
+            }
+        }
+        format!("{}:0", id)
+    }
+
+    /// The page a cursor points at
+    pub fn next_page(&self, cursor: &str) -> Result<Page, ToolError> {
+        let invalid = |reason: &str| {
+            ToolError::coded(
+                INVALID_CURSOR,
+                format!(
+                    "Cursor '{}' {}, run the tool again without it",
+                    cursor, reason
+                ),
+                Some(json!({ "cursor": cursor })),
+            )
+        };
+        let (id, offset) = cursor
+            .split_once(':')
+            .and_then(|(id, offset)| Some((id.parse().ok()?, offset.parse().ok()?)))
+            .ok_or_else(|| invalid("is not valid"))?;
+        self.page(id, offset).ok_or_else(|| invalid("has expired"))
+    }
+
+    fn page(&self, id: u64, offset: usize) -> Option<Page> {
+        let lines = self.results.get(&id)?;
+        if offset >= lines.len() {
+            return None;
+        }
+        let end = (offset + PAGE_SIZE).min(lines.len());
+        Some(Page {
+            lines: lines[offset..end].to_vec(),
+            offset,
+            total: lines.len(),
+            next_cursor: (end < lines.len()).then(|| format!("{}:{}", id, end)),
+        })
+    }
+}
@@ -0,0 +1,198 @@
+//! Tools from WebAssembly plugins.
+//!
+//! Every `.wasm` (or `.wat`) module in the plugin directory contributes tools. There is none
+//! unless it is configured with `GOOSE_DEVELOPER_PLUGIN_DIR` or given to the builder, so
+//! nothing is loaded just because it sits in the config directory. A module is sandboxed: it gets no imports, so no files, network or clock, and each call runs in a
+//! fresh instance with limited memory and fuel.
+//!
+//! A module exports:
+//! - `memory`
+//! - `alloc(len: i32) -> i32`, returning where the host may write `len` bytes
+//! - `tools() -> i64`, the JSON array of its tool definitions, in the MCP `Tool` format
+//! - `call(name_ptr: i32, name_len: i32, args_ptr: i32, args_len: i32) -> i64`, running a
+//!   tool with its JSON arguments and returning `{"text": "..."}` or `{"error": "..."}`
+//!
+//! Strings are returned as one i64, the pointer in the high 32 bits and the length in the
+//! low 32 bits.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail, Context, Result};
+use mcp_core::handler::ToolError;
+use rmcp::model::{Content, Tool};
+use serde::Deserialize;
+use serde_json::Value;
+use wasmtime::{Config, Engine, Instance, Memory, Module, Store, StoreLimits, StoreLimitsBuilder};
+
+use super::custom_tools::CustomTool;
+use super::editor_models::load_user_setting;
+
+/// Config key naming the directory plugins are loaded from
+pub const PLUGIN_DIR_KEY: &str = "GOOSE_DEVELOPER_PLUGIN_DIR";
+
+/// Most memory a plugin instance may grow to
+const MAX_MEMORY_BYTES: usize = 64 * 1024 * 1024;
+
+/// Instructions a plugin may run per call, so a runaway loop ends in an error
+const FUEL_PER_CALL: u64 = 1_000_000_000;
+
+/// The configured plugin directory, if there is one
+pub fn plugin_dir() -> Option<PathBuf> {
+    load_user_setting(PLUGIN_DIR_KEY).map(|dir| PathBuf::from(shellexpand::tilde(&dir).to_string()))
+}
+
+/// The tools of every plugin in `dir`. A plugin that fails to load is skipped with a
+/// warning, so one broken module doesn't take the others down.
+pub fn load_plugins(dir: &Path) -> Vec<CustomTool> {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.is_file()
+                && path
+                    .extension()
+                    .is_some_and(|ext| ext == "wasm" || ext == "wat")
+        })
+        .collect();
+    paths.sort();
+
+    let engine = match engine() {
+        Ok(engine) => engine,
+        Err(e) => {
+            tracing::warn!("Plugins are unavailable: {}", e);
+            return Vec::new();
+        }
+    };
+
+    paths
+        .into_iter()
+        .flat_map(|path| match Plugin::load(&engine, &path) {
+            Ok((plugin, tools)) => {
+                tracing::info!(
+                    "Loaded {} tools from plugin {}",
+                    tools.len(),
+                    path.display()
+                );
+                let plugin = Arc::new(plugin);
+                tools
+                    .into_iter()
+                    .map(|tool| plugin_tool(Arc::clone(&plugin), tool))
+                    .collect()
+            }
+            Err(e) => {
+                tracing::warn!("Skipping plugin {}: {:#}", path.display(), e);
+                Vec::new()
+            }
+        })
+        .collect()
+}
+
+fn engine() -> Result<Engine> {
+    let mut config = Config::new();
+    config.consume_fuel(true);
+    Engine::new(&config)
+}
+
+fn plugin_tool(plugin: Arc<Plugin>, tool: Tool) -> CustomTool {
+    let name = tool.name.to_string();
+    CustomTool::new(tool, move |arguments, _notifier| {
+        let plugin = Arc::clone(&plugin);
+        let name = name.clone();
+        async move {
+            // Plugins run synchronously until they return or run out of fuel
+            tokio::task::spawn_blocking(move || plugin.call(&name, &arguments))
+                .await
+                .map_err(|e| ToolError::ExecutionError(e.to_string()))?
+        }
+    })
+}
+
+/// What a plugin's `call` returns
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum PluginResult {
+    Text(String),
+    Error(String),
+}
+
+struct Plugin {
+    path: PathBuf,
+    engine: Engine,
+    module: Module,
+}
+
+impl Plugin {
+    /// Compile the module and ask it for its tools
+    fn load(engine: &Engine, path: &Path) -> Result<(Self, Vec<Tool>)> {
+        let module = Module::from_file(engine, path)?;
+        let plugin = Self {
+            path: path.to_path_buf(),
+            engine: engine.clone(),
+            module,
+        };
+
+        let (mut store, instance, memory) = plugin.instantiate()?;
+        let packed = instance
+            .get_typed_func::<(), i64>(&mut store, "tools")?
+            .call(&mut store, ())?;
+        let tools: Vec<Tool> = serde_json::from_slice(read(&store, memory, packed)?)
+            .context("tools() did not return a JSON array of tools")?;
+        Ok((plugin, tools))
+    }
+
+    fn instantiate(&self) -> Result<(Store<StoreLimits>, Instance, Memory)> {
+        let limits = StoreLimitsBuilder::new()
+            .memory_size(MAX_MEMORY_BYTES)
+            .build();
+        let mut store = Store::new(&self.engine, limits);
+        store.limiter(|limits| limits);
+        store.set_fuel(FUEL_PER_CALL)?;
+
+        let instance = Instance::new(&mut store, &self.module, &[])?;
+        let memory = instance
+            .get_memory(&mut store, "memory")
+            .ok_or_else(|| anyhow!("the module does not export its memory"))?;
+        Ok((store, instance, memory))
+    }
+
+    fn call(&self, tool: &str, arguments: &Value) -> Result<Vec<Content>, ToolError> {
+        let result = self.run(tool, arguments).map_err(|e| {
+            ToolError::ExecutionError(format!("Plugin {} failed: {:#}", self.path.display(), e))
+        })?;
+        match result {
+            PluginResult::Text(text) => Ok(vec![Content::text(text)]),
+            PluginResult::Error(message) => Err(ToolError::ExecutionError(message)),
+        }
+    }
+
+    fn run(&self, tool: &str, arguments: &Value) -> Result<PluginResult> {
+        let (mut store, instance, memory) = self.instantiate()?;
+        let alloc = instance.get_typed_func::<i32, i32>(&mut store, "alloc")?;
+        let call = instance.get_typed_func::<(i32, i32, i32, i32), i64>(&mut store, "call")?;
+
+        let mut write = |bytes: &[u8]| -> Result<(i32, i32)> {
+            let len = i32::try_from(bytes.len())?;
+            let ptr = alloc.call(&mut store, len)?;
+            memory.write(&mut store, ptr as u32 as usize, bytes)?;
+            Ok((ptr, len))
+        };
+        let (name_ptr, name_len) = write(tool.as_bytes())?;
+        let (args_ptr, args_len) = write(arguments.to_string().as_bytes())?;
+
+        let packed = call.call(&mut store, (name_ptr, name_len, args_ptr, args_len))?;
+        serde_json::from_slice(read(&store, memory, packed)?)
+            .context("call() did not return {\"text\": ...} or {\"error\": ...}")
+    }
+}
+
+/// The bytes a packed pointer and length refer to
+fn read(store: &Store<StoreLimits>, memory: Memory, packed: i64) -> Result<&[u8]> {
+    let ptr = (packed as u64 >> 32) as usize;
+    let len = (packed as u64 & 0xffff_ffff) as usize;
+    match memory.data(store).get(ptr..ptr + len) {
+        Some(bytes) => Ok(bytes),
+        None => bail!("returned a string outside its memory"),
+    }
+}
@@ -0,0 +1,92 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use rmcp::model::{
+    JsonObject, JsonRpcMessage, JsonRpcNotification, JsonRpcVersion2_0, Notification,
+};
+use rmcp::object;
+use serde_json::Value;
+use tokio::sync::mpsc;
+
+/// Shortest time between two progress notifications for the same call
+const MIN_INTERVAL: Duration = Duration::from_millis(250);
+
+/// The arguments of a builtin tool with the metadata the client attached to the request under
+/// `_meta`, where the tools read it from
+pub fn with_meta(mut arguments: Value, meta: JsonObject) -> Value {
+    if let Some(arguments) = arguments.as_object_mut().filter(|_| !meta.is_empty()) {
+        arguments.insert("_meta".to_string(), Value::Object(meta));
+    }
+    arguments
+}
+
+/// The progress token the client attached to the request, which the router passes to the
+/// builtin tools under `_meta` in their arguments
+pub fn progress_token(arguments: &Value) -> Option<Value> {
+    arguments
+        .get("_meta")
+        .and_then(|meta| meta.get("progressToken"))
+        .filter(|token| token.is_string() || token.is_number())
+        .cloned()
+}
+
+/// Sends `notifications/progress` for one tool call, throttled so fast producers don't flood
+/// the client
+pub struct ProgressReporter {
+    notifier: mpsc::Sender<JsonRpcMessage>,
+    token: Value,
+    message: String,
+    last_sent: Mutex<Option<Instant>>,
+}
+
+impl ProgressReporter {
+    pub fn new(notifier: mpsc::Sender<JsonRpcMessage>, token: Value, message: String) -> Self {
+        Self {
+            notifier,
+            token,
+            message,
+            last_sent: Mutex::new(None),
+        }
+    }
+
+    /// A reporter for the tool call, if the client asked for progress
+    pub fn requested(
+        arguments: &Value,
+        notifier: &mpsc::Sender<JsonRpcMessage>,
+        message: impl FnOnce() -> String,
+    ) -> Option<Self> {
+        progress_token(arguments).map(|token| Self::new(notifier.clone(), token, message()))
+    }
+
+    /// Report how far along the call is. `total` is left out when it isn't known, e.g. for
+    /// the lines a command has printed so far.
+    pub fn report(&self, progress: f64, total: Option<f64>) {
+        {
+            let mut last_sent = self.last_sent.lock().unwrap();
+            if last_sent.is_some_and(|sent| sent.elapsed() < MIN_INTERVAL) {
+                return;
+            }
+            *last_sent = Some(Instant::now());
+        }
+
+        let mut params = object!({
+            "progressToken": self.token.clone(),
+            "progress": progress,
+            "message": self.message.clone(),
+        });
+        if let Some(total) = total {
+            params.insert("total".to_string(), total.into());
+        }
+
+        self.notifier
+            .try_send(JsonRpcMessage::Notification(JsonRpcNotification {
+                jsonrpc: JsonRpcVersion2_0,
+                notification: Notification {
+                    method: "notifications/progress".to_string(),
+                    params,
+                    extensions: Default::default(),
+                },
+            }))
+            .ok();
+    }
+}
@@ -0,0 +1,86 @@
+use mcp_core::handler::PromptError;
+use rmcp::model::{JsonObject, Prompt};
+use serde_json::Value;
+
+/// Render a prompt template with the client's arguments.
+///
+/// `{name}` is replaced by the argument of that name, or left empty when an optional argument
+/// is not given. `{{` and `}}` produce literal braces, and braces around anything that isn't
+/// a declared argument are kept as written, so templates can contain code samples.
+pub fn render(prompt: &Prompt, arguments: &JsonObject) -> Result<String, PromptError> {
+    let declared = prompt.arguments.as_deref().unwrap_or_default();
+
+    if let Some(unknown) = arguments
+        .keys()
+        .find(|key| !declared.iter().any(|arg| &arg.name == *key))
+    {
+        return Err(PromptError::InvalidParameters(format!(
+            "Unknown argument '{}' for prompt '{}'",
+            unknown, prompt.name
+        )));
+    }
+
+    let missing: Vec<&str> = declared
+        .iter()
+        .filter(|arg| arg.required.unwrap_or(false))
+        .filter(|arg| arguments.get(&arg.name).is_none_or(is_blank))
+        .map(|arg| arg.name.as_str())
+        .collect();
+    if !missing.is_empty() {
+        return Err(PromptError::InvalidParameters(format!(
+            "Missing required arguments for prompt '{}': {}",
+            prompt.name,
+            missing.join(", ")
+        )));
+    }
+
+    let template = prompt.description.as_deref().unwrap_or_default();
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find(['{', '}']) {
+        rendered.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        if rest.starts_with("{{") || rest.starts_with("}}") {
+            rendered.push_str(&rest[..1]);
+            rest = &rest[2..];
+            continue;
+        }
+
+        let placeholder = rest
+            .strip_prefix('{')
+            .and_then(|inner| inner.find('}').map(|end| &inner[..end]))
+            .filter(|name| declared.iter().any(|arg| arg.name == *name));
+        match placeholder {
+            Some(name) => {
+                if let Some(value) = arguments.get(name) {
+                    rendered.push_str(&value_text(value));
+                }
+                rest = &rest[name.len() + 2..];
+            }
+            None => {
+                rendered.push_str(&rest[..1]);
+                rest = &rest[1..];
+            }
+        }
+    }
+    rendered.push_str(rest);
+
+    Ok(rendered)
+}
+
+fn is_blank(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::String(s) => s.trim().is_empty(),
+        _ => false,
+    }
+}
+
+fn value_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
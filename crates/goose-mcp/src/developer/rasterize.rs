@@ -0,0 +1,335 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use mcp_core::handler::ToolError;
+use once_cell::sync::Lazy;
+use resvg::{tiny_skia, usvg};
+use tokio::process::Command;
+use xcap::image::{
+    metadata::Orientation, DynamicImage, ImageDecoder, ImageReader, ImageResult, RgbaImage,
+};
+
+use super::workspace::Workspace;
+
+/// Resolution PDF pages are rendered at
+const PDF_DPI: u32 = 150;
+/// Most pages rendered from a PDF in one call
+pub const MAX_PDF_PAGES: usize = 10;
+/// Most pixels an SVG is rasterized to. Larger ones are scaled down, since the size an SVG
+/// declares can be anything.
+const MAX_SVG_PIXELS: f32 = 4096.0 * 4096.0;
+
+/// The system fonts for text in SVGs, which take a while to load
+static FONTS: Lazy<Arc<usvg::fontdb::Database>> = Lazy::new(|| {
+    let mut fonts = usvg::fontdb::Database::new();
+    fonts.load_system_fonts();
+    Arc::new(fonts)
+});
+
+fn has_extension(path: &Path, extensions: &[&str]) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| extensions.iter().any(|ext| e.eq_ignore_ascii_case(ext)))
+}
+
+pub fn is_pdf(path: &Path) -> bool {
+    has_extension(path, &["pdf"])
+}
+
+/// Decode an image file. SVGs are rasterized at their natural size unless it is huge, PDFs
+/// are rendered from their first page and EXIF orientation is applied to everything else.
+/// Images an SVG links to are only loaded from inside `workspace`.
+pub async fn load_image(path: &Path, workspace: &Workspace) -> Result<DynamicImage, ToolError> {
+    if has_extension(path, &["svg", "svgz"]) {
+        let (path, workspace) = (path.to_path_buf(), workspace.clone());
+        tokio::task::spawn_blocking(move || rasterize_svg(&path, workspace))
+            .await
+            .map_err(|e| ToolError::ExecutionError(e.to_string()))?
+    } else if is_pdf(path) {
+        render_pdf_page(path, 1).await
+    } else {
+        open_oriented(path)
+            .map_err(|e| ToolError::ExecutionError(format!("Failed to open image file: {}", e)))
+    }
+}
+
+/// Decode a raster image, rotating and flipping it as its EXIF orientation says so photos
+/// taken with a phone are upright
+fn open_oriented(path: &Path) -> ImageResult<DynamicImage> {
+    let mut decoder = ImageReader::open(path)?
+        .with_guessed_format()?
+        .into_decoder()?;
+    let orientation = decoder.orientation().unwrap_or(Orientation::NoTransforms);
+    let mut image = DynamicImage::from_decoder(decoder)?;
+    image.apply_orientation(orientation);
+    Ok(image)
+}
+
+/// Parse and render an SVG, which can take a while for a big one, so not on the runtime
+fn rasterize_svg(path: &Path, workspace: Workspace) -> Result<DynamicImage, ToolError> {
+    let data = std::fs::read(path)
+        .map_err(|e| ToolError::ExecutionError(format!("Failed to read SVG file: {}", e)))?;
+
+    let load_file = usvg::ImageHrefResolver::default_string_resolver();
+    let options = usvg::Options {
+        resources_dir: path.parent().map(Path::to_path_buf),
+        fontdb: FONTS.clone(),
+        image_href_resolver: usvg::ImageHrefResolver {
+            resolve_string: Box::new(move |href, options| {
+                let linked = options.get_abs_path(Path::new(href));
+                if workspace.confine(&linked).is_err() {
+                    tracing::warn!("Skipping SVG image {} outside the workspace", href);
+                    return None;
+                }
+                load_file(href, options)
+            }),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    let tree = usvg::Tree::from_data(&data, &options)
+        .map_err(|e| ToolError::ExecutionError(format!("Failed to parse SVG: {}", e)))?;
+    let size = tree.size();
+    let scale = (MAX_SVG_PIXELS / (size.width() * size.height()))
+        .sqrt()
+        .min(1.0);
+    let (width, height) = (
+        ((size.width() * scale).ceil() as u32).max(1),
+        ((size.height() * scale).ceil() as u32).max(1),
+    );
+    let mut pixmap = tiny_skia::Pixmap::new(width, height).ok_or_else(|| {
+        ToolError::ExecutionError(format!("SVG has an invalid size of {}x{}", width, height))
+    })?;
+    resvg::render(
+        &tree,
+        tiny_skia::Transform::from_scale(scale, scale),
+        &mut pixmap.as_mut(),
+    );
+
+    // tiny-skia stores premultiplied alpha
+    let pixels = pixmap
+        .pixels()
+        .iter()
+        .flat_map(|pixel| {
+            let color = pixel.demultiply();
+            [color.red(), color.green(), color.blue(), color.alpha()]
+        })
+        .collect();
+    RgbaImage::from_raw(width, height, pixels)
+        .map(DynamicImage::ImageRgba8)
+        .ok_or_else(|| ToolError::ExecutionError("Failed to convert rendered SVG".into()))
+}
+
+/// Render a page of a PDF (1-based) with poppler's `pdftoppm`, or `mutool` from MuPDF if
+/// poppler is not installed
+pub async fn render_pdf_page(path: &Path, page: u32) -> Result<DynamicImage, ToolError> {
+    let dir = tempfile::tempdir()
+        .map_err(|e| ToolError::ExecutionError(format!("Failed to create temp dir: {}", e)))?;
+    let output = dir.path().join("page.png");
+    let page_arg = page.to_string();
+    let dpi_arg = PDF_DPI.to_string();
+
+    let pdftoppm = Command::new("pdftoppm")
+        .args([
+            "-png",
+            "-singlefile",
+            "-r",
+            &dpi_arg,
+            "-f",
+            &page_arg,
+            "-l",
+            &page_arg,
+        ])
+        .arg(path)
+        .arg(dir.path().join("page"))
+        .output()
+        .await;
+
+    let rendered = match pdftoppm {
+        Ok(result) if result.status.success() => Ok(()),
+        Ok(result) => Err(String::from_utf8_lossy(&result.stderr).trim().to_string()),
+        Err(_) => match Command::new("mutool")
+            .args(["draw", "-r", &dpi_arg, "-o"])
+            .arg(&output)
+            .arg(path)
+            .arg(&page_arg)
+            .output()
+            .await
+        {
+            Ok(result) if result.status.success() => Ok(()),
+            Ok(result) => Err(String::from_utf8_lossy(&result.stderr).trim().to_string()),
+            Err(_) => {
+                return Err(ToolError::ExecutionError(
+                    "Rendering PDFs requires pdftoppm (poppler-utils) or mutool (mupdf-tools) to be installed".into(),
+                ))
+            }
+        },
+    };
+
+    rendered.map_err(|stderr| {
+        ToolError::ExecutionError(format!(
+            "Failed to render page {} of {}: {}",
+            page,
+            path.display(),
+            stderr
+        ))
+    })?;
+
+    xcap::image::open(&output)
+        .map_err(|e| ToolError::ExecutionError(format!("Failed to open rendered PDF page: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_huge_svgs_are_scaled_down() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("huge.svg");
+        std::fs::write(
+            &path,
+            r##"<svg xmlns="http://www.w3.org/2000/svg" width="200000" height="100000"><rect width="200000" height="100000" fill="#00ff00"/></svg>"##,
+        )
+        .unwrap();
+
+        let image = rasterize_svg(&path, Workspace::default()).unwrap();
+        assert!((image.width() * image.height()) as f32 <= MAX_SVG_PIXELS * 1.01);
+        assert!(image.width().abs_diff(image.height() * 2) <= 2);
+        assert_eq!(image.to_rgba8().get_pixel(10, 10).0, [0, 255, 0, 255]);
+    }
+
+    #[test]
+    fn test_svg_images_outside_the_workspace_are_skipped() {
+        let dir = tempfile::tempdir().unwrap();
+        let (root, outside) = (dir.path().join("project"), dir.path().join("elsewhere"));
+        std::fs::create_dir(&root).unwrap();
+        std::fs::create_dir(&outside).unwrap();
+        let green = DynamicImage::ImageRgb8(xcap::image::RgbImage::from_pixel(
+            4,
+            4,
+            xcap::image::Rgb([0, 255, 0]),
+        ));
+        green.save(root.join("inside.png")).unwrap();
+        green.save(outside.join("outside.png")).unwrap();
+        let path = root.join("linked.svg");
+        std::fs::write(
+            &path,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="20" height="10"><image href="inside.png" width="10" height="10"/><image href="../elsewhere/outside.png" x="10" width="10" height="10"/></svg>"#,
+        )
+        .unwrap();
+
+        let image = rasterize_svg(&path, Workspace::new(vec![root], true))
+            .unwrap()
+            .to_rgba8();
+        assert_eq!(image.get_pixel(5, 5).0, [0, 255, 0, 255]);
+        assert_eq!(image.get_pixel(15, 5).0[3], 0);
+    }
+
+    /// A JPEG 16 pixels wide and 8 high, red on the left and blue on the right, whose EXIF
+    /// says to turn it a quarter clockwise, as phones do for portrait photos
+    fn rotated_jpeg() -> Vec<u8> {
+        let image = xcap::image::RgbImage::from_fn(16, 8, |x, _| {
+            if x < 8 {
+                xcap::image::Rgb([255, 0, 0])
+            } else {
+                xcap::image::Rgb([0, 0, 255])
+            }
+        });
+        let mut jpeg = Vec::new();
+        DynamicImage::ImageRgb8(image)
+            .write_to(
+                &mut std::io::Cursor::new(&mut jpeg),
+                xcap::image::ImageFormat::Jpeg,
+            )
+            .unwrap();
+
+        // An APP1 segment with a big-endian TIFF header and one IFD entry: Orientation = 6
+        let mut exif = b"Exif\0\0MM\0\x2a\0\0\0\x08\0\x01".to_vec();
+        exif.extend_from_slice(&[0x01, 0x12, 0, 3, 0, 0, 0, 1, 0, 6, 0, 0, 0, 0, 0, 0]);
+        let mut segment = vec![0xff, 0xe1];
+        segment.extend_from_slice(&(exif.len() as u16 + 2).to_be_bytes());
+        segment.extend_from_slice(&exif);
+        jpeg.splice(2..2, segment);
+        jpeg
+    }
+
+    #[test]
+    fn test_exif_orientation_is_applied() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("portrait.jpg");
+        std::fs::write(&path, rotated_jpeg()).unwrap();
+
+        let image = open_oriented(&path).unwrap();
+        assert_eq!((image.width(), image.height()), (8, 16));
+        // The left of the stored image is now the top
+        let pixels = image.to_rgb8();
+        let [r, _, b] = pixels.get_pixel(4, 3).0;
+        assert!(r > 200 && b < 60, "top is {:?}", pixels.get_pixel(4, 3));
+        let [r, _, b] = pixels.get_pixel(4, 12).0;
+        assert!(r < 60 && b > 200, "bottom is {:?}", pixels.get_pixel(4, 12));
+
+        let metadata = crate::developer::image_metadata::describe(&path, &image);
+        assert!(metadata.contains("Dimensions: 8x16"), "{}", metadata);
+        assert!(metadata.contains("Orientation: "), "{}", metadata);
+    }
+
+    /// A PDF whose pages have the given sizes in points
+    fn blank_pdf(pages: &[(u32, u32)]) -> Vec<u8> {
+        let kids = (0..pages.len())
+            .map(|i| format!("{} 0 R", i + 3))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let mut objects = vec![
+            "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+            format!("<< /Type /Pages /Kids [{}] /Count {} >>", kids, pages.len()),
+        ];
+        for (width, height) in pages {
+            objects.push(format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] >>",
+                width, height
+            ));
+        }
+
+        let mut pdf = "%PDF-1.4\n".to_string();
+        let mut offsets = Vec::new();
+        for (i, object) in objects.iter().enumerate() {
+            offsets.push(pdf.len());
+            pdf.push_str(&format!("{} 0 obj\n{}\nendobj\n", i + 1, object));
+        }
+        let xref = pdf.len();
+        pdf.push_str(&format!(
+            "xref\n0 {}\n0000000000 65535 f \n",
+            objects.len() + 1
+        ));
+        for offset in offsets {
+            pdf.push_str(&format!("{:010} 00000 n \n", offset));
+        }
+        pdf.push_str(&format!(
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+            objects.len() + 1,
+            xref
+        ));
+        pdf.into_bytes()
+    }
+
+    #[tokio::test]
+    async fn test_pdf_pages_are_rendered() {
+        if which::which("pdftoppm").is_err() && which::which("mutool").is_err() {
+            return;
+        }
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("doc.pdf");
+        std::fs::write(&path, blank_pdf(&[(72, 36), (36, 72)])).unwrap();
+
+        // 150 DPI is a little over two pixels a point
+        let first = load_image(&path, &Workspace::default()).await.unwrap();
+        assert_eq!((first.width(), first.height()), (150, 75));
+        let second = render_pdf_page(&path, 2).await.unwrap();
+        assert_eq!((second.width(), second.height()), (75, 150));
+        assert_eq!(second.to_rgba8().get_pixel(5, 5).0, [255, 255, 255, 255]);
+
+        assert!(render_pdf_page(&path, 3).await.is_err());
+    }
+}
@@ -0,0 +1,157 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    hash::Hash,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use super::reload::{stamp, Stamp};
+
+/// Entries remembered of each kind unless `GOOSE_READ_CACHE_SIZE` says otherwise
+const DEFAULT_CAPACITY: usize = 32;
+
+/// Characters that make a glob path component a pattern rather than a literal directory
+const GLOB_META: &[char] = &['*', '?', '[', '{'];
+
+/// The directories a glob's matches were found in, with their stamps, and the matches
+type GlobEntry = (Vec<(PathBuf, Stamp)>, Arc<[String]>);
+
+/// Results of recent read-only calls, so an agent viewing the same file or running the same
+/// glob again in a session gets an instant answer.
+///
+/// Viewed files are checked against their modification time and size. Glob results are
+/// checked against the directories the matches were found in, and dropped whenever a tool
+/// that can change files runs, since a new file elsewhere in the tree wouldn't show up in
+/// those directories.
+#[derive(Debug)]
+pub struct ReadCache {
+    files: Fifo<PathBuf, (Stamp, Arc<str>)>,
+    globs: Fifo<(PathBuf, String), GlobEntry>,
+}
+
+impl ReadCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            files: Fifo::new(capacity),
+            globs: Fifo::new(capacity),
+        }
+    }
+
+    /// Sized from `GOOSE_READ_CACHE_SIZE`, where 0 disables caching
+    pub fn from_env() -> Self {
+        let capacity = std::env::var("GOOSE_READ_CACHE_SIZE")
+            .ok()
+            .and_then(|v| v.trim().parse().ok())
+            .unwrap_or(DEFAULT_CAPACITY);
+        Self::new(capacity)
+    }
+
+    /// The file's content as it was last read, if the file hasn't changed since
+    pub fn file(&self, path: &Path) -> Option<Arc<str>> {
+        let (stored, content) = self.files.get(path)?;
+        (stored.is_some() && *stored == stamp(path)).then(|| Arc::clone(content))
+    }
+
+    /// Remember a file's content, with the stamp taken before it was read
+    pub fn insert_file(&mut self, path: PathBuf, stamp: Stamp, content: Arc<str>) {
+        if stamp.is_some() {
+            self.files.insert(path, (stamp, content));
+        }
+    }
+
+    /// The paths a glob matched, if none of the directories they were found in changed
+    pub fn glob(&self, cwd: &Path, pattern: &str) -> Option<Arc<[String]>> {
+        let (dirs, paths) = self.globs.get(&(cwd.to_path_buf(), pattern.to_string()))?;
+        dirs.iter()
+            .all(|(dir, stored)| *stored == stamp(dir))
+            .then(|| Arc::clone(paths))
+    }
+
+    /// Remember a glob's matches, with the stamp of the pattern's base directory taken before
+    /// it ran
+    pub fn insert_glob(
+        &mut self,
+        cwd: &Path,
+        pattern: &str,
+        base: (PathBuf, Stamp),
+        paths: Arc<[String]>,
+    ) {
+        let mut dirs = vec![base];
+        for path in paths.iter() {
+            let path = cwd.join(path);
+            for dir in path.ancestors().skip(1) {
+                if !dir.starts_with(&dirs[0].0) || dirs.iter().any(|(known, _)| known == dir) {
+                    break;
+                }
+                dirs.push((dir.to_path_buf(), stamp(dir)));
+            }
+        }
+        self.globs
+            .insert((cwd.to_path_buf(), pattern.to_string()), (dirs, paths));
+    }
+
+    /// Forget every glob result, after a tool call that may have changed the tree
+    pub fn clear_globs(&mut self) {
+        self.globs.clear();
+    }
+}
+
+/// The directory a glob pattern starts searching from: its leading literal components
+pub fn glob_base(cwd: &Path, pattern: &str) -> PathBuf {
+    let literal: PathBuf = Path::new(pattern)
+        .components()
+        .take_while(|component| !component.as_os_str().to_string_lossy().contains(GLOB_META))
+        .collect();
+    // A pattern without wildcards names a file, so its directory is what can change
+    let literal = if literal == Path::new(pattern) {
+        literal.parent().map(Path::to_path_buf).unwrap_or_default()
+    } else {
+        literal
+    };
+    cwd.join(literal)
+}
+
+/// A map that evicts its least recently stored entry when it grows past its capacity
+#[derive(Debug)]
+struct Fifo<K, V> {
+    capacity: usize,
+    entries: HashMap<K, V>,
+    order: VecDeque<K>,
+}
+
+impl<K: Eq + Hash + Clone, V> Fifo<K, V> {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: std::borrow::Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.entries.get(key)
+    }
+
+    fn insert(&mut self, key: K, value: V) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.insert(key.clone(), value).is_none() {
+            self.order.push_back(key);
+        }
+        while self.order.len() > self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.entries.remove(&oldest);
+            }
+        }
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+    }
+}
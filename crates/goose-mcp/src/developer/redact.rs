@@ -0,0 +1,122 @@
+use std::borrow::Cow;
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+static PLACEHOLDER: Lazy<Regex> = Lazy::new(|| Regex::new(r"\[REDACTED:[a-z_]+\]").unwrap());
+
+/// Built-in credential patterns, each paired with the label used in the replacement
+const DEFAULT_PATTERNS: &[(&str, &str)] = &[
+    (
+        "private_key",
+        r"-----BEGIN [A-Z0-9 ]*PRIVATE KEY-----[\s\S]*?-----END [A-Z0-9 ]*PRIVATE KEY-----",
+    ),
+    ("aws_access_key", r"\b(?:AKIA|ASIA)[0-9A-Z]{16}\b"),
+    (
+        "aws_secret_key",
+        r#"(?i)aws_?secret_?access_?key\s*[:=]\s*["']?[A-Za-z0-9/+=]{40}["']?"#,
+    ),
+    (
+        "github_token",
+        r"\b(?:gh[pousr]_[A-Za-z0-9]{36,}|github_pat_[A-Za-z0-9_]{60,})\b",
+    ),
+    ("slack_token", r"\bxox[abposr]-[A-Za-z0-9-]{10,}\b"),
+    ("google_api_key", r"\bAIza[0-9A-Za-z_-]{35}\b"),
+    ("anthropic_api_key", r"\bsk-ant-[A-Za-z0-9_-]{20,}\b"),
+    ("openai_api_key", r"\bsk-(?:proj-)?[A-Za-z0-9_-]{20,}\b"),
+    (
+        "jwt",
+        r"\beyJ[A-Za-z0-9_-]{10,}\.eyJ[A-Za-z0-9_-]{10,}\.[A-Za-z0-9_-]{10,}\b",
+    ),
+    ("bearer_token", r"(?i)\bbearer\s+[A-Za-z0-9._~+/-]{20,}=*"),
+];
+
+/// Scrubs credentials from shell output and file content before it reaches the model.
+///
+/// Redaction is on by default and can be turned off with `GOOSE_REDACT_SECRETS=false`.
+/// Extra patterns can be supplied as a JSON list of regexes in `GOOSE_SECRET_PATTERNS`.
+#[derive(Debug, Clone, Default)]
+pub struct SecretRedactor {
+    patterns: Vec<(String, Regex)>,
+}
+
+impl SecretRedactor {
+    pub fn from_env() -> Self {
+        let enabled = std::env::var("GOOSE_REDACT_SECRETS")
+            .map(|v| !matches!(v.to_lowercase().as_str(), "0" | "false" | "no"))
+            .unwrap_or(true);
+        if !enabled {
+            return Self::default();
+        }
+
+        let custom_patterns: Vec<String> = std::env::var("GOOSE_SECRET_PATTERNS")
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default();
+
+        Self::new(&custom_patterns)
+    }
+
+    /// Create a redactor with the built-in patterns plus the given custom regexes.
+    /// Invalid custom patterns are skipped with a warning.
+    pub fn new(custom_patterns: &[String]) -> Self {
+        let mut patterns: Vec<(String, Regex)> = DEFAULT_PATTERNS
+            .iter()
+            .map(|(name, pattern)| {
+                (
+                    name.to_string(),
+                    Regex::new(pattern).expect("built-in secret pattern should compile"),
+                )
+            })
+            .collect();
+
+        for pattern in custom_patterns {
+            match Regex::new(pattern) {
+                Ok(regex) => patterns.push(("custom".to_string(), regex)),
+                Err(e) => tracing::warn!("Ignoring invalid secret pattern '{}': {}", pattern, e),
+            }
+        }
+
+        Self { patterns }
+    }
+
+    /// This redactor that also replaces `values` wherever they appear, for the secrets a
+    /// command was given
+    pub fn with_values<'a>(&self, values: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut patterns = self.patterns.clone();
+        for value in values.into_iter().filter(|value| !value.is_empty()) {
+            let regex = Regex::new(&regex::escape(value)).expect("escaped value should compile");
+            patterns.push(("secret".to_string(), regex));
+        }
+        Self { patterns }
+    }
+
+    /// Replace every match with `[REDACTED:<kind>]`, borrowing the input when nothing matched
+    pub fn redact<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        let mut result = Cow::Borrowed(text);
+        for (name, regex) in &self.patterns {
+            if regex.is_match(&result) {
+                let replacement = format!("[REDACTED:{}]", name);
+                result = Cow::Owned(
+                    regex
+                        .replace_all(&result, regex::NoExpand(&replacement))
+                        .into_owned(),
+                );
+            }
+        }
+        result
+    }
+
+    /// The first placeholder in `new_text` that `current` doesn't contain as-is, when this
+    /// redactor hides anything. Writing it would replace the secret it stands for, which the
+    /// model never saw.
+    pub fn new_placeholder<'a>(&self, current: &str, new_text: &'a str) -> Option<&'a str> {
+        if self.patterns.is_empty() {
+            return None;
+        }
+        PLACEHOLDER
+            .find_iter(new_text)
+            .map(|placeholder| placeholder.as_str())
+            .find(|placeholder| !current.contains(placeholder))
+    }
+}
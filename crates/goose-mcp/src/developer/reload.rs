@@ -0,0 +1,109 @@
+use std::{
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use super::editor_models::config_paths;
+use super::ignore_files::ignore_file_paths;
+use super::instructions::hints_paths;
+use super::user_prompts::{prompt_dirs, prompt_file_paths};
+
+/// A file's modification time and size, or None when it doesn't exist
+pub type Stamp = Option<(SystemTime, u64)>;
+
+/// Remembers the modification time and size of a set of files so changes made while the
+/// session is running can be picked up without a file watcher
+#[derive(Debug, Clone, Default)]
+pub struct FileStamps {
+    files: Vec<(PathBuf, Stamp)>,
+}
+
+impl FileStamps {
+    pub fn new(paths: Vec<PathBuf>) -> Self {
+        let files = paths
+            .into_iter()
+            .map(|path| {
+                let stamp = stamp(&path);
+                (path, stamp)
+            })
+            .collect();
+        Self { files }
+    }
+
+    /// Record the current state of every file, returning true if any of them was
+    /// created, modified or removed since the last check
+    pub fn refresh(&mut self) -> bool {
+        let mut changed = false;
+        for (path, previous) in &mut self.files {
+            let current = stamp(path);
+            if current != *previous {
+                *previous = current;
+                changed = true;
+            }
+        }
+        changed
+    }
+}
+
+/// Like [`FileStamps`], but for every prompt file in a set of directories, so files added
+/// or removed later are noticed too
+#[derive(Debug, Clone, Default)]
+pub struct DirStamps {
+    dirs: Vec<PathBuf>,
+    files: Vec<(PathBuf, Stamp)>,
+}
+
+impl DirStamps {
+    pub fn new(dirs: Vec<PathBuf>) -> Self {
+        let files = dir_stamps(&dirs);
+        Self { dirs, files }
+    }
+
+    /// Rescan the directories, returning true if a file was added, modified or removed
+    /// since the last check
+    pub fn refresh(&mut self) -> bool {
+        let current = dir_stamps(&self.dirs);
+        if current == self.files {
+            return false;
+        }
+        self.files = current;
+        true
+    }
+}
+
+fn dir_stamps(dirs: &[PathBuf]) -> Vec<(PathBuf, Stamp)> {
+    prompt_file_paths(dirs)
+        .into_iter()
+        .map(|path| {
+            let stamp = stamp(&path);
+            (path, stamp)
+        })
+        .collect()
+}
+
+pub fn stamp(path: &Path) -> Stamp {
+    let metadata = std::fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// The hints, ignore, prompt and config files the developer extension was configured from
+#[derive(Debug, Clone)]
+pub struct WatchedFiles {
+    pub cwd: PathBuf,
+    pub hints: FileStamps,
+    pub ignore: FileStamps,
+    pub prompts: DirStamps,
+    pub config: FileStamps,
+}
+
+impl WatchedFiles {
+    pub fn new(cwd: &Path) -> Self {
+        Self {
+            cwd: cwd.to_path_buf(),
+            hints: FileStamps::new(hints_paths(cwd)),
+            ignore: FileStamps::new(ignore_file_paths(cwd)),
+            prompts: DirStamps::new(prompt_dirs(cwd)),
+            config: FileStamps::new(config_paths(cwd)),
+        }
+    }
+}
@@ -0,0 +1,427 @@
+//! Named credentials kept in the OS keyring and passed to shell commands as environment
+//! variables.
+//!
+//! The values never go through the model: the user types them into an elicitation prompt,
+//! they are read from the keyring each time a command is spawned, and they are redacted from
+//! the command's output. Which variables a command gets is configured by the user in
+//! `GOOSE_SHELL_SECRETS`, or approved by them for the session.
+
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+
+use indoc::indoc;
+use keyring::Entry;
+use mcp_core::handler::ToolError;
+use mcp_server::confirm::CONFIRM_TIMEOUT;
+use mcp_server::requests::send_request;
+use mcp_server::structured::structured_content;
+use rmcp::model::{Content, JsonRpcMessage, Tool, ToolAnnotations};
+use rmcp::object;
+use serde::Serialize;
+use serde_json::{json, Value};
+use tokio::sync::mpsc;
+
+use super::approval::{delete_secret_action, expose_secret_action, request_approval};
+use super::editor_models::load_user_list;
+use super::DeveloperRouter;
+
+/// Config key listing the variables every shell command gets, each `NAME` for the secret of
+/// the same name or `NAME=secret`. Only read from the environment and the global config, so
+/// a project can't ask for secrets.
+pub const SHELL_SECRETS_KEY: &str = "GOOSE_SHELL_SECRETS";
+
+const KEYRING_SERVICE: &str = "goose";
+
+/// Kept apart from goose's own secrets, so only credentials stored for commands reach them
+const KEYRING_USERNAME: &str = "shell_secrets";
+
+/// Where the secrets are stored, as a map of name to value
+pub trait SecretStore: Debug + Send + Sync {
+    fn load(&self) -> Result<BTreeMap<String, String>, String>;
+    fn save(&self, secrets: &BTreeMap<String, String>) -> Result<(), String>;
+}
+
+/// The OS keyring: the macOS keychain, the Windows credential manager or the secret service
+#[derive(Debug, Default)]
+pub struct KeyringStore;
+
+impl SecretStore for KeyringStore {
+    fn load(&self) -> Result<BTreeMap<String, String>, String> {
+        let entry = Entry::new(KEYRING_SERVICE, KEYRING_USERNAME).map_err(|e| e.to_string())?;
+        match entry.get_password() {
+            Ok(content) => serde_json::from_str(&content).map_err(|e| e.to_string()),
+            Err(keyring::Error::NoEntry) => Ok(BTreeMap::new()),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    fn save(&self, secrets: &BTreeMap<String, String>) -> Result<(), String> {
+        let entry = Entry::new(KEYRING_SERVICE, KEYRING_USERNAME).map_err(|e| e.to_string())?;
+        let content = serde_json::to_string(secrets).map_err(|e| e.to_string())?;
+        entry.set_password(&content).map_err(|e| e.to_string())
+    }
+}
+
+/// Secrets held in memory, for tests and machines without a keyring
+#[derive(Debug, Default)]
+pub struct MemorySecretStore(Mutex<BTreeMap<String, String>>);
+
+impl MemorySecretStore {
+    pub fn new(secrets: impl IntoIterator<Item = (String, String)>) -> Self {
+        Self(Mutex::new(secrets.into_iter().collect()))
+    }
+}
+
+impl SecretStore for MemorySecretStore {
+    fn load(&self) -> Result<BTreeMap<String, String>, String> {
+        Ok(self.0.lock().unwrap().clone())
+    }
+
+    fn save(&self, secrets: &BTreeMap<String, String>) -> Result<(), String> {
+        *self.0.lock().unwrap() = secrets.clone();
+        Ok(())
+    }
+}
+
+pub fn check_name(name: &str) -> Result<(), ToolError> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "_.-".contains(c));
+    if valid {
+        Ok(())
+    } else {
+        Err(ToolError::InvalidParameters(format!(
+            "'{}' is not a valid secret name, use letters, digits, '_', '.' and '-'",
+            name
+        )))
+    }
+}
+
+pub fn check_variable(name: &str) -> Result<(), ToolError> {
+    let valid = name
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(ToolError::InvalidParameters(format!(
+            "'{}' is not a valid environment variable name",
+            name
+        )))
+    }
+}
+
+/// A variable a shell command gets a secret in
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Exposure {
+    pub variable: String,
+    pub secret: String,
+    /// Whether it comes from the config rather than being approved in this session
+    pub configured: bool,
+}
+
+/// The variables approved for shell commands in this session, by variable name
+#[derive(Debug, Clone, Default)]
+pub struct ExposedSecrets(Arc<Mutex<BTreeMap<String, String>>>);
+
+impl ExposedSecrets {
+    pub fn insert(&self, variable: &str, secret: &str) {
+        self.0
+            .lock()
+            .unwrap()
+            .insert(variable.to_string(), secret.to_string());
+    }
+
+    /// Stop passing `name`, a variable or the secret it holds, returning whether it was
+    pub fn remove(&self, name: &str) -> bool {
+        let mut exposed = self.0.lock().unwrap();
+        let before = exposed.len();
+        exposed.retain(|variable, secret| variable != name && secret != name);
+        exposed.len() != before
+    }
+
+    /// The configured variables and then those approved in the session, which replace a
+    /// configured variable of the same name
+    pub fn all(&self) -> Vec<Exposure> {
+        let mut exposures: BTreeMap<String, Exposure> = load_user_list(SHELL_SECRETS_KEY)
+            .into_iter()
+            .filter_map(|entry: String| {
+                let (variable, secret) = entry
+                    .split_once('=')
+                    .unwrap_or((entry.as_str(), entry.as_str()));
+                let (variable, secret) = (variable.trim(), secret.trim());
+                if check_variable(variable).is_err() || check_name(secret).is_err() {
+                    tracing::warn!("Ignoring invalid {} entry '{}'", SHELL_SECRETS_KEY, entry);
+                    return None;
+                }
+                Some((
+                    variable.to_string(),
+                    Exposure {
+                        variable: variable.to_string(),
+                        secret: secret.to_string(),
+                        configured: true,
+                    },
+                ))
+            })
+            .collect();
+        for (variable, secret) in self.0.lock().unwrap().iter() {
+            exposures.insert(
+                variable.clone(),
+                Exposure {
+                    variable: variable.clone(),
+                    secret: secret.clone(),
+                    configured: false,
+                },
+            );
+        }
+        exposures.into_values().collect()
+    }
+
+    /// The variables to set on a command about to be spawned, with their values. Secrets
+    /// that aren't stored are left out with a warning, since the command may not need them.
+    pub fn environment(&self, store: &dyn SecretStore) -> Vec<(String, String)> {
+        let exposures = self.all();
+        if exposures.is_empty() {
+            return Vec::new();
+        }
+        let secrets = match store.load() {
+            Ok(secrets) => secrets,
+            Err(e) => {
+                tracing::warn!("Failed to read the shell secrets from the keyring: {}", e);
+                return Vec::new();
+            }
+        };
+        exposures
+            .into_iter()
+            .filter_map(|exposure| match secrets.get(&exposure.secret) {
+                Some(value) => Some((exposure.variable, value.clone())),
+                None => {
+                    tracing::warn!(
+                        "The secret '{}' for {} isn't stored",
+                        exposure.secret,
+                        exposure.variable
+                    );
+                    None
+                }
+            })
+            .collect()
+    }
+}
+
+/// Ask the user to type a secret's value, which goes straight to the keyring. Returns None
+/// when they decline.
+pub async fn ask_value(
+    notifier: &mpsc::Sender<JsonRpcMessage>,
+    name: &str,
+    message: &str,
+) -> Result<Option<String>, ToolError> {
+    let params = object!({
+        "message": message,
+        "requestedSchema": {
+            "type": "object",
+            "properties": {
+                "value": {
+                    "type": "string",
+                    "title": name,
+                }
+            },
+            "required": ["value"]
+        },
+        "_meta": {
+            "goose/secret": name
+        }
+    });
+    let result = send_request(notifier, "elicitation/create", params, CONFIRM_TIMEOUT)
+        .await
+        .map_err(|e| {
+            ToolError::ExecutionError(format!("The user couldn't be asked for the secret: {}", e))
+        })?;
+    if result.get("action").and_then(Value::as_str) != Some("accept") {
+        return Ok(None);
+    }
+    Ok(result
+        .get("content")
+        .and_then(|content| content.get("value"))
+        .and_then(Value::as_str)
+        .filter(|value| !value.is_empty())
+        .map(str::to_string))
+}
+
+/// The definition of the `secrets` tool
+pub fn tool() -> Tool {
+    Tool::new(
+        "secrets",
+        indoc! {r#"
+            Manage credentials kept in the OS keyring for shell commands, so API keys and
+            tokens never have to be pasted into the chat or written to files. You never see
+            the values.
+
+            Commands:
+            - `list`: the stored secrets' names, and the environment variables shell
+              commands get them in.
+            - `set`: ask the user to type the value of the secret `name`, which is stored
+              in the keyring. Use this when a command needs a credential the user hasn't
+              stored.
+            - `expose`: with the user's approval, give every shell command in this session
+              the secret `name` in the environment variable `variable` (default the name).
+              Refer to it as e.g. "$NPM_TOKEN" in commands; its value is redacted from
+              their output.
+            - `hide`: stop giving shell commands the secret or variable `name`.
+            - `delete`: remove the secret `name` from the keyring, with the user's approval.
+
+            Secrets the user listed in GOOSE_SHELL_SECRETS are given to every command
+            without being exposed.
+        "#},
+        object!({
+            "type": "object",
+            "required": ["command"],
+            "properties": {
+                "command": {"type": "string", "enum": ["list", "set", "expose", "hide", "delete"]},
+                "name": {"type": "string", "description": "Name of the secret"},
+                "variable": {"type": "string", "description": "Environment variable for expose"}
+            }
+        }),
+    )
+    .annotate(ToolAnnotations {
+        title: Some("Keyring secrets".to_string()),
+        read_only_hint: Some(false),
+        destructive_hint: Some(true),
+        idempotent_hint: Some(false),
+        open_world_hint: Some(false),
+    })
+}
+
+impl DeveloperRouter {
+    pub(super) async fn secrets(
+        &self,
+        params: Value,
+        notifier: &mpsc::Sender<JsonRpcMessage>,
+    ) -> Result<Vec<Content>, ToolError> {
+        let command = params
+            .get("command")
+            .and_then(Value::as_str)
+            .ok_or_else(|| ToolError::InvalidParameters("Missing 'command' parameter".into()))?;
+        let name_arg = params.get("name").and_then(Value::as_str);
+        let name = || {
+            let name = name_arg
+                .ok_or_else(|| ToolError::InvalidParameters("Missing 'name' parameter".into()))?;
+            check_name(name)?;
+            Ok::<_, ToolError>(name)
+        };
+        let keyring_error =
+            |e: String| ToolError::ExecutionError(format!("The keyring can't be used: {}", e));
+
+        let text = match command {
+            "list" => {
+                let stored: Vec<String> = self
+                    .secret_store
+                    .load()
+                    .map_err(keyring_error)?
+                    .into_keys()
+                    .collect();
+                let result = json!({
+                    "stored": stored,
+                    "exposed": self.exposed_secrets.all(),
+                });
+                let text = serde_json::to_string_pretty(&result).map_err(|e| {
+                    ToolError::ExecutionError(format!("Failed to encode result: {}", e))
+                })?;
+                return Ok(vec![Content::text(text), structured_content(&result)]);
+            }
+            "set" => {
+                let name = name()?;
+                let message = self.catalog.text(
+                    "approval.enter_secret",
+                    "Enter the value of the secret {secret}. It is saved in the OS keyring and never shown to the model.",
+                    &[("secret", name)],
+                );
+                let value = ask_value(notifier, name, &message).await?.ok_or_else(|| {
+                    ToolError::ExecutionError(format!(
+                        "The user didn't enter a value for the secret {}",
+                        name
+                    ))
+                })?;
+                let mut stored = self.secret_store.load().map_err(keyring_error)?;
+                stored.insert(name.to_string(), value);
+                self.secret_store.save(&stored).map_err(keyring_error)?;
+                format!("Stored the secret {} in the keyring", name)
+            }
+            "expose" => {
+                let name = name()?;
+                let variable = params
+                    .get("variable")
+                    .and_then(Value::as_str)
+                    .unwrap_or(name);
+                check_variable(variable)?;
+                if !self
+                    .secret_store
+                    .load()
+                    .map_err(keyring_error)?
+                    .contains_key(name)
+                {
+                    return Err(ToolError::InvalidParameters(format!(
+                        "No secret named '{}' is stored, ask the user to set it first",
+                        name
+                    )));
+                }
+                request_approval(
+                    notifier,
+                    &expose_secret_action(&self.catalog, name, variable),
+                )
+                .await?;
+                self.exposed_secrets.insert(variable, name);
+                format!(
+                    "Shell commands now get the secret {} as ${}",
+                    name, variable
+                )
+            }
+            "hide" => {
+                let name = name()?;
+                if self.exposed_secrets.remove(name) {
+                    format!("Shell commands no longer get {}", name)
+                } else if self
+                    .exposed_secrets
+                    .all()
+                    .iter()
+                    .any(|exposure| exposure.variable == name || exposure.secret == name)
+                {
+                    return Err(ToolError::ExecutionError(format!(
+                        "{} is given to every command by {}, only the user can change that",
+                        name, SHELL_SECRETS_KEY
+                    )));
+                } else {
+                    return Err(ToolError::InvalidParameters(format!(
+                        "{} isn't exposed to shell commands",
+                        name
+                    )));
+                }
+            }
+            "delete" => {
+                let name = name()?;
+                let mut stored = self.secret_store.load().map_err(keyring_error)?;
+                if !stored.contains_key(name) {
+                    return Err(ToolError::InvalidParameters(format!(
+                        "No secret named '{}' is stored",
+                        name
+                    )));
+                }
+                request_approval(notifier, &delete_secret_action(&self.catalog, name)).await?;
+                stored.remove(name);
+                self.secret_store.save(&stored).map_err(keyring_error)?;
+                self.exposed_secrets.remove(name);
+                format!("Deleted the secret {}", name)
+            }
+            _ => {
+                return Err(ToolError::InvalidParameters(format!(
+                    "Unknown command '{}', expected list, set, expose, hide or delete",
+                    command
+                )))
+            }
+        };
+        Ok(vec![Content::text(text)])
+    }
+}
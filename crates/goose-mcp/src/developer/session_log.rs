@@ -0,0 +1,226 @@
+use std::{
+    fs::OpenOptions,
+    io::Write,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use etcetera::{choose_app_strategy, AppStrategy};
+use mcp_core::handler::ToolError;
+use rmcp::model::Content;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+/// The URI the session log is listed under in the extension's resources
+pub const SESSION_LOG_URI: &str = "goose://developer/session-log";
+
+/// Sessions started by this process, so each gets its own file
+static SESSIONS: AtomicUsize = AtomicUsize::new(0);
+
+/// Longest summary kept for a call, so a long heredoc doesn't swamp the timeline
+const MAX_SUMMARY_CHARS: usize = 200;
+
+/// What the developer extension did in this session, one entry per tool call.
+///
+/// Kept in memory to serve as a resource, and appended as JSONL to a file per session in
+/// the goose state directory (`GOOSE_SESSION_LOG_DIR` overrides it) so it outlives the
+/// process.
+#[derive(Debug, Clone)]
+pub struct SessionLog {
+    started: DateTime<Utc>,
+    path: Option<PathBuf>,
+    entries: Arc<Mutex<Vec<SessionLogEntry>>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionLogEntry {
+    pub timestamp: DateTime<Utc>,
+    pub tool: String,
+    pub summary: String,
+    pub duration_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<Value>,
+}
+
+impl SessionLog {
+    pub fn new(path: Option<PathBuf>) -> Self {
+        if let Some(parent) = path.as_ref().and_then(|path| path.parent()) {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        Self {
+            started: Utc::now(),
+            path,
+            entries: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    pub fn from_env() -> Self {
+        // Tests only keep the log in memory
+        if cfg!(test) {
+            return Self::new(None);
+        }
+
+        // choose_app_strategy().state_dir()
+        // - macOS/Linux: ~/.local/state/goose/logs/developer
+        // - Windows:     ~\AppData\Roaming\Block\goose\data\logs\developer
+        let dir = std::env::var("GOOSE_SESSION_LOG_DIR")
+            .ok()
+            .filter(|dir| !dir.is_empty())
+            .map(|dir| PathBuf::from(shellexpand::tilde(&dir).to_string()))
+            .or_else(|| {
+                let strategy = choose_app_strategy(crate::APP_STRATEGY.clone()).ok()?;
+                Some(
+                    strategy
+                        .in_state_dir("logs/developer")
+                        .unwrap_or_else(|| strategy.in_data_dir("logs/developer")),
+                )
+            });
+        let name = format!(
+            "session-{}-{}-{}.jsonl",
+            Utc::now().format("%Y%m%d_%H%M%S"),
+            std::process::id(),
+            SESSIONS.fetch_add(1, Ordering::Relaxed)
+        );
+        Self::new(dir.map(|dir| dir.join(name)))
+    }
+
+    /// Add a finished call to the log. `summary` should already be redacted.
+    pub fn record(
+        &self,
+        tool: &str,
+        summary: String,
+        elapsed: Duration,
+        result: &Result<Vec<Content>, ToolError>,
+    ) {
+        let entry = SessionLogEntry {
+            timestamp: Utc::now(),
+            tool: tool.to_string(),
+            summary,
+            duration_ms: elapsed.as_millis() as u64,
+            error: result.as_ref().err().map(|e| {
+                json!({
+                    "code": e.code(),
+                    "message": e.to_string(),
+                })
+            }),
+        };
+
+        if let Some(path) = &self.path {
+            let appended = serde_json::to_string(&entry).map(|line| {
+                OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .and_then(|mut file| writeln!(file, "{}", line))
+            });
+            if let Ok(Err(e)) = appended {
+                tracing::warn!("Failed to write session log entry: {}", e);
+            }
+        }
+        self.entries.lock().unwrap().push(entry);
+    }
+
+    pub fn entries(&self) -> Vec<SessionLogEntry> {
+        self.entries.lock().unwrap().clone()
+    }
+
+    /// The log as served from the resource: when the session started and every call since
+    pub fn render(&self) -> String {
+        json!({
+            "started": self.started,
+            "entries": self.entries(),
+        })
+        .to_string()
+    }
+}
+
+/// A one line description of a call, e.g. the shell command or the file being edited
+pub fn summarize(tool: &str, arguments: &Value) -> String {
+    let arg = |name: &str| arguments.get(name).and_then(Value::as_str);
+    let summary = match tool {
+        "shell" => arg("command").map(str::to_string),
+        "text_editor" => arg("path").map(|path| match arg("command") {
+            Some(command) => format!("{} {}", command, path),
+            None => path.to_string(),
+        }),
+        "glob" | "grep" => arg("pattern").map(str::to_string),
+        "fetch_page" => arg("url").map(str::to_string),
+        // The database can be a connection string with a password in it
+        "db_query" => Some(arg("query").unwrap_or("schema").to_string()),
+        "http_request" => {
+            arg("url").map(|url| format!("{} {}", arg("method").unwrap_or("GET"), url))
+        }
+        "rename_symbol" => arg("symbol")
+            .zip(arg("new_name"))
+            .map(|(symbol, new_name)| format!("{} -> {}", symbol, new_name)),
+        "git" | "forge" => arg("command").map(|command| format!("{} {}", tool, command)),
+        "format" => Some(
+            arguments
+                .get("paths")
+                .and_then(Value::as_array)
+                .map_or("format changed files".to_string(), |paths| {
+                    format!("format {} files", paths.len())
+                }),
+        ),
+        "build" => Some(
+            std::iter::once("build")
+                .chain(arg("system"))
+                .chain(
+                    arguments
+                        .get("args")
+                        .and_then(Value::as_array)
+                        .into_iter()
+                        .flatten()
+                        .filter_map(Value::as_str),
+                )
+                .collect::<Vec<_>>()
+                .join(" "),
+        ),
+        "docs_lookup" => arg("query").map(|query| format!("docs for {}", query)),
+        "secrets" => arg("command").map(|command| match arg("name") {
+            Some(name) => format!("secrets {} {}", command, name),
+            None => format!("secrets {}", command),
+        }),
+        "tasks" => Some(match (arg("command"), arg("name")) {
+            (Some("run"), Some(name)) => format!("run task {}", name),
+            _ => "list tasks".to_string(),
+        }),
+        "download" => arg("url")
+            .zip(arg("path"))
+            .map(|(url, path)| format!("download {} to {}", url, path)),
+        "wait_for" => arg("url")
+            .map(|url| format!("wait for {}", url))
+            .or_else(|| {
+                let port = arguments.get("port").and_then(Value::as_u64)?;
+                Some(format!(
+                    "wait for {}:{}",
+                    arg("host").unwrap_or("localhost"),
+                    port
+                ))
+            }),
+        "deps" => arg("command")
+            .zip(arg("package"))
+            .map(|(command, package)| format!("deps {} {}", command, package)),
+        "kubernetes" => arg("command").map(|command| {
+            [Some(command), arg("kind"), arg("name")]
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>()
+                .join(" ")
+        }),
+        _ => None,
+    }
+    .unwrap_or_else(|| tool.to_string());
+
+    let first_line = summary.lines().next().unwrap_or_default();
+    match first_line.char_indices().nth(MAX_SUMMARY_CHARS) {
+        Some((end, _)) => format!("{}...", &first_line[..end]),
+        None if first_line.len() < summary.trim_end().len() => format!("{}...", first_line),
+        None => first_line.to_string(),
+    }
+}
@@ -1,4 +1,13 @@
 use std::env;
+use std::ffi::OsStr;
+use std::io;
+use std::ops::{Deref, DerefMut};
+use std::process::{ExitStatus, Output, Stdio};
+use std::sync::Arc;
+
+use tokio::process::{Child, Command};
+
+use super::system::System;
 
 #[derive(Debug, Clone)]
 pub struct ShellConfig {
@@ -71,6 +80,47 @@ pub fn get_shell_config() -> ShellConfig {
     ShellConfig::default()
 }
 
+/// Runs the command in a process group of its own, so everything it starts can be killed
+/// together if the tool call is cancelled
+fn isolate_process_group(command: &mut Command) {
+    #[cfg(unix)]
+    command.process_group(0);
+    #[cfg(not(unix))]
+    let _ = command;
+}
+
+/// Kills a command's process group when dropped before [`ProcessGroupGuard::disarm`] is
+/// called, i.e. when the tool call was cancelled while the command was running. Killing only
+/// the shell would leave its children running and holding the output pipes open.
+#[derive(Debug)]
+struct ProcessGroupGuard {
+    pgid: Option<u32>,
+}
+
+impl ProcessGroupGuard {
+    pub fn new(pgid: Option<u32>) -> Self {
+        Self { pgid }
+    }
+
+    /// The command finished, so anything it left running in the background was intended
+    pub fn disarm(&mut self) {
+        self.pgid = None;
+    }
+}
+
+impl Drop for ProcessGroupGuard {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        if let Some(pgid) = self.pgid.and_then(|pgid| i32::try_from(pgid).ok()) {
+            // SAFETY: killpg has no memory safety requirements, and the group was created
+            // for this command by isolate_process_group
+            unsafe {
+                libc::killpg(pgid, libc::SIGKILL);
+            }
+        }
+    }
+}
+
 pub fn expand_path(path_str: &str) -> String {
     if cfg!(windows) {
         // Expand Windows environment variables (%VAR%)
@@ -105,3 +155,165 @@ pub fn normalize_line_endings(text: &str) -> String {
         text.replace("\r\n", "\n")
     }
 }
+
+/// Value substituted for scrubbed variables in mask mode
+const MASKED_VALUE: &str = "********";
+
+/// Removes or masks host environment variables, such as credentials, before they are
+/// inherited by shell commands the model runs.
+///
+/// Configured with `GOOSE_SHELL_ENV_SCRUB`, a JSON list of name patterns like
+/// `["*_TOKEN", "AWS_*"]`. Matching variables are removed unless
+/// `GOOSE_SHELL_ENV_SCRUB_MODE=mask`, which keeps them set to a placeholder so that
+/// scripts checking for their presence still work.
+#[derive(Debug, Clone, Default)]
+pub struct EnvScrubber {
+    patterns: Vec<glob::Pattern>,
+    mask: bool,
+}
+
+impl EnvScrubber {
+    pub fn from_env() -> Self {
+        let patterns: Vec<String> = env::var("GOOSE_SHELL_ENV_SCRUB")
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default();
+        let mask = env::var("GOOSE_SHELL_ENV_SCRUB_MODE")
+            .map(|mode| mode.eq_ignore_ascii_case("mask"))
+            .unwrap_or(false);
+
+        Self::new(&patterns, mask)
+    }
+
+    pub fn new(patterns: &[String], mask: bool) -> Self {
+        let patterns = patterns
+            .iter()
+            .filter_map(|pattern| match glob::Pattern::new(pattern) {
+                Ok(pattern) => Some(pattern),
+                Err(e) => {
+                    tracing::warn!("Ignoring invalid env scrub pattern '{}': {}", pattern, e);
+                    None
+                }
+            })
+            .collect();
+        Self { patterns, mask }
+    }
+
+    fn matches(&self, name: &str) -> bool {
+        // Environment variable names are case-insensitive on Windows
+        let options = glob::MatchOptions {
+            case_sensitive: !cfg!(windows),
+            ..Default::default()
+        };
+        self.patterns
+            .iter()
+            .any(|pattern| pattern.matches_with(name, options))
+    }
+
+    /// Apply the scrubbing to a command that is about to be spawned. Variables the command
+    /// sets itself are left alone, so a scrub pattern can't remove one a tool passes on.
+    pub fn apply(&self, command: &mut Command) {
+        if self.patterns.is_empty() {
+            return;
+        }
+
+        let set: Vec<_> = command
+            .as_std()
+            .get_envs()
+            .map(|(name, _)| name.to_os_string())
+            .collect();
+        for (name, _) in env::vars_os() {
+            let Some(name_str) = name.to_str() else {
+                continue;
+            };
+            if self.matches(name_str) && !set.contains(&name) {
+                if self.mask {
+                    command.env(&name, MASKED_VALUE);
+                } else {
+                    command.env_remove(&name);
+                }
+            }
+        }
+    }
+}
+
+/// Starts the commands the developer tools run, so that all of them get the same treatment
+/// as the shell tool's: injected spawn faults apply first, the host environment is scrubbed,
+/// and the command runs in a process group of its own that is killed if the tool call is
+/// cancelled before it finishes.
+#[derive(Debug, Clone, Default)]
+pub struct Spawner {
+    scrubber: Arc<EnvScrubber>,
+    system: System,
+}
+
+impl Spawner {
+    pub fn new(scrubber: Arc<EnvScrubber>, system: System) -> Self {
+        Self { scrubber, system }
+    }
+
+    pub async fn spawn(&self, command: &mut Command) -> io::Result<Process> {
+        self.system.before_spawn(&command_line(command)).await?;
+        self.scrubber.apply(command);
+        isolate_process_group(command);
+        let child = command.kill_on_drop(true).spawn()?;
+        let group = ProcessGroupGuard::new(child.id());
+        Ok(Process { child, group })
+    }
+
+    /// Run `command` without input and collect its output, like [`Command::output`]
+    pub async fn output(&self, command: &mut Command) -> io::Result<Output> {
+        command
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        self.spawn(command).await?.wait_with_output().await
+    }
+}
+
+/// The program and arguments of `command`, which spawn faults are matched against
+fn command_line(command: &Command) -> String {
+    let command = command.as_std();
+    std::iter::once(command.get_program())
+        .chain(command.get_args())
+        .map(OsStr::to_string_lossy)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// A command started by [`Spawner`]. Its process group is killed if this is dropped before
+/// the command is waited for.
+#[derive(Debug)]
+pub struct Process {
+    child: Child,
+    group: ProcessGroupGuard,
+}
+
+impl Process {
+    pub async fn wait(&mut self) -> io::Result<ExitStatus> {
+        let status = self.child.wait().await?;
+        self.group.disarm();
+        Ok(status)
+    }
+
+    pub async fn wait_with_output(self) -> io::Result<Output> {
+        let Self { child, mut group } = self;
+        let output = child.wait_with_output().await?;
+        group.disarm();
+        Ok(output)
+    }
+}
+
+impl Deref for Process {
+    type Target = Child;
+
+    fn deref(&self) -> &Child {
+        &self.child
+    }
+}
+
+impl DerefMut for Process {
+    fn deref_mut(&mut self) -> &mut Child {
+        &mut self.child
+    }
+}
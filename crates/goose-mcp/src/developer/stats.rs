@@ -0,0 +1,164 @@
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use indoc::indoc;
+use mcp_core::handler::ToolError;
+use rmcp::model::{Content, Role, Tool, ToolAnnotations};
+use rmcp::object;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use super::telemetry::Outcome;
+use super::DeveloperRouter;
+
+/// The URI the usage statistics are listed under in the extension's resources
+pub const STATS_URI: &str = "goose://developer/stats";
+
+/// Rough bytes per token, to show where the context window goes
+const BYTES_PER_TOKEN: u64 = 4;
+
+/// Totals for one tool
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct ToolStats {
+    pub calls: u64,
+    pub errors: u64,
+    pub total_ms: u64,
+    pub max_ms: u64,
+    /// Size of the content returned by successful calls
+    pub bytes: u64,
+}
+
+impl ToolStats {
+    fn add(&mut self, elapsed: Duration, outcome: &Outcome) {
+        let ms = elapsed.as_millis() as u64;
+        self.calls += 1;
+        self.total_ms += ms;
+        self.max_ms = self.max_ms.max(ms);
+        match outcome.error {
+            Some(_) => self.errors += 1,
+            None => self.bytes += outcome.bytes,
+        }
+    }
+
+    fn to_json(self) -> Value {
+        json!({
+            "calls": self.calls,
+            "errors": self.errors,
+            "error_rate": self.errors as f64 / self.calls.max(1) as f64,
+            "total_ms": self.total_ms,
+            "mean_ms": self.total_ms / self.calls.max(1),
+            "max_ms": self.max_ms,
+            "bytes": self.bytes,
+            "approx_tokens": self.bytes / BYTES_PER_TOKEN,
+        })
+    }
+}
+
+/// Per-tool call counts, durations, output sizes and errors for this session
+#[derive(Debug, Clone, Default)]
+pub struct UsageStats {
+    tools: Arc<Mutex<BTreeMap<String, ToolStats>>>,
+}
+
+impl UsageStats {
+    pub fn record(&self, tool: &str, elapsed: Duration, outcome: &Outcome) {
+        self.tools
+            .lock()
+            .unwrap()
+            .entry(tool.to_string())
+            .or_default()
+            .add(elapsed, outcome);
+    }
+
+    pub fn snapshot(&self) -> BTreeMap<String, ToolStats> {
+        self.tools.lock().unwrap().clone()
+    }
+
+    /// The statistics as served from the resource, with the totals over all tools
+    pub fn to_json(&self) -> Value {
+        let tools = self.snapshot();
+        let total = tools.values().fold(ToolStats::default(), |mut total, s| {
+            total.calls += s.calls;
+            total.errors += s.errors;
+            total.total_ms += s.total_ms;
+            total.max_ms = total.max_ms.max(s.max_ms);
+            total.bytes += s.bytes;
+            total
+        });
+        json!({
+            "tools": tools
+                .into_iter()
+                .map(|(name, stats)| (name, stats.to_json()))
+                .collect::<serde_json::Map<_, _>>(),
+            "total": total.to_json(),
+        })
+    }
+
+    /// The statistics as a table, the tools that took the longest first
+    pub fn render(&self) -> String {
+        let mut tools: Vec<_> = self.snapshot().into_iter().collect();
+        if tools.is_empty() {
+            return "No tools have been called in this session yet.".to_string();
+        }
+        tools.sort_by(|a, b| b.1.total_ms.cmp(&a.1.total_ms).then(a.0.cmp(&b.0)));
+
+        let mut table = format!(
+            "{:<16} {:>6} {:>7} {:>10} {:>9} {:>9} {:>10}\n",
+            "tool", "calls", "errors", "total", "mean", "max", "~tokens"
+        );
+        for (name, stats) in tools {
+            table.push_str(&format!(
+                "{:<16} {:>6} {:>6.0}% {:>8}ms {:>7}ms {:>7}ms {:>10}\n",
+                name,
+                stats.calls,
+                100.0 * stats.errors as f64 / stats.calls.max(1) as f64,
+                stats.total_ms,
+                stats.total_ms / stats.calls.max(1),
+                stats.max_ms,
+                stats.bytes / BYTES_PER_TOKEN,
+            ));
+        }
+        table
+    }
+}
+
+/// The definition of the `stats` tool
+pub fn tool() -> Tool {
+    Tool::new(
+        "stats",
+        indoc! {r#"
+            Show how the developer tools have been used in this session.
+
+            For each tool: the number of calls, the share that failed, the total, mean and
+            longest duration, and roughly how many tokens its output took up. Use it to see
+            where time and context are going, e.g. before deciding how to continue a long task.
+        "#},
+        object!({
+            "type": "object",
+            "required": [],
+            "properties": {}
+        }),
+    )
+    .annotate(ToolAnnotations {
+        title: Some("Show tool usage statistics".to_string()),
+        read_only_hint: Some(true),
+        destructive_hint: Some(false),
+        idempotent_hint: Some(false),
+        open_world_hint: Some(false),
+    })
+}
+
+impl DeveloperRouter {
+    pub(super) fn stats(&self) -> Result<Vec<Content>, ToolError> {
+        let table = self.stats.render();
+        Ok(vec![
+            Content::text(table.clone()).with_audience(vec![Role::Assistant]),
+            Content::text(table)
+                .with_audience(vec![Role::User])
+                .with_priority(0.0),
+        ])
+    }
+}
@@ -0,0 +1,137 @@
+use rmcp::model::JsonObject;
+use rmcp::object;
+use serde_json::{json, Value};
+
+use super::pagination::Page;
+
+/// The output schema of the tools that return structured results
+pub fn output_schema(tool_name: &str) -> Option<JsonObject> {
+    match tool_name {
+        "shell" => Some(shell_schema()),
+        "glob" => Some(glob_schema()),
+        "grep" => Some(grep_schema()),
+        _ => None,
+    }
+}
+
+fn next_cursor_schema() -> Value {
+    json!({
+        "type": ["string", "null"],
+        "description": "Pass as `cursor` to read the full result in pages; null when it was all returned"
+    })
+}
+
+fn shell_schema() -> JsonObject {
+    object!({
+        "type": "object",
+        "required": ["output"],
+        "properties": {
+            "output": {"type": "string", "description": "Combined stdout and stderr, or the last lines of it when it was long"},
+            "exit_code": {"type": ["integer", "null"], "description": "Null when the command was killed by a signal"},
+            "total_lines": {"type": "integer"},
+            "summarized": {"type": "boolean", "description": "Whether output is a summary of output too large to return"},
+            "next_cursor": next_cursor_schema()
+        }
+    })
+}
+
+fn glob_schema() -> JsonObject {
+    object!({
+        "type": "object",
+        "required": ["files"],
+        "properties": {
+            "files": {"type": "array", "items": {"type": "string"}, "description": "Matching files, newest first"},
+            "total": {"type": "integer", "description": "How many files matched in all pages"},
+            "next_cursor": next_cursor_schema()
+        }
+    })
+}
+
+fn grep_schema() -> JsonObject {
+    object!({
+        "type": "object",
+        "required": ["matches"],
+        "properties": {
+            "matches": {
+                "type": "array",
+                "description": "One entry per line of output: a path, with the line number and text for `path:line:text` lines",
+                "items": {
+                    "type": "object",
+                    "required": ["path"],
+                    "properties": {
+                        "path": {"type": "string"},
+                        "line": {"type": "integer"},
+                        "text": {"type": "string"}
+                    }
+                }
+            },
+            "exit_code": {"type": ["integer", "null"], "description": "1 means nothing matched for rg and grep"},
+            "next_cursor": next_cursor_schema()
+        }
+    })
+}
+
+/// The result of a shell or grep command
+pub fn command_result(
+    tool_name: &str,
+    output: &str,
+    total_lines: usize,
+    exit_code: Option<i32>,
+    next_cursor: Option<&str>,
+) -> Value {
+    match tool_name {
+        "grep" => json!({
+            "matches": output.lines().filter_map(parse_match).collect::<Vec<_>>(),
+            "exit_code": exit_code,
+            "next_cursor": next_cursor,
+        }),
+        _ => json!({
+            "output": output,
+            "exit_code": exit_code,
+            "total_lines": total_lines,
+            "next_cursor": next_cursor,
+        }),
+    }
+}
+
+/// The result of a glob search, or of a page of any paginated tool that has a schema
+pub fn page_result(tool_name: &str, page: &Page) -> Option<Value> {
+    let next_cursor = page.next_cursor.as_deref();
+    match tool_name {
+        "glob" => Some(json!({
+            "files": page.lines,
+            "total": page.total,
+            "next_cursor": next_cursor,
+        })),
+        "grep" => Some(json!({
+            "matches": page.lines.iter().filter_map(|line| parse_match(line)).collect::<Vec<_>>(),
+            "next_cursor": next_cursor,
+        })),
+        "shell" => Some(json!({
+            "output": page.lines.join("\n"),
+            "total_lines": page.total,
+            "next_cursor": next_cursor,
+        })),
+        _ => None,
+    }
+}
+
+/// A line of search output, as printed by `rg`, `grep -rn` or `rg -l`
+fn parse_match(line: &str) -> Option<Value> {
+    if line.trim().is_empty() {
+        return None;
+    }
+    let mut parts = line.splitn(3, ':');
+    let path = parts.next()?;
+    let match_line = parts.next().and_then(|n| n.parse::<u64>().ok());
+    match (match_line, parts.next()) {
+        (Some(line_number), Some(text)) => {
+            Some(json!({"path": path, "line": line_number, "text": text}))
+        }
+        _ => match line.split_once(':') {
+            // `grep -r` without line numbers
+            Some((path, text)) => Some(json!({"path": path, "text": text})),
+            None => Some(json!({"path": line})),
+        },
+    }
+}
@@ -0,0 +1,152 @@
+use std::time::Duration;
+
+use mcp_server::requests::send_request;
+use rmcp::model::JsonRpcMessage;
+use rmcp::object;
+use serde_json::Value;
+use tokio::sync::mpsc;
+
+/// How long to wait for the client's model to write a summary
+const SUMMARY_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// How much of the text the client's model is shown: the start, where headers and the
+/// first errors are, and the end, where results and the final errors are
+const SAMPLE_HEAD_CHARS: usize = 60_000;
+const SAMPLE_TAIL_CHARS: usize = 40_000;
+
+const SUMMARY_MAX_TOKENS: u32 = 1024;
+
+const SYSTEM_PROMPT: &str = "You summarize tool output for a coding agent that could not \
+read all of it. Keep file names, line numbers, error messages, counts and anything that \
+looks like a result. Leave out repeated and routine lines. Reply with the summary only.";
+
+/// Summarizes output too large to return by asking the client's model through MCP
+/// sampling. Enabled with `GOOSE_SUMMARIZE_LARGE_OUTPUT=true`, since a client that doesn't
+/// support sampling may never answer.
+#[derive(Debug, Clone, Default)]
+pub struct Summarizer {
+    enabled: bool,
+}
+
+impl Summarizer {
+    pub fn new(enabled: bool) -> Self {
+        Self { enabled }
+    }
+
+    pub fn from_env() -> Self {
+        let enabled = std::env::var("GOOSE_SUMMARIZE_LARGE_OUTPUT")
+            .map(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "yes"))
+            .unwrap_or(false);
+        Self::new(enabled)
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// A short summary of `text`, which is described by `subject` (e.g. "the output of
+    /// `cargo test`"). None when summaries are off or the client couldn't provide one.
+    pub async fn summarize(
+        &self,
+        notifier: &mpsc::Sender<JsonRpcMessage>,
+        subject: &str,
+        text: &str,
+    ) -> Option<String> {
+        if !self.enabled {
+            return None;
+        }
+
+        let params = object!({
+            "messages": [{
+                "role": "user",
+                "content": {
+                    "type": "text",
+                    "text": format!("Summarize {}:\n\n{}", subject, sample(text)),
+                }
+            }],
+            "systemPrompt": SYSTEM_PROMPT,
+            "includeContext": "none",
+            "maxTokens": SUMMARY_MAX_TOKENS,
+            "modelPreferences": {
+                "speedPriority": 0.8,
+                "intelligencePriority": 0.3
+            }
+        });
+
+        let result = send_request(notifier, "sampling/createMessage", params, SUMMARY_TIMEOUT)
+            .await
+            .map_err(|e| tracing::warn!("Could not summarize {}: {}", subject, e))
+            .ok()?;
+
+        let summary = result
+            .get("content")
+            .filter(|content| content.get("type").and_then(Value::as_str) == Some("text"))
+            .and_then(|content| content.get("text"))
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|summary| !summary.is_empty());
+        if summary.is_none() {
+            tracing::warn!("The client returned no text summary of {}", subject);
+        }
+        summary.map(str::to_string)
+    }
+}
+
+/// The start and end of the text, with a marker where the middle was left out
+fn sample(text: &str) -> String {
+    let char_count = text.chars().count();
+    if char_count <= SAMPLE_HEAD_CHARS + SAMPLE_TAIL_CHARS {
+        return text.to_string();
+    }
+
+    let head: String = text.chars().take(SAMPLE_HEAD_CHARS).collect();
+    let tail: String = text.chars().skip(char_count - SAMPLE_TAIL_CHARS).collect();
+    format!(
+        "{}\n\n[... {} characters left out ...]\n\n{}",
+        head,
+        char_count - SAMPLE_HEAD_CHARS - SAMPLE_TAIL_CHARS,
+        tail
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sample_keeps_the_start_and_end() {
+        assert_eq!(sample("short output"), "short output");
+
+        let text = format!(
+            "{}{}{}",
+            "a".repeat(SAMPLE_HEAD_CHARS),
+            "b".repeat(500),
+            "c".repeat(SAMPLE_TAIL_CHARS)
+        );
+        let sampled = sample(&text);
+        assert!(sampled.starts_with(&"a".repeat(SAMPLE_HEAD_CHARS)));
+        assert!(sampled.ends_with(&"c".repeat(SAMPLE_TAIL_CHARS)));
+        assert!(sampled.contains("[... 500 characters left out ...]"));
+        assert!(!sampled.contains('b'));
+    }
+
+    #[tokio::test]
+    async fn test_no_request_is_sent_when_disabled_or_the_client_is_gone() {
+        let (notifier, mut requests) = mpsc::channel(1);
+        assert_eq!(
+            Summarizer::new(false)
+                .summarize(&notifier, "the output", "text")
+                .await,
+            None
+        );
+        assert!(requests.try_recv().is_err());
+
+        drop(requests);
+        assert_eq!(
+            Summarizer::new(true)
+                .summarize(&notifier, "the output", "text")
+                .await,
+            None
+        );
+    }
+}
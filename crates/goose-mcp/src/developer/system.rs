@@ -0,0 +1,311 @@
+//! The filesystem, clock and processes the developer tools depend on.
+//!
+//! The tools reach them through [`System`] so tests can swap in their own [`FileSystem`] or a
+//! [`ManualClock`], and inject [`Faults`] through the builder: IO errors and permission
+//! failures on file reads, file writes and command spawns, and delays on spawns. Together with a router built for a fixed workspace
+//! root, this lets integration tests exercise failure paths without changing the process's
+//! current directory or running serially.
+
+use std::fmt::Debug;
+use std::io;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// The file operations of the text editor
+pub trait FileSystem: Debug + Send + Sync {
+    fn read_to_string(&self, path: &Path) -> io::Result<String>;
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()>;
+    fn file_size(&self, path: &Path) -> io::Result<u64>;
+}
+
+/// The machine's filesystem
+#[derive(Debug, Default)]
+pub struct RealFileSystem;
+
+impl FileSystem for RealFileSystem {
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        std::fs::read_to_string(path)
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        std::fs::write(path, contents)
+    }
+
+    fn file_size(&self, path: &Path) -> io::Result<u64> {
+        std::fs::metadata(path).map(|metadata| metadata.len())
+    }
+}
+
+/// Where the time tool calls take is measured from
+pub trait Clock: Debug + Send + Sync {
+    fn now(&self) -> Instant;
+}
+
+#[derive(Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// A clock that only moves when told to, so recorded durations are predictable
+#[derive(Debug)]
+pub struct ManualClock {
+    start: Instant,
+    elapsed: Mutex<Duration>,
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        Self {
+            start: Instant::now(),
+            elapsed: Mutex::new(Duration::ZERO),
+        }
+    }
+}
+
+impl ManualClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn advance(&self, by: Duration) {
+        *self.elapsed.lock().unwrap() += by;
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        self.start + *self.elapsed.lock().unwrap()
+    }
+}
+
+/// What a fault applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    Read,
+    Write,
+    Spawn,
+}
+
+/// What happens instead of, or before, the operation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Effect {
+    /// Fail with a generic IO error
+    IoError {
+        message: Option<String>,
+    },
+    PermissionDenied,
+    NotFound,
+    /// Wait before running a command, e.g. to simulate a slow one. File operations are
+    /// synchronous and run on the async runtime, so they aren't delayed.
+    Delay {
+        millis: u64,
+    },
+}
+
+/// A simulated failure, for operations whose path or command contains `target` (any when
+/// it is unset)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fault {
+    pub operation: Operation,
+    pub target: Option<String>,
+    pub effect: Effect,
+}
+
+impl Fault {
+    pub fn new(operation: Operation, target: Option<&str>, effect: Effect) -> Self {
+        Self {
+            operation,
+            target: target.map(str::to_string),
+            effect,
+        }
+    }
+
+    fn applies_to(&self, operation: Operation, target: &str) -> bool {
+        self.operation == operation
+            && self
+                .target
+                .as_deref()
+                .is_none_or(|fault_target| target.contains(fault_target))
+    }
+}
+
+/// The faults to inject, only set through `DeveloperRouterBuilder::with_faults`. None by
+/// default.
+#[derive(Debug, Clone, Default)]
+pub struct Faults {
+    faults: Vec<Fault>,
+}
+
+impl Faults {
+    pub fn new(faults: Vec<Fault>) -> Self {
+        Self { faults }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.faults.is_empty()
+    }
+
+    /// The delay and then the error to simulate for an operation, if any
+    fn lookup(&self, operation: Operation, target: &str) -> (Option<Duration>, Option<io::Error>) {
+        let mut delay = None;
+        for fault in self
+            .faults
+            .iter()
+            .filter(|f| f.applies_to(operation, target))
+        {
+            let error = match &fault.effect {
+                Effect::Delay { millis } => {
+                    delay = Some(Duration::from_millis(*millis));
+                    continue;
+                }
+                Effect::IoError { message } => io::Error::other(
+                    message
+                        .clone()
+                        .unwrap_or_else(|| "injected IO error".to_string()),
+                ),
+                Effect::PermissionDenied => io::Error::from(io::ErrorKind::PermissionDenied),
+                Effect::NotFound => io::Error::from(io::ErrorKind::NotFound),
+            };
+            return (delay, Some(error));
+        }
+        (delay, None)
+    }
+
+    /// Simulate the faults for a file operation, without its delays
+    fn check(&self, operation: Operation, path: &Path) -> io::Result<()> {
+        let (_, error) = self.lookup(operation, &path.to_string_lossy());
+        error.map_or(Ok(()), Err)
+    }
+}
+
+/// A filesystem that fails or slows down the operations matching its faults
+#[derive(Debug)]
+struct FaultyFileSystem {
+    inner: Arc<dyn FileSystem>,
+    faults: Faults,
+}
+
+impl FileSystem for FaultyFileSystem {
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        self.faults.check(Operation::Read, path)?;
+        self.inner.read_to_string(path)
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        self.faults.check(Operation::Write, path)?;
+        self.inner.write(path, contents)
+    }
+
+    fn file_size(&self, path: &Path) -> io::Result<u64> {
+        self.faults.check(Operation::Read, path)?;
+        self.inner.file_size(path)
+    }
+}
+
+/// The filesystem, clock and faults a router works with
+#[derive(Debug, Clone)]
+pub struct System {
+    fs: Arc<dyn FileSystem>,
+    clock: Arc<dyn Clock>,
+    faults: Faults,
+}
+
+impl Default for System {
+    fn default() -> Self {
+        Self::new(
+            Arc::new(RealFileSystem),
+            Arc::new(SystemClock),
+            Faults::default(),
+        )
+    }
+}
+
+impl System {
+    pub fn new(fs: Arc<dyn FileSystem>, clock: Arc<dyn Clock>, faults: Faults) -> Self {
+        let fs: Arc<dyn FileSystem> = if faults.is_empty() {
+            fs
+        } else {
+            Arc::new(FaultyFileSystem {
+                inner: fs,
+                faults: faults.clone(),
+            })
+        };
+        Self { fs, clock, faults }
+    }
+
+    pub fn fs(&self) -> &dyn FileSystem {
+        self.fs.as_ref()
+    }
+
+    pub fn clock(&self) -> &dyn Clock {
+        self.clock.as_ref()
+    }
+
+    /// Simulate the faults for running `command`, waiting out any delay
+    pub async fn before_spawn(&self, command: &str) -> io::Result<()> {
+        let (delay, error) = self.faults.lookup(Operation::Spawn, command);
+        if let Some(delay) = delay {
+            tokio::time::sleep(delay).await;
+        }
+        error.map_or(Ok(()), Err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_faults_fail_without_blocking() {
+        let faults = Faults::new(vec![
+            Fault::new(
+                Operation::Read,
+                Some("slow"),
+                Effect::Delay { millis: 60_000 },
+            ),
+            Fault::new(Operation::Write, Some(".env"), Effect::PermissionDenied),
+        ]);
+        let fs = FaultyFileSystem {
+            inner: Arc::new(RealFileSystem),
+            faults,
+        };
+
+        let started = Instant::now();
+        let err = fs
+            .read_to_string(Path::new("/no/such/slow/file"))
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        assert!(started.elapsed() < Duration::from_secs(10));
+
+        let err = fs.write(Path::new("/project/.env"), b"KEY=1").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_spawn_faults_wait_asynchronously() {
+        let system = System::new(
+            Arc::new(RealFileSystem),
+            Arc::new(SystemClock),
+            Faults::new(vec![
+                Fault::new(
+                    Operation::Spawn,
+                    Some("sleepy"),
+                    Effect::Delay { millis: 500 },
+                ),
+                Fault::new(Operation::Spawn, Some("broken"), Effect::NotFound),
+            ]),
+        );
+
+        let started = tokio::time::Instant::now();
+        system.before_spawn("sleepy --long").await.unwrap();
+        assert_eq!(started.elapsed(), Duration::from_millis(500));
+        let err = system.before_spawn("broken").await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        system.before_spawn("ls").await.unwrap();
+    }
+}
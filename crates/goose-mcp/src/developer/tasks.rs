@@ -0,0 +1,414 @@
+use std::path::{Path, PathBuf};
+
+use indoc::indoc;
+use mcp_core::handler::ToolError;
+use mcp_server::structured::structured_content;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use rmcp::model::{Content, JsonRpcMessage, Tool, ToolAnnotations};
+use rmcp::object;
+use serde::Serialize;
+use serde_json::{json, Value};
+use tokio::sync::mpsc;
+
+use super::approval::request_approval;
+use super::DeveloperRouter;
+
+/// A make target at the start of a line, which isn't a `:=` assignment, with what follows it
+static MAKE_TARGET: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^([A-Za-z0-9][\w-]*)\s*:(?:[^=]|$)(.*)").unwrap());
+
+/// A just recipe: `@` to run quietly, the name, its parameters and the colon before its
+/// dependencies
+static JUST_RECIPE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^@?([A-Za-z_][\w-]*)((?:\s+[^:]*)?)\s*:(?:[^=]|$)").unwrap());
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Source {
+    Make,
+    Just,
+    Npm,
+    Cargo,
+}
+
+impl Source {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Make => "make",
+            Self::Just => "just",
+            Self::Npm => "npm",
+            Self::Cargo => "cargo",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Task {
+    pub name: String,
+    pub source: Source,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// The parameters a just recipe takes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parameters: Option<String>,
+    /// The program that runs the task, e.g. pnpm for the scripts of a pnpm project
+    #[serde(skip)]
+    runner: &'static str,
+}
+
+impl Task {
+    fn new(name: &str, source: Source, description: Option<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            source,
+            description: description.filter(|description| !description.is_empty()),
+            parameters: None,
+            runner: source.name(),
+        }
+    }
+
+    /// The shell command that runs the task with `args`
+    pub fn command_line(&self, args: &[String]) -> String {
+        let mut words = vec![self.runner.to_string()];
+        if self.source == Source::Npm {
+            words.push("run".into());
+        }
+        words.push(quote(&self.name));
+        // npm would take the arguments as its own
+        if self.runner == "npm" && !args.is_empty() {
+            words.push("--".into());
+        }
+        words.extend(args.iter().map(|arg| quote(arg)));
+        words.join(" ")
+    }
+}
+
+/// `arg` quoted for a POSIX shell when it has anything the shell would interpret
+pub fn quote(arg: &str) -> String {
+    let plain = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./=:,+@%".contains(c));
+    if plain {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
+}
+
+/// The tasks defined for the project in `dir`, in the order of their files
+pub fn discover(dir: &Path) -> Vec<Task> {
+    let read = |names: &[&str]| {
+        names
+            .iter()
+            .find_map(|name| std::fs::read_to_string(dir.join(name)).ok())
+    };
+    let mut tasks = Vec::new();
+    if let Some(text) = read(&["GNUmakefile", "makefile", "Makefile"]) {
+        tasks.extend(make_targets(&text));
+    }
+    if let Some(text) = read(&["justfile", "Justfile", ".justfile"]) {
+        tasks.extend(just_recipes(&text));
+    }
+    if let Some(text) = read(&["package.json"]) {
+        tasks.extend(npm_scripts(&text, npm_runner(dir)));
+    }
+    if let Some(text) = dir
+        .ancestors()
+        .flat_map(|dir| [dir.join(".cargo/config.toml"), dir.join(".cargo/config")])
+        .find_map(|path| std::fs::read_to_string(path).ok())
+    {
+        tasks.extend(cargo_aliases(&text));
+    }
+    tasks
+}
+
+/// The comment right above line `index`, skipping just's attributes
+fn comment_above(lines: &[&str], index: usize) -> Option<String> {
+    let mut comment = Vec::new();
+    for line in lines[..index].iter().rev() {
+        let line = line.trim();
+        if let Some(text) = line.strip_prefix('#') {
+            comment.push(text.trim_start_matches('#').trim());
+        } else if !(line.starts_with('[') && line.ends_with(']')) {
+            break;
+        }
+    }
+    comment.reverse();
+    (!comment.is_empty()).then(|| comment.join(" "))
+}
+
+/// Targets, described by a `## text` after them or the comment above. Targets that name
+/// files, like `build/app.o` or `%.o`, are left out.
+pub fn make_targets(text: &str) -> Vec<Task> {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut tasks: Vec<Task> = Vec::new();
+    for (index, line) in lines.iter().enumerate() {
+        let Some(captures) = MAKE_TARGET.captures(line) else {
+            continue;
+        };
+        let name = &captures[1];
+        if tasks.iter().any(|task| task.name == name) {
+            continue;
+        }
+        let description = captures[2]
+            .split_once("##")
+            .map(|(_, text)| text.trim().to_string())
+            .or_else(|| comment_above(&lines, index));
+        tasks.push(Task::new(name, Source::Make, description));
+    }
+    tasks
+}
+
+/// Recipes other than private ones, which start with `_` or are marked `[private]`
+pub fn just_recipes(text: &str) -> Vec<Task> {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut tasks = Vec::new();
+    for (index, line) in lines.iter().enumerate() {
+        // An assignment whose value has a colon in it, like `url := "http://localhost"`
+        if line.find(':') == line.find(":=") {
+            continue;
+        }
+        let Some(captures) = JUST_RECIPE.captures(line) else {
+            continue;
+        };
+        let name = &captures[1];
+        let private = name.starts_with('_')
+            || lines[..index]
+                .iter()
+                .rev()
+                .take_while(|line| line.trim().starts_with('['))
+                .any(|line| line.contains("private"));
+        if private {
+            continue;
+        }
+        let mut task = Task::new(name, Source::Just, comment_above(&lines, index));
+        let parameters = captures[2].trim();
+        if !parameters.is_empty() {
+            task.parameters = Some(parameters.to_string());
+        }
+        tasks.push(task);
+    }
+    tasks
+}
+
+/// The scripts of package.json, described by the command each runs
+pub fn npm_scripts(text: &str, runner: &'static str) -> Vec<Task> {
+    let Ok(package) = serde_json::from_str::<serde_json::Value>(text) else {
+        return Vec::new();
+    };
+    let Some(scripts) = package.get("scripts").and_then(|s| s.as_object()) else {
+        return Vec::new();
+    };
+    scripts
+        .iter()
+        .map(|(name, command)| {
+            let mut task = Task::new(name, Source::Npm, command.as_str().map(str::to_string));
+            task.runner = runner;
+            task
+        })
+        .collect()
+}
+
+/// The package manager a project's lockfile belongs to, which its scripts are run with
+fn npm_runner(dir: &Path) -> &'static str {
+    let lockfile = |name: &str| dir.ancestors().any(|dir| dir.join(name).is_file());
+    if lockfile("pnpm-lock.yaml") {
+        "pnpm"
+    } else if lockfile("yarn.lock") {
+        "yarn"
+    } else if lockfile("bun.lockb") || lockfile("bun.lock") {
+        "bun"
+    } else {
+        "npm"
+    }
+}
+
+/// The aliases in the `[alias]` table of cargo's config, described by what they expand to
+pub fn cargo_aliases(text: &str) -> Vec<Task> {
+    let mut tasks = Vec::new();
+    let mut in_aliases = false;
+    for line in text.lines().map(str::trim) {
+        if line.starts_with('[') && !line.starts_with("[\"") {
+            in_aliases = line == "[alias]";
+            continue;
+        }
+        if !in_aliases {
+            continue;
+        }
+        let Some((name, value)) = line.split_once('=') else {
+            continue;
+        };
+        let name = name.trim().trim_matches('"');
+        let value = value.trim();
+        let expansion = match value.strip_prefix('[') {
+            Some(list) => list
+                .trim_end_matches(']')
+                .split(',')
+                .map(|word| word.trim().trim_matches('"'))
+                .filter(|word| !word.is_empty())
+                .collect::<Vec<_>>()
+                .join(" "),
+            None => value.trim_matches('"').to_string(),
+        };
+        tasks.push(Task::new(
+            name,
+            Source::Cargo,
+            Some(format!("cargo {}", expansion)),
+        ));
+    }
+    tasks
+}
+
+/// The task called `name`, from `source` when several files define it
+pub fn find<'a>(tasks: &'a [Task], name: &str, source: Option<&str>) -> Result<&'a Task, String> {
+    let matches: Vec<&Task> = tasks
+        .iter()
+        .filter(|task| task.name == name && source.is_none_or(|s| task.source.name() == s))
+        .collect();
+    match matches.as_slice() {
+        [task] => Ok(task),
+        [] => Err(format!(
+            "No task named '{}'{}, list the tasks to see which there are",
+            name,
+            source.map_or(String::new(), |source| format!(" in {}", source))
+        )),
+        _ => Err(format!(
+            "'{}' is defined by {}, pass `source` to choose one",
+            name,
+            matches
+                .iter()
+                .map(|task| task.source.name())
+                .collect::<Vec<_>>()
+                .join(" and ")
+        )),
+    }
+}
+
+/// Where the project's tasks are defined, `dir` or the closest parent with a task file
+pub fn project_dir(dir: &Path) -> PathBuf {
+    const TASK_FILES: &[&str] = &[
+        "GNUmakefile",
+        "makefile",
+        "Makefile",
+        "justfile",
+        "Justfile",
+        ".justfile",
+        "package.json",
+    ];
+    dir.ancestors()
+        .find(|dir| TASK_FILES.iter().any(|name| dir.join(name).is_file()))
+        .unwrap_or(dir)
+        .to_path_buf()
+}
+
+/// The definition of the `tasks` tool
+pub fn tool() -> Tool {
+    Tool::new(
+        "tasks",
+        indoc! {r#"
+            List the project's tasks, the make targets, just recipes, package.json scripts
+            and cargo aliases with their descriptions, or run one. Prefer these over
+            composing the commands yourself, since they are how the project expects to be
+            built, tested and linted.
+
+            The tasks are read from the files in `path` (default the working directory, or
+            the closest parent with a Makefile, justfile or package.json). `run` runs the
+            task `name` with `args` through the shell, like the shell tool; pass `source`
+            when several files define the same name. Make takes variables as arguments,
+            e.g. ["ARGS=--release"], since anything else is another target.
+        "#},
+        object!({
+            "type": "object",
+            "required": [],
+            "properties": {
+                "command": {"type": "string", "enum": ["list", "run"], "default": "list"},
+                "path": {"type": "string", "description": "Absolute path of the project directory"},
+                "name": {"type": "string", "description": "Task to run"},
+                "source": {"type": "string", "enum": ["make", "just", "npm", "cargo"]},
+                "args": {
+                    "type": "array",
+                    "items": {"type": "string"},
+                    "description": "Arguments passed to the task"
+                }
+            }
+        }),
+    )
+    .annotate(ToolAnnotations {
+        title: Some("Project tasks".to_string()),
+        read_only_hint: Some(false),
+        destructive_hint: Some(true),
+        idempotent_hint: Some(false),
+        open_world_hint: Some(false),
+    })
+}
+
+impl DeveloperRouter {
+    pub(super) async fn tasks(
+        &self,
+        params: Value,
+        notifier: mpsc::Sender<JsonRpcMessage>,
+    ) -> Result<Vec<Content>, ToolError> {
+        let dir = match params.get("path").and_then(Value::as_str) {
+            Some(path) => self.resolve_path(path)?,
+            None => project_dir(&self.cwd()),
+        };
+        if !dir.is_dir() {
+            return Err(ToolError::InvalidParameters(format!(
+                "'{}' is not a directory",
+                dir.display()
+            )));
+        }
+        let found = discover(&dir);
+
+        match params
+            .get("command")
+            .and_then(Value::as_str)
+            .unwrap_or("list")
+        {
+            "list" => {
+                let result = json!({ "path": dir, "tasks": found });
+                let text = serde_json::to_string_pretty(&result).map_err(|e| {
+                    ToolError::ExecutionError(format!("Failed to encode result: {}", e))
+                })?;
+                Ok(vec![Content::text(text), structured_content(&result)])
+            }
+            "run" => {
+                let name = params.get("name").and_then(Value::as_str).ok_or_else(|| {
+                    ToolError::InvalidParameters("Missing 'name' parameter".into())
+                })?;
+                let source = params.get("source").and_then(Value::as_str);
+                let task = find(&found, name, source).map_err(ToolError::InvalidParameters)?;
+                let args: Vec<String> = match params.get("args") {
+                    None | Some(Value::Null) => Vec::new(),
+                    Some(args) => serde_json::from_value(args.clone()).map_err(|_| {
+                        ToolError::InvalidParameters("'args' must be a list of strings".into())
+                    })?,
+                };
+
+                let mut command = task.command_line(&args);
+                if dir != self.cwd() {
+                    command = format!("cd {} && {}", quote(&dir.to_string_lossy()), command);
+                }
+                let mut arguments = json!({ "command": command });
+                // Tasks run as shell commands, so they need the same approval
+                if let Some(action) = self
+                    .approval_policy
+                    .pending_action("shell", &arguments, &self.catalog)
+                    .await
+                {
+                    request_approval(&notifier, &action).await?;
+                }
+                if let Some(meta) = params.get("_meta") {
+                    arguments["_meta"] = meta.clone();
+                }
+                self.bash("shell", arguments, notifier).await
+            }
+            command => Err(ToolError::InvalidParameters(format!(
+                "Unknown command '{}', expected list or run",
+                command
+            ))),
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use std::time::Duration;
+
+use mcp_core::handler::ToolError;
+use opentelemetry::{
+    global,
+    metrics::{Counter, Histogram},
+    KeyValue,
+};
+use rmcp::model::Content;
+
+/// Metrics recorded for every developer tool call. They go to the global meter provider,
+/// so they are only exported when the host process set one up, e.g. for OTLP.
+#[derive(Clone)]
+pub struct ToolMetrics {
+    calls: Counter<u64>,
+    duration: Histogram<f64>,
+    response_bytes: Histogram<u64>,
+}
+
+impl ToolMetrics {
+    pub fn new() -> Self {
+        let meter = global::meter("goose-mcp.developer");
+        Self {
+            calls: meter
+                .u64_counter("goose.tool.calls")
+                .with_description("Developer tool calls, by tool and outcome")
+                .build(),
+            duration: meter
+                .f64_histogram("goose.tool.duration")
+                .with_description("How long developer tool calls took")
+                .with_unit("s")
+                .build(),
+            response_bytes: meter
+                .u64_histogram("goose.tool.response_bytes")
+                .with_description("Size of the content developer tools returned")
+                .with_unit("By")
+                .build(),
+        }
+    }
+
+    /// Record a finished call, with the error code as its error class when it failed
+    pub fn record(
+        &self,
+        tool: &str,
+        elapsed: Duration,
+        result: &Result<Vec<Content>, ToolError>,
+    ) -> Outcome {
+        let outcome = Outcome::of(result);
+        let mut attributes = vec![
+            KeyValue::new("tool", tool.to_string()),
+            KeyValue::new(
+                "outcome",
+                if outcome.error.is_some() {
+                    "error"
+                } else {
+                    "ok"
+                },
+            ),
+        ];
+        if let Some(code) = &outcome.error {
+            attributes.push(KeyValue::new("error.type", code.clone()));
+        }
+
+        self.calls.add(1, &attributes);
+        self.duration.record(elapsed.as_secs_f64(), &attributes);
+        if outcome.error.is_none() {
+            self.response_bytes.record(outcome.bytes, &attributes);
+        }
+        outcome
+    }
+}
+
+impl Default for ToolMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// What a tool call produced, for the metrics and the call's span
+pub struct Outcome {
+    pub bytes: u64,
+    pub error: Option<String>,
+}
+
+impl Outcome {
+    fn of(result: &Result<Vec<Content>, ToolError>) -> Self {
+        match result {
+            Ok(content) => Self {
+                bytes: serde_json::to_vec(content)
+                    .map(|bytes| bytes.len() as u64)
+                    .unwrap_or_default(),
+                error: None,
+            },
+            Err(e) => Self {
+                bytes: 0,
+                error: Some(e.code().to_string()),
+            },
+        }
+    }
+}
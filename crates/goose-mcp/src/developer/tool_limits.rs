@@ -0,0 +1,144 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use mcp_core::handler::ToolError;
+use serde::Deserialize;
+use serde_json::json;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use super::error_codes::{CONCURRENCY_LIMITED, RATE_LIMITED};
+
+const WINDOW: Duration = Duration::from_secs(60);
+
+/// How often and how many at once a tool may run
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub struct ToolLimit {
+    pub calls_per_minute: Option<u32>,
+    pub max_concurrent: Option<usize>,
+}
+
+/// Limits that apply unless `GOOSE_TOOL_LIMITS` overrides them: enough for any real task,
+/// but a runaway loop can't fork shells or grab the screen without bound
+const DEFAULT_LIMITS: &[(&str, ToolLimit)] = &[
+    (
+        "shell",
+        ToolLimit {
+            calls_per_minute: None,
+            max_concurrent: Some(8),
+        },
+    ),
+    (
+        "screen_capture",
+        ToolLimit {
+            calls_per_minute: Some(30),
+            max_concurrent: Some(1),
+        },
+    ),
+];
+
+struct Limiter {
+    limit: ToolLimit,
+    concurrency: Option<Arc<Semaphore>>,
+    recent_calls: Mutex<VecDeque<Instant>>,
+}
+
+/// Per-tool call limits. A call over a limit fails straight away with `RATE_LIMITED` or
+/// `CONCURRENCY_LIMITED` rather than queueing, so the agent learns to slow down.
+#[derive(Clone, Default)]
+pub struct ToolLimits {
+    limiters: Arc<HashMap<String, Limiter>>,
+}
+
+impl ToolLimits {
+    pub fn new(limits: HashMap<String, ToolLimit>) -> Self {
+        let limiters = limits
+            .into_iter()
+            .map(|(tool, limit)| {
+                let limiter = Limiter {
+                    limit,
+                    concurrency: limit
+                        .max_concurrent
+                        .map(|max| Arc::new(Semaphore::new(max.max(1)))),
+                    recent_calls: Mutex::new(VecDeque::new()),
+                };
+                (tool, limiter)
+            })
+            .collect();
+        Self {
+            limiters: Arc::new(limiters),
+        }
+    }
+
+    /// The default limits, with any tool's limits replaced by those in `GOOSE_TOOL_LIMITS`,
+    /// a JSON object such as `{"shell": {"max_concurrent": 2, "calls_per_minute": 60}}`
+    pub fn from_env() -> Self {
+        let mut limits: HashMap<String, ToolLimit> = DEFAULT_LIMITS
+            .iter()
+            .map(|(tool, limit)| (tool.to_string(), *limit))
+            .collect();
+        if let Ok(value) = std::env::var("GOOSE_TOOL_LIMITS") {
+            match serde_json::from_str::<HashMap<String, ToolLimit>>(&value) {
+                Ok(overrides) => limits.extend(overrides),
+                Err(e) => tracing::warn!("Ignoring invalid GOOSE_TOOL_LIMITS: {}", e),
+            }
+        }
+        Self::new(limits)
+    }
+
+    /// Check the tool's limits before it runs. The returned permit holds its concurrency
+    /// slot until the call finishes and the permit is dropped.
+    pub fn acquire(&self, tool_name: &str) -> Result<Option<OwnedSemaphorePermit>, ToolError> {
+        let Some(limiter) = self.limiters.get(tool_name) else {
+            return Ok(None);
+        };
+
+        let permit = match (&limiter.concurrency, limiter.limit.max_concurrent) {
+            (Some(semaphore), Some(max)) => {
+                Some(Arc::clone(semaphore).try_acquire_owned().map_err(|_| {
+                    ToolError::coded(
+                        CONCURRENCY_LIMITED,
+                        format!(
+                            "{} is already running {} times, wait for a call to finish",
+                            tool_name, max
+                        ),
+                        Some(json!({ "tool": tool_name, "limit": max })),
+                    )
+                })?)
+            }
+            _ => None,
+        };
+
+        if let Some(limit) = limiter.limit.calls_per_minute.filter(|limit| *limit > 0) {
+            let mut calls = limiter.recent_calls.lock().unwrap();
+            let now = Instant::now();
+            while calls
+                .front()
+                .is_some_and(|call| now.duration_since(*call) >= WINDOW)
+            {
+                calls.pop_front();
+            }
+            if calls.len() >= limit as usize {
+                // Full window, so the front entry exists
+                let retry_after = (calls[0] + WINDOW - now).as_secs_f64().ceil();
+                return Err(ToolError::coded(
+                    RATE_LIMITED,
+                    format!(
+                        "{} can be called {} times per minute, try again in {} seconds",
+                        tool_name, limit, retry_after
+                    ),
+                    Some(json!({
+                        "tool": tool_name,
+                        "limit": limit,
+                        "retry_after_secs": retry_after
+                    })),
+                ));
+            }
+            calls.push_back(now);
+        }
+
+        Ok(permit)
+    }
+}
@@ -0,0 +1,62 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use etcetera::{choose_app_strategy, AppStrategy};
+use rmcp::model::Prompt;
+
+use super::{load_prompt_files, parse_prompt};
+
+/// Where prompt templates are read from besides the built-in ones: the global
+/// `~/.config/goose/prompts/` and then the project's `.goose/prompts/`
+pub fn prompt_dirs(cwd: &Path) -> Vec<PathBuf> {
+    let global = choose_app_strategy(crate::APP_STRATEGY.clone())
+        .map(|strategy| strategy.in_config_dir("prompts"))
+        .unwrap_or_else(|_| {
+            PathBuf::from(shellexpand::tilde("~/.config/goose/prompts").to_string())
+        });
+    vec![global, cwd.join(".goose").join("prompts")]
+}
+
+/// The `.json` prompt templates in the directories, in a stable order
+pub fn prompt_file_paths(dirs: &[PathBuf]) -> Vec<PathBuf> {
+    dirs.iter()
+        .flat_map(|dir| {
+            let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)
+                .into_iter()
+                .flatten()
+                .filter_map(|entry| entry.ok().map(|e| e.path()))
+                .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "json"))
+                .collect();
+            paths.sort();
+            paths
+        })
+        .collect()
+}
+
+/// The built-in prompts together with those from the prompt directories. A user prompt
+/// with the same name as an earlier one replaces it, so a project can override a global
+/// prompt and either can override a built-in one.
+pub fn load_prompts(dirs: &[PathBuf]) -> HashMap<String, Prompt> {
+    let mut prompts = load_prompt_files();
+
+    for path in prompt_file_paths(dirs) {
+        let prompt = match std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|source| parse_prompt(&source).map_err(|e| e.to_string()))
+        {
+            Ok(prompt) => prompt,
+            Err(e) => {
+                tracing::warn!("Failed to load prompt template {}: {}", path.display(), e);
+                continue;
+            }
+        };
+        if prompts.contains_key(&prompt.name) {
+            tracing::info!("Prompt '{}' overridden by {}", prompt.name, path.display());
+        }
+        prompts.insert(prompt.name.clone(), prompt);
+    }
+
+    prompts
+}
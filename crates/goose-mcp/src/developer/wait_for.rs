@@ -0,0 +1,290 @@
+use std::time::{Duration, Instant};
+
+use indoc::indoc;
+use mcp_core::handler::ToolError;
+use mcp_server::structured::structured_content;
+use reqwest::redirect;
+use rmcp::model::{Content, JsonRpcMessage, Tool, ToolAnnotations};
+use rmcp::object;
+use serde::Serialize;
+use serde_json::Value;
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+use url::Url;
+
+use super::http_request::{DomainAllowlist, ALLOWED_DOMAINS_KEY};
+use super::progress::ProgressReporter;
+use super::DeveloperRouter;
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+const MAX_TIMEOUT: Duration = Duration::from_secs(600);
+const DEFAULT_INTERVAL: Duration = Duration::from_millis(500);
+const MIN_INTERVAL: Duration = Duration::from_millis(50);
+
+/// The longest a single attempt may take, so a hung connection doesn't use up the timeout
+const ATTEMPT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// What to wait for
+#[derive(Debug, Clone, PartialEq)]
+pub enum Target {
+    /// A TCP port accepting connections
+    Port { host: String, port: u16 },
+    /// An HTTP endpoint answering with the expected status, and text if given
+    Http {
+        url: Url,
+        status: Option<u16>,
+        body_contains: Option<String>,
+    },
+}
+
+impl Target {
+    pub fn from_params(params: &Value) -> Result<Self, ToolError> {
+        let arg = |name: &str| params.get(name).and_then(Value::as_str);
+        match (arg("url"), params.get("port").and_then(Value::as_u64)) {
+            (Some(url), None) => {
+                let url = Url::parse(url).map_err(|e| {
+                    ToolError::InvalidParameters(format!("Invalid URL '{}': {}", url, e))
+                })?;
+                let status = params
+                    .get("status")
+                    .and_then(Value::as_u64)
+                    .map(|status| {
+                        u16::try_from(status)
+                            .ok()
+                            .filter(|status| (100..600).contains(status))
+                            .ok_or_else(|| {
+                                ToolError::InvalidParameters(format!("Invalid status {}", status))
+                            })
+                    })
+                    .transpose()?;
+                Ok(Self::Http {
+                    url,
+                    status,
+                    body_contains: arg("body_contains").map(str::to_string),
+                })
+            }
+            (None, Some(port)) => Ok(Self::Port {
+                host: arg("host").unwrap_or("localhost").to_string(),
+                port: u16::try_from(port)
+                    .ok()
+                    .filter(|port| *port > 0)
+                    .ok_or_else(|| {
+                        ToolError::InvalidParameters(format!("Invalid port {}", port))
+                    })?,
+            }),
+            _ => Err(ToolError::InvalidParameters(
+                "Pass either `port` or `url`".into(),
+            )),
+        }
+    }
+
+    /// The URL the allowlist checks, which for a port is its host over http
+    fn allowlist_url(&self) -> Option<Url> {
+        match self {
+            Self::Port { host, .. } => Url::parse(&format!("http://{}", host)).ok(),
+            Self::Http { url, .. } => Some(url.clone()),
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Port { host, port } => format!("{}:{}", host, port),
+            Self::Http { url, .. } => url.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Ready {
+    pub target: String,
+    pub attempts: u32,
+    pub elapsed_ms: u64,
+    /// The status the endpoint answered with, for HTTP
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+}
+
+/// Poll `target` every `interval` until it is ready, failing once `timeout` has passed with
+/// what the last attempt ran into
+pub async fn wait(
+    target: &Target,
+    allowlist: &DomainAllowlist,
+    timeout: Duration,
+    interval: Duration,
+    progress: Option<&ProgressReporter>,
+) -> Result<Ready, ToolError> {
+    // Waiting on a host is probing it, so the hosts are the ones HTTP requests may go to
+    if !target
+        .allowlist_url()
+        .is_some_and(|url| allowlist.allows(&url))
+    {
+        return Err(ToolError::ExecutionError(format!(
+            "Waiting for '{}' isn't allowed. The allowed hosts are {}; ask the user to add it to {}.",
+            target.describe(),
+            allowlist.domains().join(", "),
+            ALLOWED_DOMAINS_KEY
+        )));
+    }
+
+    let client = reqwest::Client::builder()
+        .timeout(ATTEMPT_TIMEOUT)
+        .redirect(redirect::Policy::none())
+        .build()
+        .map_err(|e| ToolError::ExecutionError(format!("Failed to create HTTP client: {}", e)))?;
+    let started = Instant::now();
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        let attempt = tokio::time::timeout(ATTEMPT_TIMEOUT, probe(target, &client)).await;
+        let last_error = match attempt {
+            Ok(Ok(status)) => {
+                return Ok(Ready {
+                    target: target.describe(),
+                    attempts,
+                    elapsed_ms: started.elapsed().as_millis() as u64,
+                    status,
+                })
+            }
+            Ok(Err(e)) => e,
+            Err(_) => format!("no answer within {}s", ATTEMPT_TIMEOUT.as_secs()),
+        };
+
+        let elapsed = started.elapsed();
+        if let Some(progress) = progress {
+            progress.report(elapsed.as_secs_f64(), Some(timeout.as_secs_f64()));
+        }
+        if elapsed + interval > timeout {
+            return Err(ToolError::ExecutionError(format!(
+                "{} wasn't ready after {}s and {} attempts: {}",
+                target.describe(),
+                elapsed.as_secs(),
+                attempts,
+                last_error
+            )));
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
+
+/// One attempt, returning the HTTP status when ready, or why not
+async fn probe(target: &Target, client: &reqwest::Client) -> Result<Option<u16>, String> {
+    match target {
+        Target::Port { host, port } => {
+            TcpStream::connect((host.as_str(), *port))
+                .await
+                .map_err(|e| e.to_string())?;
+            Ok(None)
+        }
+        Target::Http {
+            url,
+            status,
+            body_contains,
+        } => {
+            let response = client
+                .get(url.clone())
+                .send()
+                .await
+                .map_err(|e| e.to_string())?;
+            let actual = response.status();
+            let expected = match status {
+                Some(status) => actual.as_u16() == *status,
+                None => actual.is_success(),
+            };
+            if !expected {
+                return Err(format!("the endpoint answered {}", actual));
+            }
+            if let Some(text) = body_contains {
+                let body = response.text().await.map_err(|e| e.to_string())?;
+                if !body.contains(text.as_str()) {
+                    return Err(format!("the response doesn't contain '{}'", text));
+                }
+            }
+            Ok(Some(actual.as_u16()))
+        }
+    }
+}
+
+/// The timeout and polling interval from the tool's parameters
+pub fn durations(params: &Value) -> (Duration, Duration) {
+    let timeout = params
+        .get("timeout_secs")
+        .and_then(Value::as_u64)
+        .map_or(DEFAULT_TIMEOUT, Duration::from_secs)
+        .clamp(Duration::from_secs(1), MAX_TIMEOUT);
+    let interval = params
+        .get("interval_ms")
+        .and_then(Value::as_u64)
+        .map_or(DEFAULT_INTERVAL, Duration::from_millis)
+        .clamp(MIN_INTERVAL, timeout);
+    (timeout, interval)
+}
+
+/// The definition of the `wait_for` tool
+pub fn tool() -> Tool {
+    Tool::new(
+        "wait_for",
+        indoc! {r#"
+            Wait until a TCP port accepts connections or an HTTP endpoint is healthy, e.g.
+            after starting a server in the background and before running tests against it.
+            Use this instead of sleeping for a guessed duration.
+
+            Pass `port` (with `host`, default localhost) or `url`. A URL is ready when it
+            answers with a 2xx status, or `status` if given, and its body contains
+            `body_contains` if given. It is checked every `interval_ms` (default 500) until
+            `timeout_secs` (default 30, at most 600) have passed, and fails with the last
+            attempt's error. Only the hosts HTTP requests may go to can be waited for.
+        "#},
+        object!({
+            "type": "object",
+            "required": [],
+            "properties": {
+                "port": {"type": "integer", "minimum": 1, "maximum": 65535},
+                "host": {"type": "string", "description": "Host of the port, default localhost"},
+                "url": {"type": "string", "description": "HTTP(S) health endpoint"},
+                "status": {"type": "integer", "description": "Status the URL must answer with"},
+                "body_contains": {"type": "string", "description": "Text the URL's response must contain"},
+                "timeout_secs": {"type": "integer", "minimum": 1, "maximum": 600},
+                "interval_ms": {"type": "integer", "minimum": 50}
+            }
+        }),
+    )
+    .annotate(ToolAnnotations {
+        title: Some("Wait for a service".to_string()),
+        read_only_hint: Some(true),
+        destructive_hint: Some(false),
+        idempotent_hint: Some(true),
+        open_world_hint: Some(true),
+    })
+}
+
+impl DeveloperRouter {
+    pub(super) async fn wait_for(
+        &self,
+        params: Value,
+        notifier: &mpsc::Sender<JsonRpcMessage>,
+    ) -> Result<Vec<Content>, ToolError> {
+        let target = Target::from_params(&params)?;
+        let (timeout, interval) = durations(&params);
+        let progress = ProgressReporter::requested(&params, notifier, || {
+            format!("Waiting for {}", target.describe())
+        });
+        let ready = wait(
+            &target,
+            &DomainAllowlist::load(),
+            timeout,
+            interval,
+            progress.as_ref(),
+        )
+        .await?;
+
+        let result = serde_json::to_value(&ready)
+            .map_err(|e| ToolError::ExecutionError(format!("Failed to encode result: {}", e)))?;
+        Ok(vec![
+            Content::text(format!(
+                "{} was ready after {}ms and {} attempts",
+                ready.target, ready.elapsed_ms, ready.attempts
+            )),
+            structured_content(&result),
+        ])
+    }
+}
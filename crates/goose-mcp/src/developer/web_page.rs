@@ -0,0 +1,401 @@
+//! Fetching web pages and converting them to markdown.
+//!
+//! Pages are parsed and converted by htmd. Navigation, scripts, forms and other boilerplate
+//! are removed from the tree first, and when a page marks its content with `<main>` or
+//! `<article>` only that is converted.
+
+use std::rc::Rc;
+use std::time::Duration;
+
+use htmd::options::{BulletListMarker, Options};
+use htmd::HtmlToMarkdown;
+use indoc::indoc;
+use markup5ever_rcdom::{Node, NodeData};
+use mcp_core::handler::ToolError;
+use reqwest::header::CONTENT_TYPE;
+use rmcp::model::{Content, Role, Tool, ToolAnnotations};
+use rmcp::object;
+use serde_json::Value;
+use url::Url;
+
+use super::http_request::DomainAllowlist;
+use super::DeveloperRouter;
+
+/// Config key listing the hosts pages may be fetched from, like
+/// [`super::http_request::ALLOWED_DOMAINS_KEY`]. Any public host when it isn't set.
+pub const ALLOWED_DOMAINS_KEY: &str = "GOOSE_FETCH_ALLOWED_DOMAINS";
+
+/// Most bytes of a page that are downloaded
+const MAX_DOWNLOAD_BYTES: usize = 5 * 1024 * 1024;
+
+const TIMEOUT: Duration = Duration::from_secs(30);
+const MAX_REDIRECTS: usize = 10;
+
+/// Elements whose content is never part of the page's text
+const SKIPPED: &[&str] = &[
+    "script", "style", "noscript", "template", "svg", "canvas", "iframe", "object", "nav", "aside",
+    "form", "button", "select", "input", "textarea", "dialog", "head",
+];
+
+/// Elements that are only boilerplate when they belong to the whole page rather than the
+/// content, e.g. a site header versus an article's header
+const PAGE_CHROME: &[&str] = &["header", "footer"];
+
+/// Roles of landmarks around the content
+const SKIPPED_ROLES: &[&str] = &[
+    "navigation",
+    "banner",
+    "contentinfo",
+    "complementary",
+    "search",
+    "dialog",
+];
+
+/// Class and id fragments of cookie banners, sidebars and the like
+const SKIPPED_NAMES: &[&str] = &["cookie", "sidebar", "breadcrumb", "skip-link", "advert"];
+
+/// A page as markdown
+#[derive(Debug, Clone, PartialEq)]
+pub struct Page {
+    pub title: Option<String>,
+    /// Where the page was found, after any redirects
+    pub url: String,
+    pub markdown: String,
+}
+
+/// Download `url` and convert it to markdown. Plain text and markdown are returned as they
+/// are. The URL and every redirect must be to a host in `allowlist`.
+pub async fn fetch(url: &str, allowlist: &DomainAllowlist) -> Result<Page, ToolError> {
+    let url = Url::parse(url)
+        .map_err(|e| ToolError::InvalidParameters(format!("Invalid URL '{}': {}", url, e)))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(ToolError::InvalidParameters(format!(
+            "Only http and https pages can be fetched, not {}",
+            url.scheme()
+        )));
+    }
+    allowlist.check(&url)?;
+
+    let client = allowlist
+        .client_builder(MAX_REDIRECTS)
+        .timeout(TIMEOUT)
+        .user_agent(concat!("goose/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .map_err(|e| ToolError::ExecutionError(format!("Failed to create HTTP client: {}", e)))?;
+    let mut response = client
+        .get(url)
+        .header("Accept", "text/html, text/markdown;q=0.9, text/plain;q=0.8")
+        .send()
+        .await
+        .map_err(|e| ToolError::ExecutionError(format!("Failed to fetch the page: {}", e)))?;
+    if !response.status().is_success() {
+        return Err(ToolError::ExecutionError(format!(
+            "Fetching {} failed with status {}",
+            response.url(),
+            response.status()
+        )));
+    }
+
+    let final_url = response.url().clone();
+    let content_type = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("text/html")
+        .to_lowercase();
+    let is_html = content_type.contains("html");
+    if !is_html && !content_type.starts_with("text/") && !content_type.contains("json") {
+        return Err(ToolError::ExecutionError(format!(
+            "{} is {}, not a page that can be read as text",
+            final_url, content_type
+        )));
+    }
+
+    let mut body = Vec::new();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| ToolError::ExecutionError(format!("Failed to read the page: {}", e)))?
+    {
+        body.extend_from_slice(&chunk);
+        if body.len() >= MAX_DOWNLOAD_BYTES {
+            body.truncate(MAX_DOWNLOAD_BYTES);
+            break;
+        }
+    }
+    let body = String::from_utf8_lossy(&body);
+
+    if !is_html {
+        return Ok(Page {
+            title: None,
+            url: final_url.to_string(),
+            markdown: body.into_owned(),
+        });
+    }
+    let (title, markdown) = to_markdown(&body, &final_url);
+    Ok(Page {
+        title,
+        url: final_url.to_string(),
+        markdown,
+    })
+}
+
+/// The page's title and its content as markdown, with links resolved against `base`
+pub fn to_markdown(html: &str, base: &Url) -> (Option<String>, String) {
+    let converter = HtmlToMarkdown::builder()
+        .options(Options {
+            bullet_list_marker: BulletListMarker::Dash,
+            ul_bullet_spacing: 1,
+            ..Default::default()
+        })
+        .build();
+    let Ok(document) = converter.html_to_tree(html) else {
+        return (None, String::new());
+    };
+    let title = find(&document, "title")
+        .map(|title| collapse_whitespace(&text(&title)))
+        .filter(|title| !title.is_empty());
+
+    // The content the page marks as such, or else the whole body
+    let mut articles = Vec::new();
+    find_all(&document, "article", &mut articles);
+    let (content, in_content) = find(&document, "main")
+        .or_else(|| {
+            articles
+                .into_iter()
+                .max_by_key(|article| text(article).trim().len())
+        })
+        .map(|content| (content, true))
+        .or_else(|| find(&document, "body").map(|body| (body, false)))
+        .unwrap_or_else(|| (Rc::clone(&document), false));
+
+    prepare(&content, base, in_content);
+    let markdown = converter.tree_to_markdown(&content);
+    // Dropping the document empties every node under it, so it has to outlive the conversion
+    drop(document);
+    (title, markdown.trim().to_string())
+}
+
+fn element_name(node: &Node) -> Option<&str> {
+    match &node.data {
+        NodeData::Element { name, .. } => Some(&name.local),
+        _ => None,
+    }
+}
+
+fn attr(node: &Node, name: &str) -> Option<String> {
+    match &node.data {
+        NodeData::Element { attrs, .. } => attrs
+            .borrow()
+            .iter()
+            .find(|attr| &*attr.name.local == name)
+            .map(|attr| attr.value.to_string()),
+        _ => None,
+    }
+}
+
+/// The first element named `name` in document order
+fn find(node: &Rc<Node>, name: &str) -> Option<Rc<Node>> {
+    node.children.borrow().iter().find_map(|child| {
+        if element_name(child) == Some(name) {
+            Some(Rc::clone(child))
+        } else {
+            find(child, name)
+        }
+    })
+}
+
+fn find_all(node: &Rc<Node>, name: &str, found: &mut Vec<Rc<Node>>) {
+    for child in node.children.borrow().iter() {
+        if element_name(child) == Some(name) {
+            found.push(Rc::clone(child));
+        }
+        find_all(child, name, found);
+    }
+}
+
+fn text(node: &Node) -> String {
+    let mut text = String::new();
+    for child in node.children.borrow().iter() {
+        match &child.data {
+            NodeData::Text { contents } => text.push_str(&contents.borrow()),
+            NodeData::Element { .. } => text.push_str(&self::text(child)),
+            _ => {}
+        }
+    }
+    text
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn is_boilerplate(node: &Node, in_content: bool) -> bool {
+    let Some(name) = element_name(node) else {
+        return matches!(node.data, NodeData::Comment { .. });
+    };
+    if SKIPPED.contains(&name)
+        || (!in_content && PAGE_CHROME.contains(&name))
+        || attr(node, "hidden").is_some()
+        || attr(node, "aria-hidden").as_deref() == Some("true")
+        || attr(node, "role").is_some_and(|role| SKIPPED_ROLES.contains(&role.as_str()))
+    {
+        return true;
+    }
+    let names = format!(
+        "{} {}",
+        attr(node, "class").unwrap_or_default(),
+        attr(node, "id").unwrap_or_default()
+    )
+    .to_lowercase();
+    SKIPPED_NAMES.iter().any(|name| names.contains(name))
+}
+
+/// Remove the boilerplate under `node` and make its links absolute
+fn prepare(node: &Rc<Node>, base: &Url, in_content: bool) {
+    node.children
+        .borrow_mut()
+        .retain(|child| !is_boilerplate(child, in_content));
+    for child in node.children.borrow().iter() {
+        if let NodeData::Element { attrs, .. } = &child.data {
+            attrs.borrow_mut().retain_mut(|attr| {
+                if !matches!(&*attr.name.local, "href" | "src") {
+                    return true;
+                }
+                // Scripts and links within the page mean nothing outside it
+                if attr.value.starts_with("javascript:") || attr.value.starts_with('#') {
+                    return false;
+                }
+                match base.join(&attr.value) {
+                    Ok(url) => {
+                        attr.value = url.as_str().into();
+                        true
+                    }
+                    Err(_) => false,
+                }
+            });
+        }
+        prepare(child, base, in_content);
+    }
+}
+
+/// At most `max_chars` characters of `markdown` from character `offset`, ending at a line
+/// break when there is one in the last fifth. Returns the text and where the next part
+/// starts, if there is more.
+pub fn budget(markdown: &str, offset: usize, max_chars: usize) -> (&str, Option<usize>) {
+    let start = markdown
+        .char_indices()
+        .nth(offset)
+        .map_or(markdown.len(), |(index, _)| index);
+    let rest = &markdown[start..];
+    let Some((cut, _)) = rest.char_indices().nth(max_chars) else {
+        return (rest, None);
+    };
+    let cut = rest[..cut]
+        .rfind('\n')
+        .filter(|&newline| newline >= cut * 4 / 5)
+        .map_or(cut, |newline| newline + 1);
+    let part = &rest[..cut];
+    (part, Some(offset + part.chars().count()))
+}
+
+/// The definition of the `fetch_page` tool
+pub fn tool() -> Tool {
+    Tool::new(
+        "fetch_page",
+        indoc! {r#"
+            Fetch a web page and return its content as markdown, e.g. to read documentation or
+            look up an error message.
+
+            Navigation, scripts, forms and other boilerplate are left out, and when the page
+            marks its main content only that is returned. Links are made absolute. Plain text
+            and markdown pages are returned as they are. Only hosts the user allows can be
+            fetched, by default any public host but nothing local, and redirects must stay
+            on them.
+
+            At most `max_length` characters are returned (default 20000). When the page is
+            longer, the result says which `offset` to call again with for the next part.
+        "#},
+        object!({
+            "type": "object",
+            "required": ["url"],
+            "properties": {
+                "url": {"type": "string", "description": "An http or https URL"},
+                "max_length": {"type": "integer", "description": "Most characters to return, default 20000, at most 100000"},
+                "offset": {"type": "integer", "description": "The character to start from, to read the rest of a long page"}
+            }
+        }),
+    )
+    .annotate(ToolAnnotations {
+        title: Some("Fetch a web page as markdown".to_string()),
+        read_only_hint: Some(true),
+        destructive_hint: Some(false),
+        idempotent_hint: Some(true),
+        open_world_hint: Some(true),
+    })
+}
+
+impl DeveloperRouter {
+    pub(super) async fn fetch_page(&self, params: Value) -> Result<Vec<Content>, ToolError> {
+        const DEFAULT_MAX_LENGTH: u64 = 20_000;
+        const MAX_LENGTH: u64 = 100_000;
+
+        let url = params
+            .get("url")
+            .and_then(Value::as_str)
+            .ok_or_else(|| ToolError::InvalidParameters("Missing 'url' parameter".into()))?;
+        let max_length = params
+            .get("max_length")
+            .and_then(Value::as_u64)
+            .unwrap_or(DEFAULT_MAX_LENGTH)
+            .clamp(1, MAX_LENGTH) as usize;
+        let offset = params.get("offset").and_then(Value::as_u64).unwrap_or(0) as usize;
+
+        let page = fetch(url, &DomainAllowlist::load_or_public(ALLOWED_DOMAINS_KEY)).await?;
+        let total = page.markdown.chars().count();
+        if offset > 0 && offset >= total {
+            return Err(ToolError::InvalidParameters(format!(
+                "The page only has {} characters",
+                total
+            )));
+        }
+        let (part, next) = budget(&page.markdown, offset, max_length);
+
+        let mut text = match &page.title {
+            Some(title) => format!("# {}\n\nSource: {}\n\n", title, page.url),
+            None => format!("Source: {}\n\n", page.url),
+        };
+        text.push_str(part.trim_end());
+        if let Some(next) = next {
+            text.push_str(&format!(
+                "\n\n[characters {}-{} of {}, call fetch_page again with \"offset\": {} for more]",
+                offset + 1,
+                next,
+                total,
+                next
+            ));
+        }
+        let text = self.redactor.redact(&text).into_owned();
+        Ok(vec![
+            Content::text(text.clone()).with_audience(vec![Role::Assistant]),
+            Content::text(text)
+                .with_audience(vec![Role::User])
+                .with_priority(0.0),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_budget_counts_characters() {
+        assert_eq!(
+            budget("line one\nline two\n", 0, 10),
+            ("line one\n", Some(9))
+        );
+        assert_eq!(budget("héllo wörld", 1, 3), ("éll", Some(4)));
+        assert_eq!(budget("héllo", 9, 3), ("", None));
+    }
+}
@@ -0,0 +1,155 @@
+use std::path::{Component, Path, PathBuf};
+
+use super::shell::expand_path;
+
+/// The set of project roots the developer extension is allowed to touch.
+///
+/// Confinement is opt-in: unless `GOOSE_WORKSPACE_JAIL` is enabled every path is
+/// accepted as-is, matching the historical behavior.
+#[derive(Debug, Clone, Default)]
+pub struct Workspace {
+    roots: Vec<PathBuf>,
+    jailed: bool,
+}
+
+impl Workspace {
+    pub fn new(roots: Vec<PathBuf>, jailed: bool) -> Self {
+        let roots = roots
+            .into_iter()
+            .map(|root| canonicalize_lenient(&root))
+            .collect();
+        Self { roots, jailed }
+    }
+
+    /// Build the workspace from `GOOSE_WORKSPACE_JAIL` and `GOOSE_WORKSPACE_ROOTS`
+    /// (a JSON list of directories), defaulting the roots to the current directory.
+    pub fn from_env(cwd: &Path) -> Self {
+        let jailed = std::env::var("GOOSE_WORKSPACE_JAIL")
+            .map(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "yes"))
+            .unwrap_or(false);
+
+        let roots = std::env::var("GOOSE_WORKSPACE_ROOTS")
+            .ok()
+            .and_then(|s| serde_json::from_str::<Vec<String>>(&s).ok())
+            .filter(|roots| !roots.is_empty())
+            .map(|roots| {
+                roots
+                    .iter()
+                    .map(|root| PathBuf::from(expand_path(root)))
+                    .collect()
+            })
+            .unwrap_or_else(|| vec![cwd.to_path_buf()]);
+
+        Self::new(roots, jailed)
+    }
+
+    pub fn is_jailed(&self) -> bool {
+        self.jailed
+    }
+
+    pub fn roots(&self) -> &[PathBuf] {
+        &self.roots
+    }
+
+    /// Returns the path to operate on, or an error if confinement is enabled and the
+    /// path escapes every root. When jailed the canonical path is returned so that the
+    /// checked path and the path actually used can't diverge through symlinks.
+    pub fn confine(&self, path: &Path) -> Result<PathBuf, String> {
+        if !self.jailed {
+            return Ok(path.to_path_buf());
+        }
+
+        let canonical = canonicalize_lenient(path);
+        if self.roots.iter().any(|root| canonical.starts_with(root)) {
+            Ok(canonical)
+        } else {
+            Err(format!(
+                "Access to '{}' is outside the workspace roots ({})",
+                path.display(),
+                self.roots
+                    .iter()
+                    .map(|root| root.display().to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ))
+        }
+    }
+
+    /// Whether a path is allowed, used to filter results of directory walks.
+    pub fn contains(&self, path: &Path) -> bool {
+        self.confine(path).is_ok()
+    }
+}
+
+/// Canonicalize a path that may not exist yet (e.g. the target of a write).
+///
+/// Components are resolved one at a time against the filesystem, which follows
+/// symlinks, and once a component is missing the rest are applied lexically. A `..` that
+/// leads back to a directory that exists resolves against the filesystem again, so a
+/// symlink after it is still followed.
+fn canonicalize_lenient(path: &Path) -> PathBuf {
+    let mut resolved = PathBuf::new();
+    let mut missing = false;
+
+    for component in path.components() {
+        if !missing {
+            match resolved.join(component).canonicalize() {
+                Ok(canonical) => {
+                    resolved = canonical;
+                    continue;
+                }
+                Err(_) => missing = true,
+            }
+        }
+
+        match component {
+            Component::ParentDir => {
+                resolved.pop();
+                if let Ok(canonical) = resolved.canonicalize() {
+                    resolved = canonical;
+                    missing = false;
+                }
+            }
+            Component::CurDir => {}
+            other => resolved.push(other),
+        }
+    }
+
+    resolved
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(unix)]
+    fn test_symlinks_after_a_missing_directory_are_followed() {
+        let root = tempfile::tempdir().unwrap();
+        let outside = tempfile::tempdir().unwrap();
+        let workspace = root.path().join("ws");
+        std::fs::create_dir(&workspace).unwrap();
+        std::os::unix::fs::symlink(outside.path(), workspace.join("link")).unwrap();
+        let jail = Workspace::new(vec![workspace.clone()], true);
+
+        let escaping = workspace.join("missing/../link/secret.txt");
+        assert_eq!(
+            canonicalize_lenient(&escaping),
+            outside.path().canonicalize().unwrap().join("secret.txt")
+        );
+        assert!(jail.confine(&escaping).is_err());
+
+        // Paths that stay inside still resolve, whether or not they exist yet
+        let new_file = workspace.join("missing/../new/file.txt");
+        assert_eq!(
+            jail.confine(&new_file).unwrap(),
+            workspace.canonicalize().unwrap().join("new/file.txt")
+        );
+        assert!(jail
+            .confine(&workspace.join("missing/../../ws/a.txt"))
+            .is_ok());
+        assert!(jail
+            .confine(&workspace.join("missing/../../other"))
+            .is_err());
+    }
+}
@@ -14,7 +14,10 @@ mod memory;
 mod tutorial;
 
 pub use computercontroller::ComputerControllerRouter;
-pub use developer::DeveloperRouter;
+pub use developer::{
+    editor_models, error_codes, secrets, system, DeveloperRouter, DeveloperRouterBuilder,
+    InstructionLayer,
+};
 pub use google_drive::GoogleDriveRouter;
 pub use memory::MemoryRouter;
 pub use tutorial::TutorialRouter;
@@ -0,0 +1,139 @@
+use anyhow::{Context, Result};
+use opentelemetry::{global, trace::TracerProvider as _};
+use opentelemetry_otlp::{MetricExporter, SpanExporter};
+use opentelemetry_sdk::{metrics::SdkMeterProvider, trace::SdkTracerProvider, Resource};
+use std::env;
+use std::sync::OnceLock;
+use tracing::Subscriber;
+use tracing_opentelemetry::OpenTelemetryLayer;
+use tracing_subscriber::registry::LookupSpan;
+
+const ENDPOINT_VARS: [&str; 3] = [
+    "OTEL_EXPORTER_OTLP_ENDPOINT",
+    "OTEL_EXPORTER_OTLP_TRACES_ENDPOINT",
+    "OTEL_EXPORTER_OTLP_METRICS_ENDPOINT",
+];
+
+static PROVIDERS: OnceLock<(SdkTracerProvider, SdkMeterProvider)> = OnceLock::new();
+
+/// Export spans and metrics over OTLP/HTTP when one of the standard
+/// `OTEL_EXPORTER_OTLP_*ENDPOINT` variables is set.
+///
+/// Installs the global meter provider, so metrics recorded anywhere in the process (such
+/// as the developer extension's tool call metrics) are exported too, and returns the layer
+/// that turns tracing spans into OpenTelemetry spans. Returns None when no endpoint is set
+/// and an error when the exporters can't be created, which the caller should report once
+/// its subscriber is running.
+pub fn create_otlp_layer<S>(
+    service_name: &str,
+) -> Result<Option<OpenTelemetryLayer<S, opentelemetry_sdk::trace::Tracer>>>
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+{
+    if !ENDPOINT_VARS
+        .iter()
+        .any(|var| env::var(var).is_ok_and(|value| !value.is_empty()))
+    {
+        return Ok(None);
+    }
+
+    let span_exporter = SpanExporter::builder()
+        .with_http()
+        .build()
+        .context("Failed to create OTLP span exporter")?;
+    let metric_exporter = MetricExporter::builder()
+        .with_http()
+        .build()
+        .context("Failed to create OTLP metric exporter")?;
+
+    // OTEL_SERVICE_NAME and OTEL_RESOURCE_ATTRIBUTES take precedence
+    let resource = match env::var("OTEL_SERVICE_NAME") {
+        Ok(_) => Resource::builder().build(),
+        Err(_) => Resource::builder()
+            .with_service_name(service_name.to_string())
+            .build(),
+    };
+
+    let tracer_provider = SdkTracerProvider::builder()
+        .with_batch_exporter(span_exporter)
+        .with_resource(resource.clone())
+        .build();
+    let meter_provider = SdkMeterProvider::builder()
+        .with_periodic_exporter(metric_exporter)
+        .with_resource(resource)
+        .build();
+
+    let tracer = tracer_provider.tracer("goose");
+    global::set_tracer_provider(tracer_provider.clone());
+    global::set_meter_provider(meter_provider.clone());
+    PROVIDERS.set((tracer_provider, meter_provider)).ok();
+
+    Ok(Some(tracing_opentelemetry::layer().with_tracer(tracer)))
+}
+
+/// Flush spans and metrics that haven't been exported yet. Call before the process exits.
+pub fn shutdown_otlp() {
+    if let Some((tracer_provider, meter_provider)) = PROVIDERS.get() {
+        if let Err(e) = tracer_provider.shutdown() {
+            tracing::warn!("Failed to flush OTLP spans: {}", e);
+        }
+        if let Err(e) = meter_provider.shutdown() {
+            tracing::warn!("Failed to flush OTLP metrics: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serial_test::serial;
+    use tracing_subscriber::layer::SubscriberExt;
+    use wiremock::matchers::method;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[test]
+    #[serial]
+    fn test_no_layer_without_an_endpoint() {
+        temp_env::with_vars_unset(ENDPOINT_VARS, || {
+            let layer = create_otlp_layer::<tracing_subscriber::Registry>("goose").unwrap();
+            assert!(layer.is_none());
+        });
+    }
+
+    #[test]
+    #[serial]
+    fn test_spans_and_metrics_are_exported() {
+        // The exporters block, so the collector runs on its own runtime
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let collector = runtime.block_on(async {
+            let server = MockServer::start().await;
+            Mock::given(method("POST"))
+                .respond_with(ResponseTemplate::new(200))
+                .mount(&server)
+                .await;
+            server
+        });
+
+        temp_env::with_var("OTEL_EXPORTER_OTLP_ENDPOINT", Some(collector.uri()), || {
+            let layer = create_otlp_layer("goose-test").unwrap().unwrap();
+            let subscriber = tracing_subscriber::registry().with(layer);
+            tracing::subscriber::with_default(subscriber, || {
+                tracing::info_span!("tool_call", tool = "shell").in_scope(|| {});
+            });
+            global::meter("goose-test")
+                .u64_counter("goose.tool.calls")
+                .build()
+                .add(1, &[]);
+            shutdown_otlp();
+        });
+
+        let paths: Vec<String> = runtime
+            .block_on(collector.received_requests())
+            .unwrap()
+            .iter()
+            .map(|request| request.url.path().to_string())
+            .collect();
+        assert!(paths.contains(&"/v1/traces".to_string()), "{:?}", paths);
+        assert!(paths.contains(&"/v1/metrics".to_string()), "{:?}", paths);
+    }
+}
@@ -14,6 +14,52 @@ pub enum ToolError {
     SchemaError(String),
     #[error("Tool not found: {0}")]
     NotFound(String),
+    #[error("{}: {}", .0.code, .0.message)]
+    Coded(ToolErrorData),
+}
+
+/// A failure with a stable code, such as `FILE_TOO_LARGE`, and structured details, such as
+/// the size and the limit, so clients can branch on the kind of failure instead of parsing
+/// the message
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct ToolErrorData {
+    pub code: String,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<serde_json::Value>,
+}
+
+impl ToolError {
+    pub fn coded(
+        code: impl Into<String>,
+        message: impl Into<String>,
+        data: Option<serde_json::Value>,
+    ) -> Self {
+        ToolError::Coded(ToolErrorData {
+            code: code.into(),
+            message: message.into(),
+            data,
+        })
+    }
+
+    /// The stable code for the error. Errors without a specific code get one for their kind.
+    pub fn code(&self) -> &str {
+        match self {
+            ToolError::InvalidParameters(_) => "INVALID_PARAMETERS",
+            ToolError::ExecutionError(_) => "EXECUTION_ERROR",
+            ToolError::SchemaError(_) => "SCHEMA_ERROR",
+            ToolError::NotFound(_) => "TOOL_NOT_FOUND",
+            ToolError::Coded(error) => &error.code,
+        }
+    }
+
+    /// The structured details of the error, if it has any
+    pub fn data(&self) -> Option<&serde_json::Value> {
+        match self {
+            ToolError::Coded(error) => error.data.as_ref(),
+            _ => None,
+        }
+    }
 }
 
 pub type ToolResult<T> = std::result::Result<T, ToolError>;
@@ -35,3 +81,46 @@ pub enum PromptError {
     #[error("Prompt not found: {0}")]
     NotFound(String),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tool_error_codes() {
+        let error = ToolError::coded(
+            "FILE_TOO_LARGE",
+            "File 'big.log' is too large",
+            Some(json!({ "size": 20, "limit": 10 })),
+        );
+        assert_eq!(error.code(), "FILE_TOO_LARGE");
+        assert_eq!(error.data(), Some(&json!({ "size": 20, "limit": 10 })));
+        assert_eq!(
+            error.to_string(),
+            "FILE_TOO_LARGE: File 'big.log' is too large"
+        );
+
+        let error = ToolError::InvalidParameters("Missing 'path'".into());
+        assert_eq!(error.code(), "INVALID_PARAMETERS");
+        assert_eq!(error.data(), None);
+        assert_eq!(ToolError::NotFound("x".into()).code(), "TOOL_NOT_FOUND");
+    }
+
+    #[test]
+    fn test_tool_error_data_leaves_out_missing_details() {
+        let data = ToolErrorData {
+            code: "RATE_LIMITED".into(),
+            message: "Too many calls".into(),
+            data: None,
+        };
+        let value = serde_json::to_value(&data).unwrap();
+        assert_eq!(
+            value,
+            json!({ "code": "RATE_LIMITED", "message": "Too many calls" })
+        );
+        assert_eq!(
+            serde_json::from_value::<ToolErrorData>(value).unwrap(),
+            data
+        );
+    }
+}
@@ -2,4 +2,4 @@ pub mod handler;
 pub mod tool;
 pub use tool::{Tool, ToolCall};
 pub mod protocol;
-pub use handler::{ToolError, ToolResult};
+pub use handler::{ToolError, ToolErrorData, ToolResult};
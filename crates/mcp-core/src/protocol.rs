@@ -71,11 +71,11 @@ impl TryFrom<JsonRpcRaw> for JsonRpcMessage {
 
     fn try_from(raw: JsonRpcRaw) -> Result<Self, <Self as TryFrom<JsonRpcRaw>>::Error> {
         // If it has an error field, it's an error response
-        if raw.error.is_some() {
+        if let Some(error) = raw.error {
             return Ok(JsonRpcMessage::Error(JsonRpcError {
                 jsonrpc: raw.jsonrpc,
                 id: raw.id,
-                error: raw.error.unwrap(),
+                error,
             }));
         }
 
@@ -200,6 +200,8 @@ pub struct CallToolResult {
     pub content: Vec<Content>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub is_error: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub structured_content: Option<Value>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
@@ -0,0 +1,168 @@
+//! Asking the user to confirm an action before a tool performs it.
+//!
+//! A tool describes what it is about to do as a [`PendingAction`] and calls [`confirm`]
+//! with the notifier of the current call. The user is asked through an MCP elicitation
+//! request, and the structured action is attached under `_meta` so clients can render
+//! their own confirmation UI instead of the message.
+
+use std::time::Duration;
+
+use rmcp::model::JsonRpcMessage;
+use rmcp::object;
+use serde::Serialize;
+use serde_json::Value;
+use thiserror::Error;
+use tokio::sync::mpsc;
+
+use crate::requests::{send_request, ClientRequestError};
+
+/// How long to wait for the user to answer
+pub const CONFIRM_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// An action waiting on the user's confirmation
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PendingAction {
+    /// What kind of action this is, e.g. `delete`, `overwrite` or `force_push`
+    pub kind: String,
+    /// What will happen, in a sentence the user can answer yes or no to
+    pub summary: String,
+    /// The files, refs or commands affected
+    pub targets: Vec<String>,
+    /// Why it needs confirmation, e.g. what can't be undone
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub risk: Option<String>,
+}
+
+impl PendingAction {
+    pub fn new(kind: impl Into<String>, summary: impl Into<String>) -> Self {
+        Self {
+            kind: kind.into(),
+            summary: summary.into(),
+            targets: Vec::new(),
+            risk: None,
+        }
+    }
+
+    pub fn with_target(mut self, target: impl Into<String>) -> Self {
+        self.targets.push(target.into());
+        self
+    }
+
+    pub fn with_risk(mut self, risk: impl Into<String>) -> Self {
+        self.risk = Some(risk.into());
+        self
+    }
+
+    /// The action as plain text, for clients that only show the elicitation message
+    pub fn describe(&self) -> String {
+        let mut description = self.summary.clone();
+        if let Some(risk) = &self.risk {
+            description.push_str(&format!("\n\n{}", risk));
+        }
+        for target in &self.targets {
+            description.push_str(&format!("\n- {}", target));
+        }
+        description
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum ConfirmError {
+    #[error("The user did not approve this action: {}", .0.summary)]
+    Declined(PendingAction),
+    #[error("This action requires user approval, which could not be obtained ({1}): {}", .0.summary)]
+    Unavailable(PendingAction, ClientRequestError),
+}
+
+/// Ask the user to approve an action, returning Ok only if they explicitly accepted with
+/// `approve` set to true.
+///
+/// Anything else, including an accept without `approve` or a client that does not support
+/// elicitation, is treated as a refusal.
+pub async fn confirm(
+    notifier: &mpsc::Sender<JsonRpcMessage>,
+    action: &PendingAction,
+) -> Result<(), ConfirmError> {
+    let description = action.describe();
+    let params = object!({
+        "message": format!("goose wants to perform a destructive action:\n\n{}", description),
+        "requestedSchema": {
+            "type": "object",
+            "properties": {
+                "approve": {
+                    "type": "boolean",
+                    "title": "Approve",
+                    "description": action.summary,
+                }
+            },
+            "required": ["approve"]
+        },
+        "_meta": {
+            "goose/pendingAction": action
+        }
+    });
+
+    let result = send_request(notifier, "elicitation/create", params, CONFIRM_TIMEOUT)
+        .await
+        .map_err(|e| ConfirmError::Unavailable(action.clone(), e))?;
+
+    let accepted = result.get("action").and_then(Value::as_str) == Some("accept")
+        && result
+            .get("content")
+            .and_then(|content| content.get("approve"))
+            .and_then(Value::as_bool)
+            == Some(true);
+
+    if accepted {
+        Ok(())
+    } else {
+        Err(ConfirmError::Declined(action.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rmcp::model::JsonObject;
+    use serde_json::json;
+
+    use super::*;
+    use crate::requests::resolve;
+
+    /// Confirm an action, answering the elicitation with `answer`
+    async fn answer_with(answer: Value) -> Result<(), ConfirmError> {
+        let (notifier, mut requests) = mpsc::channel(1);
+        let sent_through = notifier.downgrade();
+        let action = PendingAction::new("delete", "Delete the file").with_target("a.txt");
+        let confirming = tokio::spawn(async move { confirm(&notifier, &action).await });
+
+        let Some(JsonRpcMessage::Request(request)) = requests.recv().await else {
+            panic!("expected an elicitation request");
+        };
+        assert_eq!(request.request.method, "elicitation/create");
+        let answer: JsonObject = serde_json::from_value(answer).unwrap();
+        assert!(resolve(&request.id, Ok(answer), &[sent_through]));
+        confirming.await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_only_an_explicit_approval_confirms() {
+        assert!(
+            answer_with(json!({"action": "accept", "content": {"approve": true}}))
+                .await
+                .is_ok()
+        );
+        for answer in [
+            json!({"action": "accept"}),
+            json!({"action": "accept", "content": {}}),
+            json!({"action": "accept", "content": {"approve": false}}),
+            json!({"action": "accept", "content": {"approve": "yes"}}),
+            json!({"action": "decline"}),
+            json!({"action": "cancel", "content": {"approve": true}}),
+        ] {
+            assert!(matches!(
+                answer_with(answer).await,
+                Err(ConfirmError::Declined(_))
+            ));
+        }
+    }
+}
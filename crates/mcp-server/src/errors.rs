@@ -104,3 +104,15 @@ impl From<mcp_core::handler::ResourceError> for RouterError {
         }
     }
 }
+
+impl From<mcp_core::handler::PromptError> for RouterError {
+    fn from(err: mcp_core::handler::PromptError) -> Self {
+        match err {
+            mcp_core::handler::PromptError::InvalidParameters(msg) => {
+                RouterError::InvalidParams(msg)
+            }
+            mcp_core::handler::PromptError::NotFound(msg) => RouterError::PromptNotFound(msg),
+            mcp_core::handler::PromptError::InternalError(msg) => RouterError::Internal(msg),
+        }
+    }
+}
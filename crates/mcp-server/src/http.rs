@@ -0,0 +1,802 @@
+//! Serving a router over streamable HTTP, so a server can run on another machine.
+//!
+//! Clients POST JSON-RPC messages to `/mcp`. A request is answered with a `text/event-stream`
+//! carrying the notifications and server-initiated requests sent while it is processed and
+//! then its response, or with just the response as JSON for clients that don't accept event
+//! streams. The client's responses to server-initiated requests and its notifications are
+//! accepted with `202 Accepted`.
+//!
+//! `initialize` opens a session whose id is returned in the `Mcp-Session-Id` header and must
+//! be sent with every later message. Each session gets its own service from the factory the
+//! server was created with, and ends when the client sends `DELETE` or has sent nothing for
+//! the idle timeout while no request is in flight. Answers to server-initiated requests are
+//! only accepted from the session the request was sent in. When the server has a token,
+//! every message must carry it as `Authorization: Bearer <token>`.
+
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use axum::{
+    body::Bytes,
+    extract::State,
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+    routing::post,
+    Json,
+};
+use futures::{stream, StreamExt};
+use rmcp::model::{
+    ErrorCode, ErrorData, JsonObject, JsonRpcError, JsonRpcMessage, JsonRpcRequest,
+    JsonRpcVersion2_0, RequestId,
+};
+use tokio::{
+    net::TcpListener,
+    sync::{mpsc, oneshot},
+};
+
+use crate::{
+    cancelled_request, requests, router::McpRequest, BoundedService, ServerError, TransportError,
+};
+
+/// The path the server answers on
+pub const MCP_PATH: &str = "/mcp";
+
+pub const SESSION_ID_HEADER: &str = "mcp-session-id";
+
+/// How long a session with nothing in flight is kept after the client's last message
+pub const DEFAULT_IDLE_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+/// Why a message wasn't accepted, answered with the status and text
+type Rejection = (StatusCode, &'static str);
+
+/// A request being processed
+struct InFlight {
+    /// Stops the request when the client cancels it
+    cancel_tx: oneshot::Sender<()>,
+    /// Where the request sends its notifications and server-initiated requests
+    notifier: mpsc::WeakSender<JsonRpcMessage>,
+}
+
+/// One client's session
+struct Session<S> {
+    service: Mutex<S>,
+    in_flight: Mutex<HashMap<RequestId, InFlight>>,
+    last_seen: Mutex<Instant>,
+}
+
+impl<S> Session<S> {
+    fn touch(&self) {
+        *self.last_seen.lock().unwrap() = Instant::now();
+    }
+
+    /// Hand the client's answer to a request sent in this session to the router waiting on it
+    fn answer(&self, id: &RequestId, result: Result<JsonObject, ErrorData>) -> Response {
+        let notifiers: Vec<_> = self
+            .in_flight
+            .lock()
+            .unwrap()
+            .values()
+            .map(|request| request.notifier.clone())
+            .collect();
+        if requests::resolve(id, result, &notifiers) {
+            StatusCode::ACCEPTED.into_response()
+        } else {
+            (
+                StatusCode::NOT_FOUND,
+                "No request with this id is waiting for an answer in this session",
+            )
+                .into_response()
+        }
+    }
+}
+
+struct Shared<F, S> {
+    make_service: F,
+    token: Option<String>,
+    idle_timeout: Duration,
+    sessions: Mutex<HashMap<String, Arc<Session<S>>>>,
+}
+
+/// A streamable HTTP server, creating a service with `make_service` for each session
+pub struct HttpServer<F> {
+    make_service: F,
+    token: Option<String>,
+    idle_timeout: Duration,
+}
+
+impl<F, S> HttpServer<F>
+where
+    F: Fn() -> S + Send + Sync + 'static,
+    S: BoundedService,
+{
+    pub fn new(make_service: F) -> Self {
+        Self {
+            make_service,
+            token: None,
+            idle_timeout: DEFAULT_IDLE_TIMEOUT,
+        }
+    }
+
+    /// Require clients to send `token` as a bearer token
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    /// End sessions that have had nothing in flight and sent nothing for `idle_timeout`,
+    /// instead of after [`DEFAULT_IDLE_TIMEOUT`]
+    pub fn with_idle_timeout(mut self, idle_timeout: Duration) -> Self {
+        self.idle_timeout = idle_timeout;
+        self
+    }
+
+    /// The routes of the server, to serve them with other routes or in tests
+    pub fn into_router(self) -> axum::Router {
+        let shared = Arc::new(Shared {
+            make_service: self.make_service,
+            token: self.token,
+            idle_timeout: self.idle_timeout,
+            sessions: Mutex::new(HashMap::new()),
+        });
+        axum::Router::new()
+            .route(
+                MCP_PATH,
+                post(handle_post::<F, S>)
+                    .get(handle_get::<F, S>)
+                    .delete(handle_delete::<F, S>),
+            )
+            .with_state(shared)
+    }
+
+    pub async fn run(self, listener: TcpListener) -> Result<(), ServerError> {
+        tracing::info!(address = ?listener.local_addr().ok(), "HTTP server started");
+        axum::serve(listener, self.into_router())
+            .await
+            .map_err(|e| ServerError::Transport(TransportError::Io(e)))
+    }
+}
+
+async fn handle_post<F, S>(
+    State(shared): State<Arc<Shared<F, S>>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response
+where
+    F: Fn() -> S + Send + Sync + 'static,
+    S: BoundedService,
+{
+    if let Some(rejection) = authorize(&shared, &headers) {
+        return rejection;
+    }
+
+    tracing::info!(json = %String::from_utf8_lossy(&body), "incoming message");
+    let message = match serde_json::from_slice::<JsonRpcMessage>(&body) {
+        Ok(message) => message,
+        Err(e) => {
+            return error_response(
+                StatusCode::BAD_REQUEST,
+                RequestId::Number(0),
+                ErrorCode::PARSE_ERROR,
+                e.to_string(),
+            )
+        }
+    };
+
+    // A new session starts with its initialize request
+    let new_session = session_id(&headers).is_none()
+        && matches!(
+            &message,
+            JsonRpcMessage::Request(request) if request.request.method == "initialize"
+        );
+    let (session_id, session) = if new_session {
+        start_session(&shared)
+    } else {
+        match find_session(&shared, &headers) {
+            Ok(session) => session,
+            Err(rejection) => return rejection.into_response(),
+        }
+    };
+
+    let mut response = match message {
+        JsonRpcMessage::Request(request) => respond(session, request, &headers).await,
+        JsonRpcMessage::Response(response) => session.answer(&response.id, Ok(response.result)),
+        JsonRpcMessage::Error(error) => session.answer(&error.id, Err(error.error)),
+        JsonRpcMessage::Notification(notification) => {
+            if let Some(id) = cancelled_request(&notification) {
+                if let Some(request) = session.in_flight.lock().unwrap().remove(&id) {
+                    request.cancel_tx.send(()).ok();
+                }
+            }
+            StatusCode::ACCEPTED.into_response()
+        }
+        JsonRpcMessage::BatchRequest(_) | JsonRpcMessage::BatchResponse(_) => error_response(
+            StatusCode::BAD_REQUEST,
+            RequestId::Number(0),
+            ErrorCode::INVALID_REQUEST,
+            "Batches are not supported".to_string(),
+        ),
+    };
+    if new_session {
+        response.headers_mut().insert(
+            SESSION_ID_HEADER,
+            HeaderValue::from_str(&session_id).expect("a uuid is a valid header value"),
+        );
+    }
+    response
+}
+
+/// There is no stream for messages outside of requests; they are sent on the request's stream
+async fn handle_get<F, S>(State(shared): State<Arc<Shared<F, S>>>, headers: HeaderMap) -> Response
+where
+    F: Fn() -> S + Send + Sync + 'static,
+    S: BoundedService,
+{
+    authorize(&shared, &headers).unwrap_or_else(|| StatusCode::METHOD_NOT_ALLOWED.into_response())
+}
+
+async fn handle_delete<F, S>(
+    State(shared): State<Arc<Shared<F, S>>>,
+    headers: HeaderMap,
+) -> Response
+where
+    F: Fn() -> S + Send + Sync + 'static,
+    S: BoundedService,
+{
+    if let Some(rejection) = authorize(&shared, &headers) {
+        return rejection;
+    }
+    let Some(session_id) = session_id(&headers) else {
+        return (StatusCode::BAD_REQUEST, "Missing Mcp-Session-Id header").into_response();
+    };
+    match shared.sessions.lock().unwrap().remove(session_id) {
+        Some(session) => {
+            // Stop whatever the session was still doing
+            for (_, request) in session.in_flight.lock().unwrap().drain() {
+                request.cancel_tx.send(()).ok();
+            }
+            tracing::info!(session_id, "Session ended");
+            StatusCode::NO_CONTENT.into_response()
+        }
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+/// A rejection when the server has a token and the request doesn't carry it
+fn authorize<F, S>(shared: &Shared<F, S>, headers: &HeaderMap) -> Option<Response> {
+    let expected = shared.token.as_deref()?;
+    let given = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    match given {
+        Some(given) if same_token(expected, given.trim()) => None,
+        _ => {
+            tracing::warn!("Rejected a request without a valid token");
+            Some(
+                (
+                    StatusCode::UNAUTHORIZED,
+                    [(header::WWW_AUTHENTICATE, "Bearer")],
+                    "Missing or invalid bearer token",
+                )
+                    .into_response(),
+            )
+        }
+    }
+}
+
+/// Compare tokens in time that doesn't depend on where they differ
+fn same_token(expected: &str, given: &str) -> bool {
+    expected.len() == given.len()
+        && expected
+            .bytes()
+            .zip(given.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+fn session_id(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(SESSION_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+}
+
+/// End the sessions that have been idle for longer than the idle timeout, so clients that
+/// went away without ending their session don't keep their service around
+fn expire_idle_sessions<F, S>(shared: &Shared<F, S>) {
+    shared
+        .sessions
+        .lock()
+        .unwrap()
+        .retain(|session_id, session| {
+            let idle = session.in_flight.lock().unwrap().is_empty()
+                && session.last_seen.lock().unwrap().elapsed() >= shared.idle_timeout;
+            if idle {
+                tracing::info!(session_id, "Session expired");
+            }
+            !idle
+        });
+}
+
+fn start_session<F, S>(shared: &Shared<F, S>) -> (String, Arc<Session<S>>)
+where
+    F: Fn() -> S,
+{
+    expire_idle_sessions(shared);
+    let session_id = uuid::Uuid::new_v4().to_string();
+    let session = Arc::new(Session {
+        service: Mutex::new((shared.make_service)()),
+        in_flight: Mutex::new(HashMap::new()),
+        last_seen: Mutex::new(Instant::now()),
+    });
+    shared
+        .sessions
+        .lock()
+        .unwrap()
+        .insert(session_id.clone(), Arc::clone(&session));
+    tracing::info!(session_id, "Session started");
+    (session_id, session)
+}
+
+fn find_session<F, S>(
+    shared: &Shared<F, S>,
+    headers: &HeaderMap,
+) -> Result<(String, Arc<Session<S>>), Rejection> {
+    let Some(session_id) = session_id(headers) else {
+        return Err((StatusCode::BAD_REQUEST, "Missing Mcp-Session-Id header"));
+    };
+    expire_idle_sessions(shared);
+    // Not found tells the client to start a new session
+    match shared.sessions.lock().unwrap().get(session_id) {
+        Some(session) => {
+            session.touch();
+            Ok((session_id.to_string(), Arc::clone(session)))
+        }
+        None => Err((StatusCode::NOT_FOUND, "")),
+    }
+}
+
+/// Process a request in its session, streaming what it sends when the client accepts that
+async fn respond<S: BoundedService>(
+    session: Arc<Session<S>>,
+    request: JsonRpcRequest,
+    headers: &HeaderMap,
+) -> Response {
+    tracing::info!(method = ?request.request.method, "Received request");
+    let streaming = headers
+        .get(header::ACCEPT)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|accept| accept.contains("text/event-stream"));
+
+    let request_id = request.id.clone();
+    let (notify_tx, notify_rx) = mpsc::channel(256);
+    let (cancel_tx, cancel_rx) = oneshot::channel();
+    // Cancellations and answers are routed by id, so it can only be in flight once
+    match session.in_flight.lock().unwrap().entry(request_id.clone()) {
+        Entry::Occupied(_) => {
+            return error_response(
+                StatusCode::CONFLICT,
+                request_id,
+                ErrorCode::INVALID_REQUEST,
+                "A request with this id is already in flight".to_string(),
+            )
+        }
+        Entry::Vacant(entry) => {
+            entry.insert(InFlight {
+                cancel_tx,
+                notifier: notify_tx.downgrade(),
+            });
+        }
+    }
+
+    let responder = notify_tx.clone();
+    let mut request = Some(McpRequest {
+        request,
+        notifier: notify_tx,
+    });
+    // The service is called under the same lock it reported ready under
+    let ready = std::future::poll_fn(|cx| {
+        let mut service = session.service.lock().unwrap();
+        service
+            .poll_ready(cx)
+            .map_ok(|()| service.call(request.take().expect("called once")))
+    })
+    .await;
+    let future = match ready {
+        Ok(future) => future,
+        Err(e) => {
+            session.in_flight.lock().unwrap().remove(&request_id);
+            tracing::error!(error = %e, "Service not ready");
+            return error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                request_id,
+                ErrorCode::INTERNAL_ERROR,
+                e.to_string(),
+            );
+        }
+    };
+
+    // Dropping the request's future when it is cancelled stops the work it was doing
+    tokio::spawn(async move {
+        let response = tokio::select! {
+            result = future => Some(match result {
+                Ok(response) => JsonRpcMessage::Response(response),
+                Err(e) => {
+                    let error_msg = e.to_string();
+                    tracing::error!(error = %error_msg, "Request processing failed");
+                    JsonRpcMessage::Error(JsonRpcError {
+                        jsonrpc: JsonRpcVersion2_0,
+                        id: request_id.clone(),
+                        error: ErrorData {
+                            code: ErrorCode::INTERNAL_ERROR,
+                            message: error_msg.into(),
+                            data: None,
+                        },
+                    })
+                }
+            }),
+            Ok(()) = cancel_rx => None,
+        };
+        session.in_flight.lock().unwrap().remove(&request_id);
+        // The idle timeout counts from the end of the last request
+        session.touch();
+
+        match response {
+            Some(response) => {
+                responder.send(response).await.ok();
+            }
+            None => tracing::info!("Request cancelled by the client"),
+        }
+    });
+
+    // The stream ends with the request's response, or when it was cancelled
+    let messages = stream::unfold(Some(notify_rx), |rx| async move {
+        let mut rx = rx?;
+        let message = rx.recv().await?;
+        let last = matches!(
+            message,
+            JsonRpcMessage::Response(_) | JsonRpcMessage::Error(_)
+        );
+        Some((message, (!last).then_some(rx)))
+    });
+
+    if streaming {
+        let events = messages.map(|message| Event::default().json_data(message));
+        // Keep-alives stop proxies from closing the stream during long tool calls
+        return Sse::new(events)
+            .keep_alive(KeepAlive::default())
+            .into_response();
+    }
+
+    // Without a stream only the response can be sent, so anything else is dropped
+    let responses = messages.filter(|message| {
+        std::future::ready(matches!(
+            message,
+            JsonRpcMessage::Response(_) | JsonRpcMessage::Error(_)
+        ))
+    });
+    match Box::pin(responses).next().await {
+        Some(response) => Json(response).into_response(),
+        // Cancelled
+        None => StatusCode::NO_CONTENT.into_response(),
+    }
+}
+
+fn error_response(status: StatusCode, id: RequestId, code: ErrorCode, message: String) -> Response {
+    let error: JsonRpcMessage = JsonRpcMessage::Error(JsonRpcError {
+        jsonrpc: JsonRpcVersion2_0,
+        id,
+        error: ErrorData {
+            code,
+            message: message.into(),
+            data: None,
+        },
+    });
+    (status, Json(error)).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{future::Future, pin::Pin, task::Poll};
+
+    use axum::body::Body;
+    use rmcp::model::JsonRpcResponse;
+    use serde_json::{json, Value};
+    use tower::ServiceExt;
+    use tower_service::Service;
+
+    use super::*;
+    use crate::BoxError;
+
+    const TOKEN: &str = "secret";
+
+    /// Answers `initialize`, waits forever on `wait` and asks the client on `ask`
+    struct TestService;
+
+    impl Service<McpRequest> for TestService {
+        type Response = JsonRpcResponse;
+        type Error = BoxError;
+        type Future = Pin<Box<dyn Future<Output = Result<JsonRpcResponse, BoxError>> + Send>>;
+
+        fn poll_ready(&mut self, _: &mut std::task::Context<'_>) -> Poll<Result<(), BoxError>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, req: McpRequest) -> Self::Future {
+            Box::pin(async move {
+                let result = match req.request.request.method.as_str() {
+                    "wait" => std::future::pending().await,
+                    "ask" => {
+                        requests::send_request(
+                            &req.notifier,
+                            "elicitation/create",
+                            JsonObject::new(),
+                            Duration::from_secs(10),
+                        )
+                        .await?
+                    }
+                    _ => JsonObject::new(),
+                };
+                Ok(JsonRpcResponse {
+                    jsonrpc: JsonRpcVersion2_0,
+                    id: req.request.id,
+                    result,
+                })
+            })
+        }
+    }
+
+    /// A service that can't take requests
+    struct BrokenService;
+
+    impl Service<McpRequest> for BrokenService {
+        type Response = JsonRpcResponse;
+        type Error = BoxError;
+        type Future = Pin<Box<dyn Future<Output = Result<JsonRpcResponse, BoxError>> + Send>>;
+
+        fn poll_ready(&mut self, _: &mut std::task::Context<'_>) -> Poll<Result<(), BoxError>> {
+            Poll::Ready(Err("the service is shutting down".into()))
+        }
+
+        fn call(&mut self, _: McpRequest) -> Self::Future {
+            panic!("called without being ready")
+        }
+    }
+
+    fn router() -> axum::Router {
+        HttpServer::new(|| TestService)
+            .with_token(TOKEN)
+            .into_router()
+    }
+
+    fn post(
+        session: Option<&str>,
+        token: Option<&str>,
+        message: Value,
+    ) -> axum::http::Request<Body> {
+        let mut request = axum::http::Request::builder()
+            .method("POST")
+            .uri(MCP_PATH)
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(header::ACCEPT, "application/json, text/event-stream");
+        if let Some(session) = session {
+            request = request.header(SESSION_ID_HEADER, session);
+        }
+        if let Some(token) = token {
+            request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+        }
+        request.body(Body::from(message.to_string())).unwrap()
+    }
+
+    fn request(id: &str, method: &str) -> Value {
+        json!({"jsonrpc": "2.0", "id": id, "method": method, "params": {}})
+    }
+
+    async fn initialize(router: &axum::Router) -> String {
+        let response = router
+            .clone()
+            .oneshot(post(None, Some(TOKEN), request("1", "initialize")))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        response.headers()[SESSION_ID_HEADER]
+            .to_str()
+            .unwrap()
+            .to_string()
+    }
+
+    /// The next message on an event stream
+    async fn next_event(
+        body: &mut (impl futures::Stream<Item = Result<Bytes, axum::Error>> + Unpin),
+    ) -> Value {
+        let chunk = body.next().await.unwrap().unwrap();
+        let text = String::from_utf8(chunk.to_vec()).unwrap();
+        let data = text
+            .lines()
+            .find_map(|line| line.strip_prefix("data:"))
+            .unwrap();
+        serde_json::from_str(data.trim()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_requests_without_the_token_are_rejected() {
+        let router = router();
+        for token in [None, Some("wrong")] {
+            let response = router
+                .clone()
+                .oneshot(post(None, token, request("1", "initialize")))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+            assert!(response.headers().get(SESSION_ID_HEADER).is_none());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_unknown_sessions_are_not_found() {
+        let router = router();
+        let response = router
+            .clone()
+            .oneshot(post(Some("unknown"), Some(TOKEN), request("1", "ping")))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let response = router
+            .clone()
+            .oneshot(post(None, Some(TOKEN), request("1", "ping")))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_idle_sessions_expire() {
+        let router = HttpServer::new(|| TestService)
+            .with_idle_timeout(Duration::ZERO)
+            .into_router();
+        let response = router
+            .clone()
+            .oneshot(post(None, None, request("1", "initialize")))
+            .await
+            .unwrap();
+        let session = response.headers()[SESSION_ID_HEADER]
+            .to_str()
+            .unwrap()
+            .to_string();
+        // Once the response has been read nothing is in flight
+        axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let response = router
+            .clone()
+            .oneshot(post(Some(&session), None, request("2", "ping")))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_requests_stop() {
+        let router = router();
+        let session = initialize(&router).await;
+        let mut waiting = post(Some(&session), Some(TOKEN), request("2", "wait"));
+        waiting
+            .headers_mut()
+            .insert(header::ACCEPT, HeaderValue::from_static("application/json"));
+        let waiting = tokio::spawn(router.clone().oneshot(waiting));
+
+        // Cancel once the request is in flight
+        let cancel = json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/cancelled",
+            "params": {"requestId": "2"}
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let response = router
+            .clone()
+            .oneshot(post(Some(&session), Some(TOKEN), cancel))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+
+        let response = tokio::time::timeout(Duration::from_secs(5), waiting)
+            .await
+            .expect("the request should stop when cancelled")
+            .unwrap()
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    }
+
+    #[tokio::test]
+    async fn test_answers_are_only_accepted_from_the_asking_session() {
+        let router = router();
+        let asking = initialize(&router).await;
+        let other = initialize(&router).await;
+
+        let response = router
+            .clone()
+            .oneshot(post(Some(&asking), Some(TOKEN), request("2", "ask")))
+            .await
+            .unwrap();
+        let mut events = response.into_body().into_data_stream();
+        let question = next_event(&mut events).await;
+        assert_eq!(question["method"], "elicitation/create");
+        let answer = json!({
+            "jsonrpc": "2.0",
+            "id": question["id"],
+            "result": {"action": "accept"}
+        });
+
+        let response = router
+            .clone()
+            .oneshot(post(Some(&other), Some(TOKEN), answer.clone()))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let response = router
+            .clone()
+            .oneshot(post(Some(&asking), Some(TOKEN), answer))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        let response = next_event(&mut events).await;
+        assert_eq!(response["id"], "2");
+        assert_eq!(response["result"]["action"], "accept");
+    }
+
+    #[tokio::test]
+    async fn test_ids_already_in_flight_are_rejected() {
+        let router = router();
+        let session = initialize(&router).await;
+        let mut waiting = post(Some(&session), Some(TOKEN), request("2", "wait"));
+        waiting
+            .headers_mut()
+            .insert(header::ACCEPT, HeaderValue::from_static("application/json"));
+        let waiting = tokio::spawn(router.clone().oneshot(waiting));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let response = router
+            .clone()
+            .oneshot(post(Some(&session), Some(TOKEN), request("2", "ping")))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let error: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(error["id"], "2");
+        assert_eq!(error["error"]["code"], ErrorCode::INVALID_REQUEST.0);
+
+        // The first request is still the one in flight
+        assert!(!waiting.is_finished());
+        waiting.abort();
+    }
+
+    #[tokio::test]
+    async fn test_services_that_are_not_ready_are_not_called() {
+        let router = HttpServer::new(|| BrokenService).into_router();
+        let mut initialize = post(None, None, request("1", "initialize"));
+        initialize
+            .headers_mut()
+            .insert(header::ACCEPT, HeaderValue::from_static("application/json"));
+        let response = router.oneshot(initialize).await.unwrap();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let error: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(error["error"]["code"], ErrorCode::INTERNAL_ERROR.0);
+        assert!(error["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("shutting down"));
+    }
+}
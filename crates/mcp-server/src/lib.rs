@@ -6,20 +6,27 @@ use std::{
 use futures::{Future, Stream};
 use pin_project::pin_project;
 use rmcp::model::{
-    ErrorData, JsonRpcError, JsonRpcMessage, JsonRpcResponse, JsonRpcVersion2_0, RequestId,
+    ErrorData, JsonRpcError, JsonRpcMessage, JsonRpcNotification, JsonRpcResponse,
+    JsonRpcVersion2_0, RequestId,
 };
 use router::McpRequest;
 use tokio::{
     io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader},
-    sync::mpsc,
+    sync::{mpsc, oneshot},
 };
 use tower_service::Service;
 
+pub mod confirm;
 mod errors;
 pub use errors::{BoxError, RouterError, ServerError, TransportError};
 
+pub mod http;
+pub use http::HttpServer;
+
+pub mod requests;
 pub mod router;
 pub use router::Router;
+pub mod structured;
 
 /// A transport layer that handles JSON-RPC messages over byte
 #[pin_project]
@@ -145,9 +152,18 @@ where
     {
         use futures::StreamExt;
         let mut service = self.service;
+        // Messages that arrived while a request was being processed
+        let mut deferred = std::collections::VecDeque::new();
 
         tracing::info!("Server started");
-        while let Some(msg_result) = transport.next().await {
+        loop {
+            let msg_result = match deferred.pop_front() {
+                Some(msg_result) => msg_result,
+                None => match transport.next().await {
+                    Some(msg_result) => msg_result,
+                    None => break,
+                },
+            };
             let _span = tracing::span!(tracing::Level::INFO, "message_processing").entered();
             match msg_result {
                 Ok(msg) => {
@@ -163,36 +179,89 @@ where
                             );
 
                             // Process the request using our service
+                            let request_id = request.id.clone();
                             let (notify_tx, mut notify_rx) = mpsc::channel(256);
+                            let (cancel_tx, cancel_rx) = oneshot::channel();
+                            let notifier = notify_tx.downgrade();
                             let mcp_request = McpRequest {
                                 request,
                                 notifier: notify_tx,
                             };
 
+                            // While the request is processed, forward its notifications and
+                            // server-initiated requests, route the client's responses to
+                            // those requests back to the waiting router, and watch for the
+                            // client cancelling the request
                             let transport_fut = tokio::spawn(async move {
-                                while let Some(notification) = notify_rx.recv().await {
-                                    if transport.write_message(notification).await.is_err() {
-                                        break;
+                                let mut incoming = Vec::new();
+                                let mut closed = false;
+                                let mut cancel_tx = Some(cancel_tx);
+                                loop {
+                                    tokio::select! {
+                                        outgoing = notify_rx.recv() => match outgoing {
+                                            Some(message) => {
+                                                if transport.write_message(message).await.is_err() {
+                                                    break;
+                                                }
+                                            }
+                                            None => break,
+                                        },
+                                        message = transport.next(), if !closed => match message {
+                                            Some(Ok(JsonRpcMessage::Response(response))) => {
+                                                requests::resolve(
+                                                    &response.id,
+                                                    Ok(response.result),
+                                                    std::slice::from_ref(&notifier),
+                                                );
+                                            }
+                                            Some(Ok(JsonRpcMessage::Error(error))) => {
+                                                if !requests::resolve(
+                                                    &error.id,
+                                                    Err(error.error.clone()),
+                                                    std::slice::from_ref(&notifier),
+                                                ) {
+                                                    incoming.push(Ok(JsonRpcMessage::Error(error)));
+                                                }
+                                            }
+                                            Some(Ok(JsonRpcMessage::Notification(notification)))
+                                                if cancels(&notification, &request_id) =>
+                                            {
+                                                if let Some(cancel_tx) = cancel_tx.take() {
+                                                    cancel_tx.send(()).ok();
+                                                }
+                                            }
+                                            Some(message) => incoming.push(message),
+                                            None => closed = true,
+                                        },
                                     }
                                 }
-                                transport
+                                (transport, incoming)
                             });
 
-                            let response = match service.call(mcp_request).await {
-                                Ok(resp) => resp,
-                                Err(e) => {
-                                    let error_msg = e.into().to_string();
-                                    tracing::error!(error = %error_msg, "Request processing failed");
+                            // Dropping the request's future when it is cancelled stops the
+                            // work it was doing, and releases its notifier so the transport
+                            // task finishes
+                            let response = tokio::select! {
+                                result = service.call(mcp_request) => match result {
+                                    Ok(resp) => Some(resp),
+                                    Err(e) => {
+                                        let error_msg = e.into().to_string();
+                                        tracing::error!(error = %error_msg, "Request processing failed");
 
-                                    // Return an error response instead of a regular response
-                                    return Err(ServerError::Transport(TransportError::Protocol(
-                                        error_msg,
-                                    )));
-                                }
+                                        // Return an error response instead of a regular response
+                                        return Err(ServerError::Transport(TransportError::Protocol(
+                                            error_msg,
+                                        )));
+                                    }
+                                },
+                                Ok(()) = cancel_rx => None,
                             };
 
                             transport = match transport_fut.await {
-                                Ok(transport) => transport,
+                                Ok((transport, incoming)) => {
+                                    deferred.extend(incoming);
+                                    transport
+                                }
                                 Err(e) => {
                                     tracing::error!(error = %e, "Failed to spawn transport task");
                                     return Err(ServerError::Transport(TransportError::Io(
@@ -201,6 +270,12 @@ where
                                 }
                             };
 
+                            // The client doesn't expect a response to a cancelled request
+                            let Some(response) = response else {
+                                tracing::info!("Request cancelled by the client");
+                                continue;
+                            };
+
                             // Serialize response for logging
                             let response_json = serde_json::to_string(&response)
                                 .unwrap_or_else(|_| "Failed to serialize response".to_string());
@@ -265,6 +340,23 @@ where
     }
 }
 
+/// Whether a notification from the client is `notifications/cancelled` for the request
+fn cancels(notification: &JsonRpcNotification, request_id: &RequestId) -> bool {
+    cancelled_request(notification).is_some_and(|id| id == *request_id)
+}
+
+/// The request a `notifications/cancelled` notification from the client cancels
+pub(crate) fn cancelled_request(notification: &JsonRpcNotification) -> Option<RequestId> {
+    if notification.notification.method != "notifications/cancelled" {
+        return None;
+    }
+    notification
+        .notification
+        .params
+        .get("requestId")
+        .and_then(|id| serde_json::from_value::<RequestId>(id.clone()).ok())
+}
+
 // Define a specific service implementation that we need for any
 // Any router implements this
 pub trait BoundedService:
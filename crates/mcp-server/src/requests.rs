@@ -0,0 +1,115 @@
+//! Requests initiated by the server and sent to the client, such as elicitation.
+//!
+//! A router sends the request through the notifier it was handed for the current call
+//! and awaits the client's answer, which the [`crate::Server`] run loop routes back here
+//! while the call is still in flight. An answer is only accepted from the connection or
+//! session the request was sent on.
+
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        LazyLock, Mutex,
+    },
+    time::Duration,
+};
+
+use rmcp::model::{
+    ErrorData, JsonObject, JsonRpcMessage, JsonRpcRequest, JsonRpcVersion2_0, Request, RequestId,
+};
+use thiserror::Error;
+use tokio::sync::{mpsc, oneshot};
+
+/// A request waiting for the client's answer
+struct PendingResponse {
+    /// The notifier the request was sent through
+    sent_through: mpsc::WeakSender<JsonRpcMessage>,
+    tx: oneshot::Sender<Result<JsonObject, ErrorData>>,
+}
+
+static PENDING: LazyLock<Mutex<HashMap<RequestId, PendingResponse>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+// Server-initiated ids are strings so they can't collide with the client's numeric ids
+static NEXT_ID: AtomicU32 = AtomicU32::new(1);
+
+#[derive(Error, Debug)]
+pub enum ClientRequestError {
+    #[error("Failed to send request to client")]
+    SendFailed,
+    #[error("Client did not respond within {0:?}")]
+    Timeout(Duration),
+    #[error("Client returned an error: {0}")]
+    Client(String),
+    #[error("Connection closed before the client responded")]
+    Closed,
+}
+
+/// Send a request to the client and wait for its result
+pub async fn send_request(
+    notifier: &mpsc::Sender<JsonRpcMessage>,
+    method: &str,
+    params: JsonObject,
+    timeout: Duration,
+) -> Result<JsonObject, ClientRequestError> {
+    let id =
+        RequestId::String(format!("server-{}", NEXT_ID.fetch_add(1, Ordering::Relaxed)).into());
+    let (tx, rx) = oneshot::channel();
+    PENDING.lock().unwrap().insert(
+        id.clone(),
+        PendingResponse {
+            sent_through: notifier.downgrade(),
+            tx,
+        },
+    );
+
+    let request = JsonRpcMessage::Request(JsonRpcRequest {
+        jsonrpc: JsonRpcVersion2_0,
+        id: id.clone(),
+        request: Request {
+            method: method.to_string(),
+            params,
+            extensions: Default::default(),
+        },
+    });
+
+    if notifier.send(request).await.is_err() {
+        PENDING.lock().unwrap().remove(&id);
+        return Err(ClientRequestError::SendFailed);
+    }
+
+    match tokio::time::timeout(timeout, rx).await {
+        Ok(Ok(Ok(result))) => Ok(result),
+        Ok(Ok(Err(error))) => Err(ClientRequestError::Client(error.message.to_string())),
+        Ok(Err(_)) => Err(ClientRequestError::Closed),
+        Err(_) => {
+            PENDING.lock().unwrap().remove(&id);
+            Err(ClientRequestError::Timeout(timeout))
+        }
+    }
+}
+
+/// Hand a response from the client to whoever is waiting on it, when the request was sent
+/// through one of `notifiers`, the notifiers of the requests the client is connected to.
+/// Returns false if no such request with that id is outstanding.
+pub(crate) fn resolve(
+    id: &RequestId,
+    result: Result<JsonObject, ErrorData>,
+    notifiers: &[mpsc::WeakSender<JsonRpcMessage>],
+) -> bool {
+    let mut pending = PENDING.lock().unwrap();
+    let sent_here = pending.get(id).is_some_and(|request| {
+        request.sent_through.upgrade().is_some_and(|sent_through| {
+            notifiers
+                .iter()
+                .filter_map(mpsc::WeakSender::upgrade)
+                .any(|notifier| notifier.same_channel(&sent_through))
+        })
+    });
+    if !sent_here {
+        return false;
+    }
+    let request = pending.remove(id).expect("checked above");
+    let _ = request.tx.send(result);
+    true
+}
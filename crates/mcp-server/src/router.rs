@@ -14,13 +14,15 @@ use mcp_core::{
     },
 };
 use rmcp::model::{
-    Content, GetPromptResult, JsonRpcMessage, JsonRpcRequest, JsonRpcResponse, JsonRpcVersion2_0,
-    Prompt, PromptMessage, PromptMessageRole, RequestId, Resource, ResourceContents,
+    Content, GetPromptResult, JsonObject, JsonRpcMessage, JsonRpcRequest, JsonRpcResponse,
+    JsonRpcVersion2_0, Prompt, PromptMessage, PromptMessageRole, RequestId, Resource,
+    ResourceContents,
 };
 use serde_json::Value;
 use tokio::sync::mpsc;
 use tower_service::Service;
 
+use crate::structured::take_structured_content;
 use crate::{BoxError, RouterError};
 
 /// Builder for configuring and constructing capabilities
@@ -45,7 +47,8 @@ impl CapabilitiesBuilder {
         }
     }
 
-    /// Add multiple tools to the router
+    /// Enable tools capability. Set `list_changed` when the server sends
+    /// `notifications/tools/list_changed`, so clients know to fetch the list again.
     pub fn with_tools(mut self, list_changed: bool) -> Self {
         self.tools = Some(ToolsCapability {
             list_changed: Some(list_changed),
@@ -53,7 +56,8 @@ impl CapabilitiesBuilder {
         self
     }
 
-    /// Enable prompts capability
+    /// Enable prompts capability, with `list_changed` set when the server sends
+    /// `notifications/prompts/list_changed`
     pub fn with_prompts(mut self, list_changed: bool) -> Self {
         self.prompts = Some(PromptsCapability {
             list_changed: Some(list_changed),
@@ -61,7 +65,9 @@ impl CapabilitiesBuilder {
         self
     }
 
-    /// Enable resources capability
+    /// Enable resources capability. `subscribe` declares support for
+    /// `resources/subscribe`, and `list_changed` that the server sends
+    /// `notifications/resources/list_changed`.
     pub fn with_resources(mut self, subscribe: bool, list_changed: bool) -> Self {
         self.resources = Some(ResourcesCapability {
             subscribe: Some(subscribe),
@@ -87,12 +93,33 @@ pub trait Router: Send + Sync + 'static {
     fn instructions(&self) -> String;
     fn capabilities(&self) -> ServerCapabilities;
     fn list_tools(&self) -> Vec<rmcp::model::Tool>;
+
+    /// The JSON Schema of a tool's structured result, sent as the tool's `outputSchema` in
+    /// the tool list. None by default, for tools that only return content.
+    fn output_schema(&self, _tool_name: &str) -> Option<JsonObject> {
+        None
+    }
+
     fn call_tool(
         &self,
         tool_name: &str,
         arguments: Value,
         notifier: mpsc::Sender<JsonRpcMessage>,
     ) -> Pin<Box<dyn Future<Output = Result<Vec<Content>, ToolError>> + Send + 'static>>;
+
+    /// Call a tool with the metadata the client attached to the request under `_meta`, like
+    /// its progress token, which is kept apart from the arguments the model chose. By default
+    /// the metadata is dropped.
+    fn call_tool_with_meta(
+        &self,
+        tool_name: &str,
+        arguments: Value,
+        _meta: JsonObject,
+        notifier: mpsc::Sender<JsonRpcMessage>,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<Content>, ToolError>> + Send + 'static>> {
+        self.call_tool(tool_name, arguments, notifier)
+    }
+
     fn list_resources(&self) -> Vec<Resource>;
     fn read_resource(
         &self,
@@ -101,6 +128,21 @@ pub trait Router: Send + Sync + 'static {
     fn list_prompts(&self) -> Vec<Prompt>;
     fn get_prompt(&self, prompt_name: &str) -> PromptFuture;
 
+    /// Render a prompt with the client's arguments. By default every `{name}` placeholder in
+    /// the text returned by `get_prompt` is replaced with the argument of that name.
+    fn render_prompt(&self, prompt_name: &str, arguments: &JsonObject) -> PromptFuture {
+        let prompt = self.get_prompt(prompt_name);
+        let arguments = arguments.clone();
+        Box::pin(async move {
+            let mut text = prompt.await?;
+            for (key, value) in &arguments {
+                let placeholder = format!("{{{}}}", key);
+                text = text.replace(&placeholder, value.as_str().unwrap_or_default());
+            }
+            Ok(text)
+        })
+    }
+
     // Helper method to create base response
     fn create_response(&self, id: RequestId) -> JsonRpcResponse {
         JsonRpcResponse {
@@ -156,10 +198,20 @@ pub trait Router: Send + Sync + 'static {
         async move {
             let tools = self.list_tools();
 
-            let result = ListToolsResult {
+            let mut result = serde_json::to_value(ListToolsResult {
                 tools,
                 next_cursor: None,
-            };
+            })
+            .map_err(|e| RouterError::Internal(format!("JSON serialization error: {}", e)))?;
+            // rmcp's tools have no field for the output schema, so it is added to the JSON
+            for tool in result["tools"].as_array_mut().into_iter().flatten() {
+                let schema = tool["name"]
+                    .as_str()
+                    .and_then(|name| self.output_schema(name));
+                if let (Some(schema), Some(tool)) = (schema, tool.as_object_mut()) {
+                    tool.insert("outputSchema".to_string(), Value::Object(schema));
+                }
+            }
             let mut response = self.create_response(req.id);
             self.set_result(&mut response, result)?;
             Ok(response)
@@ -181,14 +233,37 @@ pub trait Router: Send + Sync + 'static {
 
             let arguments = params.get("arguments").cloned().unwrap_or(Value::Null);
 
-            let result = match self.call_tool(name, arguments, notifier).await {
-                Ok(result) => CallToolResult {
-                    content: result,
-                    is_error: None,
-                },
+            // Hand the request metadata (e.g. the client's progress token) to the tool so
+            // long running calls can report progress
+            let meta = params
+                .get("_meta")
+                .and_then(Value::as_object)
+                .cloned()
+                .unwrap_or_default();
+
+            // Errors carry a stable code and any details alongside the message, so clients
+            // can tell failures apart without parsing it
+            let result = match self
+                .call_tool_with_meta(name, arguments, meta, notifier)
+                .await
+            {
+                Ok(mut content) => {
+                    let structured_content = take_structured_content(&mut content);
+                    CallToolResult {
+                        content,
+                        is_error: None,
+                        structured_content,
+                    }
+                }
                 Err(err) => CallToolResult {
                     content: vec![Content::text(err.to_string())],
                     is_error: Some(true),
+                    structured_content: Some(serde_json::json!({
+                        "error": {
+                            "code": err.code(),
+                            "data": err.data(),
+                        }
+                    })),
                 },
             };
 
@@ -229,10 +304,19 @@ pub trait Router: Send + Sync + 'static {
 
             let contents = self.read_resource(uri).await.map_err(RouterError::from)?;
 
+            // Report the MIME type the resource was listed with, when it is a real one
+            let mime_type = self
+                .list_resources()
+                .into_iter()
+                .find(|resource| resource.raw.uri == uri)
+                .and_then(|resource| resource.raw.mime_type)
+                .filter(|mime_type| mime_type.contains('/'))
+                .unwrap_or_else(|| "text/plain".to_string());
+
             let result = ReadResourceResult {
                 contents: vec![ResourceContents::TextResourceContents {
                     uri: uri.to_string(),
-                    mime_type: Some("text/plain".to_string()),
+                    mime_type: Some(mime_type),
                     text: contents,
                 }],
             };
@@ -306,12 +390,6 @@ pub trait Router: Send + Sync + 'static {
                 }
             }
 
-            // Now get the prompt content
-            let description = self
-                .get_prompt(prompt_name)
-                .await
-                .map_err(|e| RouterError::Internal(e.to_string()))?;
-
             // Validate prompt arguments for potential security issues from user text input
             // Checks:
             // - Prompt must be less than 10000 total characters
@@ -345,23 +423,19 @@ pub trait Router: Send + Sync + 'static {
                 }
             }
 
+            // Fill in the arguments
+            let description_filled = self
+                .render_prompt(prompt_name, arguments)
+                .await
+                .map_err(RouterError::from)?;
+
             // Validate the prompt description length
-            if description.len() > 10000 {
+            if description_filled.len() > 10000 {
                 return Err(RouterError::Internal(
                     "Prompt description exceeds maximum allowed length".into(),
                 ));
             }
 
-            // Create a mutable copy of the description to fill in arguments
-            let mut description_filled = description.clone();
-
-            // Replace each argument placeholder with its value from the arguments object
-            for (key, value) in arguments {
-                let placeholder = format!("{{{}}}", key);
-                description_filled =
-                    description_filled.replace(&placeholder, value.as_str().unwrap_or_default());
-            }
-
             let messages = vec![PromptMessage::new_text(
                 PromptMessageRole::User,
                 description_filled.to_string(),
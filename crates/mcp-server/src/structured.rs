@@ -0,0 +1,36 @@
+//! Structured results for tools that declare an output schema.
+//!
+//! [`Router::call_tool`](crate::Router::call_tool) only returns content, so a tool adds its
+//! JSON result as one more item made with [`structured_content`]. The router takes that item
+//! out again and sends it as the `structuredContent` of the call result, next to the text the
+//! tool returned for the model and the user.
+
+use rmcp::model::{Content, RawContent, ResourceContents};
+use serde_json::Value;
+
+/// Marks the content item that carries the structured result
+pub const STRUCTURED_CONTENT_URI: &str = "mcp://structured-content";
+
+/// Content carrying a tool's structured result, which should match its output schema
+pub fn structured_content(value: &Value) -> Content {
+    Content::embedded_text(STRUCTURED_CONTENT_URI, value.to_string())
+}
+
+/// Remove the structured result from a tool's content, returning it if there was one
+pub fn take_structured_content(content: &mut Vec<Content>) -> Option<Value> {
+    let index = content.iter().position(|item| match &item.raw {
+        RawContent::Resource(resource) => matches!(
+            &resource.resource,
+            ResourceContents::TextResourceContents { uri, .. } if uri == STRUCTURED_CONTENT_URI
+        ),
+        _ => false,
+    })?;
+
+    match content.remove(index).raw {
+        RawContent::Resource(resource) => match resource.resource {
+            ResourceContents::TextResourceContents { text, .. } => serde_json::from_str(&text).ok(),
+            _ => None,
+        },
+        _ => None,
+    }
+}